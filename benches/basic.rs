@@ -64,7 +64,6 @@ fn create_program(b: &mut Bencher) {
 }
 
 #[bench]
-#[ignore]       // TODO: segfaults
 fn draw_triangle(b: &mut Bencher) {
     let display = support::build_context();
 
@@ -124,6 +123,150 @@ fn draw_triangle(b: &mut Bencher) {
     });
 }
 
+#[bench]
+fn draw_depth_prepass_and_shading(b: &mut Bencher) {
+    let display = support::build_context();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let meshes = (0 .. 16).map(|_| {
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-0.5, -0.5], color: [1.0, 0.0, 0.0] },
+                Vertex { position: [ 0.0,  0.5], color: [0.0, 1.0, 0.0] },
+                Vertex { position: [ 0.5, -0.5], color: [0.0, 0.0, 1.0] },
+            ]
+        ).unwrap()
+    }).collect::<Vec<_>>();
+
+    let prepass = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec3 color;
+
+                void main() {
+                    gl_Position = vec4(position, color.x * 0.0, 1.0);
+                }
+            ",
+
+            fragment: "
+                #version 140
+
+                void main() {
+                }
+            ",
+        },
+    ).unwrap();
+
+    let shading = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec3 color;
+
+                out vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+
+            fragment: "
+                #version 140
+
+                in vec3 v_color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(v_color, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    b.iter(|| {
+        let mut target = glium::Frame::new(display.clone(), (800, 600));
+        for mesh in &meshes {
+            for program in &[&prepass, &shading] {
+                target.draw(mesh, &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+                            program, &uniform!{}, &Default::default()).unwrap();
+            }
+        }
+        target.finish().unwrap();
+    });
+}
+
+#[bench]
+fn draw_structure_of_arrays(b: &mut Bencher) {
+    let display = support::build_context();
+
+    macro_rules! attribute_buffer {
+        ($name:ident) => ({
+            #[derive(Copy, Clone)]
+            struct Vertex {
+                $name: [f32; 4],
+            }
+
+            implement_vertex!(Vertex, $name);
+
+            glium::VertexBuffer::new(&display, &[Vertex { $name: [0.0; 4] }; 3]).unwrap()
+        })
+    }
+
+    let a0 = attribute_buffer!(a0);
+    let a1 = attribute_buffer!(a1);
+    let a2 = attribute_buffer!(a2);
+    let a3 = attribute_buffer!(a3);
+    let a4 = attribute_buffer!(a4);
+    let a5 = attribute_buffer!(a5);
+    let a6 = attribute_buffer!(a6);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec4 a0; in vec4 a1; in vec4 a2; in vec4 a3;
+                in vec4 a4; in vec4 a5; in vec4 a6;
+
+                void main() {
+                    gl_Position = a0 + a1 + a2 + a3 + a4 + a5 + a6;
+                }
+            ",
+
+            fragment: "
+                #version 140
+
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(1.0, 1.0, 1.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    b.iter(|| {
+        let mut target = glium::Frame::new(display.clone(), (800, 600));
+        target.draw((&a0, &a1, &a2, &a3, &a4, &a5, &a6),
+                    &glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList),
+                    &program, &uniform!{}, &Default::default()).unwrap();
+        target.finish().unwrap();
+    });
+}
+
 #[bench]
 fn build_buffer(b: &mut Bencher) {
     let display = support::build_context();
@@ -3,6 +3,10 @@ extern crate libc;
 
 use std::ptr;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+/// Size passed to the last call to `glBufferData`, returned by `GL_BUFFER_SIZE`.
+static BUFFER_SIZE: AtomicIsize = AtomicIsize::new(0);
 
 /// Builds a context with dummy OpenGL functions.
 pub fn build_context() -> Rc<glium::backend::Context> {
@@ -32,7 +36,9 @@ unsafe impl glium::backend::Backend for DummyBackend {
             },
 
             "glBufferData" => {
-                extern "system" fn buffer_data(_: u32, _: isize, _: *const (), _: u32) {}
+                extern "system" fn buffer_data(_: u32, size: isize, _: *const (), _: u32) {
+                    BUFFER_SIZE.store(size, Ordering::Relaxed);
+                }
                 buffer_data as *const _
             },
 
@@ -72,6 +78,11 @@ unsafe impl glium::backend::Backend for DummyBackend {
                 delete as *const _
             },
 
+            "glDrawArrays" => {
+                extern "system" fn draw_arrays(_: u32, _: i32, _: i32) {}
+                draw_arrays as *const _
+            },
+
             "glEnable" | "glDisable" => {
                 extern "system" fn enable(_: u32) {}
                 enable as *const _
@@ -102,7 +113,9 @@ unsafe impl glium::backend::Backend for DummyBackend {
             "glGetBufferParameteriv" => {
                 extern "system" fn get_buf_paramiv(_: u32, param: u32, out: *mut i32) {
                     match param {
-                        0x8764 /* GL_BUFFER_SIZE */ => unsafe { *out = 60 },      // FIXME:
+                        0x8764 /* GL_BUFFER_SIZE */ => unsafe {
+                            *out = BUFFER_SIZE.load(Ordering::Relaxed) as i32;
+                        },
                         _ => unsafe { *out = 0; }
                     }
                 }
@@ -124,6 +137,10 @@ unsafe impl glium::backend::Backend for DummyBackend {
                 extern "system" fn get_integerv(name: u32, out: *mut i32) {
                     match name {
                         0x821D /* GL_NUM_EXTENSIONS */ => unsafe { *out = 0; },
+                        0x0D3A /* GL_MAX_VIEWPORT_DIMS */ => unsafe {
+                            *out = 16384;
+                            *out.offset(1) = 16384;
+                        },
                         _ => unsafe { *out = 0; },
                     }
                 }
@@ -172,6 +189,11 @@ unsafe impl glium::backend::Backend for DummyBackend {
                 shader_source as *const _
             }
 
+            "glViewport" => {
+                extern "system" fn viewport(_: i32, _: i32, _: i32, _: i32) {}
+                viewport as *const _
+            },
+
             "glUseProgram" => {
                 extern "system" fn use_program(_: u32) {}
                 use_program as *const _
@@ -52,6 +52,7 @@ fn generate_gl_bindings<W>(dest: &mut W) where W: Write {
             "GL_ARB_seamless_cube_map",
             "GL_ARB_shader_image_load_store",
             "GL_ARB_shader_objects",
+            "GL_ARB_sparse_buffer",
             "GL_ARB_texture_buffer_object",
             "GL_ARB_texture_float",
             "GL_ARB_texture_multisample",
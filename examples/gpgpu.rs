@@ -52,7 +52,7 @@ fn main() {
               glium::uniforms::UniformBuffer::empty(&display).unwrap();
 
     {
-        let mut mapping = buffer.map();
+        let mut mapping = buffer.map().unwrap();
         mapping.power = rand::random();
         for val in mapping.values.iter_mut() {
             *val = [rand::random::<f32>(),rand::random::<f32>(),rand::random::<f32>(),rand::random::<f32>()];
@@ -62,7 +62,7 @@ fn main() {
     program.execute(uniform! { MyBlock: &*buffer }, 4096, 1, 1);
 
     {
-        let mapping = buffer.map();
+        let mapping = buffer.map().unwrap();
         println!("Power is: {:?}", mapping.power);
         for val in mapping.values.iter().take(3) {
             println!("{:?}", val[0]);
@@ -93,7 +93,7 @@ fn main() {
     support::start_loop(event_loop, move |events| {
         // updating the teapots
         {
-            let mut mapping = per_instance.map();
+            let mut mapping = per_instance.map().unwrap();
             for (src, dest) in teapots.iter_mut().zip(mapping.iter_mut()) {
                 (src.0).0 += (src.1).0 * 0.001;
                 (src.0).1 += (src.1).1 * 0.001;
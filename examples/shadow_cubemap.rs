@@ -0,0 +1,313 @@
+#[macro_use]
+extern crate glium;
+extern crate cgmath;
+
+use std::time::Instant;
+
+#[allow(unused_imports)]
+use glium::{glutin, Surface};
+use glium::texture::DepthCubemap;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::vertex::EmptyInstanceAttributes;
+
+mod support;
+
+const SHADOW_MAP_SIZE: u32 = 1024;
+const LIGHT_NEAR: f32 = 0.1;
+const LIGHT_FAR: f32 = 25.0;
+
+fn main() {
+    println!("This example renders the shadows of a point light. The six faces of the shadow \
+              cubemap are rendered in a single draw call per object: the object is drawn with \
+              six instances, and the geometry shader sends each instance to a face of the \
+              cubemap with `gl_Layer`.");
+
+    let event_loop = glutin::event_loop::EventLoop::new();
+    let wb = glutin::window::WindowBuilder::new().with_title("Point light shadows");
+    let cb = glutin::ContextBuilder::new().with_depth_buffer(24);
+    let display = glium::Display::new(wb, cb, &event_loop).unwrap();
+
+    if !glium::framebuffer::is_layered_supported(&display) {
+        println!("Layered framebuffers are not supported by this backend");
+        return;
+    }
+
+    let (vertex_buffer, index_buffer) = create_box(&display);
+
+    // a floor, four pillars and a box floating above the light
+    let models = [
+        model_matrix([0.0, -2.0, 0.0], [12.0, 0.2, 12.0]),
+        model_matrix([ 2.5, -0.9,  2.5], [0.6, 2.0, 0.6]),
+        model_matrix([-2.5, -0.9,  2.5], [0.6, 2.0, 0.6]),
+        model_matrix([ 2.5, -0.9, -2.5], [0.6, 2.0, 0.6]),
+        model_matrix([-2.5, -0.9, -2.5], [0.6, 2.0, 0.6]),
+        model_matrix([0.0, 2.5, 0.0], [1.5, 0.3, 1.5]),
+    ];
+
+    // renders the distance between the light and each fragment to the six faces at once
+    let shadow_program = program!(&display,
+        150 => {
+            vertex: "
+                #version 150
+
+                uniform mat4 model;
+
+                in vec3 position;
+
+                out vec4 v_world_position;
+                flat out int v_face;
+
+                void main() {
+                    v_world_position = model * vec4(position, 1.0);
+                    v_face = gl_InstanceID;
+                }
+            ",
+            geometry: "
+                #version 150
+
+                layout(triangles) in;
+                layout(triangle_strip, max_vertices = 3) out;
+
+                uniform mat4 face_transforms[6];
+
+                in vec4 v_world_position[];
+                flat in int v_face[];
+
+                out vec4 g_world_position;
+
+                void main() {
+                    for (int i = 0; i < 3; i++) {
+                        gl_Layer = v_face[0];
+                        g_world_position = v_world_position[i];
+                        gl_Position = face_transforms[v_face[0]] * v_world_position[i];
+                        EmitVertex();
+                    }
+                    EndPrimitive();
+                }
+            ",
+            fragment: "
+                #version 150
+
+                uniform vec3 light_position;
+                uniform float far;
+
+                in vec4 g_world_position;
+
+                void main() {
+                    gl_FragDepth = length(g_world_position.xyz - light_position) / far;
+                }
+            ",
+        }).unwrap();
+
+    let render_program = program!(&display,
+        150 => {
+            vertex: "
+                #version 150
+
+                uniform mat4 model;
+                uniform mat4 view_projection;
+
+                in vec3 position;
+                in vec3 normal;
+
+                out vec3 v_world_position;
+                out vec3 v_normal;
+
+                void main() {
+                    vec4 world_position = model * vec4(position, 1.0);
+                    v_world_position = world_position.xyz;
+                    v_normal = mat3(model) * normal;
+                    gl_Position = view_projection * world_position;
+                }
+            ",
+            fragment: "
+                #version 150
+
+                uniform samplerCube shadow_map;
+                uniform vec3 light_position;
+                uniform float far;
+
+                in vec3 v_world_position;
+                in vec3 v_normal;
+
+                out vec4 f_color;
+
+                void main() {
+                    vec3 to_fragment = v_world_position - light_position;
+                    float closest = texture(shadow_map, to_fragment).r * far;
+                    float lit = length(to_fragment) - 0.05 > closest ? 0.0 : 1.0;
+
+                    float lum = max(dot(normalize(v_normal), normalize(-to_fragment)), 0.0);
+                    f_color = vec4(vec3(0.1 + 0.9 * lum * lit), 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let shadow_map = DepthCubemap::empty(&display, SHADOW_MAP_SIZE).unwrap();
+
+    let start = Instant::now();
+
+    support::start_loop(event_loop, move |events| {
+        let time = start.elapsed().as_secs() as f32 + start.elapsed().subsec_millis() as f32 * 1e-3;
+        let light_position = [1.5 * time.cos(), 0.5, 1.5 * time.sin()];
+
+        // rendering the shadow cubemap with one instanced draw call per object
+        {
+            let transforms = cubemap_face_transforms(light_position);
+
+            let mut target = SimpleFrameBuffer::layered_depth_only(&display,
+                                                                   *shadow_map.main_level())
+                                                                   .unwrap();
+            target.clear_depth(1.0);
+
+            let params = glium::DrawParameters {
+                depth: glium::Depth {
+                    test: glium::DepthTest::IfLess,
+                    write: true,
+                    .. Default::default()
+                },
+                .. Default::default()
+            };
+
+            for model in models.iter() {
+                let uniforms = uniform! {
+                    model: *model,
+                    light_position: light_position,
+                    far: LIGHT_FAR,
+                }.add("face_transforms[0]", transforms[0])
+                 .add("face_transforms[1]", transforms[1])
+                 .add("face_transforms[2]", transforms[2])
+                 .add("face_transforms[3]", transforms[3])
+                 .add("face_transforms[4]", transforms[4])
+                 .add("face_transforms[5]", transforms[5]);
+
+                target.draw((&vertex_buffer, EmptyInstanceAttributes { len: 6 }),
+                            &index_buffer, &shadow_program, &uniforms, &params).unwrap();
+            }
+        }
+
+        // rendering the scene from the camera
+        let mut target = display.draw();
+        target.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
+
+        let (width, height) = target.get_dimensions();
+        let projection = cgmath::perspective(cgmath::Deg(60.0), width as f32 / height as f32,
+                                             0.1, 100.0);
+        let view = cgmath::Matrix4::look_at(cgmath::Point3::new(7.0, 5.0, 7.0),
+                                            cgmath::Point3::new(0.0, 0.0, 0.0),
+                                            cgmath::Vector3::new(0.0, 1.0, 0.0));
+        let view_projection: [[f32; 4]; 4] = (projection * view).into();
+
+        let params = glium::DrawParameters {
+            depth: glium::Depth {
+                test: glium::DepthTest::IfLess,
+                write: true,
+                .. Default::default()
+            },
+            .. Default::default()
+        };
+
+        for model in models.iter() {
+            let uniforms = uniform! {
+                model: *model,
+                view_projection: view_projection,
+                light_position: light_position,
+                far: LIGHT_FAR,
+                shadow_map: glium::uniforms::Sampler::new(&shadow_map)
+                    .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
+                    .minify_filter(glium::uniforms::MinifySamplerFilter::Nearest),
+            };
+
+            target.draw(&vertex_buffer, &index_buffer, &render_program, &uniforms,
+                        &params).unwrap();
+        }
+
+        target.finish().unwrap();
+
+        let mut action = support::Action::Continue;
+
+        for event in events {
+            match event {
+                glutin::event::Event::WindowEvent { event, .. } => match event {
+                    glutin::event::WindowEvent::CloseRequested => action = support::Action::Stop,
+                    _ => (),
+                },
+                _ => (),
+            }
+        };
+
+        action
+    });
+}
+
+/// Returns the projection and view matrices of the six faces of a cubemap centered on the light,
+/// in the order of the layers of a cubemap.
+fn cubemap_face_transforms(light_position: [f32; 3]) -> [[[f32; 4]; 4]; 6] {
+    let projection = cgmath::perspective(cgmath::Deg(90.0), 1.0, LIGHT_NEAR, LIGHT_FAR);
+    let eye = cgmath::Point3::new(light_position[0], light_position[1], light_position[2]);
+
+    let face = |direction: [f32; 3], up: [f32; 3]| -> [[f32; 4]; 4] {
+        let center = eye + cgmath::Vector3::new(direction[0], direction[1], direction[2]);
+        let view = cgmath::Matrix4::look_at(eye, center,
+                                            cgmath::Vector3::new(up[0], up[1], up[2]));
+        (projection * view).into()
+    };
+
+    [
+        face([ 1.0,  0.0,  0.0], [0.0, -1.0,  0.0]),
+        face([-1.0,  0.0,  0.0], [0.0, -1.0,  0.0]),
+        face([ 0.0,  1.0,  0.0], [0.0,  0.0,  1.0]),
+        face([ 0.0, -1.0,  0.0], [0.0,  0.0, -1.0]),
+        face([ 0.0,  0.0,  1.0], [0.0, -1.0,  0.0]),
+        face([ 0.0,  0.0, -1.0], [0.0, -1.0,  0.0]),
+    ]
+}
+
+fn model_matrix(translation: [f32; 3], scale: [f32; 3]) -> [[f32; 4]; 4] {
+    let matrix = cgmath::Matrix4::from_translation(translation.into()) *
+                 cgmath::Matrix4::from_nonuniform_scale(scale[0], scale[1], scale[2]);
+    matrix.into()
+}
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+implement_vertex!(Vertex, position, normal);
+
+fn create_box(display: &glium::Display) -> (glium::VertexBuffer<Vertex>, glium::IndexBuffer<u16>) {
+    let faces = [
+        ([ 1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0,  1.0, 0.0], [0.0, 0.0, 1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0]),
+        ([0.0, 0.0,  1.0], [1.0, 0.0, 0.0]),
+        ([0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+    ];
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for &(normal, u) in faces.iter() {
+        // the third axis of the face, so that (u, v, normal) is direct
+        let v = [normal[1] * u[2] - normal[2] * u[1],
+                 normal[2] * u[0] - normal[0] * u[2],
+                 normal[0] * u[1] - normal[1] * u[0]];
+
+        let base = vertices.len() as u16;
+        for &(su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)].iter() {
+            let position = [normal[0] * 0.5 + u[0] * su + v[0] * sv,
+                            normal[1] * 0.5 + u[1] * su + v[1] * sv,
+                            normal[2] * 0.5 + u[2] * su + v[2] * sv];
+            vertices.push(Vertex { position: position, normal: normal });
+        }
+
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    (glium::VertexBuffer::new(display, &vertices).unwrap(),
+     glium::IndexBuffer::new(display, glium::index::PrimitiveType::TrianglesList,
+                             &indices).unwrap())
+}
@@ -52,7 +52,7 @@ fn main() {
         let mut ib_data = Vec::with_capacity(SPRITES_COUNT * 6);
 
         // initializing with random data
-        for (num, sprite) in vb.map().chunks_mut(4).enumerate() {
+        for (num, sprite) in vb.map().unwrap().chunks_mut(4).enumerate() {
             let tex_id: u32 = rand::random();
             let tex_id = tex_id % texture.get_array_size().unwrap();
             let position: (f32, f32) = (rand::random(), rand::random());
@@ -216,7 +216,7 @@ fn main() {
         // moving the sprites in a random direction
         // in a game, you would typically write the exact positions and texture IDs of your sprites
         {
-            let mut mapping = vertex_buffer.map();
+            let mut mapping = vertex_buffer.map().unwrap();
             for sprite in mapping.chunks_mut(4) {
                 let mv: (f32, f32) = (rand::random(), rand::random());
                 let mv = (mv.0 * 0.01 - 0.005, mv.1 * 0.01 - 0.005);
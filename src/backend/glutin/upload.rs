@@ -0,0 +1,359 @@
+/*!
+Uploading vertex buffers from a background thread.
+
+Glium objects can't be sent between threads, because they keep a reference to the context they
+belong to. However uploading a large amount of data with `glBufferData` can take a while, and you
+may not want to block your rendering thread while this happens.
+
+An `UploadQueue` owns a second OpenGL context that shares its objects with the context of your
+`Display`. It runs on a dedicated thread and creates the buffers that you submit to it. Each
+upload returns a `PendingVertexBuffer` that can be sent back to the rendering thread and turned
+into a regular `VertexBuffer` once the upload is over.
+
+# Example
+
+```no_run
+# #[macro_use] extern crate glium;
+# fn main() {
+use glium::glutin;
+use glium::backend::glutin::upload::UploadQueue;
+# #[derive(Copy, Clone)]
+# struct Vertex { position: [f32; 2] }
+# implement_vertex!(Vertex, position);
+# let event_loop = glutin::event_loop::EventLoop::new();
+# let wb = glutin::window::WindowBuilder::new();
+# let cb = glutin::ContextBuilder::new();
+# let display = glium::Display::new(wb, cb, &event_loop).unwrap();
+# let size = glutin::dpi::PhysicalSize::new(1, 1);
+
+// the upload context must share its objects with the context of the display
+let upload_context = {
+    let gl_window = display.gl_window();
+    glutin::ContextBuilder::new()
+        .with_shared_lists(gl_window.context())
+        .build_headless(&event_loop, size)
+        .unwrap()
+};
+
+let queue = UploadQueue::new(upload_context).unwrap();
+let pending = queue.upload(vec![Vertex { position: [0.0, 0.0] }; 100000]);
+
+// ... later, on the rendering thread
+let vertex_buffer = pending.wait(&display).unwrap();
+# }
+```
+
+*/
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::sync::mpsc::{self, Sender, Receiver, TryRecvError};
+use std::thread::{self, JoinHandle};
+
+use IncompatibleOpenGl;
+use gl;
+use backend::Facade;
+use vertex::{Vertex, VertexBuffer, VertexBufferAny, VertexFormat};
+use vertex::BufferCreationError;
+use super::glutin;
+use super::headless::Headless;
+
+/// A job executed by the upload thread.
+type Job = Box<dyn FnOnce(&Headless) + Send>;
+
+/// Message sent to the upload thread. `None` stops the thread.
+type Message = Option<Job>;
+
+/// Result of an upload, as sent back by the upload thread.
+type Uploaded = Result<(gl::types::GLuint, usize), BufferCreationError>;
+
+/// Thread that owns a shared OpenGL context and uploads buffers with it.
+///
+/// Dropping the queue waits for the uploads that have already been submitted to finish, then
+/// stops the thread. Uploads submitted afterwards with an `Uploader` fail.
+pub struct UploadQueue {
+    uploader: Option<Uploader>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Handle to an `UploadQueue` that can be cloned and sent to other threads.
+#[derive(Clone)]
+pub struct Uploader {
+    sender: Sender<Message>,
+}
+
+/// Error that can happen while creating an `UploadQueue`.
+#[derive(Debug)]
+pub enum UploadQueueCreationError {
+    /// The OpenGL implementation of the upload context is too old.
+    IncompatibleOpenGl(IncompatibleOpenGl),
+    /// The upload thread panicked while creating its context.
+    ThreadPanicked,
+}
+
+impl fmt::Display for UploadQueueCreationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UploadQueueCreationError::IncompatibleOpenGl(err) => write!(fmt, "{}", err),
+            UploadQueueCreationError::ThreadPanicked =>
+                fmt.write_str("The upload thread panicked while creating its context"),
+        }
+    }
+}
+
+impl Error for UploadQueueCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            UploadQueueCreationError::IncompatibleOpenGl(ref err) => Some(err),
+            UploadQueueCreationError::ThreadPanicked => None,
+        }
+    }
+}
+
+impl From<IncompatibleOpenGl> for UploadQueueCreationError {
+    #[inline]
+    fn from(err: IncompatibleOpenGl) -> UploadQueueCreationError {
+        UploadQueueCreationError::IncompatibleOpenGl(err)
+    }
+}
+
+/// Error that can happen while retrieving the buffer of a `PendingVertexBuffer`.
+#[derive(Copy, Clone, Debug)]
+pub enum UploadError {
+    /// The upload thread has stopped before finishing the upload, for example because the
+    /// `UploadQueue` has been dropped.
+    ThreadStopped,
+    /// The upload thread couldn't create the buffer.
+    BufferCreationError(BufferCreationError),
+}
+
+impl fmt::Display for UploadError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UploadError::ThreadStopped =>
+                fmt.write_str("The upload thread has stopped before finishing the upload"),
+            UploadError::BufferCreationError(err) => write!(fmt, "{}", err),
+        }
+    }
+}
+
+impl Error for UploadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            UploadError::ThreadStopped => None,
+            UploadError::BufferCreationError(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<BufferCreationError> for UploadError {
+    #[inline]
+    fn from(err: BufferCreationError) -> UploadError {
+        UploadError::BufferCreationError(err)
+    }
+}
+
+/// A vertex buffer that is being uploaded by an `UploadQueue`.
+///
+/// If the handle is dropped before the buffer has been retrieved, the buffer is destroyed by the
+/// upload thread once the upload is over. It is leaked if the `UploadQueue` has been dropped in
+/// the meantime.
+pub struct PendingVertexBuffer<T> where T: Copy {
+    // `None` once the buffer has been retrieved
+    receiver: Option<Receiver<Uploaded>>,
+    // used to destroy the buffer on the upload thread if it is never retrieved
+    sender: Sender<Message>,
+    bindings: VertexFormat,
+    marker: PhantomData<T>,
+}
+
+impl UploadQueue {
+    /// Starts the upload thread.
+    ///
+    /// `context` must share its objects with the contexts that the buffers are going to be used
+    /// with, ie. it must have been built with `ContextBuilder::with_shared_lists`. Otherwise the
+    /// buffers returned by `PendingVertexBuffer` can't be used.
+    pub fn new(context: glutin::Context<glutin::NotCurrent>)
+               -> Result<UploadQueue, UploadQueueCreationError>
+    {
+        let (sender, receiver) = mpsc::channel::<Message>();
+        let (init_sender, init_receiver) = mpsc::channel();
+
+        let thread = thread::spawn(move || {
+            let headless = match Headless::new(context) {
+                Ok(h) => { init_sender.send(Ok(())).ok(); h },
+                Err(err) => { init_sender.send(Err(err)).ok(); return; },
+            };
+
+            for message in receiver {
+                match message {
+                    Some(job) => job(&headless),
+                    None => break,
+                }
+            }
+        });
+
+        match init_receiver.recv() {
+            Ok(Ok(())) => (),
+            Ok(Err(err)) => return Err(err.into()),
+            Err(_) => return Err(UploadQueueCreationError::ThreadPanicked),
+        };
+
+        Ok(UploadQueue {
+            uploader: Some(Uploader { sender: sender }),
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns a handle that can be used to submit uploads from other threads.
+    #[inline]
+    pub fn uploader(&self) -> Uploader {
+        self.uploader.as_ref().unwrap().clone()
+    }
+
+    /// Submits a vertex buffer to upload. See `Uploader::upload`.
+    #[inline]
+    pub fn upload<T>(&self, data: Vec<T>) -> PendingVertexBuffer<T>
+                     where T: Vertex + Send + 'static
+    {
+        self.uploader.as_ref().unwrap().upload(data)
+    }
+}
+
+impl Drop for UploadQueue {
+    fn drop(&mut self) {
+        // the thread stops once all the jobs submitted until now have been processed
+        if let Some(uploader) = self.uploader.take() {
+            uploader.sender.send(None).ok();
+        }
+
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Uploader {
+    /// Submits a vertex buffer to upload.
+    ///
+    /// The buffer is created with `BufferMode::Default` on the upload thread. The returned
+    /// handle can be sent to the thread that owns the destination context.
+    pub fn upload<T>(&self, data: Vec<T>) -> PendingVertexBuffer<T>
+                     where T: Vertex + Send + 'static
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        let job: Job = Box::new(move |headless: &Headless| {
+            let result = VertexBuffer::new(headless, &data).map(|buffer| {
+                let len = buffer.len();
+                let buffer: VertexBufferAny = buffer.into();
+                (buffer.into_raw_id(), len)
+            });
+
+            // the buffer must be entirely uploaded before it is used by another context
+            headless.finish();
+
+            // destroy the buffer if the `PendingVertexBuffer` has already been dropped
+            if let Err(err) = sender.send(result) {
+                if let Ok((id, len)) = err.0 {
+                    unsafe {
+                        VertexBufferAny::from_raw_id(headless, id, <T as Vertex>::build_bindings(),
+                                                     mem::size_of::<T>(), len, true);
+                    }
+                }
+            }
+        });
+
+        // if the thread has stopped, the receiver will report it
+        self.sender.send(Some(job)).ok();
+
+        PendingVertexBuffer {
+            receiver: Some(receiver),
+            sender: self.sender.clone(),
+            bindings: <T as Vertex>::build_bindings(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PendingVertexBuffer<T> where T: Copy {
+    /// Returns the buffer if the upload is over, or `None` if it is still in progress or if the
+    /// buffer has already been returned by a previous call.
+    ///
+    /// Returns `UploadError::ThreadStopped` if the upload thread has stopped before finishing
+    /// the upload.
+    pub fn try_get<F: ?Sized>(&mut self, facade: &F)
+                              -> Option<Result<VertexBuffer<T>, UploadError>>
+                              where F: Facade
+    {
+        let result = match self.receiver.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                self.receiver = None;
+                return Some(Err(UploadError::ThreadStopped));
+            },
+        };
+
+        self.receiver = None;
+        Some(self.build(facade, result))
+    }
+
+    /// Blocks until the upload is over and returns the buffer.
+    ///
+    /// Returns `UploadError::ThreadStopped` if the upload thread has stopped before finishing
+    /// the upload.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer has already been returned by `try_get`.
+    pub fn wait<F: ?Sized>(mut self, facade: &F) -> Result<VertexBuffer<T>, UploadError>
+                           where F: Facade
+    {
+        let receiver = self.receiver.take().expect("The buffer has already been returned");
+        match receiver.recv() {
+            Ok(result) => self.build(facade, result),
+            Err(_) => Err(UploadError::ThreadStopped),
+        }
+    }
+
+    fn build<F: ?Sized>(&self, facade: &F, result: Uploaded)
+                        -> Result<VertexBuffer<T>, UploadError> where F: Facade
+    {
+        let (id, len) = result?;
+
+        unsafe {
+            let buffer = VertexBufferAny::from_raw_id(facade, id, self.bindings.clone(),
+                                                      mem::size_of::<T>(), len, true);
+            Ok(buffer.into_vertex_buffer())
+        }
+    }
+}
+
+impl<T> Drop for PendingVertexBuffer<T> where T: Copy {
+    fn drop(&mut self) {
+        let receiver = match self.receiver.take() {
+            Some(r) => r,
+            None => return,
+        };
+
+        let bindings = self.bindings.clone();
+        let elements_size = mem::size_of::<T>();
+
+        // jobs are executed in order, so the upload is over when this one runs
+        let job: Job = Box::new(move |headless: &Headless| {
+            if let Ok(Ok((id, len))) = receiver.try_recv() {
+                unsafe {
+                    VertexBufferAny::from_raw_id(headless, id, bindings, elements_size, len,
+                                                 true);
+                }
+            }
+        });
+
+        // if the queue has been dropped, the buffer can't be destroyed anymore
+        self.sender.send(Some(job)).ok();
+    }
+}
@@ -7,8 +7,8 @@ use ContextExt;
 use gl;
 use std::os::raw;
 use std::error::Error;
-use std::{fmt, mem, ptr};
-use std::cell::Cell;
+use std::{fmt, mem, ptr, slice};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::ops::{Deref, DerefMut, Range};
 use GlObject;
@@ -28,6 +28,12 @@ pub enum ReadError {
 
     /// The context has been lost. Reading from the buffer would return garbage data.
     ContextLost,
+
+    /// Not enough memory to create the staging buffer of an asynchronous read.
+    OutOfMemory,
+
+    /// One of the requested ranges is out of the bounds of the buffer.
+    OutOfRange,
 }
 
 impl fmt::Display for ReadError {
@@ -36,6 +42,8 @@ impl fmt::Display for ReadError {
         let desc = match *self {
             NotSupported => "The backend doesn't support reading from a buffer",
             ContextLost => "The context has been lost. Reading from the buffer would return garbage data",
+            OutOfMemory => "Not enough memory to create the staging buffer",
+            OutOfRange => "One of the requested ranges is out of the bounds of the buffer",
         };
         fmt.write_str(desc)
     }
@@ -43,6 +51,48 @@ impl fmt::Display for ReadError {
 
 impl Error for ReadError {}
 
+/// Error that can happen when mapping a buffer.
+#[derive(Debug, Copy, Clone)]
+pub enum MapError {
+    /// The backend doesn't support mapping this buffer.
+    NotSupported,
+
+    /// The driver failed to map the buffer.
+    MappingFailed,
+
+    /// Not enough memory to map the buffer or to create the temporary buffer of the mapping.
+    OutOfMemory,
+
+    /// The context has been lost.
+    ContextLost,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::MapError::*;
+        let desc = match *self {
+            NotSupported => "The backend doesn't support mapping this buffer",
+            MappingFailed => "The driver failed to map the buffer",
+            OutOfMemory => "Not enough memory to map the buffer",
+            ContextLost => "The context has been lost",
+        };
+        fmt.write_str(desc)
+    }
+}
+
+impl Error for MapError {}
+
+impl From<MapError> for ReadError {
+    #[inline]
+    fn from(err: MapError) -> ReadError {
+        match err {
+            MapError::ContextLost => ReadError::ContextLost,
+            MapError::OutOfMemory => ReadError::OutOfMemory,
+            MapError::NotSupported | MapError::MappingFailed => ReadError::NotSupported,
+        }
+    }
+}
+
 /// Error that can happen when copying data between buffers.
 #[derive(Debug, Copy, Clone)]
 pub enum CopyError {
@@ -78,6 +128,10 @@ pub struct Alloc {
     /// A pointer to the persistent mapping of this buffer in memory, if there is one.
     persistent_mapping: Option<*mut raw::c_void>,
 
+    /// If true, the persistent mapping was created with `GL_MAP_COHERENT_BIT` and writes through
+    /// it don't need to be flushed. Always false if there is no persistent mapping.
+    coherent: bool,
+
     /// If true, then this buffer can only be modified by calls to `glCopyBufferSubData` or through
     /// the persistent mapping.
     immutable: bool,
@@ -88,13 +142,36 @@ pub struct Alloc {
     /// If true, the buffer was created with `glBufferStorage`.
     created_with_buffer_storage: bool,
 
+    /// If false, the storage of the buffer was allocated without `GL_MAP_READ_BIT` and
+    /// `GL_MAP_WRITE_BIT` and the buffer can't be passed to `glMapBufferRange`.
+    mappable: bool,
+
+    /// If true, the buffer was created with `GL_SPARSE_STORAGE_BIT_ARB` and its pages must be
+    /// committed before being used.
+    sparse: bool,
+
     /// True if the buffer is currently mapped with something else than persistent mapping.
     ///
     /// The purpose of this flag is to detect if the user mem::forgets the `Mapping` object.
     mapped: Cell<bool>,
 
+    /// Ranges of the persistent mapping that are currently mapped for writing. A draw call that
+    /// reads from one of these ranges is a data race.
+    write_mappings: RefCell<Vec<Range<usize>>>,
+
     /// ID of the draw call where the buffer was last written as an SSBO.
     latest_shader_write: Cell<u64>,
+
+    /// Incremented every time the content of the buffer may be modified, by the CPU or by the
+    /// GPU.
+    modifications: Cell<u64>,
+
+    /// If false, the buffer was created outside of glium and must not be destroyed on drop.
+    owned: bool,
+
+    /// Copy in memory of the content of the buffer, for backends that can neither map a buffer
+    /// nor read from it. Kept in sync with every upload so that `map_shadow` can read from it.
+    cpu_copy: RefCell<Option<Vec<u8>>>,
 }
 
 impl Alloc {
@@ -104,13 +181,21 @@ impl Alloc {
                              -> Result<Alloc, BufferCreationError>
                              where D: Content, F: Facade
     {
-        let mut ctxt = facade.get_context().make_current();
-
         let size = mem::size_of_val(data);
 
-        let (id, immutable, created_with_buffer_storage, persistent_mapping) = unsafe {
-            create_buffer(&mut ctxt, size, Some(data), ty, mode)
-        }?;
+        let (id, immutable, created_with_buffer_storage, persistent_mapping) = {
+            let mut ctxt = facade.get_context().make_current();
+            unsafe { create_buffer(&mut ctxt, size, Some(data), ty, mode) }?
+        };
+
+        facade.get_context().notify_buffer_alloc(id, 0, size);
+
+        let cpu_copy = if needs_cpu_copy(&**facade.get_context()) {
+            let bytes = unsafe { slice::from_raw_parts(data.to_void_ptr() as *const u8, size) };
+            Some(bytes.to_vec())
+        } else {
+            None
+        };
 
         Ok(Alloc {
             context: facade.get_context().clone(),
@@ -118,11 +203,19 @@ impl Alloc {
             ty: ty,
             size: size,
             persistent_mapping: persistent_mapping,
+            coherent: persistent_mapping.is_some() &&
+                      (immutable_storage_flags(mode) & gl::MAP_COHERENT_BIT) != 0,
             immutable: immutable,
             created_with_buffer_storage: created_with_buffer_storage,
+            mappable: is_mappable(mode, created_with_buffer_storage),
+            sparse: false,
             creation_mode: mode,
             mapped: Cell::new(false),
+            write_mappings: RefCell::new(Vec::new()),
             latest_shader_write: Cell::new(0),
+            modifications: Cell::new(0),
+            owned: true,
+            cpu_copy: RefCell::new(cpu_copy),
         })
     }
 
@@ -130,11 +223,18 @@ impl Alloc {
     pub fn empty<F: ?Sized>(facade: &F, ty: BufferType, size: usize, mode: BufferMode)
                     -> Result<Alloc, BufferCreationError> where F: Facade
     {
-        let mut ctxt = facade.get_context().make_current();
+        let (id, immutable, created_with_buffer_storage, persistent_mapping) = {
+            let mut ctxt = facade.get_context().make_current();
+            unsafe { create_buffer::<()>(&mut ctxt, size, None, ty, mode) }?
+        };
 
-        let (id, immutable, created_with_buffer_storage, persistent_mapping) = unsafe {
-            create_buffer::<()>(&mut ctxt, size, None, ty, mode)
-        }?;
+        facade.get_context().notify_buffer_alloc(id, 0, size);
+
+        let cpu_copy = if needs_cpu_copy(&**facade.get_context()) {
+            Some(vec![0; size])
+        } else {
+            None
+        };
 
         Ok(Alloc {
             context: facade.get_context().clone(),
@@ -142,14 +242,107 @@ impl Alloc {
             ty: ty,
             size: size,
             persistent_mapping: persistent_mapping,
+            coherent: persistent_mapping.is_some() &&
+                      (immutable_storage_flags(mode) & gl::MAP_COHERENT_BIT) != 0,
             immutable: immutable,
             created_with_buffer_storage: created_with_buffer_storage,
+            mappable: is_mappable(mode, created_with_buffer_storage),
+            sparse: false,
             creation_mode: mode,
             mapped: Cell::new(false),
+            write_mappings: RefCell::new(Vec::new()),
             latest_shader_write: Cell::new(0),
+            modifications: Cell::new(0),
+            owned: true,
+            cpu_copy: RefCell::new(cpu_copy),
         })
     }
 
+    /// Builds a new sparse buffer of the given size. None of its pages are committed.
+    ///
+    /// Sparse buffers can't be mapped directly, so mappings go through a temporary buffer.
+    pub fn empty_sparse<F: ?Sized>(facade: &F, ty: BufferType, size: usize)
+                           -> Result<Alloc, BufferCreationError> where F: Facade
+    {
+        let id = {
+            let mut ctxt = facade.get_context().make_current();
+            unsafe { create_sparse_buffer(&mut ctxt, size, ty) }?
+        };
+
+        facade.get_context().notify_buffer_alloc(id, 0, size);
+
+        Ok(Alloc {
+            context: facade.get_context().clone(),
+            id: id,
+            ty: ty,
+            size: size,
+            persistent_mapping: None,
+            coherent: false,
+            immutable: false,
+            created_with_buffer_storage: true,
+            mappable: false,
+            sparse: true,
+            creation_mode: BufferMode::Default,
+            mapped: Cell::new(false),
+            write_mappings: RefCell::new(Vec::new()),
+            latest_shader_write: Cell::new(0),
+            modifications: Cell::new(0),
+            owned: true,
+            cpu_copy: RefCell::new(None),
+        })
+    }
+
+    /// Adopts a buffer that was created outside of glium, for example by another library.
+    ///
+    /// The buffer is considered to be a regular mutable buffer of `size` bytes that was created
+    /// with `glBufferData`. If `owned` is true, the buffer is destroyed when the `Alloc` is
+    /// dropped.
+    ///
+    /// # Safety
+    ///
+    /// `id` must be the name of a buffer of at least `size` bytes that belongs to the context of
+    /// `facade` or to a context that shares its objects with it.
+    pub unsafe fn from_raw_id<F: ?Sized>(facade: &F, id: gl::types::GLuint, ty: BufferType,
+                                         size: usize, owned: bool) -> Alloc where F: Facade
+    {
+        if owned {
+            facade.get_context().notify_buffer_alloc(id, 0, size);
+        }
+
+        Alloc {
+            context: facade.get_context().clone(),
+            id: id,
+            ty: ty,
+            size: size,
+            persistent_mapping: None,
+            coherent: false,
+            immutable: false,
+            created_with_buffer_storage: false,
+            mappable: true,
+            sparse: false,
+            creation_mode: BufferMode::Default,
+            mapped: Cell::new(false),
+            write_mappings: RefCell::new(Vec::new()),
+            latest_shader_write: Cell::new(0),
+            modifications: Cell::new(0),
+            owned: owned,
+            cpu_copy: RefCell::new(None),
+        }
+    }
+
+    /// Gives up the ownership of the buffer and returns its name.
+    ///
+    /// The buffer is no longer destroyed when the `Alloc` is dropped.
+    #[inline]
+    pub fn release(&mut self) -> gl::types::GLuint {
+        if self.owned {
+            self.owned = false;
+            self.context.notify_buffer_alloc(self.id, self.size, 0);
+        }
+
+        self.id
+    }
+
     /// Returns the context corresponding to this buffer.
     #[inline]
     pub fn get_context(&self) -> &Rc<Context> {
@@ -162,12 +355,113 @@ impl Alloc {
         self.size
     }
 
+    /// Returns the mode that was requested when creating this buffer.
+    #[inline]
+    pub fn get_creation_mode(&self) -> BufferMode {
+        self.creation_mode
+    }
+
+    /// Queries the size in bytes of the storage of this buffer from OpenGL.
+    ///
+    /// Contrary to `get_size`, this isn't the value tracked by glium.
+    pub fn query_size(&self) -> usize {
+        let mut ctxt = self.context.make_current();
+        let mut obtained_size: gl::types::GLint = 0;
+
+        unsafe {
+            if ctxt.version >= &Version(Api::Gl, 4, 5) ||
+               ctxt.extensions.gl_arb_direct_state_access
+            {
+                ctxt.gl.GetNamedBufferParameteriv(self.id, gl::BUFFER_SIZE, &mut obtained_size);
+
+            } else if ctxt.extensions.gl_ext_direct_state_access {
+                ctxt.gl.GetNamedBufferParameterivEXT(self.id, gl::BUFFER_SIZE,
+                                                     &mut obtained_size);
+
+            } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
+                      ctxt.version >= &Version(Api::GlEs, 2, 0)
+            {
+                let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_SIZE, &mut obtained_size);
+
+            } else if ctxt.extensions.gl_arb_vertex_buffer_object {
+                let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                ctxt.gl.GetBufferParameterivARB(bind, gl::BUFFER_SIZE, &mut obtained_size);
+
+            } else {
+                unreachable!();
+            }
+        }
+
+        obtained_size as usize
+    }
+
     /// Returns true if the buffer is persistently mapped in memory.
     #[inline]
     pub fn uses_persistent_mapping(&self) -> bool {
         self.persistent_mapping.is_some()
     }
 
+    /// Returns a number that changes every time the content of the buffer may have been
+    /// modified, by the CPU or by the GPU.
+    #[inline]
+    pub fn get_modifications_count(&self) -> u64 {
+        self.modifications.get()
+    }
+
+    /// Records that the content of the buffer may be modified.
+    #[inline]
+    fn mark_modified(&self) {
+        self.modifications.set(self.modifications.get() + 1);
+    }
+
+    /// Returns true if the buffer is persistently mapped in memory and the mapping is coherent.
+    #[inline]
+    pub fn is_coherent(&self) -> bool {
+        self.coherent
+    }
+
+    /// Returns true if the buffer was created with `empty_sparse`.
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    /// Commits or decommits the physical memory of a range of a sparse buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer is not sparse, if the range is out of range, or if the start and
+    /// the end of the range are not multiples of the page size. The end of the range is also
+    /// allowed to be the end of the buffer.
+    pub fn page_commitment(&self, bytes_range: Range<usize>, commit: bool) {
+        assert!(self.sparse);
+        assert!(bytes_range.start <= bytes_range.end);
+        assert!(bytes_range.end <= self.size);
+
+        let mut ctxt = self.context.make_current();
+
+        let page_size = ctxt.capabilities.sparse_buffer_page_size.unwrap() as usize;
+        assert_eq!(bytes_range.start % page_size, 0);
+        if bytes_range.end != self.size {
+            assert_eq!(bytes_range.end % page_size, 0);
+        }
+
+        if !commit {
+            self.mark_modified();
+        }
+
+        unsafe {
+            self.assert_unmapped(&mut ctxt);
+
+            let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+            ctxt.gl.BufferPageCommitmentARB(bind, bytes_range.start as gl::types::GLintptr,
+                                            (bytes_range.end - bytes_range.start)
+                                                                    as gl::types::GLsizeiptr,
+                                            if commit { gl::TRUE } else { gl::FALSE });
+        }
+    }
+
     /// Changes the type of the buffer. Returns `Err` if this is forbidden.
     pub fn set_type(mut self, ty: BufferType) -> Result<Alloc, Alloc> {
         // FIXME: return Err for GLES2
@@ -198,8 +492,28 @@ impl Alloc {
         }
     }
 
+    /// In debug mode, panics if `bytes_range` overlaps a part of the persistent mapping that is
+    /// currently mapped for writing.
+    ///
+    /// This can only happen if a `Mapping` has been leaked with `mem::forget`, as the borrow
+    /// checker forbids drawing from a buffer while a mapping of it exists.
+    fn debug_assert_not_mapped_for_write(&self, bytes_range: &Range<usize>) {
+        if cfg!(debug_assertions) {
+            for range in self.write_mappings.borrow().iter() {
+                assert!(range.end <= bytes_range.start || range.start >= bytes_range.end,
+                        "The bytes {:?} of the buffer are used by the GPU while they are \
+                         mapped for writing", range);
+            }
+        }
+    }
+
     /// Calls `glMemoryBarrier(GL_VERTEX_ATTRIB_ARRAY_BARRIER_BIT)` if necessary.
-    pub fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext) {
+    ///
+    /// `bytes_range` is the part of the buffer that is going to be read.
+    pub fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext,
+                                           bytes_range: Range<usize>)
+    {
+        self.debug_assert_not_mapped_for_write(&bytes_range);
         self.assert_unmapped(ctxt);
         self.assert_not_transform_feedback(ctxt);
 
@@ -210,7 +524,10 @@ impl Alloc {
     }
 
     /// Calls `glMemoryBarrier(ELEMENT_ARRAY_BARRIER_BIT)` if necessary.
-    pub fn prepare_for_element_array(&self, ctxt: &mut CommandContext) {
+    ///
+    /// `bytes_range` is the part of the buffer that is going to be read.
+    pub fn prepare_for_element_array(&self, ctxt: &mut CommandContext, bytes_range: Range<usize>) {
+        self.debug_assert_not_mapped_for_write(&bytes_range);
         self.assert_unmapped(ctxt);
         self.assert_not_transform_feedback(ctxt);
 
@@ -239,6 +556,7 @@ impl Alloc {
     pub fn prepare_and_bind_for_pixel_pack(&self, ctxt: &mut CommandContext) {
         self.assert_unmapped(ctxt);
         self.assert_not_transform_feedback(ctxt);
+        self.mark_modified();
 
         if self.latest_shader_write.get() >= ctxt.state.latest_memory_barrier_pixel_buffer {
             unsafe { ctxt.gl.MemoryBarrier(gl::PIXEL_BUFFER_BARRIER_BIT); }
@@ -283,6 +601,7 @@ impl Alloc {
 
         self.assert_unmapped(ctxt);
         self.assert_not_transform_feedback(ctxt);
+        self.mark_modified();
 
         if self.latest_shader_write.get() >= ctxt.state.latest_memory_barrier_pixel_buffer {
             unsafe { ctxt.gl.MemoryBarrier(gl::QUERY_BUFFER_BARRIER_BIT); }
@@ -358,6 +677,7 @@ impl Alloc {
         self.indexed_bind(ctxt, BufferType::ShaderStorageBuffer, index, range);
 
         self.latest_shader_write.set(ctxt.state.next_draw_call_id);        // TODO: put this somewhere else
+        self.mark_modified();
     }
 
     /// Makes sure that the buffer is bound to the indexed `GL_ATOMIC_COUNTER_BUFFER` point and calls
@@ -376,6 +696,7 @@ impl Alloc {
         self.indexed_bind(ctxt, BufferType::AtomicCounterBuffer, index, range);
 
         self.latest_shader_write.set(ctxt.state.next_draw_call_id);        // TODO: put this somewhere else
+        self.mark_modified();
     }
 
     /// Binds the buffer to `GL_TRANSFORM_FEEDBACk_BUFFER` regardless of the current transform
@@ -385,6 +706,7 @@ impl Alloc {
                                       range: Range<usize>)
     {
         self.indexed_bind(ctxt, BufferType::TransformFeedbackBuffer, index, range);
+        self.mark_modified();
     }
 
     /// Makes sure that the buffer is bound to a specific bind point.
@@ -395,11 +717,17 @@ impl Alloc {
     ///
     /// Panics if the backend doesn't allow binding this buffer to the specified point.
     #[inline]
-    fn bind(&self, ctxt: &mut CommandContext, ty: BufferType) {
+    pub fn bind(&self, ctxt: &mut CommandContext, ty: BufferType) {
         self.assert_unmapped(ctxt);
         unsafe { bind_buffer(ctxt, self.id, ty); }
     }
 
+    /// Makes sure that nothing is bound to the bind point `ty`.
+    #[inline]
+    pub fn unbind(ctxt: &mut CommandContext, ty: BufferType) {
+        unsafe { bind_buffer(ctxt, 0, ty); }
+    }
+
     /// Makes sure that the buffer is bound to a specific indexed bind point.
     ///
     /// The bind point is the value passed to `ty`.
@@ -434,10 +762,20 @@ impl Alloc {
     pub unsafe fn upload<D: ?Sized>(&self, offset_bytes: usize, data: &D)
                                     where D: Content
     {
-        assert!(offset_bytes + mem::size_of_val(data) <= self.size);
+        let end = offset_bytes.checked_add(mem::size_of_val(data)).expect("Range out of bounds");
+        assert!(end <= self.size);
+        self.mark_modified();
+
+        if let Some(ref mut cpu_copy) = *self.cpu_copy.borrow_mut() {
+            ptr::copy_nonoverlapping(data.to_void_ptr() as *const u8,
+                                     cpu_copy[offset_bytes ..].as_mut_ptr(),
+                                     mem::size_of_val(data));
+        }
 
         if self.persistent_mapping.is_some() {
-            let mapping = Mapping { mapping: self.map_shared(offset_bytes .. offset_bytes + mem::size_of_val(data), false, true) };
+            let range = offset_bytes .. offset_bytes + mem::size_of_val(data);
+            // mapping a persistent-mapped buffer never fails
+            let mapping = Mapping { mapping: self.map_shared(range, false, true).unwrap() };
             ptr::copy_nonoverlapping(data.to_void_ptr() as *const u8, <D as Content>::to_void_ptr(&mapping) as *mut u8, mem::size_of_val(data));
 
         } else if self.immutable {
@@ -470,29 +808,23 @@ impl Alloc {
                 ctxt.gl.InvalidateBufferData(self.id);
             }
 
+            let (offset, size) = gl_range(offset_bytes .. offset_bytes + mem::size_of_val(data));
+
             if ctxt.version >= &Version(Api::Gl, 4, 5) {
-                ctxt.gl.NamedBufferSubData(self.id, offset_bytes as gl::types::GLintptr,
-                                           mem::size_of_val(data) as gl::types::GLsizeiptr,
-                                           data.to_void_ptr() as *const _)
+                ctxt.gl.NamedBufferSubData(self.id, offset, size, data.to_void_ptr() as *const _)
 
             } else if ctxt.extensions.gl_ext_direct_state_access {
-                ctxt.gl.NamedBufferSubDataEXT(self.id, offset_bytes as gl::types::GLintptr,
-                                              mem::size_of_val(data) as gl::types::GLsizeiptr,
-                                              data.to_void_ptr() as *const _)
+                ctxt.gl.NamedBufferSubDataEXT(self.id, offset, size, data.to_void_ptr() as *const _)
 
             } else if ctxt.version >= &Version(Api::Gl, 1, 5) ||
                 ctxt.version >= &Version(Api::GlEs, 2, 0)
             {
                 let bind = bind_buffer(&mut ctxt, self.id, self.ty);
-                ctxt.gl.BufferSubData(bind, offset_bytes as gl::types::GLintptr,
-                                      mem::size_of_val(data) as gl::types::GLsizeiptr,
-                                      data.to_void_ptr() as *const _);
+                ctxt.gl.BufferSubData(bind, offset, size, data.to_void_ptr() as *const _);
 
             } else if ctxt.extensions.gl_arb_vertex_buffer_object {
                 let bind = bind_buffer(&mut ctxt, self.id, self.ty);
-                ctxt.gl.BufferSubDataARB(bind, offset_bytes as gl::types::GLintptr,
-                                         mem::size_of_val(data) as gl::types::GLsizeiptr,
-                                         data.to_void_ptr() as *const _);
+                ctxt.gl.BufferSubDataARB(bind, offset, size, data.to_void_ptr() as *const _);
 
             } else {
                 unreachable!();
@@ -510,6 +842,7 @@ impl Alloc {
     ///
     pub fn invalidate(&self, offset: usize, size: usize) {
         assert!(offset + size <= self.size);
+        self.mark_modified();
 
         let is_whole_buffer = offset == 0 && size == self.size;
 
@@ -530,8 +863,10 @@ impl Alloc {
         } else if !self.created_with_buffer_storage {
             if is_whole_buffer {
                 let flags = match self.creation_mode {
-                    BufferMode::Default | BufferMode::Immutable => gl::STATIC_DRAW,
-                    BufferMode::Persistent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
+                    BufferMode::Default | BufferMode::Immutable |
+                    BufferMode::ImmutableClientStorage => gl::STATIC_DRAW,
+                    BufferMode::Persistent | BufferMode::PersistentClientStorage |
+                    BufferMode::PersistentCoherent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
                 };
 
                 if ctxt.version >= &Version(Api::Gl, 1, 5) ||
@@ -574,6 +909,8 @@ impl Alloc {
     ///
     /// Contrary to `map_mut`, this function only requires a `&self`.
     ///
+    /// Returns an error if the temporary buffer can't be created or mapped.
+    ///
     /// # Panic
     ///
     /// Panics if the `bytes_range` is not aligned to a mappable slice.
@@ -587,7 +924,7 @@ impl Alloc {
     /// `false` for `write`, you **must not** write the returned buffer.
     ///
     unsafe fn map_shared<D: ?Sized>(&self, bytes_range: Range<usize>, read: bool, write: bool)
-                                    -> MappingImpl<D> where D: Content
+                                    -> Result<MappingImpl<D>, MapError> where D: Content
     {
         if let Some(existing_mapping) = self.persistent_mapping.clone() {
             // TODO: optimize so that it's not always necessary to make the context current
@@ -598,12 +935,20 @@ impl Alloc {
             let data = Content::ref_from_ptr(data as *mut (),
                                              bytes_range.end - bytes_range.start).unwrap();
 
-            MappingImpl::PersistentMapping {
+            if write {
+                self.write_mappings.borrow_mut().push(bytes_range.clone());
+            }
+
+            Ok(MappingImpl::PersistentMapping {
                 buffer: self,
                 offset_bytes: bytes_range.start,
                 data: data,
                 needs_flushing: write,
-            }
+            })
+
+        } else if bytes_range.start == bytes_range.end {
+            // `glMapBufferRange` returns a null pointer for an empty range
+            self.map_shadow(bytes_range, false, write)
 
         } else {
             let size_bytes = bytes_range.end - bytes_range.start;
@@ -613,11 +958,13 @@ impl Alloc {
             // we have to construct a temporary buffer that we will map in memory
             // then after the Mapping is destroyed, we will copy from the temporary buffer to the
             // real one
-            let temporary_buffer = {
-                let (temporary_buffer, _, _, _) = create_buffer::<D>(&mut ctxt, size_bytes,
-                                                                     None, BufferType::CopyWriteBuffer,
-                                                                     BufferMode::Dynamic).unwrap();
-                temporary_buffer
+            let temporary_buffer = match create_buffer::<D>(&mut ctxt, size_bytes, None,
+                                                            BufferType::CopyWriteBuffer,
+                                                            BufferMode::Dynamic)
+            {
+                Ok((temporary_buffer, _, _, _)) => temporary_buffer,
+                Err(BufferCreationError::ContextLost) => return Err(MapError::ContextLost),
+                Err(_) => return Err(MapError::OutOfMemory),
             };
 
             let ptr = {
@@ -629,8 +976,15 @@ impl Alloc {
                                 temporary_buffer, 0, size_bytes).unwrap();
                 }
 
-                map_buffer(&mut ctxt, temporary_buffer, self.ty, 0 .. size_bytes, true, true)
-                                    .expect("Buffer mapping is not supported by the backend")
+                match map_buffer(&mut ctxt, temporary_buffer, self.ty, 0 .. size_bytes, true,
+                                 true, Invalidate::No)
+                {
+                    Ok(ptr) => ptr,
+                    Err(err) => {
+                        destroy_buffer(&mut ctxt, temporary_buffer);
+                        return Err(err);
+                    }
+                }
             };
 
             let data = match Content::ref_from_ptr(ptr, bytes_range.end - bytes_range.start) {
@@ -641,13 +995,13 @@ impl Alloc {
                 }
             };
 
-            MappingImpl::TemporaryBuffer {
+            Ok(MappingImpl::TemporaryBuffer {
                 original_buffer: self,
                 original_buffer_offset: bytes_range.start,
                 temporary_buffer: temporary_buffer,
                 temporary_buffer_data: data,
                 needs_flushing: write,
-            }
+            })
         }
     }
 
@@ -657,11 +1011,15 @@ impl Alloc {
     ///
     ///  - If the buffer uses persistent mapping, it will simply return a wrapper around the
     ///    pointer to the existing mapping.
-    ///  - If the buffer doesn't use persistent mapping, it will map the buffer.
+    ///  - If the buffer doesn't use persistent mapping, it will map the buffer. Buffers that
+    ///    can't be mapped, like immutable buffers, are mapped through a temporary buffer
+    ///    like in `map_shared`.
     ///
     /// Contrary to `map_shared`, this function requires a `&mut self`. It can only be used if
     /// you have exclusive access to the buffer.
     ///
+    /// Returns an error if the driver fails to map the buffer.
+    ///
     /// # Panic
     ///
     /// Panics if the `bytes_range` is not aligned to a mappable slice.
@@ -674,12 +1032,27 @@ impl Alloc {
     /// If you pass `false` for `read`, you **must not** read the returned buffer. If you pass
     /// `false` for `write`, you **must not** write the returned buffer.
     ///
-    unsafe fn map_impl<D: ?Sized>(&mut self, bytes_range: Range<usize>, read: bool, write: bool)
-                                  -> MappingImpl<D> where D: Content
+    unsafe fn map_impl<D: ?Sized>(&mut self, bytes_range: Range<usize>, read: bool, write: bool,
+                                  invalidate: Invalidate) -> Result<MappingImpl<D>, MapError>
+                                  where D: Content
     {
-        if self.persistent_mapping.is_some() || self.immutable {
+        if write {
+            self.mark_modified();
+        }
+
+        // `glMapBufferRange` returns a null pointer for an empty range, so we don't call it
+        if bytes_range.start == bytes_range.end {
+            return self.map_shadow(bytes_range, false, write);
+        }
+
+        // buffers that were created without the map flags can't be mapped directly, so they
+        // go through a temporary buffer as well
+        if self.persistent_mapping.is_some() || self.immutable || !self.mappable {
             self.map_shared(bytes_range, read, write)
 
+        } else if !is_map_buffer_range_supported(&*self.context) {
+            self.map_shadow(bytes_range, read, write)
+
         } else {
             let data = {
                 let mut ctxt = self.context.make_current();
@@ -689,8 +1062,7 @@ impl Alloc {
                     self.assert_not_transform_feedback(&mut ctxt);
                     self.barrier_for_buffer_update(&mut ctxt);
                     let ptr = map_buffer(&mut ctxt, self.id, self.ty, bytes_range.clone(),
-                                         read, write)
-                                        .expect("Buffer mapping is not supported by the backend");
+                                         read, write, invalidate)?;
                     self.mapped.set(true);
                     ptr
                 };
@@ -704,12 +1076,56 @@ impl Alloc {
                 }
             };
 
-            MappingImpl::RegularMapping {
+            Ok(MappingImpl::RegularMapping {
                 buffer: self,
                 data: data,
                 needs_flushing: write,
+            })
+        }
+    }
+
+    /// Returns a mapping of a copy in memory of the content of the buffer, for backends that
+    /// don't support `glMapBufferRange` and for empty ranges.
+    ///
+    /// The content is read with `glGetBufferSubData`, or from the copy in memory on backends
+    /// that can't read from a buffer, and uploaded back with `glBufferSubData` when the mapping
+    /// is destroyed if `write` is true.
+    ///
+    /// Returns `MapError::NotSupported` if `read` is true, the backend doesn't support reading
+    /// from a buffer and the buffer was adopted with `from_raw_id`, as its content is unknown to
+    /// glium.
+    ///
+    unsafe fn map_shadow<D: ?Sized>(&self, bytes_range: Range<usize>, read: bool, write: bool)
+                                    -> Result<MappingImpl<D>, MapError> where D: Content
+    {
+        let size_bytes = bytes_range.end - bytes_range.start;
+        let mut storage = vec![0u64; (size_bytes + 7) / 8];
+
+        if read {
+            if let Some(ref cpu_copy) = *self.cpu_copy.borrow() {
+                ptr::copy_nonoverlapping(cpu_copy[bytes_range.clone()].as_ptr(),
+                                         storage.as_mut_ptr() as *mut u8, size_bytes);
+            } else {
+                let content = match self.read::<[u8]>(bytes_range.clone()) {
+                    Ok(content) => content,
+                    Err(ReadError::ContextLost) => return Err(MapError::ContextLost),
+                    Err(_) => return Err(MapError::NotSupported),
+                };
+                ptr::copy_nonoverlapping(content.as_ptr(), storage.as_mut_ptr() as *mut u8,
+                                         size_bytes);
             }
         }
+
+        let data = Content::ref_from_ptr(storage.as_mut_ptr() as *mut (), size_bytes)
+                                        .expect("Wrong bytes range");
+
+        Ok(MappingImpl::ShadowCopy {
+            buffer: self,
+            offset_bytes: bytes_range.start,
+            storage: storage,
+            data: data,
+            needs_flushing: write,
+        })
     }
 
     /// Returns a read and write mapping in memory of the content of the buffer.
@@ -725,11 +1141,11 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                 -> Mapping<D> where D: Content
+                                 -> Result<Mapping<D>, MapError> where D: Content
     {
-        Mapping {
-            mapping: self.map_impl(bytes_range, true, true)
-        }
+        Ok(Mapping {
+            mapping: self.map_impl(bytes_range, true, true, Invalidate::No)?
+        })
     }
 
     /// Returns a read-only mapping in memory of the content of the buffer.
@@ -745,11 +1161,11 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map_read<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                      -> ReadMapping<D> where D: Content
+                                      -> Result<ReadMapping<D>, MapError> where D: Content
     {
-        ReadMapping {
-            mapping: self.map_impl(bytes_range, true, false)
-        }
+        Ok(ReadMapping {
+            mapping: self.map_impl(bytes_range, true, false, Invalidate::No)?
+        })
     }
 
     /// Returns a write-only mapping in memory of the content of the buffer.
@@ -765,11 +1181,58 @@ impl Alloc {
     ///
     #[inline]
     pub unsafe fn map_write<D: ?Sized>(&mut self, bytes_range: Range<usize>)
-                                       -> WriteMapping<D> where D: Content
+                                       -> Result<WriteMapping<D>, MapError> where D: Content
     {
-        WriteMapping {
-            mapping: self.map_impl(bytes_range, false, true)
-        }
+        Ok(WriteMapping {
+            mapping: self.map_impl(bytes_range, false, true, Invalidate::No)?
+        })
+    }
+
+    /// Returns a write-only mapping in memory of the whole content of the buffer, and discards
+    /// its previous content.
+    ///
+    /// The previous content of the buffer is undefined after this call, and so is the content
+    /// of the mapping until it is written.
+    ///
+    /// # Unsafety
+    ///
+    /// If the buffer uses persistent mapping, the caller of this function must handle
+    /// synchronization.
+    ///
+    #[inline]
+    pub unsafe fn map_write_invalidate<D: ?Sized>(&mut self) -> Result<WriteMapping<D>, MapError>
+                                                  where D: Content
+    {
+        let size = self.size;
+        Ok(WriteMapping {
+            mapping: self.map_impl(0 .. size, false, true, Invalidate::Buffer)?
+        })
+    }
+
+    /// Returns a write-only mapping in memory of a range of the buffer, and discards the
+    /// previous content of this range without waiting for the GPU.
+    ///
+    /// The buffer is mapped with `GL_MAP_INVALIDATE_RANGE_BIT` and `GL_MAP_UNSYNCHRONIZED_BIT`.
+    /// The content of the range is undefined after this call, and so is the content of the
+    /// mapping until it is written.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the `bytes_range` is not aligned to a mappable slice.
+    ///
+    /// # Unsafety
+    ///
+    /// The caller of this function must handle synchronization, even if the buffer doesn't
+    /// use persistent mapping.
+    ///
+    #[inline]
+    pub unsafe fn map_write_invalidate_range<D: ?Sized>(&mut self, bytes_range: Range<usize>)
+                                                        -> Result<WriteMapping<D>, MapError>
+                                                        where D: Content
+    {
+        Ok(WriteMapping {
+            mapping: self.map_impl(bytes_range, false, true, Invalidate::RangeUnsynchronized)?
+        })
     }
 
     /// Reads the content of the buffer.
@@ -787,10 +1250,11 @@ impl Alloc {
                                   -> Result<D::Owned, ReadError>
                                   where D: Content
     {
+        assert!(range.start <= range.end && range.end <= self.size);
         let size_to_read = range.end - range.start;
 
         if self.persistent_mapping.is_some() {
-            let mapping = ReadMapping { mapping: self.map_shared(range, true, false) };
+            let mapping = ReadMapping { mapping: self.map_shared(range, true, false)? };
             <D as Content>::read(size_to_read, |output| {
                 ptr::copy_nonoverlapping(<D as Content>::to_void_ptr(&mapping) as *const u8, output as *mut D as *mut u8, size_to_read);
                 Ok(())
@@ -806,23 +1270,19 @@ impl Alloc {
             self.assert_unmapped(&mut ctxt);
             self.barrier_for_buffer_update(&mut ctxt);
 
+            let (offset, size) = gl_range(range);
+
             <D as Content>::read(size_to_read, |output| {
                 if ctxt.version >= &Version(Api::Gl, 4, 5) {
-                    ctxt.gl.GetNamedBufferSubData(self.id, range.start as gl::types::GLintptr,
-                                                  size_to_read as gl::types::GLsizeiptr,
-                                                  output as *mut _ as *mut _);
+                    ctxt.gl.GetNamedBufferSubData(self.id, offset, size, output as *mut _ as *mut _);
 
                 } else if ctxt.version >= &Version(Api::Gl, 1, 5) {
                     let bind = bind_buffer(&mut ctxt, self.id, self.ty);
-                    ctxt.gl.GetBufferSubData(bind, range.start as gl::types::GLintptr,
-                                             size_to_read as gl::types::GLsizeiptr,
-                                             output as *mut _ as *mut _);
+                    ctxt.gl.GetBufferSubData(bind, offset, size, output as *mut _ as *mut _);
 
                 } else if ctxt.extensions.gl_arb_vertex_buffer_object {
                     let bind = bind_buffer(&mut ctxt, self.id, self.ty);
-                    ctxt.gl.GetBufferSubDataARB(bind, range.start as gl::types::GLintptr,
-                                                size_to_read as gl::types::GLsizeiptr,
-                                                output as *mut _ as *mut _);
+                    ctxt.gl.GetBufferSubDataARB(bind, offset, size, output as *mut _ as *mut _);
 
                 } else if ctxt.version >= &Version(Api::GlEs, 1, 0) {
                     return Err(ReadError::NotSupported);
@@ -853,13 +1313,80 @@ impl Alloc {
         assert!(range.end >= range.start);
         assert!(range.end <= self.size);
         assert!(dest_offset + range.end - range.start <= target.size);
+        target.mark_modified();
 
         let mut ctxt = self.context.make_current();
 
         unsafe {
             copy_buffer(&mut ctxt, self.id, range.start, target.id, dest_offset,
-                        range.end - range.start)
+                        range.end - range.start)?;
+        }
+
+        if target.cpu_copy.borrow().is_some() {
+            let copied = self.cpu_copy.borrow().as_ref().map(|c| c[range.clone()].to_vec());
+            let mut target_copy = target.cpu_copy.borrow_mut();
+            match copied {
+                Some(copied) => {
+                    let dest = dest_offset .. dest_offset + copied.len();
+                    target_copy.as_mut().unwrap()[dest].copy_from_slice(&copied);
+                },
+                // the content of the source is unknown, so is the content of the target
+                None => *target_copy = None,
+            }
         }
+
+        Ok(())
+    }
+
+    /// Fills the whole buffer with repetitions of `pattern` with `glClearBufferData`.
+    ///
+    /// Returns `false` and does nothing if the backend doesn't support `glClearBufferData` or if
+    /// the length of `pattern` doesn't correspond to a format that it accepts (1, 2, 4, 8, 12 or
+    /// 16 bytes).
+    ///
+    /// Returns `true` and does nothing if the buffer or the pattern is empty.
+    pub fn clear_with_pattern(&self, pattern: &[u8]) -> bool {
+        // an empty buffer is allocated with a size of 1 byte, which isn't a multiple of most
+        // patterns
+        if self.size == 0 || pattern.is_empty() {
+            return true;
+        }
+
+        assert_eq!(self.size % pattern.len(), 0);
+
+        let (internal_format, format, ty) = match pattern.len() {
+            1 => (gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE),
+            2 => (gl::R16UI, gl::RED_INTEGER, gl::UNSIGNED_SHORT),
+            4 => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT),
+            8 => (gl::RG32UI, gl::RG_INTEGER, gl::UNSIGNED_INT),
+            12 => (gl::RGB32UI, gl::RGB_INTEGER, gl::UNSIGNED_INT),
+            16 => (gl::RGBA32UI, gl::RGBA_INTEGER, gl::UNSIGNED_INT),
+            _ => return false
+        };
+
+        let mut ctxt = self.context.make_current();
+        self.assert_unmapped(&mut ctxt);
+        self.assert_not_transform_feedback(&mut ctxt);
+        self.mark_modified();
+
+        unsafe {
+            if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+                ctxt.gl.ClearNamedBufferData(self.id, internal_format, format, ty,
+                                             pattern.as_ptr() as *const _);
+
+            } else if ctxt.version >= &Version(Api::Gl, 4, 3) ||
+                      ctxt.extensions.gl_arb_clear_buffer_object
+            {
+                let bind = bind_buffer(&mut ctxt, self.id, self.ty);
+                ctxt.gl.ClearBufferData(bind, internal_format, format, ty,
+                                        pattern.as_ptr() as *const _);
+
+            } else {
+                return false;
+            }
+        }
+
+        true
     }
 }
 
@@ -876,8 +1403,15 @@ impl Drop for Alloc {
             self.assert_unmapped(&mut ctxt);
             self.assert_not_transform_feedback(&mut ctxt);
             VertexAttributesSystem::purge_buffer(&mut ctxt, self.id);
+
+            if !self.owned {
+                return;
+            }
+
             destroy_buffer(&mut ctxt, self.id);
         }
+
+        self.context.notify_buffer_alloc(self.id, self.size, 0);
     }
 }
 
@@ -912,6 +1446,15 @@ enum MappingImpl<'b, D: ?Sized> {
         data: *mut D,
         needs_flushing: bool,
     },
+
+    ShadowCopy {
+        buffer: &'b Alloc,
+        offset_bytes: usize,
+        // copy of the content in memory, made of `u64`s so that it is suitably aligned
+        storage: Vec<u64>,
+        data: *mut D,
+        needs_flushing: bool,
+    },
 }
 
 unsafe impl<'a, D: ?Sized> Sync for MappingImpl<'a, D> where D: Send + Sync {}
@@ -920,11 +1463,18 @@ impl<'a, D: ?Sized> Drop for MappingImpl<'a, D> {
     fn drop(&mut self) {
         match self {
             &mut MappingImpl::PersistentMapping { buffer, offset_bytes, data, needs_flushing } => {
+                let range = offset_bytes .. offset_bytes + mem::size_of_val(unsafe { &*data });
                 let mut ctxt = buffer.context.make_current();
                 unsafe {
-                    if needs_flushing {
-                        flush_range(&mut ctxt, buffer.id, buffer.ty,
-                                    offset_bytes .. offset_bytes + mem::size_of_val(&*data));
+                    if needs_flushing && !buffer.coherent {
+                        flush_range(&mut ctxt, buffer.id, buffer.ty, range.clone());
+                    }
+                }
+
+                if needs_flushing {
+                    let mut write_mappings = buffer.write_mappings.borrow_mut();
+                    if let Some(pos) = write_mappings.iter().position(|r| *r == range) {
+                        write_mappings.remove(pos);
                     }
                 }
             },
@@ -964,11 +1514,27 @@ impl<'a, D: ?Sized> Drop for MappingImpl<'a, D> {
 
                 buffer.mapped.set(false);
             },
+
+            &mut MappingImpl::ShadowCopy { buffer, offset_bytes, data, needs_flushing, .. } => {
+                let size_bytes = mem::size_of_val(unsafe { &*data });
+                if needs_flushing && size_bytes != 0 {
+                    unsafe {
+                        let bytes = slice::from_raw_parts(data as *const u8, size_bytes);
+                        buffer.upload(offset_bytes, bytes);
+                    }
+                }
+            },
         }
     }
 }
 
 /// A mapping of a buffer for reading and writing.
+///
+/// For persistent-mapped buffers, creating the mapping waits for the fences that glium inserts
+/// after each command that uses the buffer, so you never write data that the GPU is still
+/// reading. Dropping the mapping flushes the written range if the mapping isn't coherent, and
+/// the commands submitted afterwards see the new content. These commands insert their own
+/// fences, which the next mapping will wait for.
 pub struct Mapping<'b, D: ?Sized> where D: Content {
     mapping: MappingImpl<'b, D>,
 }
@@ -990,6 +1556,10 @@ impl<'a, D: ?Sized> Deref for Mapping<'a, D> where D: Content {
             MappingImpl::RegularMapping { data, .. } => {
                 unsafe { &*data }
             },
+
+            MappingImpl::ShadowCopy { data, .. } => {
+                unsafe { &*data }
+            },
         }
     }
 }
@@ -1009,6 +1579,10 @@ impl<'a, D: ?Sized> DerefMut for Mapping<'a, D> where D: Content {
             MappingImpl::RegularMapping { data, .. } => {
                 unsafe { &mut *data }
             },
+
+            MappingImpl::ShadowCopy { data, .. } => {
+                unsafe { &mut *data }
+            },
         }
     }
 }
@@ -1035,6 +1609,10 @@ impl<'a, D: ?Sized> Deref for ReadMapping<'a, D> where D: Content {
             MappingImpl::RegularMapping { data, .. } => {
                 unsafe { &*data }
             },
+
+            MappingImpl::ShadowCopy { data, .. } => {
+                unsafe { &*data }
+            },
         }
     }
 }
@@ -1059,6 +1637,10 @@ impl<'b, D: ?Sized> WriteMapping<'b, D> where D: Content {
             MappingImpl::RegularMapping { data, .. } => {
                 unsafe { &mut *data }
             },
+
+            MappingImpl::ShadowCopy { data, .. } => {
+                unsafe { &mut *data }
+            },
         }
     }
 }
@@ -1080,6 +1662,7 @@ impl<'b, D> WriteMapping<'b, [D]> where [D]: Content, D: Copy {
             MappingImpl::PersistentMapping { data, .. } => unsafe { (&*data).len() },
             MappingImpl::TemporaryBuffer { temporary_buffer_data, .. } => unsafe { (&*temporary_buffer_data).len() },
             MappingImpl::RegularMapping { data, .. } => unsafe { (&*data).len() },
+            MappingImpl::ShadowCopy { data, .. } => unsafe { (&*data).len() },
         }
     }
 
@@ -1115,6 +1698,87 @@ pub fn is_buffer_read_supported<C: ?Sized>(ctxt: &C) -> bool where C: Capabiliti
     }
 }
 
+/// Returns true if the backend supports persistent-mapped buffers.
+///
+/// This is the case with OpenGL 4.4, with `GL_ARB_buffer_storage` or, on OpenGL ES 3.1, with
+/// `GL_EXT_buffer_storage`. If this returns false, buffers created with
+/// `BufferMode::Persistent` fall back to regular buffers.
+pub fn is_persistent_mapping_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    is_buffer_storage_supported(ctxt)
+}
+
+/// Returns true if the backend supports `glBufferStorage`, ie. if buffers can have an immutable
+/// storage.
+///
+/// This is the case with OpenGL 4.4, with `GL_ARB_buffer_storage` or, on OpenGL ES 3.1, with
+/// `GL_EXT_buffer_storage`.
+pub fn is_buffer_storage_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_version() >= &Version(Api::Gl, 4, 4) ||
+        ctxt.get_extensions().gl_arb_buffer_storage ||
+        (ctxt.get_version() >= &Version(Api::GlEs, 3, 1) &&
+         ctxt.get_extensions().gl_ext_buffer_storage)
+}
+
+/// Returns true if the backend supports sparse buffers.
+pub fn is_sparse_buffer_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_extensions().gl_arb_sparse_buffer &&
+        (ctxt.get_version() >= &Version(Api::Gl, 4, 4) ||
+         ctxt.get_extensions().gl_arb_buffer_storage)
+}
+
+/// Creates a new sparse buffer with `glBufferStorage` and returns its ID.
+unsafe fn create_sparse_buffer(mut ctxt: &mut CommandContext, size: usize, ty: BufferType)
+                               -> Result<gl::types::GLuint, BufferCreationError>
+{
+    if !is_sparse_buffer_supported(ctxt) {
+        return Err(BufferCreationError::NotSupported);
+    }
+
+    if !is_buffer_type_supported(ctxt, ty) {
+        return Err(BufferCreationError::BufferTypeNotSupported);
+    }
+
+    if size > isize::MAX as usize {
+        return Err(BufferCreationError::FormatTooLarge);
+    }
+
+    let mut id: gl::types::GLuint = 0;
+    if ctxt.version >= &Version(Api::Gl, 4, 5) || ctxt.extensions.gl_arb_direct_state_access {
+        ctxt.gl.CreateBuffers(1, &mut id);
+    } else {
+        ctxt.gl.GenBuffers(1, &mut id);
+    }
+
+    // `glBufferStorage` doesn't accept a size of 0, so we use 1 instead like in `create_buffer`
+    // note that according to glium the size of the buffer will remain 0
+    let size = match size {
+        0 => 1,
+        a => a
+    };
+
+    let bind = bind_buffer(&mut ctxt, id, ty);
+    ctxt.gl.BufferStorage(bind, size as gl::types::GLsizeiptr, ptr::null(),
+                          gl::SPARSE_STORAGE_BIT_ARB | gl::DYNAMIC_STORAGE_BIT);
+
+    // sparse buffers are typically larger than 2 GiB, which doesn't fit in a `GLint`
+    let obtained_size = if ctxt.version >= &Version(Api::Gl, 3, 2) {
+        let mut obtained_size: gl::types::GLint64 = 0;
+        ctxt.gl.GetBufferParameteri64v(bind, gl::BUFFER_SIZE, &mut obtained_size);
+        obtained_size as usize
+    } else {
+        let mut obtained_size: gl::types::GLint = 0;
+        ctxt.gl.GetBufferParameteriv(bind, gl::BUFFER_SIZE, &mut obtained_size);
+        obtained_size as usize
+    };
+
+    if size != obtained_size {
+        ctxt.gl.DeleteBuffers(1, [id].as_ptr());
+        return Err(BufferCreationError::OutOfMemory);
+    }
+
+    Ok(id)
+}
+
 /// Creates a new buffer.
 ///
 /// # Panic
@@ -1126,6 +1790,10 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
                                              BufferCreationError>
                                    where D: Content
 {
+    if ctxt.state.lost_context {
+        return Err(BufferCreationError::ContextLost);
+    }
+
     if !is_buffer_type_supported(ctxt, ty) {
         return Err(BufferCreationError::BufferTypeNotSupported);
     }
@@ -1134,6 +1802,10 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         assert!(mem::size_of_val(data) == size);
     }
 
+    if size > isize::MAX as usize {
+        return Err(BufferCreationError::FormatTooLarge);
+    }
+
     // creating the id of the buffer
     let id = {
         let mut id: gl::types::GLuint = 0;
@@ -1171,23 +1843,21 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
 
     // the flags to use in the case where only `glBufferData` is supported
     let mutable_storage_flags = match mode {
-        BufferMode::Persistent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
-        BufferMode::Default | BufferMode::Immutable => gl::STATIC_DRAW,
+        BufferMode::Persistent | BufferMode::PersistentClientStorage |
+        BufferMode::PersistentCoherent | BufferMode::Dynamic => gl::DYNAMIC_DRAW,
+        BufferMode::Default | BufferMode::Immutable |
+        BufferMode::ImmutableClientStorage => gl::STATIC_DRAW,
     };
 
     // the flags to use if `glBufferStorage` is supported
-    let immutable_storage_flags = match mode {
-        BufferMode::Default => gl::DYNAMIC_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
-        BufferMode::Dynamic => gl::DYNAMIC_STORAGE_BIT | gl::CLIENT_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
-        BufferMode::Persistent => gl::MAP_PERSISTENT_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
-        BufferMode::Immutable => 0,
-    };
+    let immutable_storage_flags = immutable_storage_flags(mode);
 
     // if true, there is a possibility that the buffer won't be modifiable with regular OpenGL
     // function calls
     let could_be_immutable = match mode {
         BufferMode::Default | BufferMode::Dynamic => false,
-        BufferMode::Immutable | BufferMode::Persistent => true,
+        BufferMode::Immutable | BufferMode::Persistent | BufferMode::ImmutableClientStorage |
+        BufferMode::PersistentClientStorage | BufferMode::PersistentCoherent => true,
     };
 
     // will store the actual size of the buffer so that we can compare it with the expected size
@@ -1229,7 +1899,9 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
         immutable = could_be_immutable;
         created_with_buffer_storage = true;
 
-    } else if ctxt.extensions.gl_ext_buffer_storage {
+    } else if ctxt.version >= &Version(Api::GlEs, 3, 1) &&
+              ctxt.extensions.gl_ext_buffer_storage
+    {
         let bind = bind_buffer(&mut ctxt, id, ty);
         ctxt.gl.BufferStorageEXT(bind, size as gl::types::GLsizeiptr,
                                  data_ptr as *const _,
@@ -1271,23 +1943,35 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
             unreachable!();
         }
 
+        // the size is also wrong if the context was lost during the allocation
+        if ::get_gl_error(ctxt) == Some("GL_CONTEXT_LOST") {
+            return Err(BufferCreationError::ContextLost);
+        }
+
         return Err(BufferCreationError::OutOfMemory);
     }
 
-    let persistent_mapping = if let BufferMode::Persistent = mode {
+    let persistent_mapping = if mode == BufferMode::Persistent ||
+                                mode == BufferMode::PersistentClientStorage ||
+                                mode == BufferMode::PersistentCoherent
+    {
         if immutable {
+            // the mapping must be flushed explicitly unless the storage is coherent
+            let map_flags = gl::MAP_READ_BIT | gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT |
+                            match immutable_storage_flags & gl::MAP_COHERENT_BIT {
+                                0 => gl::MAP_FLUSH_EXPLICIT_BIT,
+                                _ => gl::MAP_COHERENT_BIT,
+                            };
+
             let ptr = if ctxt.version >= &Version(Api::Gl, 4, 5) {
-                ctxt.gl.MapNamedBufferRange(id, 0, size as gl::types::GLsizeiptr,
-                                            gl::MAP_READ_BIT | gl::MAP_WRITE_BIT |
-                                            gl::MAP_PERSISTENT_BIT | gl::MAP_FLUSH_EXPLICIT_BIT)
+                ctxt.gl.MapNamedBufferRange(id, 0, size as gl::types::GLsizeiptr, map_flags)
 
             } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                      ctxt.version >= &Version(Api::GlEs, 3, 0) ||
                       ctxt.extensions.gl_arb_map_buffer_range
             {
                 let bind = bind_buffer(&mut ctxt, id, ty);
-                ctxt.gl.MapBufferRange(bind, 0, size as gl::types::GLsizeiptr,
-                                       gl::MAP_READ_BIT | gl::MAP_WRITE_BIT |
-                                       gl::MAP_PERSISTENT_BIT | gl::MAP_FLUSH_EXPLICIT_BIT)
+                ctxt.gl.MapBufferRange(bind, 0, size as gl::types::GLsizeiptr, map_flags)
             } else {
                 unreachable!();
             };
@@ -1309,6 +1993,31 @@ unsafe fn create_buffer<D: ?Sized>(mut ctxt: &mut CommandContext, size: usize, d
     Ok((id, immutable, created_with_buffer_storage, persistent_mapping))
 }
 
+/// Returns the flags to pass to `glBufferStorage` when creating a buffer with the given mode.
+fn immutable_storage_flags(mode: BufferMode) -> gl::types::GLbitfield {
+    match mode {
+        BufferMode::Default => gl::DYNAMIC_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::Dynamic => gl::DYNAMIC_STORAGE_BIT | gl::CLIENT_STORAGE_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::Persistent => gl::MAP_PERSISTENT_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::Immutable => 0,
+        BufferMode::PersistentClientStorage => gl::MAP_PERSISTENT_BIT | gl::CLIENT_STORAGE_BIT |
+                                               gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::PersistentCoherent => gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT |
+                                          gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        BufferMode::ImmutableClientStorage => gl::CLIENT_STORAGE_BIT,
+    }
+}
+
+/// Returns true if a buffer created with the given mode can be mapped with `glMapBufferRange`.
+///
+/// Buffers whose storage was allocated by `glBufferStorage` can only be mapped if they were
+/// created with `GL_MAP_READ_BIT` or `GL_MAP_WRITE_BIT`, which is not the case of immutable
+/// buffers. Those must go through a temporary buffer instead.
+fn is_mappable(mode: BufferMode, created_with_buffer_storage: bool) -> bool {
+    immutable_storage_flags(mode) & (gl::MAP_READ_BIT | gl::MAP_WRITE_BIT) != 0 ||
+    !created_with_buffer_storage
+}
+
 /// Returns true if a given buffer type is supported on a platform.
 fn is_buffer_type_supported(ctxt: &mut CommandContext, ty: BufferType) -> bool {
     match ty {
@@ -1733,36 +2442,98 @@ unsafe fn flush_range(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty:
     }
 }
 
+/// How the previous content of a buffer is handled when mapping it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Invalidate {
+    /// The content is preserved.
+    No,
+    /// The content of the whole buffer is discarded with `GL_MAP_INVALIDATE_BUFFER_BIT`.
+    Buffer,
+    /// The content of the mapped range is discarded with `GL_MAP_INVALIDATE_RANGE_BIT`, and
+    /// the driver doesn't wait for the GPU thanks to `GL_MAP_UNSYNCHRONIZED_BIT`.
+    RangeUnsynchronized,
+}
+
+/// Converts a range of bytes to the offset and size passed to OpenGL.
+///
+/// # Panic
+///
+/// Panics if the range doesn't fit in a `GLsizeiptr`, which is only 32 bits on 32-bit
+/// platforms. This can't happen for ranges within a buffer, as buffers larger than that are
+/// refused at creation.
+#[inline]
+fn gl_range(range: Range<usize>) -> (gl::types::GLintptr, gl::types::GLsizeiptr) {
+    assert!(range.start <= range.end && range.end <= isize::MAX as usize,
+            "The range {:?} is too large to be passed to OpenGL", range);
+    (range.start as gl::types::GLintptr, (range.end - range.start) as gl::types::GLsizeiptr)
+}
+
+/// Returns true if `glMapBufferRange` is supported, which `map_buffer` requires.
+fn is_map_buffer_range_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_version() >= &Version(Api::Gl, 3, 0) ||
+    ctxt.get_version() >= &Version(Api::GlEs, 3, 0) ||
+    ctxt.get_extensions().gl_arb_map_buffer_range
+}
+
+/// Returns true if the backend can neither map a buffer nor read from it, in which case buffers
+/// keep a copy of their content in memory. This is the case with OpenGL ES 2.0.
+fn needs_cpu_copy<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    !is_map_buffer_range_supported(ctxt) && !is_buffer_read_supported(ctxt)
+}
+
 /// Maps a range of a buffer.
 ///
+/// Invalidating the previous content is only allowed for write-only mappings.
+///
 /// *Warning*: always passes `GL_MAP_FLUSH_EXPLICIT_BIT`.
 unsafe fn map_buffer(mut ctxt: &mut CommandContext, id: gl::types::GLuint, ty: BufferType,
-                     range: Range<usize>, read: bool, write: bool) -> Option<*mut ()>
+                     range: Range<usize>, read: bool, write: bool, invalidate: Invalidate)
+                     -> Result<*mut (), MapError>
 {
-    let flags = match (read, write) {
+    let mut flags = match (read, write) {
         (true, true) => gl::MAP_FLUSH_EXPLICIT_BIT | gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
         (true, false) => gl::MAP_READ_BIT,
         (false, true) => gl::MAP_FLUSH_EXPLICIT_BIT | gl::MAP_WRITE_BIT,
         (false, false) => 0,
     };
 
-    if ctxt.version >= &Version(Api::Gl, 4, 5) {
-        Some(ctxt.gl.MapNamedBufferRange(id, range.start as gl::types::GLintptr,
-                                         (range.end - range.start) as gl::types::GLsizeiptr,
-                                         flags) as *mut ())
+    match invalidate {
+        Invalidate::No => (),
+        Invalidate::Buffer => {
+            debug_assert!(!read);
+            flags |= gl::MAP_INVALIDATE_BUFFER_BIT;
+        },
+        Invalidate::RangeUnsynchronized => {
+            debug_assert!(!read);
+            flags |= gl::MAP_INVALIDATE_RANGE_BIT | gl::MAP_UNSYNCHRONIZED_BIT;
+        },
+    }
+
+    let (offset, size) = gl_range(range);
+
+    let ptr = if ctxt.version >= &Version(Api::Gl, 4, 5) {
+        ctxt.gl.MapNamedBufferRange(id, offset, size, flags) as *mut ()
 
     } else if ctxt.version >= &Version(Api::Gl, 3, 0) ||
         ctxt.version >= &Version(Api::GlEs, 3, 0) ||
         ctxt.extensions.gl_arb_map_buffer_range
     {
         let bind = bind_buffer(&mut ctxt, id, ty);
-        Some(ctxt.gl.MapBufferRange(bind, range.start as gl::types::GLintptr,
-                                    (range.end - range.start) as gl::types::GLsizeiptr,
-                                    flags) as *mut ())
+        ctxt.gl.MapBufferRange(bind, offset, size, flags) as *mut ()
 
     } else {
-        None       // FIXME:
+        return Err(MapError::NotSupported);
+    };
+
+    if ptr.is_null() {
+        return Err(match ::get_gl_error(ctxt) {
+            Some("GL_CONTEXT_LOST") => MapError::ContextLost,
+            Some("GL_OUT_OF_MEMORY") => MapError::OutOfMemory,
+            _ => MapError::MappingFailed,
+        });
     }
+
+    Ok(ptr)
 }
 
 /// Unmaps a previously-mapped buffer.
@@ -50,6 +50,21 @@ impl Fences {
         *existing_fences = new_fences;
     }
 
+    /// Returns true if the given range is accessible, in other words if `wait` wouldn't block.
+    pub fn is_available(&self, ctxt: &mut CommandContext, range: Range<usize>) -> bool {
+        let existing_fences = self.fences.borrow();
+
+        for existing in existing_fences.iter() {
+            if existing.0.start < range.end && existing.0.end > range.start &&
+               !unsafe { sync::is_linear_sync_fence_signaled(ctxt, &existing.1) }
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// Cleans up all fences in the container. Must be called or you'll get a panic.
     pub fn clean(&mut self, ctxt: &mut CommandContext) {
         let mut fences = self.fences.borrow_mut();
@@ -40,15 +40,18 @@
 //!                                                               64, BufferMode::Default).unwrap();
 //!
 //! // you can then write to it like you normally would
-//! buffer.map().data[4] = 2.1;
+//! buffer.map().unwrap().data[4] = 2.1;
 //! # }
 //! ```
 //!
 pub use self::view::{Buffer, BufferAny, BufferMutSlice};
 pub use self::view::{BufferSlice, BufferAnySlice};
-pub use self::alloc::{Mapping, WriteMapping, ReadMapping, ReadError, CopyError};
-pub use self::alloc::{is_buffer_read_supported};
+pub use self::alloc::{Mapping, WriteMapping, ReadMapping, ReadError, MapError, CopyError};
+pub use self::alloc::{is_buffer_read_supported, is_persistent_mapping_supported};
+pub use self::alloc::is_sparse_buffer_supported;
+pub use self::alloc::is_buffer_storage_supported;
 pub use self::fences::Inserter;
+pub use self::readback::{PersistentReadback, ReadbackFuture};
 
 /// DEPRECATED. Only here for backwards compatibility.
 #[deprecated(note = "Only here for backwards compatibility")]
@@ -74,6 +77,7 @@ use std::slice;
 
 mod alloc;
 mod fences;
+mod readback;
 mod view;
 
 /// Trait for types of data that can be put inside buffers.
@@ -188,6 +192,15 @@ pub enum BufferCreationError {
 
     /// This type of buffer is not supported.
     BufferTypeNotSupported,
+
+    /// The backend doesn't support the requested kind of storage, for example sparse buffers.
+    NotSupported,
+
+    /// The requested size is too large to be passed to OpenGL.
+    FormatTooLarge,
+
+    /// The context has been lost. See `Context::is_context_lost`.
+    ContextLost,
 }
 
 impl fmt::Display for BufferCreationError {
@@ -195,6 +208,9 @@ impl fmt::Display for BufferCreationError {
         let desc = match self {
             BufferCreationError::OutOfMemory => "Not enough memory to create the buffer",
             BufferCreationError::BufferTypeNotSupported => "This type of buffer is not supported",
+            BufferCreationError::NotSupported => "The backend doesn't support this kind of buffer",
+            BufferCreationError::FormatTooLarge => "The requested size is too large to be passed to OpenGL",
+            BufferCreationError::ContextLost => "The context has been lost",
         };
         fmt.write_str(desc)
     }
@@ -251,10 +267,17 @@ pub enum BufferMode {
     /// and draw the first third, then modify and draw the second third, then the last part, then
     /// go back to the first third, etc.
     ///
+    /// Each part must be modified through a mapping of its own slice (for example with
+    /// `buffer.slice_mut(0 .. len).unwrap().map_write()`), and this mapping must be dropped before
+    /// drawing from the same part. Dropping the mapping flushes the writes and lets glium insert
+    /// the fence that protects the part while the GPU reads it. In debug mode, glium panics if a
+    /// draw call reads a range that is still mapped for writing, which can happen if a mapping
+    /// is leaked with `mem::forget`.
+    ///
     /// # Implementation
     ///
-    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT`. Sync fences are automatically
-    /// managed by glium.
+    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT`. The buffer is mapped with
+    /// `GL_MAP_FLUSH_EXPLICIT_BIT`. Sync fences are automatically managed by glium.
     ///
     /// If this function is not available, falls back to `glBufferData` with `GL_DYNAMIC_DRAW`.
     ///
@@ -274,6 +297,59 @@ pub enum BufferMode {
     /// If this function is not available, falls back to `glBufferData` with `GL_STATIC_DRAW`.
     ///
     Immutable,
+
+    /// Same as `Persistent`, but asks the driver to keep the storage of the buffer in the
+    /// system memory instead of the video memory.
+    ///
+    /// Use this for buffers that the CPU accesses more often than the GPU, for example a buffer
+    /// that is rewritten entirely every frame and read only once by the GPU. Writing to and
+    /// reading from the mapping is faster, but the GPU has to fetch the data through the bus,
+    /// which makes drawing from the buffer slower. This is only a hint and the driver is free to
+    /// ignore it.
+    ///
+    /// # Implementation
+    ///
+    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT` and `GL_CLIENT_STORAGE_BIT`.
+    /// This requires OpenGL 4.4 or `GL_ARB_buffer_storage`.
+    ///
+    /// If this function is not available, falls back to `glBufferData` with `GL_DYNAMIC_DRAW`
+    /// like `Persistent`, and the hint is ignored.
+    ///
+    PersistentClientStorage,
+
+    /// Same as `Persistent`, but the mapping is coherent.
+    ///
+    /// Writes through the mapping are visible to the GPU without being flushed, and what the GPU
+    /// writes to the buffer is visible through the mapping once the commands have finished.
+    /// Coherent mappings can be slower to access, so only use this mode if you need these
+    /// guarantees, for example to read back data written by the GPU every frame.
+    ///
+    /// # Implementation
+    ///
+    /// Tries to use `glBufferStorage` with `GL_MAP_PERSISTENT_BIT` and `GL_MAP_COHERENT_BIT`.
+    /// This requires OpenGL 4.4 or `GL_ARB_buffer_storage`.
+    ///
+    /// If this function is not available, falls back to `glBufferData` with `GL_DYNAMIC_DRAW`
+    /// like `Persistent`, and the buffer is not persistent-mapped.
+    ///
+    PersistentCoherent,
+
+    /// Same as `Immutable`, but asks the driver to keep the storage of the buffer in the system
+    /// memory instead of the video memory.
+    ///
+    /// This has the same trade-off as `PersistentClientStorage`: modifying the buffer and
+    /// reading it back are faster, but the GPU reads it slower. This is only a hint and the
+    /// driver is free to ignore it.
+    ///
+    /// # Implementation
+    ///
+    /// Tries to use `glBufferStorage` with `GL_CLIENT_STORAGE_BIT`. This requires OpenGL 4.4 or
+    /// `GL_ARB_buffer_storage`.
+    ///
+    /// If this function is not available, falls back to `glBufferData` with `GL_STATIC_DRAW`
+    /// like `Immutable`, and the hint is ignored.
+    ///
+    ImmutableClientStorage,
 }
 
 impl Default for BufferMode {
@@ -321,4 +397,24 @@ impl BufferType {
             BufferType::ElementArrayBuffer => gl::ELEMENT_ARRAY_BUFFER,
         }
     }
+
+    fn from_glenum(target: gl::types::GLenum) -> Option<BufferType> {
+        match target {
+            gl::ARRAY_BUFFER => Some(BufferType::ArrayBuffer),
+            gl::PIXEL_PACK_BUFFER => Some(BufferType::PixelPackBuffer),
+            gl::PIXEL_UNPACK_BUFFER => Some(BufferType::PixelUnpackBuffer),
+            gl::UNIFORM_BUFFER => Some(BufferType::UniformBuffer),
+            gl::COPY_READ_BUFFER => Some(BufferType::CopyReadBuffer),
+            gl::COPY_WRITE_BUFFER => Some(BufferType::CopyWriteBuffer),
+            gl::ATOMIC_COUNTER_BUFFER => Some(BufferType::AtomicCounterBuffer),
+            gl::DISPATCH_INDIRECT_BUFFER => Some(BufferType::DispatchIndirectBuffer),
+            gl::DRAW_INDIRECT_BUFFER => Some(BufferType::DrawIndirectBuffer),
+            gl::QUERY_BUFFER => Some(BufferType::QueryBuffer),
+            gl::SHADER_STORAGE_BUFFER => Some(BufferType::ShaderStorageBuffer),
+            gl::TEXTURE_BUFFER => Some(BufferType::TextureBuffer),
+            gl::TRANSFORM_FEEDBACK_BUFFER => Some(BufferType::TransformFeedbackBuffer),
+            gl::ELEMENT_ARRAY_BUFFER => Some(BufferType::ElementArrayBuffer),
+            _ => None,
+        }
+    }
 }
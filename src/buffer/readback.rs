@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+use std::ptr;
+
+use backend::Facade;
+use buffer::{Buffer, BufferType, BufferMode, BufferCreationError, BufferMutSlice, ReadMapping};
+use buffer::ReadError;
+use buffer::Content;
+use buffer::is_persistent_mapping_supported;
+use sync::{SyncFence, SyncWaitStatus};
+use version::{Api, Version};
+use ContextExt;
+
+use gl;
+
+/// Ring of persistent-mapped buffers that the GPU writes into and that the CPU reads from.
+///
+/// This is useful to read back the results of a compute shader or of transform feedback every
+/// frame without mapping and unmapping a buffer, and without waiting for the GPU. The buffer
+/// is split into several frames. Each call to `write_frame` lets the GPU write to the next
+/// frame, and `latest` returns the content of the most recent frame that the GPU has finished
+/// writing.
+///
+/// The buffer is created with `BufferMode::PersistentCoherent`, so what the GPU writes is visible through
+/// the mapping as soon as the fence of the frame is signaled, without any explicit flush.
+///
+/// Persistent mapping requires OpenGL 4.4 or `GL_ARB_buffer_storage`.
+///
+/// # Example
+///
+/// ```no_run
+/// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+/// # fn dispatch_compute(_: glium::buffer::BufferMutSlice<[u32]>) {}
+/// use glium::buffer::{BufferType, PersistentReadback};
+///
+/// let mut readback = PersistentReadback::<u32>::new(&display, BufferType::ShaderStorageBuffer,
+///                                                   1024, 3).unwrap();
+///
+/// // every frame
+/// readback.write_frame(|target| dispatch_compute(target));
+/// if let Some(results) = readback.latest() {
+///     println!("{}", results[0]);
+/// };
+/// ```
+pub struct PersistentReadback<T> where T: Copy + Send + 'static {
+    buffer: Buffer<[T]>,
+    // number of elements in each frame
+    len: usize,
+    // for each frame, the fence that is signaled when the GPU has finished writing it
+    fences: Vec<Option<SyncFence>>,
+    // frames that have been written by the GPU, from the oldest to the most recent
+    pending: VecDeque<usize>,
+    // frame that `write_frame` will write next
+    next: usize,
+    // most recent frame that the GPU has finished writing
+    latest: Option<usize>,
+}
+
+impl<T> PersistentReadback<T> where T: Copy + Send + 'static {
+    /// Returns true if persistent readback buffers are supported by the backend.
+    #[inline]
+    pub fn is_supported<F: ?Sized>(facade: &F) -> bool where F: Facade {
+        is_persistent_mapping_supported(facade)
+    }
+
+    /// Builds a ring of `frames` frames of `len` elements each.
+    ///
+    /// `ty` is the kind of buffer that the GPU writes to, for example
+    /// `BufferType::ShaderStorageBuffer` for a compute shader. Using at least three frames
+    /// gives the GPU enough time to finish a frame before it is read.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if persistent mapping is not supported, and
+    /// `BufferCreationError::FormatTooLarge` if the total number of elements overflows.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `frames` is inferior to 2.
+    pub fn new<F: ?Sized>(facade: &F, ty: BufferType, len: usize, frames: usize)
+                          -> Result<PersistentReadback<T>, BufferCreationError> where F: Facade
+    {
+        assert!(frames >= 2, "A readback ring needs at least two frames");
+
+        if !PersistentReadback::<T>::is_supported(facade) {
+            return Err(BufferCreationError::NotSupported);
+        }
+
+        let total = len.checked_mul(frames).ok_or(BufferCreationError::FormatTooLarge)?;
+        let buffer = Buffer::empty_array(facade, ty, total, BufferMode::PersistentCoherent)?;
+
+        Ok(PersistentReadback {
+            buffer: buffer,
+            len: len,
+            fences: (0 .. frames).map(|_| None).collect(),
+            pending: VecDeque::with_capacity(frames),
+            next: 0,
+            latest: None,
+        })
+    }
+
+    /// Returns the number of elements in each frame.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the frames are empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of frames of the ring.
+    #[inline]
+    pub fn frames(&self) -> usize {
+        self.fences.len()
+    }
+
+    /// Lets the GPU write to the next frame of the ring.
+    ///
+    /// `f` receives the slice of the buffer that corresponds to the frame, and must submit the
+    /// commands that write to it, for example by binding it to a compute shader. A fence is
+    /// then inserted in the commands queue, and the frame is returned by `latest` once this
+    /// fence is signaled.
+    ///
+    /// If the frame that is overwritten had not been read yet, its content is lost.
+    pub fn write_frame<F>(&mut self, f: F) where F: FnOnce(BufferMutSlice<[T]>) {
+        let frame = self.next;
+        self.next = (self.next + 1) % self.fences.len();
+
+        // the previous content of this frame is overwritten
+        if self.latest == Some(frame) {
+            self.latest = None;
+        }
+        self.pending.retain(|&f| f != frame);
+        self.fences[frame] = None;
+
+        let range = frame * self.len .. (frame + 1) * self.len;
+        f(self.buffer.slice_mut(range).unwrap());
+
+        {
+            let ctxt = self.buffer.get_context().make_current();
+
+            // makes the writes of the shaders visible through the mapping
+            if ctxt.version >= &Version(Api::Gl, 4, 2) ||
+               ctxt.extensions.gl_arb_shader_image_load_store
+            {
+                unsafe { ctxt.gl.MemoryBarrier(gl::CLIENT_MAPPED_BUFFER_BARRIER_BIT); }
+            }
+        }
+
+        self.fences[frame] = SyncFence::new(self.buffer.get_context()).ok();
+        self.pending.push_back(frame);
+    }
+
+    /// Returns the content of the most recent frame that the GPU has finished writing, or `None`
+    /// if no frame has been finished yet.
+    ///
+    /// This never waits for the GPU. The same frame is returned until a more recent one is
+    /// finished.
+    pub fn latest(&mut self) -> Option<ReadMapping<[T]>> {
+        while let Some(&frame) = self.pending.front() {
+            let finished = match self.fences[frame] {
+                Some(ref fence) => match fence.wait_timeout(0) {
+                    SyncWaitStatus::AlreadySignaled | SyncWaitStatus::ConditionSatisfied => true,
+                    SyncWaitStatus::TimeoutExpired | SyncWaitStatus::WaitFailed => false,
+                },
+                None => true,
+            };
+
+            if !finished {
+                break;
+            }
+
+            self.pending.pop_front();
+            self.fences[frame] = None;
+            self.latest = Some(frame);
+        }
+
+        let frame = self.latest?;
+        let range = frame * self.len .. (frame + 1) * self.len;
+        // the buffer is always persistent-mapped, and mapping it never fails
+        Some(self.buffer.slice_mut(range).unwrap().map_read().unwrap())
+    }
+}
+
+/// Content of a buffer that is being copied to a staging buffer by the GPU.
+///
+/// Returned by `read_async`. For a buffer of `[T]`, the result of the read is a `Vec<T>`.
+///
+/// # Example
+///
+/// ```no_run
+/// # let buffer: glium::VertexBuffer<[f32; 2]> = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+/// let mut readback = buffer.read_async().unwrap();
+///
+/// // every frame
+/// if let Some(data) = readback.poll() {
+///     let data = data.unwrap();
+///     println!("{:?}", data[0]);
+/// }
+/// ```
+pub struct ReadbackFuture<T: ?Sized> where T: Content {
+    staging: Buffer<T>,
+    // `None` if fences are not supported, in which case reading waits for the copy
+    fence: Option<SyncFence>,
+}
+
+impl<T: ?Sized> ReadbackFuture<T> where T: Content {
+    /// Builds a future that reads `staging` once the commands submitted until now are finished.
+    pub fn new(staging: Buffer<T>) -> ReadbackFuture<T> {
+        let fence = SyncFence::new(staging.get_context()).ok();
+
+        ReadbackFuture {
+            staging: staging,
+            fence: fence,
+        }
+    }
+
+    /// Returns true if the GPU has finished copying the data.
+    ///
+    /// Always returns true if the backend doesn't support fences.
+    pub fn is_ready(&self) -> bool {
+        match self.fence {
+            Some(ref fence) => match fence.wait_timeout(0) {
+                SyncWaitStatus::AlreadySignaled | SyncWaitStatus::ConditionSatisfied => true,
+                SyncWaitStatus::TimeoutExpired | SyncWaitStatus::WaitFailed => false,
+            },
+            None => true,
+        }
+    }
+
+    /// Returns the data if the GPU has finished copying it, or `None` otherwise.
+    ///
+    /// This never waits for the GPU, except if the backend doesn't support fences. Once the
+    /// data is available, each call returns it again. Returns an error if the staging buffer
+    /// can't be mapped.
+    pub fn poll(&mut self) -> Option<Result<T::Owned, ReadError>> {
+        if !self.is_ready() {
+            return None;
+        }
+
+        self.fence = None;
+        Some(self.read())
+    }
+
+    /// Waits until the GPU has finished copying the data and returns it.
+    ///
+    /// Returns an error if the staging buffer can't be mapped.
+    pub fn wait(mut self) -> Result<T::Owned, ReadError> {
+        if let Some(fence) = self.fence.take() {
+            fence.wait();
+        }
+
+        self.read()
+    }
+
+    fn read(&mut self) -> Result<T::Owned, ReadError> {
+        let size = self.staging.get_size();
+        let mapping = self.staging.map_read()?;
+
+        <T as Content>::read(size, |output| {
+            unsafe {
+                ptr::copy_nonoverlapping(<T as Content>::to_void_ptr(&mapping) as *const u8,
+                                         output as *mut T as *mut u8, size);
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,5 +1,8 @@
 use std::fmt;
 use std::mem;
+use std::ptr;
+use std::slice;
+use std::ops::Range;
 use std::borrow::Cow;
 use utils::range::RangeArgument;
 use std::marker::PhantomData;
@@ -8,6 +11,7 @@ use texture::{PixelValue, Texture1dDataSink};
 use gl;
 
 use backend::Facade;
+use BufferAnyExt;
 use BufferExt;
 use BufferSliceExt;
 use GlObject;
@@ -16,6 +20,8 @@ use context::Context;
 use context::CommandContext;
 use std::rc::Rc;
 use ContextExt;
+use QueryExt;
+use draw_parameters::TimeElapsedQuery;
 
 use buffer::BufferType;
 use buffer::BufferMode;
@@ -28,7 +34,9 @@ use buffer::alloc::Mapping;
 use buffer::alloc::ReadMapping;
 use buffer::alloc::WriteMapping;
 use buffer::alloc::ReadError;
+use buffer::alloc::MapError;
 use buffer::alloc::CopyError;
+use buffer::readback::ReadbackFuture;
 use field::Field;
 
 /// Represents a view of a buffer.
@@ -52,6 +60,9 @@ impl<T: ?Sized> GlObject for Buffer<T> where T: Content {
 impl<T: ?Sized> Buffer<T> where T: Content {
     /// Builds a new buffer containing the given data. The size of the buffer is equal to the size
     /// of the data.
+    ///
+    /// The data is copied before this function returns, so it can be borrowed from anywhere,
+    /// for example from a memory-mapped file, and dropped right afterwards.
     pub fn new<F: ?Sized>(facade: &F, data: &T, ty: BufferType, mode: BufferMode)
                   -> Result<Buffer<T>, BufferCreationError>
                   where F: Facade
@@ -100,20 +111,68 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         self.alloc.as_ref().unwrap().uses_persistent_mapping()
     }
 
-    /// Uploads some data in this buffer.
+    /// Returns the mode that was requested when creating this buffer.
+    #[inline]
+    pub fn get_mode(&self) -> BufferMode {
+        self.alloc.as_ref().unwrap().get_creation_mode()
+    }
+
+    /// Returns true if this buffer is a sparse buffer.
+    #[inline]
+    pub fn is_sparse(&self) -> bool {
+        self.alloc.as_ref().unwrap().is_sparse()
+    }
+
+    /// Returns the size in bytes of a page if this buffer is a sparse buffer.
     ///
-    /// # Implementation
+    /// The ranges passed to `commit_range` and `decommit_range` must be aligned to this size.
+    #[inline]
+    pub fn get_sparse_page_size(&self) -> Option<usize> {
+        if !self.is_sparse() {
+            return None;
+        }
+
+        self.get_context().capabilities().sparse_buffer_page_size.map(|s| s as usize)
+    }
+
+    /// Returns true if this buffer uses persistent mapping and if the mapping is coherent.
     ///
-    /// - For persistent-mapped buffers, waits untils the data is no longer used by the GPU then
-    ///   memcpies the data to the mapping.
-    /// - For immutable buffers, creates a temporary buffer that contains the data then calls
-    ///   `glCopyBufferSubData` to copy from the temporary buffer to the real one.
-    /// - For other types, calls `glBufferSubData`.
+    /// Writes through a coherent mapping are automatically visible to the GPU, while writes
+    /// through a non-coherent mapping are flushed by glium when the `Mapping` is dropped.
+    /// Only buffers created with `BufferMode::PersistentCoherent` have a coherent mapping, so this
+    /// always returns `false` for the other modes and for buffers that don't use persistent
+    /// mapping.
+    #[inline]
+    pub fn is_coherent(&self) -> bool {
+        self.alloc.as_ref().unwrap().is_coherent()
+    }
+
+    /// Checks that the size of the buffer known by OpenGL is the same as the size tracked by
+    /// glium.
+    ///
+    /// This is meant to be used in tests and while debugging. It does nothing if debug
+    /// assertions are disabled.
     ///
     /// # Panic
     ///
-    /// Panics if the length of `data` is different from the length of this buffer.
-    pub fn write(&self, data: &T) {
+    /// Panics if the sizes don't match.
+    pub fn assert_valid(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        // OpenGL doesn't allow empty buffers, so glium allocates one byte instead
+        if self.get_size() == 0 {
+            return;
+        }
+
+        let obtained = self.query_size();
+        assert_eq!(obtained, self.get_size(), "The size of the buffer in OpenGL ({} bytes) \
+                   doesn't match the size tracked by glium ({} bytes)", obtained, self.get_size());
+    }
+
+    /// Uploads `data` in the whole buffer. See `write`.
+    fn write_content(&self, data: &T) {
         assert!(mem::size_of_val(data) == self.get_size());
 
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
@@ -150,8 +209,19 @@ impl<T: ?Sized> Buffer<T> where T: Content {
         }
     }
 
+    /// Starts reading the content of the buffer without waiting for the GPU.
+    ///
+    /// See `BufferSlice::read_async`.
+    #[inline]
+    pub fn read_async(&self) -> Result<ReadbackFuture<T>, ReadError> {
+        self.as_slice().read_async()
+    }
+
     /// Maps the buffer in memory for both reading and writing.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -160,16 +230,49 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///   maps it. When the mapping object is destroyed, copies the content of the temporary buffer
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, reads the content with `glGetBufferSubData` to a
+    ///   copy in memory. When the mapping object is destroyed, uploads the copy back with
+    ///   `glBufferSubData`. On OpenGL ES 2, which can't read from a buffer, the content is
+    ///   instead taken from a copy that glium keeps in memory and updates on every upload.
     ///
-    pub fn map(&mut self) -> Mapping<T> {
+    pub fn map(&mut self) -> Result<Mapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
         unsafe { self.alloc.as_mut().unwrap().map(0 .. size) }
     }
 
+    /// Same as `map`, but returns `Ok(None)` instead of blocking if the GPU is still using the
+    /// buffer. You can then try again later, for example during the next frame.
+    ///
+    /// # Implementation
+    ///
+    /// For persistent-mapped buffers, calls `glClientWaitSync` with a timeout of zero on the
+    /// fences that glium inserted after the commands that use the buffer. Glium doesn't track
+    /// the usage of the other buffers, which are always mapped like with `map`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `glClientWaitSync` fails, for example after an OpenGL error.
+    ///
+    pub fn try_map(&mut self) -> Result<Option<Mapping<T>>, MapError> {
+        let size = self.get_size();
+
+        {
+            let mut ctxt = self.alloc.as_ref().unwrap().get_context().make_current();
+            if !self.fence.as_ref().unwrap().is_available(&mut ctxt, 0 .. size) {
+                return Ok(None);
+            }
+        }
+
+        self.map().map(Some)
+    }
+
     /// Maps the buffer in memory for reading.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -177,8 +280,12 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     /// - For immutable buffers, creates a temporary buffer containing the data of the buffer and
     ///   maps it.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, reads the content with `glGetBufferSubData` to a
+    ///   copy in memory. When the mapping object is destroyed, uploads the copy back with
+    ///   `glBufferSubData`. On OpenGL ES 2, which can't read from a buffer, the content is
+    ///   instead taken from a copy that glium keeps in memory and updates on every upload.
     ///
-    pub fn map_read(&mut self) -> ReadMapping<T> {
+    pub fn map_read(&mut self) -> Result<ReadMapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
@@ -187,6 +294,9 @@ impl<T: ?Sized> Buffer<T> where T: Content {
 
     /// Maps the buffer in memory for writing only.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -195,14 +305,42 @@ impl<T: ?Sized> Buffer<T> where T: Content {
     ///   maps it. When the mapping object is destroyed, copies the content of the temporary buffer
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, returns a mapping of a copy in memory. When the
+    ///   mapping object is destroyed, uploads the copy with `glBufferSubData`.
     ///
-    pub fn map_write(&mut self) -> WriteMapping<T> {
+    pub fn map_write(&mut self) -> Result<WriteMapping<T>, MapError> {
         self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
                                           0 .. self.get_size());
         let size = self.get_size();
         unsafe { self.alloc.as_mut().unwrap().map_write(0 .. size) }
     }
 
+    /// Maps the whole buffer in memory for writing only, discarding its previous content.
+    ///
+    /// This is the fastest way to rewrite the entire content of a buffer, for example once per
+    /// frame. Contrary to `map_write`, the previous content of the buffer is lost even for
+    /// elements that you don't write, and the content of the mapping is undefined until you
+    /// write it.
+    ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
+    /// # Implementation
+    ///
+    /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
+    ///   returns a pointer to the existing mapping.
+    /// - For immutable buffers, creates a temporary buffer and maps it. When the mapping object
+    ///   is destroyed, copies the content of the temporary buffer to the real buffer.
+    /// - For other types, calls `glMapBufferRange` with `GL_MAP_INVALIDATE_BUFFER_BIT`. The
+    ///   driver can then give you new memory instead of waiting for the GPU to stop using the
+    ///   old one.
+    ///
+    pub fn map_write_invalidate(&mut self) -> Result<WriteMapping<T>, MapError> {
+        self.fence.as_ref().unwrap().wait(&mut self.alloc.as_ref().unwrap().get_context().make_current(),
+                                          0 .. self.get_size());
+        unsafe { self.alloc.as_mut().unwrap().map_write_invalidate() }
+    }
+
     /// Copies the content of the buffer to another buffer.
     ///
     /// # Panic
@@ -330,14 +468,96 @@ impl<T> Buffer<T> where T: Content + Copy {
                 }
             })
     }
+
+    /// Uploads some data in this buffer.
+    ///
+    /// # Implementation
+    ///
+    /// - For persistent-mapped buffers, waits untils the data is no longer used by the GPU then
+    ///   memcpies the data to the mapping.
+    /// - For immutable buffers, creates a temporary buffer that contains the data then calls
+    ///   `glCopyBufferSubData` to copy from the temporary buffer to the real one.
+    /// - For other types, calls `glBufferSubData`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from the length of this buffer.
+    #[inline]
+    pub fn write(&self, data: &T) {
+        self.write_content(data)
+    }
 }
 
 impl<T> Buffer<[T]> where [T]: Content, T: Copy {
+    /// Uploads some data in this buffer.
+    ///
+    /// Anything that can be viewed as a slice can be passed, like a reference to a slice or to
+    /// an array, an array or a `Vec`.
+    ///
+    /// # Implementation
+    ///
+    /// - For persistent-mapped buffers, waits untils the data is no longer used by the GPU then
+    ///   memcpies the data to the mapping.
+    /// - For immutable buffers, creates a temporary buffer that contains the data then calls
+    ///   `glCopyBufferSubData` to copy from the temporary buffer to the real one.
+    /// - For other types, calls `glBufferSubData`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from the length of this buffer.
+    #[inline]
+    pub fn write<D>(&self, data: D) where D: AsRef<[T]> {
+        self.write_content(data.as_ref())
+    }
+
     /// Builds a new buffer of the given size.
     pub fn empty_array<F: ?Sized>(facade: &F, ty: BufferType, len: usize, mode: BufferMode)
                           -> Result<Buffer<[T]>, BufferCreationError> where F: Facade
     {
-        Alloc::empty(facade, ty, len * mem::size_of::<T>(), mode)
+        let size = match len.checked_mul(mem::size_of::<T>()) {
+            Some(size) => size,
+            None => return Err(BufferCreationError::FormatTooLarge),
+        };
+
+        Alloc::empty(facade, ty, size, mode)
+            .map(|buffer| {
+                Buffer {
+                    alloc: Some(buffer),
+                    fence: Some(Fences::new()),
+                    marker: PhantomData,
+                }
+            })
+    }
+
+    /// Builds a new sparse buffer of the given size, if the backend supports it.
+    ///
+    /// Only the virtual address space of a sparse buffer is allocated. Physical memory is only
+    /// used for the ranges that you pass to `commit_range`. Reading or drawing from a range
+    /// that isn't committed returns undefined values, and writing to it is ignored.
+    ///
+    /// Returns `None` if `GL_ARB_sparse_buffer` is not supported or if the creation fails. See
+    /// `try_empty_array_sparse` to know why the creation failed.
+    #[inline]
+    pub fn empty_array_sparse_if_supported<F: ?Sized>(facade: &F, ty: BufferType, len: usize)
+                                              -> Option<Buffer<[T]>> where F: Facade
+    {
+        Buffer::try_empty_array_sparse(facade, ty, len).ok()
+    }
+
+    /// Same as `empty_array_sparse_if_supported`, but returns an error describing why the
+    /// buffer couldn't be created.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if `GL_ARB_sparse_buffer` is not supported
+    /// and `BufferCreationError::FormatTooLarge` if the size in bytes of the buffer overflows.
+    pub fn try_empty_array_sparse<F: ?Sized>(facade: &F, ty: BufferType, len: usize)
+                                     -> Result<Buffer<[T]>, BufferCreationError> where F: Facade
+    {
+        let size = match len.checked_mul(mem::size_of::<T>()) {
+            Some(size) => size,
+            None => return Err(BufferCreationError::FormatTooLarge),
+        };
+
+        Alloc::empty_sparse(facade, ty, size)
             .map(|buffer| {
                 Buffer {
                     alloc: Some(buffer),
@@ -347,12 +567,67 @@ impl<T> Buffer<[T]> where [T]: Content, T: Copy {
             })
     }
 
+    /// Allocates physical memory for the elements within `range` of a sparse buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the buffer is not sparse, if the range is out of bounds, or if the range
+    /// doesn't start and end on a page boundary. See `get_sparse_page_size`. The end of
+    /// the range can also be the end of the buffer.
+    #[inline]
+    pub fn commit_range<R: RangeArgument<usize>>(&self, range: R) {
+        let range = self.sparse_bytes_range(range);
+        self.alloc.as_ref().unwrap().page_commitment(range, true);
+    }
+
+    /// Releases the physical memory of the elements within `range` of a sparse buffer. Their
+    /// content becomes undefined.
+    ///
+    /// # Panic
+    ///
+    /// Same as `commit_range`.
+    #[inline]
+    pub fn decommit_range<R: RangeArgument<usize>>(&self, range: R) {
+        let range = self.sparse_bytes_range(range);
+        self.alloc.as_ref().unwrap().page_commitment(range, false);
+    }
+
+    fn sparse_bytes_range<R: RangeArgument<usize>>(&self, range: R) -> Range<usize> {
+        let bytes = |e: usize| e.checked_mul(mem::size_of::<T>()).expect("Range out of bounds");
+        bytes(range.start().map_or(0, |e| *e)) .. bytes(range.end().map_or(self.len(), |e| *e))
+    }
+
     /// Returns the number of elements in this buffer.
     #[inline]
     pub fn len(&self) -> usize {
         self.alloc.as_ref().unwrap().get_size() / mem::size_of::<T>()
     }
 
+    /// Sets every element of the buffer to `value`.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glClearBufferData` if it is supported and if the size of `T` is 1, 2, 4, 8, 12
+    /// or 16 bytes. Otherwise, builds a `Vec` that contains `value` repeated and uploads it.
+    pub fn fill(&self, value: T) {
+        let alloc = self.alloc.as_ref().unwrap();
+
+        let pattern = unsafe {
+            slice::from_raw_parts(&value as *const T as *const u8, mem::size_of::<T>())
+        };
+
+        if alloc.clear_with_pattern(pattern) {
+            if let Some(inserter) = self.as_slice().add_fence() {
+                let mut ctxt = alloc.get_context().make_current();
+                inserter.insert(&mut ctxt);
+            }
+
+        } else {
+            let data = vec![value; self.len()];
+            self.write(&data[..]);
+        }
+    }
+
     /// Builds a slice of this subbuffer. Returns `None` if out of range.
     ///
     /// This method builds an object that represents a slice of the buffer. No actual operation
@@ -372,6 +647,23 @@ impl<T> Buffer<[T]> where [T]: Content, T: Copy {
     }
 }
 
+impl<T> Buffer<[T]> where T: Copy {
+    /// Reads the elements within each of `ranges`, and returns them in the same order.
+    ///
+    /// This is faster than reading the whole buffer when you only need a few scattered parts
+    /// of it. Each range is read separately, the same way as `slice(range).read()`.
+    ///
+    /// Returns `ReadError::OutOfRange` if one of the ranges is out of range, in which case
+    /// nothing is read.
+    pub fn read_ranges(&self, ranges: &[Range<usize>]) -> Result<Vec<Vec<T>>, ReadError> {
+        let slices = ranges.iter().map(|range| self.slice(range.clone()))
+                                  .collect::<Option<Vec<_>>>()
+                                  .ok_or(ReadError::OutOfRange)?;
+
+        slices.iter().map(|slice| slice.read()).collect()
+    }
+}
+
 impl<T> Buffer<[T]> where T: PixelValue {
     /// Reads the content of the buffer.
     #[inline]
@@ -403,16 +695,26 @@ impl<T: ?Sized> BufferExt for Buffer<T> where T: Content {
         0
     }
 
+    #[inline]
+    fn query_size(&self) -> usize {
+        self.alloc.as_ref().unwrap().query_size()
+    }
+
+    #[inline]
+    fn get_modifications_count(&self) -> u64 {
+        self.alloc.as_ref().unwrap().get_modifications_count()
+    }
+
     #[inline]
     fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext) {
         let alloc = self.alloc.as_ref().unwrap();
-        alloc.prepare_for_vertex_attrib_array(ctxt);
+        alloc.prepare_for_vertex_attrib_array(ctxt, 0 .. alloc.get_size());
     }
 
     #[inline]
     fn prepare_for_element_array(&self, ctxt: &mut CommandContext) {
         let alloc = self.alloc.as_ref().unwrap();
-        alloc.prepare_for_element_array(ctxt);
+        alloc.prepare_for_element_array(ctxt, 0 .. alloc.get_size());
     }
 
     #[inline]
@@ -535,6 +837,42 @@ impl<'a, T: ?Sized> BufferSlice<'a, T> where T: Content + 'a {
         unsafe { self.alloc.upload(self.bytes_start, data); }
     }
 
+    /// Same as `write`, but measures the time that the GPU spends on the upload.
+    ///
+    /// The upload is enclosed in a `GL_TIME_ELAPSED` query, which is returned so that its result
+    /// can be retrieved later without stalling. This helps finding the buffer updates that
+    /// stall the pipeline, for example because the GPU is still using the buffer. The time
+    /// elapsed query of the draw parameters, if any, is stopped.
+    ///
+    /// Timer queries have a cost, which is why `write` doesn't use them. If they are not
+    /// supported by the backend, this does the same as `write` and returns `None`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from the length of this buffer.
+    pub fn write_timed(&self, data: &T) -> Option<TimeElapsedQuery> {
+        assert!(mem::size_of_val(data) == self.get_size());
+
+        let query = TimeElapsedQuery::new(self.alloc.get_context()).ok();
+
+        {
+            let mut ctxt = self.alloc.get_context().make_current();
+            self.fence.wait(&mut ctxt, self.bytes_start .. self.bytes_end);
+            if let Some(ref query) = query {
+                query.begin_query(&mut ctxt).unwrap();
+            }
+        }
+
+        unsafe { self.alloc.upload(self.bytes_start, data); }
+
+        if query.is_some() {
+            let mut ctxt = self.alloc.get_context().make_current();
+            TimeElapsedQuery::end_time_elapsed_query(&mut ctxt);
+        }
+
+        query
+    }
+
     /// Invalidates the content of the slice. The data becomes undefined.
     ///
     /// This operation is a no-op if the backend doesn't support it and for persistent-mapped
@@ -559,6 +897,46 @@ impl<'a, T: ?Sized> BufferSlice<'a, T> where T: Content + 'a {
         }
     }
 
+    /// Starts reading the content of the slice without waiting for the GPU.
+    ///
+    /// The content is copied on the GPU to a staging buffer, and a fence is inserted in the
+    /// commands queue. The returned `ReadbackFuture` can then be polled, for example once per
+    /// frame, until the copy is finished. Contrary to `read`, this never stalls until the
+    /// commands that write to the buffer have been executed.
+    ///
+    /// Returns `ReadError::NotSupported` if the backend doesn't support copying between buffers,
+    /// and `ReadError::OutOfMemory` if the staging buffer can't be allocated.
+    ///
+    /// # Implementation
+    ///
+    /// Calls `glCopyBufferSubData` to a `GL_PIXEL_PACK_BUFFER`, then `glFenceSync`. The staging
+    /// buffer is mapped once the fence is signaled.
+    ///
+    pub fn read_async(&self) -> Result<ReadbackFuture<T>, ReadError> {
+        if self.alloc.get_context().make_current().state.lost_context {
+            return Err(ReadError::ContextLost);
+        }
+
+        let staging = match Buffer::empty_unsized(self.alloc.get_context(),
+                                                  BufferType::PixelPackBuffer,
+                                                  self.get_size(), BufferMode::Default)
+        {
+            Ok(staging) => staging,
+            Err(BufferCreationError::ContextLost) => return Err(ReadError::ContextLost),
+            Err(BufferCreationError::OutOfMemory) |
+            Err(BufferCreationError::FormatTooLarge) => return Err(ReadError::OutOfMemory),
+            Err(BufferCreationError::BufferTypeNotSupported) |
+            Err(BufferCreationError::NotSupported) => return Err(ReadError::NotSupported),
+        };
+
+        match self.copy_to(&staging) {
+            Ok(()) => (),
+            Err(_) => return Err(ReadError::NotSupported),
+        };
+
+        Ok(ReadbackFuture::new(staging))
+    }
+
     /// Copies the content of this slice to another slice.
     ///
     /// # Panic
@@ -655,6 +1033,10 @@ impl<'a, T> BufferSlice<'a, [T]> where [T]: Content + 'a {
             return None;
         }
 
+        if range.start().map_or(0, |e| *e) > range.end().map_or(self.len(), |e| *e) {
+            return None;
+        }
+
         Some(BufferSlice {
             alloc: self.alloc,
             bytes_start: self.bytes_start + range.start().map_or(0, |e| *e) * mem::size_of::<T>(),
@@ -725,14 +1107,24 @@ impl<'a, T: ?Sized> BufferExt for BufferSlice<'a, T> where T: Content {
         self.bytes_start
     }
 
+    #[inline]
+    fn query_size(&self) -> usize {
+        self.alloc.query_size()
+    }
+
+    #[inline]
+    fn get_modifications_count(&self) -> u64 {
+        self.alloc.get_modifications_count()
+    }
+
     #[inline]
     fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_vertex_attrib_array(ctxt);
+        self.alloc.prepare_for_vertex_attrib_array(ctxt, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
     fn prepare_for_element_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_element_array(ctxt);
+        self.alloc.prepare_for_element_array(ctxt, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
@@ -819,6 +1211,9 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
 
     /// Maps the buffer in memory for both reading and writing.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -827,16 +1222,38 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     ///   maps it. When the mapping object is destroyed, copies the content of the temporary buffer
     ///   to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, reads the content with `glGetBufferSubData` to a
+    ///   copy in memory. When the mapping object is destroyed, uploads the copy back with
+    ///   `glBufferSubData`. On OpenGL ES 2, which can't read from a buffer, the content is
+    ///   instead taken from a copy that glium keeps in memory and updates on every upload.
     ///
     #[inline]
-    pub fn map(self) -> Mapping<'a, T> {
+    pub fn map(self) -> Result<Mapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map(self.bytes_start .. self.bytes_end) }
     }
 
+    /// Same as `map`, but returns `Ok(None)` instead of blocking if the GPU is still using the
+    /// slice.
+    ///
+    /// See `Buffer::try_map`.
+    #[inline]
+    pub fn try_map(self) -> Result<Option<Mapping<'a, T>>, MapError> {
+        if !self.fence.is_available(&mut self.alloc.get_context().make_current(),
+                                    self.bytes_start .. self.bytes_end)
+        {
+            return Ok(None);
+        }
+
+        self.map().map(Some)
+    }
+
     /// Maps the buffer in memory for reading.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -844,9 +1261,13 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     /// - For immutable buffers, creates a temporary buffer containing the data of the buffer and
     ///   maps it.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, reads the content with `glGetBufferSubData` to a
+    ///   copy in memory. When the mapping object is destroyed, uploads the copy back with
+    ///   `glBufferSubData`. On OpenGL ES 2, which can't read from a buffer, the content is
+    ///   instead taken from a copy that glium keeps in memory and updates on every upload.
     ///
     #[inline]
-    pub fn map_read(self) -> ReadMapping<'a, T> {
+    pub fn map_read(self) -> Result<ReadMapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map_read(self.bytes_start .. self.bytes_end) }
@@ -854,6 +1275,9 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
 
     /// Maps the buffer in memory for writing only.
     ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
     /// # Implementation
     ///
     /// - For persistent-mapped buffers, waits until the data is no longer accessed by the GPU then
@@ -861,14 +1285,46 @@ impl<'a, T: ?Sized> BufferMutSlice<'a, T> where T: Content + 'a {
     /// - For immutable buffers, creates a temporary buffer and maps it. When the mapping object
     ///   is destroyed, copies the content of the temporary buffer to the real buffer.
     /// - For other types, calls `glMapBuffer` or `glMapSubBuffer`.
+    /// - If `glMapBufferRange` is not supported, returns a mapping of a copy in memory. When the
+    ///   mapping object is destroyed, uploads the copy with `glBufferSubData`.
     ///
     #[inline]
-    pub fn map_write(self) -> WriteMapping<'a, T> {
+    pub fn map_write(self) -> Result<WriteMapping<'a, T>, MapError> {
         self.fence.wait(&mut self.alloc.get_context().make_current(),
                         self.bytes_start .. self.bytes_end);
         unsafe { self.alloc.map_write(self.bytes_start .. self.bytes_end) }
     }
 
+    /// Maps the slice in memory for writing only, discarding its previous content without
+    /// waiting for the GPU.
+    ///
+    /// This is the fastest way to overwrite scattered parts of a buffer, for example when
+    /// updating particles. The driver neither preserves the old content of the slice nor waits
+    /// for the draw commands that are still using it.
+    ///
+    /// You must write every element of the slice. The content of the mapping is undefined until
+    /// you write it, and elements that you don't write have an undefined value afterwards.
+    /// If a draw command that hasn't finished executing uses this part of the buffer, it may
+    /// see either the old or the new values. Mapping overlapping slices before the previous
+    /// draw commands are over therefore leads to undefined rendering results.
+    ///
+    /// Returns an error if the buffer can't be mapped, for example if the driver fails to map it
+    /// or if the temporary buffer can't be allocated.
+    ///
+    /// # Implementation
+    ///
+    /// - For persistent-mapped buffers, returns a pointer to the existing mapping without waiting
+    ///   for the GPU.
+    /// - For immutable buffers, creates a temporary buffer and maps it. When the mapping object
+    ///   is destroyed, copies the content of the temporary buffer to the real buffer.
+    /// - For other types, calls `glMapBufferRange` with `GL_MAP_WRITE_BIT`,
+    ///   `GL_MAP_INVALIDATE_RANGE_BIT` and `GL_MAP_UNSYNCHRONIZED_BIT`.
+    ///
+    #[inline]
+    pub fn map_write_invalidate_range(self) -> Result<WriteMapping<'a, T>, MapError> {
+        unsafe { self.alloc.map_write_invalidate_range(self.bytes_start .. self.bytes_end) }
+    }
+
     /// Uploads some data in this buffer.
     ///
     /// # Implementation
@@ -1007,6 +1463,10 @@ impl<'a, T> BufferMutSlice<'a, [T]> where [T]: Content, T: Copy + 'a {
             return None;
         }
 
+        if range.start().map_or(0, |e| *e) > range.end().map_or(self.len(), |e| *e) {
+            return None;
+        }
+
         let len = self.len();
         Some(BufferMutSlice {
             alloc: self.alloc,
@@ -1063,6 +1523,140 @@ pub struct BufferAny {
 }
 
 impl BufferAny {
+    /// Builds a new buffer containing the given bytes, and considers that it is made of
+    /// elements of `elements_size` bytes.
+    ///
+    /// Like `Buffer::new`, the bytes are copied before this function returns.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is not a multiple of `elements_size`.
+    pub unsafe fn new_raw_bytes<F: ?Sized>(facade: &F, data: &[u8], elements_size: usize,
+                                           ty: BufferType, mode: BufferMode)
+                                           -> Result<BufferAny, BufferCreationError>
+                                           where F: Facade
+    {
+        assert!(elements_size != 0);
+        assert_eq!(data.len() % elements_size, 0);
+
+        let mut buffer: BufferAny = Buffer::new(facade, data, ty, mode)?.into();
+        buffer.elements_size = elements_size;
+        Ok(buffer)
+    }
+
+    /// Wraps a buffer that was created outside of glium, without copying its content.
+    ///
+    /// The buffer must contain `len` elements of `elements_size` bytes. If `owned` is true,
+    /// glium takes the ownership of the buffer and destroys it when the `BufferAny` is dropped.
+    /// Otherwise the buffer must outlive the `BufferAny` and must be destroyed by its creator.
+    ///
+    /// # Safety
+    ///
+    /// - `id` must be the name of a buffer of at least `len * elements_size` bytes, created
+    ///   with `glBufferData` in the context of `facade` or in a context that shares its objects
+    ///   with it.
+    /// - The buffer must not be modified or destroyed by its creator while it is being used by
+    ///   glium. Glium can't track the reads and writes that happen outside of it.
+    pub unsafe fn from_raw_id<F: ?Sized>(facade: &F, id: gl::types::GLuint, ty: BufferType,
+                                         elements_size: usize, len: usize, owned: bool)
+                                         -> BufferAny where F: Facade
+    {
+        assert!(elements_size != 0);
+
+        let size = elements_size * len;
+
+        BufferAny {
+            alloc: Alloc::from_raw_id(facade, id, ty, size, owned),
+            size: size,
+            elements_size: elements_size,
+            fence: Fences::new(),
+        }
+    }
+
+    /// Destroys the `BufferAny` without destroying the underlying OpenGL buffer, and returns
+    /// its name.
+    ///
+    /// The buffer is leaked unless you destroy it yourself or give it back to glium with
+    /// `from_raw_id`, for example in another context that shares its objects with this one.
+    #[inline]
+    pub fn into_raw_id(mut self) -> gl::types::GLuint {
+        self.alloc.release()
+    }
+
+    /// Turns the `BufferAny` into a `Buffer<T>` without checking the type.
+    ///
+    /// # Safety
+    ///
+    /// The content of the buffer must be a valid `T`.
+    pub unsafe fn into_buffer<T: ?Sized>(self) -> Buffer<T> where T: Content {
+        debug_assert!(<T as Content>::is_size_suitable(self.size));
+
+        let alloc = ptr::read(&self.alloc);
+        let fence = ptr::read(&self.fence);
+        mem::forget(self);
+
+        Buffer {
+            alloc: Some(alloc),
+            fence: Some(fence),
+            marker: PhantomData,
+        }
+    }
+
+    /// Binds the buffer to `target` with `glBindBuffer`, for use with raw OpenGL calls.
+    ///
+    /// Glium caches the buffer bound to each target. Binding through this function keeps the
+    /// cache up to date, while calling `glBindBuffer` yourself would make glium skip some of
+    /// its own binds.
+    ///
+    /// The targets that can be used are `GL_ARRAY_BUFFER`, `GL_PIXEL_PACK_BUFFER`,
+    /// `GL_PIXEL_UNPACK_BUFFER`, `GL_UNIFORM_BUFFER`, `GL_COPY_READ_BUFFER`,
+    /// `GL_COPY_WRITE_BUFFER`, `GL_ATOMIC_COUNTER_BUFFER`, `GL_DISPATCH_INDIRECT_BUFFER`,
+    /// `GL_DRAW_INDIRECT_BUFFER`, `GL_QUERY_BUFFER`, `GL_SHADER_STORAGE_BUFFER` and
+    /// `GL_TEXTURE_BUFFER`. `GL_ELEMENT_ARRAY_BUFFER` is part of the state of the current
+    /// vertex array object, and `GL_TRANSFORM_FEEDBACK_BUFFER` of the current transform
+    /// feedback object, so they can't be used.
+    ///
+    /// # Safety
+    ///
+    /// The binding is only valid until the next glium operation, which may bind another buffer
+    /// to the same target. The context of the buffer must be current when you use it, and the
+    /// buffer must not be mapped.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `target` is not one of the targets above, or if the buffer is mapped.
+    pub unsafe fn bind(&self, target: gl::types::GLenum) {
+        let ty = BufferAny::check_raw_target(target);
+        let mut ctxt = self.alloc.get_context().make_current();
+        self.alloc.bind(&mut ctxt, ty);
+    }
+
+    /// Unbinds whatever buffer is bound to `target`, and updates glium's cache.
+    ///
+    /// This is the counterpart of `bind`. See `bind` for the list of targets.
+    ///
+    /// # Safety
+    ///
+    /// Same as `bind`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `target` is not one of the targets of `bind`.
+    pub unsafe fn unbind(&self, target: gl::types::GLenum) {
+        let ty = BufferAny::check_raw_target(target);
+        let mut ctxt = self.alloc.get_context().make_current();
+        Alloc::unbind(&mut ctxt, ty);
+    }
+
+    /// Returns the `BufferType` of a target that can be used with `bind` and `unbind`.
+    fn check_raw_target(target: gl::types::GLenum) -> BufferType {
+        match BufferType::from_glenum(target) {
+            Some(BufferType::ElementArrayBuffer) | Some(BufferType::TransformFeedbackBuffer) |
+            None => panic!("Buffers can't be bound to the target 0x{:x}", target),
+            Some(ty) => ty,
+        }
+    }
+
     /// Builds a slice-any containing the whole subbuffer.
     #[inline]
     pub fn as_slice_any(&self) -> BufferAnySlice {
@@ -1127,6 +1721,12 @@ impl BufferAny {
         self.size
     }
 
+    /// Returns the mode that was requested when creating this buffer.
+    #[inline]
+    pub fn get_mode(&self) -> BufferMode {
+        self.alloc.get_creation_mode()
+    }
+
     /// Invalidates the content of the buffer. The data becomes undefined.
     ///
     /// This operation is a no-op if the backend doesn't support it and for persistent-mapped
@@ -1146,7 +1746,7 @@ impl BufferAny {
     /// For example, trying to read some `(u8, u8, u8, u8)`s from a buffer of 7 bytes will panic.
     ///
     #[inline]
-    pub unsafe fn read<T>(&self) -> Result<T::Owned, ReadError> where T: Content {
+    pub unsafe fn read<T: ?Sized>(&self) -> Result<T::Owned, ReadError> where T: Content {
         // TODO: add check
         self.fence.wait(&mut self.alloc.get_context().make_current(), 0 .. self.get_size());
         self.alloc.read::<T>(0 .. self.get_size())
@@ -1167,6 +1767,14 @@ impl<T: ?Sized> From<Buffer<T>> for BufferAny where T: Content + Send + 'static
     }
 }
 
+impl BufferAnyExt for BufferAny {
+    #[inline]
+    fn truncate(&mut self, size: usize) {
+        assert!(size <= self.size);
+        self.size = size;
+    }
+}
+
 impl Drop for BufferAny {
     #[inline]
     fn drop(&mut self) {
@@ -1187,14 +1795,24 @@ impl BufferExt for BufferAny {
         0
     }
 
+    #[inline]
+    fn query_size(&self) -> usize {
+        self.alloc.query_size()
+    }
+
+    #[inline]
+    fn get_modifications_count(&self) -> u64 {
+        self.alloc.get_modifications_count()
+    }
+
     #[inline]
     fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_vertex_attrib_array(ctxt);
+        self.alloc.prepare_for_vertex_attrib_array(ctxt, 0 .. self.size);
     }
 
     #[inline]
     fn prepare_for_element_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_element_array(ctxt);
+        self.alloc.prepare_for_element_array(ctxt, 0 .. self.size);
     }
 
     #[inline]
@@ -1303,6 +1921,25 @@ impl<'a> BufferAnySlice<'a> {
         self.get_size() / self.elements_size
     }
 
+    /// Builds a slice of the same size whose start is moved forward by `offset` bytes.
+    ///
+    /// Contrary to slicing, `offset` doesn't need to be a multiple of the size of the elements.
+    /// Returns `None` if the new slice would go past the end of the buffer.
+    #[inline]
+    pub fn with_base_offset(&self, offset: usize) -> Option<BufferAnySlice<'a>> {
+        if self.bytes_end + offset > self.alloc.get_size() {
+            return None;
+        }
+
+        Some(BufferAnySlice {
+            alloc: self.alloc,
+            bytes_start: self.bytes_start + offset,
+            bytes_end: self.bytes_end + offset,
+            elements_size: self.elements_size,
+            fence: self.fence,
+        })
+    }
+
     /// Invalidates the content of the slice. The data becomes undefined.
     ///
     /// This operation is a no-op if the backend doesn't support it and for persistent-mapped
@@ -1317,6 +1954,24 @@ impl<'a> BufferAnySlice<'a> {
     pub fn get_context(&self) -> &Rc<Context> {
         self.alloc.get_context()
     }
+
+    /// UNSTABLE. This function can be removed at any moment without any further notice.
+    ///
+    /// Considers that the slice is filled with elements of type `T` and reads them.
+    ///
+    /// # Safety
+    ///
+    /// The content of the slice must be valid values of type `T`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the size of the slice is not a multiple of the size of the data.
+    #[inline]
+    pub unsafe fn read<T: ?Sized>(&self) -> Result<T::Owned, ReadError> where T: Content {
+        self.fence.wait(&mut self.alloc.get_context().make_current(),
+                        self.bytes_start .. self.bytes_end);
+        self.alloc.read::<T>(self.bytes_start .. self.bytes_end)
+    }
 }
 
 impl<'a> fmt::Debug for BufferAnySlice<'a> {
@@ -1343,14 +1998,24 @@ impl<'a> BufferExt for BufferAnySlice<'a> {
         self.bytes_start
     }
 
+    #[inline]
+    fn query_size(&self) -> usize {
+        self.alloc.query_size()
+    }
+
+    #[inline]
+    fn get_modifications_count(&self) -> u64 {
+        self.alloc.get_modifications_count()
+    }
+
     #[inline]
     fn prepare_for_vertex_attrib_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_vertex_attrib_array(ctxt);
+        self.alloc.prepare_for_vertex_attrib_array(ctxt, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
     fn prepare_for_element_array(&self, ctxt: &mut CommandContext) {
-        self.alloc.prepare_for_element_array(ctxt);
+        self.alloc.prepare_for_element_array(ctxt, self.bytes_start .. self.bytes_end);
     }
 
     #[inline]
@@ -98,6 +98,14 @@ pub struct Capabilities {
     /// Maximum size of a texture (i.e. GL_MAX_TEXTURE_SIZE)
     pub max_texture_size: gl::types::GLint,
 
+    /// Maximum size of a 3D texture (i.e. GL_MAX_3D_TEXTURE_SIZE). `None` if 3D textures are
+    /// not supported.
+    pub max_3d_texture_size: Option<gl::types::GLint>,
+
+    /// Maximum number of layers of an array texture (i.e. GL_MAX_ARRAY_TEXTURE_LAYERS). `None`
+    /// if array textures are not supported.
+    pub max_array_texture_layers: Option<gl::types::GLint>,
+
     /// Maximum size of a buffer texture. `None` if this is not supported.
     pub max_texture_buffer_size: Option<gl::types::GLint>,
 
@@ -107,9 +115,20 @@ pub struct Capabilities {
     /// Maximum number of elements that can be passed with `glDrawBuffers`.
     pub max_draw_buffers: gl::types::GLint,
 
+    /// Maximum number of locations that can be used by the attributes of a vertex shader
+    /// (i.e. GL_MAX_VERTEX_ATTRIBS).
+    pub max_vertex_attribs: gl::types::GLint,
+
+    /// Maximum number of vertex buffer binding points (i.e. GL_MAX_VERTEX_ATTRIB_BINDINGS).
+    /// `None` if the separate attribute format API is not supported.
+    pub max_vertex_attrib_bindings: Option<gl::types::GLint>,
+
     /// Maximum number of vertices per patch. `None` if tessellation is not supported.
     pub max_patch_vertices: Option<gl::types::GLint>,
 
+    /// Size in bytes of a page of a sparse buffer. `None` if sparse buffers are not supported.
+    pub sparse_buffer_page_size: Option<gl::types::GLint>,
+
     /// Number of available buffer bind points for `GL_ATOMIC_COUNTER_BUFFER`.
     pub max_indexed_atomic_counter_buffer: gl::types::GLint,
 
@@ -416,6 +435,30 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             val
         },
 
+        max_3d_texture_size: {
+            if version >= &Version(Api::Gl, 1, 2) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_oes_texture_3d
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_3D_TEXTURE_SIZE, &mut val);
+                Some(val)
+            } else {
+                None
+            }
+        },
+
+        max_array_texture_layers: {
+            if version >= &Version(Api::Gl, 3, 0) || version >= &Version(Api::GlEs, 3, 0) ||
+               extensions.gl_ext_texture_array
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_ARRAY_TEXTURE_LAYERS, &mut val);
+                Some(val)
+            } else {
+                None
+            }
+        },
+
         max_texture_buffer_size: {
             if version >= &Version(Api::Gl, 3, 0) || extensions.gl_arb_texture_buffer_object ||
                extensions.gl_ext_texture_buffer_object || extensions.gl_oes_texture_buffer ||
@@ -438,6 +481,18 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             (val[0], val[1])
         },
 
+        max_vertex_attribs: {
+            if version >= &Version(Api::Gl, 2, 0) || version >= &Version(Api::GlEs, 2, 0) ||
+               extensions.gl_arb_vertex_shader
+            {
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_VERTEX_ATTRIBS, &mut val);
+                val
+            } else {
+                0
+            }
+        },
+
         max_draw_buffers: {
             if version >= &Version(Api::Gl, 2, 0) ||
                 version >= &Version(Api::GlEs, 3, 0) ||
@@ -451,6 +506,19 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             }
         },
 
+        max_vertex_attrib_bindings: if version >= &Version(Api::Gl, 4, 3) ||
+            version >= &Version(Api::GlEs, 3, 1) || extensions.gl_arb_vertex_attrib_binding
+        {
+            Some({
+                let mut val = 0;
+                gl.GetIntegerv(gl::MAX_VERTEX_ATTRIB_BINDINGS, &mut val);
+                val
+            })
+
+        } else {
+            None
+        },
+
         max_patch_vertices: if version >= &Version(Api::Gl, 4, 0) ||
             extensions.gl_arb_tessellation_shader
         {
@@ -464,6 +532,17 @@ pub unsafe fn get_capabilities(gl: &gl::Gl, version: &Version, extensions: &Exte
             None
         },
 
+        sparse_buffer_page_size: if extensions.gl_arb_sparse_buffer {
+            Some({
+                let mut val = 0;
+                gl.GetIntegerv(gl::SPARSE_BUFFER_PAGE_SIZE_ARB, &mut val);
+                val
+            })
+
+        } else {
+            None
+        },
+
         max_indexed_atomic_counter_buffer: if version >= &Version(Api::Gl, 4, 2) {      // TODO: ARB_shader_atomic_counters   // TODO: GLES
             let mut val = 0;
             gl.GetIntegerv(gl::MAX_ATOMIC_COUNTER_BUFFER_BINDINGS, &mut val);
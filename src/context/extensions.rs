@@ -56,6 +56,7 @@ extensions! {
     "GL_APPLE_vertex_array_object" => gl_apple_vertex_array_object,
     "GL_ARB_bindless_texture" => gl_arb_bindless_texture,
     "GL_ARB_buffer_storage" => gl_arb_buffer_storage,
+    "GL_ARB_clear_buffer_object" => gl_arb_clear_buffer_object,
     "GL_ARB_compute_shader" => gl_arb_compute_shader,
     "GL_ARB_copy_buffer" => gl_arb_copy_buffer,
     "GL_ARB_debug_output" => gl_arb_debug_output,
@@ -86,6 +87,7 @@ extensions! {
     "GL_ARB_program_interface_query" => gl_arb_program_interface_query,
     "GL_ARB_query_buffer_object" => gl_arb_query_buffer_object,
     "GL_ARB_map_buffer_range" => gl_arb_map_buffer_range,
+    "GL_ARB_multi_bind" => gl_arb_multi_bind,
     "GL_ARB_multi_draw_indirect" => gl_arb_multi_draw_indirect,
     "GL_ARB_provoking_vertex" => gl_arb_provoking_vertex,
     "GL_ARB_robustness" => gl_arb_robustness,
@@ -97,6 +99,7 @@ extensions! {
     "GL_ARB_shader_objects" => gl_arb_shader_objects,
     "GL_ARB_shader_storage_buffer_object" => gl_arb_shader_storage_buffer_object,
     "GL_ARB_shader_subroutine" => gl_arb_shader_subroutine,
+    "GL_ARB_sparse_buffer" => gl_arb_sparse_buffer,
     "GL_ARB_sync" => gl_arb_sync,
     "GL_ARB_tessellation_shader" => gl_arb_tessellation_shader,
     "GL_ARB_texture_buffer_object" => gl_arb_texture_buffer_object,
@@ -114,7 +117,9 @@ extensions! {
     "GL_ARB_timer_query" => gl_arb_timer_query,
     "GL_ARB_transform_feedback3" => gl_arb_transform_feedback3,
     "GL_ARB_uniform_buffer_object" => gl_arb_uniform_buffer_object,
+    "GL_ARB_vertex_array_bgra" => gl_arb_vertex_array_bgra,
     "GL_ARB_vertex_array_object" => gl_arb_vertex_array_object,
+    "GL_ARB_vertex_attrib_binding" => gl_arb_vertex_attrib_binding,
     "GL_ARB_vertex_buffer_object" => gl_arb_vertex_buffer_object,
     "GL_ARB_vertex_half_float" => gl_arb_vertex_half_float,
     "GL_ARB_vertex_shader" => gl_arb_vertex_shader,
@@ -127,6 +132,7 @@ extensions! {
     "GL_EXT_blend_minmax" => gl_ext_blend_minmax,
     "GL_EXT_buffer_storage" => gl_ext_buffer_storage,
     "GL_EXT_debug_marker" => gl_ext_debug_marker,
+    "GL_EXT_depth_clamp" => gl_ext_depth_clamp,
     "GL_EXT_direct_state_access" => gl_ext_direct_state_access,
     "GL_EXT_disjoint_timer_query" => gl_ext_disjoint_timer_query,
     "GL_EXT_framebuffer_blit" => gl_ext_framebuffer_blit,
@@ -158,6 +164,7 @@ extensions! {
     "GL_EXT_texture_snorm" => gl_ext_texture_snorm,
     "GL_EXT_texture_sRGB" => gl_ext_texture_srgb,
     "GL_EXT_transform_feedback" => gl_ext_transform_feedback,
+    "GL_EXT_vertex_array_bgra" => gl_ext_vertex_array_bgra,
     "GL_GREMEDY_string_marker" => gl_gremedy_string_marker,
     "GL_KHR_debug" => gl_khr_debug,
     "GL_KHR_context_flush_control" => gl_khr_context_flush_control,
@@ -0,0 +1,134 @@
+use context::Capabilities;
+use context::ExtensionsList;
+use version::Version;
+use version::Api;
+
+use buffer;
+use program;
+use vertex;
+use CapabilitiesSource;
+
+/// Summary of the optional features that the backend supports.
+///
+/// This is computed once when the context is created, so that you don't have to check the
+/// version and the extensions yourself before choosing a code path.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Features {
+    /// True if buffers can be persistently mapped. See `BufferMode::Persistent`.
+    pub persistent_mapping: bool,
+
+    /// True if `glBufferStorage` is available, ie. if buffers can have an immutable storage.
+    pub buffer_storage: bool,
+
+    /// True if the content of a buffer can be read back.
+    pub buffer_read: bool,
+
+    /// True if sparse buffers are supported.
+    pub sparse_buffers: bool,
+
+    /// True if per-instance attributes are supported with `glVertexAttribDivisor`.
+    pub instancing: bool,
+
+    /// True if a base vertex can be passed when drawing with an index buffer.
+    pub base_vertex: bool,
+
+    /// True if `glMultiDrawArraysIndirect` and `glMultiDrawElementsIndirect` are available.
+    pub multi_draw_indirect: bool,
+
+    /// True if vertex array objects are supported.
+    pub vertex_array_objects: bool,
+
+    /// True if compute shaders are supported.
+    pub compute_shaders: bool,
+
+    /// True if geometry shaders are supported.
+    pub geometry_shaders: bool,
+
+    /// True if tessellation shaders are supported.
+    pub tessellation_shaders: bool,
+
+    /// True if transform feedback is supported.
+    pub transform_feedback: bool,
+
+    /// True if uniform buffers are supported.
+    pub uniform_buffers: bool,
+
+    /// True if shader storage buffers are supported.
+    pub shader_storage_buffers: bool,
+
+    /// True if buffer textures are supported.
+    pub buffer_textures: bool,
+
+    /// True if program binaries can be retrieved and reloaded.
+    pub program_binaries: bool,
+
+    /// True if shader subroutines are supported.
+    pub subroutines: bool,
+}
+
+/// Borrows the values retrieved at initialization so that the `is_*_supported` functions can
+/// be used before the context is built.
+struct Source<'a> {
+    version: &'a Version,
+    extensions: &'a ExtensionsList,
+    capabilities: &'a Capabilities,
+}
+
+impl<'a> CapabilitiesSource for Source<'a> {
+    #[inline]
+    fn get_version(&self) -> &Version {
+        self.version
+    }
+
+    #[inline]
+    fn get_extensions(&self) -> &ExtensionsList {
+        self.extensions
+    }
+
+    #[inline]
+    fn get_capabilities(&self) -> &Capabilities {
+        self.capabilities
+    }
+}
+
+/// Computes the list of features from the version, extensions and capabilities of a context.
+pub fn get_features(version: &Version, extensions: &ExtensionsList,
+                    capabilities: &Capabilities) -> Features
+{
+    let source = Source { version: version, extensions: extensions, capabilities: capabilities };
+
+    Features {
+        persistent_mapping: buffer::is_persistent_mapping_supported(&source),
+        buffer_storage: buffer::is_buffer_storage_supported(&source),
+        buffer_read: buffer::is_buffer_read_supported(&source),
+        sparse_buffers: buffer::is_sparse_buffer_supported(&source),
+        instancing: vertex::is_instancing_supported(&source),
+        base_vertex: version >= &Version(Api::Gl, 3, 2) || version >= &Version(Api::GlEs, 3, 2) ||
+                     extensions.gl_arb_draw_elements_base_vertex ||
+                     extensions.gl_oes_draw_elements_base_vertex,
+        multi_draw_indirect: version >= &Version(Api::Gl, 4, 3) ||
+                             extensions.gl_arb_multi_draw_indirect ||
+                             extensions.gl_ext_multi_draw_indirect,
+        vertex_array_objects: version >= &Version(Api::Gl, 3, 0) ||
+                              version >= &Version(Api::GlEs, 3, 0) ||
+                              extensions.gl_arb_vertex_array_object ||
+                              extensions.gl_oes_vertex_array_object ||
+                              extensions.gl_apple_vertex_array_object,
+        compute_shaders: program::ComputeShader::is_supported(&source),
+        geometry_shaders: program::is_geometry_shader_supported(&source),
+        tessellation_shaders: program::is_tessellation_shader_supported(&source),
+        transform_feedback: version >= &Version(Api::Gl, 3, 0) ||
+                            version >= &Version(Api::GlEs, 3, 0) ||
+                            extensions.gl_ext_transform_feedback,
+        uniform_buffers: version >= &Version(Api::Gl, 3, 1) ||
+                         version >= &Version(Api::GlEs, 3, 0) ||
+                         extensions.gl_arb_uniform_buffer_object,
+        shader_storage_buffers: version >= &Version(Api::Gl, 4, 3) ||
+                                version >= &Version(Api::GlEs, 3, 1) ||
+                                extensions.gl_arb_shader_storage_buffer_object ||
+                                extensions.gl_nv_shader_storage_buffer_object,
+        buffer_textures: capabilities.max_texture_buffer_size.is_some(),
+        program_binaries: program::is_binary_supported(&source),
+        subroutines: program::is_subroutine_supported(&source),
+    }
+}
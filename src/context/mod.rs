@@ -18,6 +18,9 @@ use std::hash::BuildHasherDefault;
 use fnv::FnvHasher;
 
 use IncompatibleOpenGl;
+use DrawError;
+use Program;
+use draw_parameters::ConstantAttribute;
 use SwapBuffersError;
 use CapabilitiesSource;
 use ContextExt;
@@ -28,18 +31,22 @@ use version::Version;
 
 use debug;
 use fbo;
+use index::IndicesSource;
 use ops;
 use sampler_object;
 use texture;
 use uniforms;
+use vertex::MultiVerticesSource;
 use vertex_array_object;
 
 pub use self::capabilities::{ReleaseBehavior, Capabilities, Profile};
+pub use self::features::Features;
 pub use self::extensions::ExtensionsList;
 pub use self::state::GlState;
 
 mod capabilities;
 mod extensions;
+mod features;
 mod state;
 
 /// Stores the state and information required for glium to execute commands. Most public glium
@@ -62,6 +69,10 @@ pub struct Context {
     /// of texture units, maximum size of the viewport, etc.
     capabilities: Capabilities,
 
+    /// Optional features supported by the backend, computed from the version, extensions and
+    /// capabilities.
+    features: Features,
+
     /// Glue between glium and the code that handles windowing. Contains functions that allows
     /// you to swap buffers, retrieve the size of the framebuffer, etc.
     backend: RefCell<Box<dyn Backend>>,
@@ -96,8 +107,30 @@ pub struct Context {
     /// List of images handles that are resident. We need to call `MakeImageHandleResidentARB`
     /// when rebuilding the context.
     resident_image_handles: RefCell<Vec<(gl::types::GLuint64, gl::types::GLenum)>>,
+
+    /// Hook that is called whenever a buffer is allocated or destroyed.
+    buffer_alloc_hook: RefCell<Option<BufferAllocHook>>,
+
+    /// Result of `vertex::is_instancing_divisor_working`, if it has already been called.
+    instancing_divisor_works: Cell<Option<bool>>,
+
+    /// Hook that is called when the context is detected as lost.
+    context_lost_hook: RefCell<Option<ContextLostHook>>,
+
+    /// True if the hook has already been called for the current loss of the context.
+    context_lost_notified: Cell<bool>,
+
+    /// If false, draw commands don't insert fences for the persistent-mapped buffers they use.
+    auto_fence: Cell<bool>,
 }
 
+/// Hook called with the identifier, the previous size and the new size in bytes of a buffer
+/// whenever it is allocated or destroyed.
+type BufferAllocHook = Box<dyn Fn(gl::types::GLuint, usize, usize)>;
+
+/// Hook called when the context is detected as lost.
+type ContextLostHook = Rc<dyn Fn()>;
+
 /// This struct is a guard that is returned when you want to access the OpenGL backend.
 pub struct CommandContext<'a> {
     /// Source of OpenGL function pointers.
@@ -171,6 +204,7 @@ impl Context {
         check_gl_compatibility(&version, &extensions)?;
 
         let capabilities = capabilities::get_capabilities(&gl, &version, &extensions);
+        let features = features::get_features(&version, &extensions, &capabilities);
         let report_debug_output_errors = Cell::new(true);
 
         let vertex_array_objects = vertex_array_object::VertexAttributesSystem::new();
@@ -202,6 +236,7 @@ impl Context {
             version: version,
             extensions: extensions,
             capabilities: capabilities,
+            features: features,
             debug_callback: debug_callback,
             report_debug_output_errors: report_debug_output_errors,
             backend: RefCell::new(Box::new(backend)),
@@ -211,6 +246,11 @@ impl Context {
             samplers: samplers,
             resident_texture_handles: resident_texture_handles,
             resident_image_handles: resident_image_handles,
+            buffer_alloc_hook: RefCell::new(None),
+            instancing_divisor_works: Cell::new(None),
+            context_lost_hook: RefCell::new(None),
+            context_lost_notified: Cell::new(false),
+            auto_fence: Cell::new(true),
         });
 
         if context.debug_callback.is_some() {
@@ -258,6 +298,7 @@ impl Context {
         new_backend.make_current();
 
         *self.state.borrow_mut() = Default::default();
+        self.context_lost_notified.set(false);
         // FIXME: verify version, capabilities and extensions
         *self.backend.borrow_mut() = Box::new(new_backend);
 
@@ -279,6 +320,7 @@ impl Context {
     /// Swaps the buffers in the backend.
     pub fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
         if self.state.borrow().lost_context {
+            self.notify_context_lost();
             return Err(SwapBuffersError::ContextLost);
         }
 
@@ -308,17 +350,21 @@ impl Context {
             }
         }
 
-        let backend = self.backend.borrow();
-        if self.check_current_context {
-            if !backend.is_current() {
-                unsafe { backend.make_current() };
+        let err = {
+            let backend = self.backend.borrow();
+            if self.check_current_context {
+                if !backend.is_current() {
+                    unsafe { backend.make_current() };
+                }
             }
-        }
 
-        // swapping
-        let err = backend.swap_buffers();
+            // swapping
+            backend.swap_buffers()
+        };
+
         if let Err(SwapBuffersError::ContextLost) = err {
             self.state.borrow_mut().lost_context = true;
+            self.notify_context_lost();
         }
         err
     }
@@ -410,32 +456,47 @@ impl Context {
 
     /// Returns true if the context has been lost and needs to be recreated.
     ///
+    /// When the context is lost, the content of all the buffers, textures and other objects is
+    /// lost as well and drawing produces garbage. glium can't restore them: it is the
+    /// responsibility of the application to recreate the context with `rebuild`, then to
+    /// recreate and reupload all its objects. See also `on_context_lost`.
+    ///
+    /// Robustness is not always available, which means that a loss can't always be detected.
+    /// It is also detected when swapping buffers.
+    ///
     /// # Implementation
     ///
     /// If it has been determined that the context has been lost before, then the function
-    /// immediately returns true. Otherwise, calls `glGetGraphicsResetStatus`. If this function
-    /// is not available, returns false.
+    /// immediately returns true. Otherwise, calls `glGetGraphicsResetStatus` if OpenGL 4.5,
+    /// OpenGL ES 3.2 or one of the `KHR_robustness`, `EXT_robustness` or `ARB_robustness`
+    /// extensions is supported. Otherwise, returns false.
     pub fn is_context_lost(&self) -> bool {
         if self.state.borrow().lost_context {
+            self.notify_context_lost();
             return true;
         }
 
-        let mut ctxt = self.make_current();
+        let lost = {
+            let mut ctxt = self.make_current();
 
-        let lost = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                      ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                      ctxt.extensions.gl_khr_robustness
-        {
-            unsafe { ctxt.gl.GetGraphicsResetStatus() != gl::NO_ERROR }
-        } else if ctxt.extensions.gl_ext_robustness {
-            unsafe { ctxt.gl.GetGraphicsResetStatusEXT() != gl::NO_ERROR }
-        } else if ctxt.extensions.gl_arb_robustness {
-            unsafe { ctxt.gl.GetGraphicsResetStatusARB() != gl::NO_ERROR }
-        } else {
-            false
+            let lost = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
+                          ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+                          ctxt.extensions.gl_khr_robustness
+            {
+                unsafe { ctxt.gl.GetGraphicsResetStatus() != gl::NO_ERROR }
+            } else if ctxt.extensions.gl_ext_robustness {
+                unsafe { ctxt.gl.GetGraphicsResetStatusEXT() != gl::NO_ERROR }
+            } else if ctxt.extensions.gl_arb_robustness {
+                unsafe { ctxt.gl.GetGraphicsResetStatusARB() != gl::NO_ERROR }
+            } else {
+                false
+            };
+
+            if lost { ctxt.state.lost_context = true; }
+            lost
         };
 
-        if lost { ctxt.state.lost_context = true; }
+        if lost { self.notify_context_lost(); }
         lost
     }
 
@@ -455,6 +516,14 @@ impl Context {
         self.capabilities().max_texture_max_anisotropy.map(|v| v as u16)
     }
 
+    /// Returns the list of optional features that the backend supports.
+    ///
+    /// The value is computed once when the context is created.
+    #[inline]
+    pub fn get_features(&self) -> &Features {
+        &self.features
+    }
+
     /// Returns the maximum dimensions of the viewport.
     ///
     /// Glium will panic if you request a larger viewport than this when drawing.
@@ -464,6 +533,108 @@ impl Context {
         (d.0 as u32, d.1 as u32)
     }
 
+    /// Returns the maximum width and height of a texture (i.e. `GL_MAX_TEXTURE_SIZE`).
+    ///
+    /// Like the other limits, this value is queried once when the context is created.
+    #[inline]
+    pub fn max_texture_size(&self) -> u32 {
+        self.capabilities().max_texture_size as u32
+    }
+
+    /// Returns the maximum width, height and depth of a 3D texture, or `None` if 3D textures
+    /// are not supported.
+    #[inline]
+    pub fn max_3d_texture_size(&self) -> Option<u32> {
+        self.capabilities().max_3d_texture_size.map(|v| v as u32)
+    }
+
+    /// Returns the maximum number of layers of an array texture, or `None` if array textures
+    /// are not supported.
+    #[inline]
+    pub fn max_array_texture_layers(&self) -> Option<u32> {
+        self.capabilities().max_array_texture_layers.map(|v| v as u32)
+    }
+
+    /// Returns the maximum number of texels of a buffer texture, or `None` if buffer textures
+    /// are not supported.
+    #[inline]
+    pub fn max_texture_buffer_size(&self) -> Option<u32> {
+        self.capabilities().max_texture_buffer_size.map(|v| v as u32)
+    }
+
+    /// Returns the number of vertex array objects that glium currently keeps in its cache.
+    ///
+    /// A vertex array object is created for each combination of buffers and attribute layout
+    /// that you draw with. Programs that use the same attribute locations share the same entries,
+    /// so drawing the same buffers with such programs doesn't add any. An entry is destroyed when
+    /// one of its buffers is destroyed, when the last program that used it is destroyed, or when
+    /// it is evicted because of `set_vao_cache_limit`.
+    #[inline]
+    pub fn vao_cache_len(&self) -> usize {
+        self.vertex_array_objects.len()
+    }
+
+    /// Destroys all the vertex array objects of the cache with `glDeleteVertexArrays`.
+    ///
+    /// They will be created again the next time they are needed.
+    pub fn clear_vao_cache(&self) {
+        let mut ctxt = self.make_current();
+        vertex_array_object::VertexAttributesSystem::purge_all(&mut ctxt);
+    }
+
+    /// Builds the vertex array object that is used to draw these vertices and indices with this
+    /// program, and stores it in the cache without drawing anything.
+    ///
+    /// Creating a vertex array object the first time a combination of buffers and program is
+    /// drawn can cause a hitch. Calling this function at load time moves this cost off the
+    /// critical path. Does nothing if the vertex array object is already in the cache, or if the
+    /// backend doesn't support vertex array objects.
+    ///
+    /// `constant_attributes` must be the same as the `constant_attributes` of the
+    /// `DrawParameters` that are used when drawing.
+    ///
+    /// Returns an error if the attributes of the program are provided neither by the vertices
+    /// nor by `constant_attributes`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let vertex_buffer: glium::VertexBuffer<[f32; 2]> = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let program: glium::Program = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    /// display.prewarm_vao(&vertex_buffer, &indices, &program, &[]).unwrap();
+    /// ```
+    pub fn prewarm_vao<'a, 'b, V, I>(&self, vertex_buffer: V, index_buffer: I, program: &Program,
+                                     constant_attributes: &[(&str, ConstantAttribute)])
+                                     -> Result<(), DrawError>
+                                     where V: MultiVerticesSource<'b>, I: Into<IndicesSource<'a>>
+    {
+        ops::prewarm_vao(self, vertex_buffer, index_buffer.into(), program, constant_attributes)
+    }
+
+    /// Returns a description of each vertex array object that glium currently keeps in its
+    /// cache, with the buffers and program attributes it was built for.
+    ///
+    /// This is meant to help diagnosing attributes that are not delivered as expected. If you
+    /// don't compile with `debug_assertions`, this always returns an empty list.
+    pub fn dump_vao_cache(&self) -> Vec<vertex_array_object::VaoCacheEntry> {
+        if cfg!(debug_assertions) {
+            self.vertex_array_objects.dump()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Sets the maximum number of vertex array objects in the cache, or `None` for no limit.
+    ///
+    /// When the limit is reached, the least recently used vertex array object is destroyed.
+    /// There is no limit by default.
+    pub fn set_vao_cache_limit(&self, limit: Option<usize>) {
+        let mut ctxt = self.make_current();
+        vertex_array_object::VertexAttributesSystem::set_max_len(&mut ctxt, limit);
+    }
+
     /// Releases the shader compiler, indicating that no new programs will be created for a while.
     ///
     /// This method is a no-op if it's not available in the implementation.
@@ -548,6 +719,93 @@ impl Context {
         action()
     }
 
+    /// Registers a hook that is called whenever a buffer of this context is allocated or
+    /// destroyed. Replaces the hook that was previously registered, if any.
+    ///
+    /// The hook receives the OpenGL identifier of the buffer, its previous size and its new size
+    /// in bytes. The previous size is `0` when the buffer is allocated, and the new size is `0`
+    /// when it is destroyed.
+    ///
+    /// The hook must not create or destroy buffers itself.
+    #[inline]
+    pub fn on_buffer_alloc<F>(&self, hook: F) where F: Fn(gl::types::GLuint, usize, usize) + 'static {
+        *self.buffer_alloc_hook.borrow_mut() = Some(Box::new(hook));
+    }
+
+    /// Removes the hook registered with `on_buffer_alloc`, if any.
+    #[inline]
+    pub fn remove_buffer_alloc_hook(&self) {
+        *self.buffer_alloc_hook.borrow_mut() = None;
+    }
+
+    /// Enables or disables the fences that are inserted after each draw command that uses a
+    /// persistent-mapped buffer. They are enabled by default.
+    ///
+    /// These fences are what makes mapping or writing a persistent buffer wait until the GPU
+    /// has finished reading the data used by previous draw commands. Disabling them removes
+    /// the cost of creating one sync object per draw command, for engines that already
+    /// synchronize their streaming buffers themselves, for example with `SyncFence` and a ring
+    /// of buffers.
+    ///
+    /// This applies to every persistent-mapped buffer that a draw command uses: vertex, index
+    /// and indirect commands buffers, as well as the uniform, shader storage and atomic counter
+    /// buffers of the uniforms. The fences inserted by buffer copies are not affected.
+    ///
+    /// # Safety
+    ///
+    /// While the fences are disabled, modifying a persistent-mapped buffer doesn't wait for the
+    /// draw commands that use it, whatever its role in the draw command. This includes shader
+    /// storage and atomic counter buffers that the GPU writes, whose content may not be
+    /// written yet when they are mapped for reading. The caller must ensure that the GPU has
+    /// finished accessing a range before it is mapped or written again, otherwise the GPU may
+    /// read partially written data and the CPU may read stale data.
+    #[inline]
+    pub unsafe fn set_auto_fence(&self, enabled: bool) {
+        self.auto_fence.set(enabled);
+    }
+
+    /// Returns false if fences are not inserted after draw commands. See `set_auto_fence`.
+    #[inline]
+    pub fn is_auto_fence_enabled(&self) -> bool {
+        self.auto_fence.get()
+    }
+
+    /// Registers a hook that is called once when the context is detected as lost, either by
+    /// `is_context_lost` or when swapping buffers. Replaces the hook that was previously
+    /// registered, if any.
+    ///
+    /// The hook is only called for the first detection of the loss, and is called again only
+    /// if the context is lost again after `rebuild`. It may register another hook or remove
+    /// itself.
+    ///
+    /// The hook is typically used to schedule the recreation of the context and of all the
+    /// buffers and textures, which are not restored by glium. It must not draw or create any
+    /// object with this context.
+    #[inline]
+    pub fn on_context_lost<F>(&self, hook: F) where F: Fn() + 'static {
+        *self.context_lost_hook.borrow_mut() = Some(Rc::new(hook));
+    }
+
+    /// Removes the hook registered with `on_context_lost`, if any.
+    #[inline]
+    pub fn remove_context_lost_hook(&self) {
+        *self.context_lost_hook.borrow_mut() = None;
+    }
+
+    /// Calls the hook registered with `on_context_lost`, if any, unless it has already been
+    /// called since the context was lost.
+    fn notify_context_lost(&self) {
+        if self.context_lost_notified.replace(true) {
+            return;
+        }
+
+        // the cell is not borrowed during the call, so that the hook can replace itself
+        let hook = self.context_lost_hook.borrow().clone();
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+
     /// Asserts that there are no OpenGL errors pending.
     ///
     /// This function should be used in tests.
@@ -670,6 +928,18 @@ impl ContextExt for Context {
     fn capabilities(&self) -> &Capabilities {
         &self.capabilities
     }
+
+    #[inline]
+    fn notify_buffer_alloc(&self, id: gl::types::GLuint, old_size: usize, new_size: usize) {
+        if let Some(ref hook) = *self.buffer_alloc_hook.borrow() {
+            hook(id, old_size, new_size);
+        }
+    }
+
+    #[inline]
+    fn instancing_divisor_works(&self) -> &Cell<Option<bool>> {
+        &self.instancing_divisor_works
+    }
 }
 
 impl CapabilitiesSource for Context {
@@ -48,6 +48,12 @@ pub struct GlState {
     /// Whether GL_PRIMITIVE_RESTART_FIXED_INDEX is enabled
     pub enabled_primitive_fixed_restart: bool,
 
+    /// Whether GL_PRIMITIVE_RESTART is enabled
+    pub enabled_primitive_restart: bool,
+
+    /// The latest value passed to `glPrimitiveRestartIndex`.
+    pub primitive_restart_index: gl::types::GLuint,
+
     /// Whether GL_RASTERIZER_DISCARD is enabled
     pub enabled_rasterizer_discard: bool,
 
@@ -387,6 +393,8 @@ impl Default for GlState {
             enabled_line_smooth: false,
             enabled_polygon_smooth: false,
             enabled_primitive_fixed_restart: false,
+            enabled_primitive_restart: false,
+            primitive_restart_index: 0,
             enabled_program_point_size: false,
             enabled_clip_planes: 0,
 
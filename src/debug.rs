@@ -12,6 +12,7 @@ use gl;
 use std::rc::Rc;
 
 pub use context::DebugCallbackBehavior;
+pub use vertex_array_object::{VaoCacheAttribute, VaoCacheEntry};
 
 /// Represents a callback that can be used for the debug output feature of OpenGL.
 ///
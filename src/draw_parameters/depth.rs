@@ -41,6 +41,9 @@ pub struct Depth {
 
     /// Sets whether the depth values of samples should be clamped to `0.0` and `1.0`.
     ///
+    /// The state is only changed when it differs from the one of the previous draw call, so
+    /// going back to `NoClamp` disables clamping again.
+    ///
     /// The default value is `NoClamp`.
     pub clamp: DepthClamp,
 }
@@ -149,7 +152,12 @@ pub enum DepthClamp {
 
     /// Clamp the depth values. All samples will always be drawn.
     ///
-    /// This value is only supported on OpenGL.
+    /// This enables `GL_DEPTH_CLAMP`, which disables the clipping against the near and far
+    /// planes. This is useful for shadow volumes, or to avoid holes in the geometry that
+    /// crosses the near plane with a wide field of view.
+    ///
+    /// This value requires OpenGL 3.2, `GL_ARB_depth_clamp` or `GL_NV_depth_clamp`, or
+    /// `GL_EXT_depth_clamp` on OpenGL ES.
     Clamp,
 
     /// Depth values inferior to `0.0` will be clamped to `0.0`.
@@ -174,8 +182,8 @@ pub fn sync_depth(ctxt: &mut CommandContext, depth: &Depth) -> Result<(), DrawEr
             (DepthClamp::Clamp, &mut true, &mut true) => (),
 
             (DepthClamp::NoClamp, near, far) => {
-                if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_depth_clamp ||
-                   ctxt.extensions.gl_nv_depth_clamp
+                if ctxt.version >= &Version(Api::Gl, 3, 2) || ctxt.extensions.gl_arb_depth_clamp ||
+                   ctxt.extensions.gl_nv_depth_clamp || ctxt.extensions.gl_ext_depth_clamp
                 {
                     unsafe { ctxt.gl.Disable(gl::DEPTH_CLAMP) };
                     *near = false;
@@ -186,8 +194,8 @@ pub fn sync_depth(ctxt: &mut CommandContext, depth: &Depth) -> Result<(), DrawEr
             },
 
             (DepthClamp::Clamp, near, far) => {
-                if ctxt.version >= &Version(Api::Gl, 3, 0) || ctxt.extensions.gl_arb_depth_clamp ||
-                   ctxt.extensions.gl_nv_depth_clamp
+                if ctxt.version >= &Version(Api::Gl, 3, 2) || ctxt.extensions.gl_arb_depth_clamp ||
+                   ctxt.extensions.gl_nv_depth_clamp || ctxt.extensions.gl_ext_depth_clamp
                 {
                     unsafe { ctxt.gl.Enable(gl::DEPTH_CLAMP) };
                     *near = true;
@@ -216,6 +216,11 @@ impl ToGlEnum for Smooth {
 }
 
 /// The vertex to use for flat shading.
+///
+/// Meshes that were authored for Direct3D, or exported by tools that follow its convention,
+/// usually expect the first vertex of each primitive to provide the `flat` values. Drawing them
+/// with the default `LastVertex` gives each triangle the color of another vertex. Use
+/// `is_provoking_vertex_supported` to check whether `FirstVertex` can be used.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ProvokingVertex {
     /// Use the last vertex of each primitive.
@@ -392,6 +397,100 @@ pub struct DrawParameters<'a> {
     /// If the backend does not support GL_PRIMITIVE_RESTART_FIXED_INDEX, an Error
     /// of type `FixedIndexRestartingNotSupported` will be returned.
     pub primitive_restart_index: bool,
+
+    /// Constant values for attributes that the program expects but that aren't provided by any
+    /// of the vertices sources.
+    ///
+    /// Each value is applied with the `glVertexAttrib*` function that matches its type. A
+    /// constant attribute has the same value for every vertex and every instance, as if it was
+    /// a per-instance attribute whose divisor is larger than the number of instances. Constants
+    /// whose attribute is provided by a vertex buffer are ignored.
+    ///
+    /// Attributes that are neither in the vertices sources nor in this list, and constants whose
+    /// kind doesn't match the type of the attribute, make the draw operation panic, unless
+    /// `check_errors` is true.
+    pub constant_attributes: &'a [(&'a str, ConstantAttribute)],
+
+    /// If true, the draw operation returns `AttributeMissing` or `AttributeTypeMismatch`
+    /// instead of panicking when the vertices sources don't match the program, and calls
+    /// `glGetError` after the draw command to report OpenGL errors as `DrawError::OpenGlError`.
+    ///
+    /// Calling `glGetError` forces a synchronization with the driver, which is why this is
+    /// disabled by default. See also `Surface::draw_checked`.
+    pub check_errors: bool,
+
+    /// Smallest and largest values, both inclusive, of the indices used by the draw command.
+    ///
+    /// If `Some`, drawing with an index buffer calls `glDrawRangeElements` instead of
+    /// `glDrawElements`, which lets the driver know which part of the vertex buffers is used.
+    /// This is mostly useful when a few meshes are drawn from a large shared vertex buffer.
+    ///
+    /// This is only a hint and is ignored if the backend doesn't support `glDrawRangeElements`
+    /// (OpenGL 1.2 or OpenGL ES 3.0), for instanced draws and when a base vertex is used.
+    ///
+    /// The indices must be within the range, otherwise the result is undefined. If debug
+    /// assertions are enabled, the index buffer is read back before drawing and the draw
+    /// returns `DrawError::IndexRangeMismatch` if an index is outside of the range. The primitive
+    /// restart index of the buffer and, if `primitive_restart_index` is enabled, the maximum
+    /// value of the index type are allowed. The check is skipped if the backend doesn't support
+    /// reading from a buffer. A range whose start is larger than its end always returns
+    /// `DrawError::IndexRangeMismatch`.
+    pub index_range: Option<(u32, u32)>,
+}
+
+/// Value of a vertex attribute that is not backed by a buffer.
+///
+/// See the `constant_attributes` field of `DrawParameters`.
+///
+/// The value is applied while the attribute array is disabled, and always contains four
+/// components. A `vec3` input only reads the first three of them, but a `vec4` input reads the
+/// full value, which makes it possible to choose `w` instead of getting the default `1.0`.
+///
+/// This doesn't apply to attributes that are provided by a vertex buffer. When a buffer
+/// provides fewer components than the shader input, OpenGL always fills the missing `y` and
+/// `z` components with `0.0` and `w` with `1.0`. To get a `w` of `0.0` for a direction stored
+/// with three components, either store four components or write `vec4(direction, 0.0)` in
+/// the shader.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstantAttribute {
+    /// Set with `glVertexAttrib4f`. Used for `float`, `vec` and normalized attributes.
+    Float([f32; 4]),
+    /// Set with one `glVertexAttrib4f` per column. Used for `mat` attributes, which occupy one
+    /// location per column. Only the columns and rows that exist in the attribute are read.
+    Matrix([[f32; 4]; 4]),
+    /// Set with `glVertexAttribI4i`. Used for `int` and `ivec` attributes, which require
+    /// OpenGL 3.0 or OpenGL ES 3.0.
+    Int([i32; 4]),
+    /// Set with `glVertexAttribI4ui`. Used for `uint` and `uvec` attributes, which require
+    /// OpenGL 3.0 or OpenGL ES 3.0.
+    UnsignedInt([u32; 4]),
+}
+
+impl ConstantAttribute {
+    /// Builds a `vec4` constant for a position, whose `w` is `1.0`.
+    #[inline]
+    pub fn position(value: [f32; 3]) -> ConstantAttribute {
+        ConstantAttribute::Float([value[0], value[1], value[2], 1.0])
+    }
+
+    /// Builds a `vec4` constant for a direction, whose `w` is `0.0`. Contrary to a position, a
+    /// direction isn't affected by the translation of a 4x4 matrix.
+    #[inline]
+    pub fn direction(value: [f32; 3]) -> ConstantAttribute {
+        ConstantAttribute::Float([value[0], value[1], value[2], 0.0])
+    }
+
+    /// Builds an identity matrix constant, for example for a per-instance transformation that
+    /// only some of the meshes provide.
+    #[inline]
+    pub fn identity() -> ConstantAttribute {
+        ConstantAttribute::Matrix([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
 }
 
 /// Condition whether to render or not.
@@ -459,10 +558,25 @@ impl<'a> Default for DrawParameters<'a> {
             provoking_vertex: ProvokingVertex::LastVertex,
             primitive_bounding_box: (-1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0, -1.0 .. 1.0),
             primitive_restart_index: false,
+            constant_attributes: &[],
+            check_errors: false,
+            index_range: None,
         }
     }
 }
 
+/// Returns true if the backend supports choosing the provoking vertex with `glProvokingVertex`
+/// (OpenGL 3.2, `GL_ARB_provoking_vertex` or `GL_EXT_provoking_vertex`).
+///
+/// If this returns false, drawing with `ProvokingVertex::FirstVertex` returns
+/// `DrawError::ProvokingVertexNotSupported`.
+#[inline]
+pub fn is_provoking_vertex_supported<C: ?Sized>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 3, 2) ||
+        context.get_extensions().gl_arb_provoking_vertex ||
+        context.get_extensions().gl_ext_provoking_vertex
+}
+
 /// DEPRECATED. Checks parameters and returns an error if something is wrong.
 pub fn validate(context: &Context, params: &DrawParameters) -> Result<(), DrawError> {
     if params.depth.range.0 < 0.0 || params.depth.range.0 > 1.0 ||
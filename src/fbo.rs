@@ -64,6 +64,7 @@ use GlObject;
 use TextureExt;
 
 use texture::CubeLayer;
+use texture::Dimensions;
 use texture::TextureAnyImage;
 use texture::TextureAnyMipmap;
 use texture::TextureKind;
@@ -85,6 +86,16 @@ pub fn is_dimensions_mismatch_supported<C: ?Sized>(context: &C) -> bool where C:
     context.get_extensions().gl_arb_framebuffer_object
 }
 
+/// Returns true if the backend supports layered framebuffers, where each attachment covers all
+/// the layers of a texture array, 3D texture or cubemap and a geometry shader picks the layer
+/// to render to with `gl_Layer`.
+pub fn is_layered_supported<C: ?Sized>(context: &C) -> bool where C: CapabilitiesSource {
+    context.get_version() >= &Version(Api::Gl, 3, 2) ||
+    context.get_extensions().gl_arb_direct_state_access ||
+    (context.get_extensions().gl_ext_direct_state_access &&
+     context.get_extensions().gl_ext_geometry_shader4)
+}
+
 /// Represents the attachments to use for an OpenGL framebuffer.
 #[derive(Clone)]
 pub enum FramebufferAttachments<'a> {
@@ -128,6 +139,28 @@ impl<'a> RegularAttachment<'a> {
 #[derive(Copy, Clone)]
 pub struct LayeredAttachment<'a>(TextureAnyMipmap<'a>);
 
+impl<'a> LayeredAttachment<'a> {
+    /// Builds a layered attachment that covers all the layers of a mipmap.
+    #[inline]
+    pub fn new(mipmap: TextureAnyMipmap<'a>) -> LayeredAttachment<'a> {
+        LayeredAttachment(mipmap)
+    }
+
+    /// Returns the number of layers of the attachment, or `None` if the texture can't be
+    /// attached as a layered attachment.
+    fn get_layers(&self) -> Option<u32> {
+        match self.0.get_texture().get_texture_type() {
+            Dimensions::Texture1dArray { array_size, .. } => Some(array_size),
+            Dimensions::Texture2dArray { array_size, .. } => Some(array_size),
+            Dimensions::Texture2dMultisampleArray { array_size, .. } => Some(array_size),
+            Dimensions::Texture3d { .. } => self.0.get_depth(),
+            Dimensions::Cubemap { .. } => Some(6),
+            Dimensions::CubemapArray { array_size, .. } => Some(6 * array_size),
+            _ => None,
+        }
+    }
+}
+
 /// Depth and/or stencil attachment to use.
 #[derive(Copy, Clone)]
 pub enum DepthStencilAttachments<T> {
@@ -220,16 +253,26 @@ impl<'a> FramebufferAttachments<'a> {
                            -> Result<ValidatedAttachments<'a>, ValidationError>
                            where C: CapabilitiesSource
     {
-        // TODO: make sure that all attachments are layered
+        if !is_layered_supported(context) {
+            return Err(ValidationError::LayeredFramebuffersNotSupported);
+        }
 
         macro_rules! handle_tex {
-            ($tex:ident, $dim:ident, $samples:ident, $num_bits:ident) => ({
-                $num_bits = Some($tex.get_texture().get_internal_format()
+            ($tex:ident, $dim:ident, $samples:ident, $layers:ident, $num_bits:ident) => ({
+                $num_bits = Some($tex.0.get_texture().get_internal_format()
                                      .map(|f| f.get_total_bits()).ok().unwrap_or(24) as u16);     // TODO: how to handle this?
-                handle_tex!($tex, $dim, $samples)
+                handle_tex!($tex, $dim, $samples, $layers)
             });
 
-            ($tex:ident, $dim:ident, $samples:ident) => ({
+            ($tex:ident, $dim:ident, $samples:ident, $layers:ident) => ({
+                let tex_layers = match $tex.get_layers() {
+                    Some(l) => l,
+                    None => return Err(ValidationError::AttachmentNotLayered),
+                };
+                $layers = Some(cmp::min($layers.unwrap_or(tex_layers), tex_layers));
+
+                let $tex = &$tex.0;
+
                 // TODO: check that internal format is renderable
                 let context = $tex.get_texture().get_context();
 
@@ -298,35 +341,40 @@ impl<'a> FramebufferAttachments<'a> {
         let mut stencil_bits = None;
         let mut samples = None;     // contains `0` if not multisampling and `None` if unknown
 
-        for &(index, LayeredAttachment(ref attachment)) in colors.iter() {
+        let mut layers = None;
+
+        for &(index, ref attachment) in colors.iter() {
             if index >= max_color_attachments as u32 {
                 return Err(ValidationError::TooManyColorAttachments{
                     maximum: max_color_attachments as usize,
                     obtained: index as usize,
                 });
             }
-            raw_attachments.color.push((index, handle_tex!(attachment, dimensions, samples)));
+            raw_attachments.color.push((index, handle_tex!(attachment, dimensions, samples,
+                                                           layers)));
         }
 
         match depth_stencil {
             DepthStencilAttachments::None => (),
-            DepthStencilAttachments::DepthAttachment(LayeredAttachment(ref d)) => {
-                raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, depth_bits));
+            DepthStencilAttachments::DepthAttachment(ref d) => {
+                raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, layers,
+                                                         depth_bits));
             },
-            DepthStencilAttachments::StencilAttachment(LayeredAttachment(ref s)) => {
-                raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, stencil_bits));
+            DepthStencilAttachments::StencilAttachment(ref s) => {
+                raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, layers,
+                                                           stencil_bits));
             },
-            DepthStencilAttachments::DepthAndStencilAttachments(LayeredAttachment(ref d),
-                                                                 LayeredAttachment(ref s))
-            => {
-                raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, depth_bits));
-                raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, stencil_bits));
+            DepthStencilAttachments::DepthAndStencilAttachments(ref d, ref s) => {
+                raw_attachments.depth = Some(handle_tex!(d, dimensions, samples, layers,
+                                                         depth_bits));
+                raw_attachments.stencil = Some(handle_tex!(s, dimensions, samples, layers,
+                                                           stencil_bits));
             },
-            DepthStencilAttachments::DepthStencilAttachment(LayeredAttachment(ref ds)) => {
-                let depth_stencil_bits = ds.get_texture().get_depth_stencil_bits();
+            DepthStencilAttachments::DepthStencilAttachment(ref ds) => {
+                let depth_stencil_bits = ds.0.get_texture().get_depth_stencil_bits();
                 depth_bits = Some(depth_stencil_bits.0);
                 stencil_bits = Some(depth_stencil_bits.1);
-                raw_attachments.depth_stencil = Some(handle_tex!(ds, dimensions, samples));
+                raw_attachments.depth_stencil = Some(handle_tex!(ds, dimensions, samples, layers));
             },
         }
 
@@ -340,7 +388,7 @@ impl<'a> FramebufferAttachments<'a> {
         Ok(ValidatedAttachments {
             raw: raw_attachments,
             dimensions: dimensions,
-            layers: None,       // FIXME: count layers
+            layers: layers,
             depth_buffer_bits: depth_bits,
             stencil_buffer_bits: stencil_bits,
             marker: PhantomData,
@@ -598,6 +646,12 @@ pub enum ValidationError {
         /// Number of attachments that were given.
         obtained: usize,
     },
+
+    /// You requested a layered framebuffer, but they are not supported.
+    LayeredFramebuffersNotSupported,
+
+    /// A layered attachment must be a texture array, a 3D texture or a cubemap.
+    AttachmentNotLayered,
 }
 
 impl fmt::Display for ValidationError {
@@ -614,6 +668,10 @@ impl fmt::Display for ValidationError {
                 "All attachments must have the same number of samples",
             TooManyColorAttachments {..} =>
                 "Backends only support a certain number of color attachments",
+            LayeredFramebuffersNotSupported =>
+                "You requested a layered framebuffer, but they are not supported",
+            AttachmentNotLayered =>
+                "A layered attachment must be a texture array, a 3D texture or a cubemap",
         };
         match self {
             TooManyColorAttachments{ ref maximum, ref obtained } =>
@@ -16,6 +16,7 @@ use uniforms;
 
 use {Program, Surface};
 use DrawError;
+use ReadError;
 
 use {fbo, gl};
 use framebuffer;
@@ -74,6 +75,14 @@ impl Surface for DefaultFramebuffer {
         self.context.capabilities().stencil_bits
     }
 
+    fn read_depth(&self, rect: &Rect) -> Result<Vec<f32>, ReadError> {
+        if !self.has_depth_buffer() {
+            return Err(ReadError::NoDepthBuffer);
+        }
+
+        ops::read_depth(&self.context, None, rect)
+    }
+
     fn draw<'a, 'b, V, I, U>(&mut self, vertex_buffer: V,
                          index_buffer: I, program: &Program, uniforms: &U,
                          draw_parameters: &DrawParameters) -> Result<(), DrawError>
@@ -35,6 +35,22 @@ let framebuffer = glium::framebuffer::MultiOutputFrameBuffer::new(&display, outp
 
 **Note**: depth-stencil attachments are not yet implemented.
 
+# Rendering to a mipmap level
+
+Passing a texture attaches its main level. To draw on another level of the mipmap chain, for
+example for a downsampling pass, attach the result of `mipmap()` instead. It returns `None` if
+the level doesn't exist in the texture.
+
+```no_run
+# let display: glium::Display = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+# let texture: glium::texture::Texture2d = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+let level = texture.mipmap(2).expect("the texture doesn't have 3 levels");
+let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, level);
+// framebuffer.draw(...);    // draws over the third level of `texture`
+```
+
+The dimensions of the framebuffer are the dimensions of the attached level.
+
 # A note on restrictions
 
 Some restrictions apply when you use framebuffers:
@@ -63,13 +79,22 @@ You can check whether they are supported by calling `EmptyFrameBuffer::is_suppor
 
 # Layered framebuffers
 
-Not yet supported
+A layered framebuffer has attachments that cover all the layers of a texture array, a 3D texture
+or a cubemap (in which case each face is a layer). Each primitive is rendered to the layer chosen
+by the geometry shader with `gl_Layer`. Combined with instancing, this lets you render to all
+the faces of a cubemap in a single draw call, for example by passing `gl_InstanceID` from the
+vertex shader to the geometry shader.
+
+Layered framebuffers are created with `SimpleFrameBuffer::layered`,
+`SimpleFrameBuffer::layered_with_depth_buffer` and `SimpleFrameBuffer::layered_depth_only`.
+You can check whether they are supported by calling `is_layered_supported(&display)`.
 
 */
 use std::rc::Rc;
 use smallvec::SmallVec;
 
 use texture::TextureAnyImage;
+use texture::TextureAnyMipmap;
 
 use backend::Facade;
 use context::Context;
@@ -87,6 +112,7 @@ use uniforms;
 
 use {Program, Surface};
 use DrawError;
+use ReadError;
 
 use {fbo, gl};
 
@@ -95,6 +121,7 @@ pub use self::render_buffer::{RenderBuffer, RenderBufferAny, DepthRenderBuffer};
 pub use self::render_buffer::{StencilRenderBuffer, DepthStencilRenderBuffer};
 pub use self::render_buffer::CreationError as RenderBufferCreationError;
 pub use fbo::is_dimensions_mismatch_supported;
+pub use fbo::is_layered_supported;
 pub use fbo::ValidationError;
 
 mod default_fb;
@@ -210,6 +237,67 @@ impl<'a> SimpleFrameBuffer<'a> {
                                     Some(depthstencil.to_depth_stencil_attachment()))
     }
 
+    /// Creates a layered `SimpleFrameBuffer` with a single color attachment and no depth
+    /// nor stencil buffer.
+    ///
+    /// The attachment must be a mipmap of a texture array, a 3D texture or a cubemap. See the
+    /// module-level documentation about layered framebuffers.
+    #[inline]
+    pub fn layered<F: ?Sized>(facade: &F, color: TextureAnyMipmap<'a>)
+                              -> Result<SimpleFrameBuffer<'a>, ValidationError> where F: Facade
+    {
+        SimpleFrameBuffer::new_layered_impl(facade, Some(color), None)
+    }
+
+    /// Creates a layered `SimpleFrameBuffer` with a single color attachment and a depth
+    /// buffer, but no stencil buffer.
+    ///
+    /// The attachments must be mipmaps of texture arrays, 3D textures or cubemaps. The
+    /// framebuffer has as many layers as the attachment with the fewest layers.
+    #[inline]
+    pub fn layered_with_depth_buffer<F: ?Sized>(facade: &F, color: TextureAnyMipmap<'a>,
+                                                depth: TextureAnyMipmap<'a>)
+                                                -> Result<SimpleFrameBuffer<'a>, ValidationError>
+                                                where F: Facade
+    {
+        SimpleFrameBuffer::new_layered_impl(facade, Some(color), Some(depth))
+    }
+
+    /// Creates a layered `SimpleFrameBuffer` with only a depth buffer, for example to render
+    /// all the faces of a shadow cubemap at once.
+    #[inline]
+    pub fn layered_depth_only<F: ?Sized>(facade: &F, depth: TextureAnyMipmap<'a>)
+                                         -> Result<SimpleFrameBuffer<'a>, ValidationError>
+                                         where F: Facade
+    {
+        SimpleFrameBuffer::new_layered_impl(facade, None, Some(depth))
+    }
+
+    fn new_layered_impl<F: ?Sized>(facade: &F, color: Option<TextureAnyMipmap<'a>>,
+                                   depth: Option<TextureAnyMipmap<'a>>)
+                                   -> Result<SimpleFrameBuffer<'a>, ValidationError>
+                                   where F: Facade
+    {
+        let attachments = fbo::FramebufferAttachments::Layered(fbo::FramebufferSpecificAttachments {
+            colors: if let Some(color) = color {
+                let mut v = SmallVec::new(); v.push((0, fbo::LayeredAttachment::new(color))); v
+            } else {
+                SmallVec::new()
+            },
+            depth_stencil: if let Some(depth) = depth {
+                fbo::DepthStencilAttachments::DepthAttachment(fbo::LayeredAttachment::new(depth))
+            } else {
+                fbo::DepthStencilAttachments::None
+            }
+        });
+
+        let attachments = attachments.validate(facade)?;
+
+        Ok(SimpleFrameBuffer {
+            context: facade.get_context().clone(),
+            attachments: attachments,
+        })
+    }
 
     fn new_impl<F: ?Sized>(facade: &F, color: Option<ColorAttachment<'a>>,
                    depth: Option<DepthAttachment<'a>>, stencil: Option<StencilAttachment<'a>>,
@@ -287,6 +375,14 @@ impl<'a> Surface for SimpleFrameBuffer<'a> {
         self.attachments.get_stencil_buffer_bits()
     }
 
+    fn read_depth(&self, rect: &Rect) -> Result<Vec<f32>, ReadError> {
+        if !self.has_depth_buffer() {
+            return Err(ReadError::NoDepthBuffer);
+        }
+
+        ops::read_depth(&self.context, Some(&self.attachments), rect)
+    }
+
     fn draw<'b, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
         uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
         where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
@@ -555,6 +651,14 @@ impl<'a> Surface for MultiOutputFrameBuffer<'a> {
         self.example_attachments.get_stencil_buffer_bits()
     }
 
+    fn read_depth(&self, rect: &Rect) -> Result<Vec<f32>, ReadError> {
+        if !self.has_depth_buffer() {
+            return Err(ReadError::NoDepthBuffer);
+        }
+
+        ops::read_depth(&self.context, Some(&self.example_attachments), rect)
+    }
+
     fn draw<'i, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
         uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
         where I: Into<::index::IndicesSource<'i>>,
@@ -724,6 +828,11 @@ impl Surface for EmptyFrameBuffer {
         None
     }
 
+    #[inline]
+    fn read_depth(&self, _: &Rect) -> Result<Vec<f32>, ReadError> {
+        Err(ReadError::NoDepthBuffer)
+    }
+
     fn draw<'b, 'v, V, I, U>(&mut self, vb: V, ib: I, program: &::Program,
         uniforms: &U, draw_parameters: &::DrawParameters) -> Result<(), DrawError>
         where I: Into<::index::IndicesSource<'b>>, U: ::uniforms::Uniforms,
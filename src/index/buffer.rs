@@ -1,7 +1,8 @@
 use buffer::{Buffer, BufferSlice, BufferMutSlice, BufferAny, BufferType};
-use buffer::{BufferMode, BufferCreationError};
+use buffer::{BufferAnySlice, BufferMode, BufferCreationError};
 use gl;
 use GlObject;
+use BufferExt;
 
 use backend::Facade;
 
@@ -10,6 +11,7 @@ use index::Index;
 use index::IndexType;
 use index::PrimitiveType;
 
+use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::fmt;
 use std::error::Error;
@@ -65,6 +67,7 @@ impl From<BufferCreationError> for CreationError {
 pub struct IndexBuffer<T> where T: Index {
     buffer: Buffer<[T]>,
     primitives: PrimitiveType,
+    primitive_restart: Option<u32>,
 }
 
 impl<T> IndexBuffer<T> where T: Index {
@@ -120,6 +123,7 @@ impl<T> IndexBuffer<T> where T: Index {
         Ok(IndexBuffer {
             buffer: Buffer::new(facade, data, BufferType::ElementArrayBuffer, mode)?.into(),
             primitives: prim,
+            primitive_restart: None,
         })
     }
 
@@ -176,6 +180,7 @@ impl<T> IndexBuffer<T> where T: Index {
             buffer: Buffer::empty_array(facade, BufferType::ElementArrayBuffer, len,
                                                  mode)?.into(),
             primitives: prim,
+            primitive_restart: None,
         })
     }
 
@@ -191,13 +196,42 @@ impl<T> IndexBuffer<T> where T: Index {
         <T as Index>::get_type()
     }
 
-    /// Returns `None` if out of range.
+    /// Makes every draw that uses this buffer restart the primitive when `index` is
+    /// encountered.
+    ///
+    /// This is useful with strips and fans, in order to draw multiple objects with a single
+    /// index buffer. Primitive restart is enabled when the buffer is used and disabled again for
+    /// the buffers that don't have a restart index, independently of the
+    /// `primitive_restart_index` draw parameter.
+    ///
+    /// Drawing returns `DrawError::PrimitiveRestartNotSupported` if the backend doesn't support
+    /// `GL_PRIMITIVE_RESTART` (OpenGL 3.1) and `index` is not the maximum value of `T`, and
+    /// `DrawError::PrimitiveRestartIndexConflict` if the `primitive_restart_index` draw parameter
+    /// is enabled and `index` is not the maximum value of `T`.
+    #[inline]
+    pub fn with_primitive_restart(mut self, index: T) -> IndexBuffer<T> where T: Into<u32> {
+        self.primitive_restart = Some(index.into());
+        self
+    }
+
+    /// Returns the index that restarts the primitive, if any.
+    #[inline]
+    pub fn get_primitive_restart(&self) -> Option<u32> {
+        self.primitive_restart
+    }
+
+    /// Builds a slice containing the indices of the given range. The range is in indices,
+    /// not in bytes.
+    ///
+    /// Drawing the slice only uses these indices, which makes it possible to store several
+    /// meshes in the same index buffer. Returns `None` if out of range.
     #[inline]
     pub fn slice<R: RangeArgument<usize>>(&self, range: R) -> Option<IndexBufferSlice<T>> {
         self.buffer.slice(range).map(|b| {
             IndexBufferSlice {
                 buffer: b,
                 primitives: self.primitives,
+                primitive_restart: self.primitive_restart,
             }
         })
     }
@@ -248,19 +282,21 @@ impl<T> GlObject for IndexBuffer<T> where T: Index {
 impl<'a, T> From<&'a IndexBuffer<T>> for IndicesSource<'a> where T: Index {
     #[inline]
     fn from(buf: &'a IndexBuffer<T>) -> IndicesSource<'a> {
-        IndicesSource::IndexBuffer {
-            buffer: buf.buffer.as_slice_any(),
-            data_type: buf.get_indices_type(),
-            primitives: buf.primitives,
-        }
+        indices_source(buf.buffer.as_slice_any(), buf.get_indices_type(), buf.primitives,
+                       buf.primitive_restart)
     }
 }
 
 /// Slice of an `IndexBuffer`.
+///
+/// When drawing, the offset of the slice is passed to `glDrawElements` and only the indices
+/// of the slice are used. The type of primitives and the primitive restart index are the ones
+/// of the buffer.
 #[derive(Debug)]
 pub struct IndexBufferSlice<'a, T: 'a> where T: Index {
     buffer: BufferSlice<'a, [T]>,
     primitives: PrimitiveType,
+    primitive_restart: Option<u32>,
 }
 
 impl<'a, T: 'a> IndexBufferSlice<'a, T> where T: Index {
@@ -276,6 +312,8 @@ impl<'a, T: 'a> IndexBufferSlice<'a, T> where T: Index {
         <T as Index>::get_type()
     }
 
+    /// Builds a slice of this slice. The range is relative to the slice.
+    ///
     /// Returns `None` if out of range.
     #[inline]
     pub fn slice<R: RangeArgument<usize>>(&self, range: R) -> Option<IndexBufferSlice<'a, T>> {
@@ -283,9 +321,16 @@ impl<'a, T: 'a> IndexBufferSlice<'a, T> where T: Index {
             IndexBufferSlice {
                 buffer: b,
                 primitives: self.primitives,
+                primitive_restart: self.primitive_restart,
             }
         })
     }
+
+    /// Returns the position of the first index of this slice within the buffer.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.buffer.get_offset_bytes() / mem::size_of::<T>()
+    }
 }
 
 impl<'a, T> Deref for IndexBufferSlice<'a, T> where T: Index {
@@ -314,22 +359,16 @@ impl<'a, T> From<IndexBufferSlice<'a, T>> for BufferSlice<'a, [T]> where T: Inde
 impl<'a, T> From<IndexBufferSlice<'a, T>> for IndicesSource<'a> where T: Index {
     #[inline]
     fn from(buf: IndexBufferSlice<'a, T>) -> IndicesSource<'a> {
-        IndicesSource::IndexBuffer {
-            buffer: buf.buffer.as_slice_any(),
-            data_type: buf.get_indices_type(),
-            primitives: buf.primitives,
-        }
+        indices_source(buf.buffer.as_slice_any(), buf.get_indices_type(), buf.primitives,
+                       buf.primitive_restart)
     }
 }
 
 impl<'a, 'r, T> From<&'r IndexBufferSlice<'a, T>> for IndicesSource<'a> where T: Index {
     #[inline]
     fn from(buf: &'r IndexBufferSlice<'a, T>) -> IndicesSource<'a> {
-        IndicesSource::IndexBuffer {
-            buffer: buf.buffer.as_slice_any(),
-            data_type: buf.get_indices_type(),
-            primitives: buf.primitives,
-        }
+        indices_source(buf.buffer.as_slice_any(), buf.get_indices_type(), buf.primitives,
+                       buf.primitive_restart)
     }
 }
 
@@ -341,6 +380,7 @@ pub struct IndexBufferAny {
     buffer: BufferAny,
     primitives: PrimitiveType,
     data_type: IndexType,
+    primitive_restart: Option<u32>,
 }
 
 impl IndexBufferAny {
@@ -382,6 +422,7 @@ impl<T> From<IndexBuffer<T>> for IndexBufferAny where T: Index {
             buffer: buffer.buffer.into(),
             data_type: ty,
             primitives: buffer.primitives,
+            primitive_restart: buffer.primitive_restart,
         }
     }
 }
@@ -389,10 +430,27 @@ impl<T> From<IndexBuffer<T>> for IndexBufferAny where T: Index {
 impl<'a> From<&'a IndexBufferAny> for IndicesSource<'a> {
     #[inline]
     fn from(buf: &'a IndexBufferAny) -> IndicesSource<'a> {
-        IndicesSource::IndexBuffer {
-            buffer: buf.buffer.as_slice_any(),
-            data_type: buf.data_type,
-            primitives: buf.primitives,
-        }
+        indices_source(buf.buffer.as_slice_any(), buf.data_type, buf.primitives,
+                       buf.primitive_restart)
+    }
+}
+
+/// Builds the `IndicesSource` of an index buffer, with or without a primitive restart index.
+#[inline]
+fn indices_source<'a>(buffer: BufferAnySlice<'a>, data_type: IndexType, primitives: PrimitiveType,
+                      primitive_restart: Option<u32>) -> IndicesSource<'a>
+{
+    match primitive_restart {
+        Some(restart_index) => IndicesSource::IndexBufferWithRestart {
+            buffer: buffer,
+            data_type: data_type,
+            primitives: primitives,
+            restart_index: restart_index,
+        },
+        None => IndicesSource::IndexBuffer {
+            buffer: buffer,
+            data_type: data_type,
+            primitives: primitives,
+        },
     }
 }
@@ -60,6 +60,19 @@ pub enum IndicesSource<'a> {
         primitives: PrimitiveType,
     },
 
+    /// A buffer uploaded in video memory, with an index that restarts the primitive. See
+    /// `IndexBuffer::with_primitive_restart`.
+    IndexBufferWithRestart {
+        /// The buffer.
+        buffer: BufferAnySlice<'a>,
+        /// Type of indices in the buffer.
+        data_type: IndexType,
+        /// Type of primitives contained in the vertex source.
+        primitives: PrimitiveType,
+        /// The index that restarts the primitive.
+        restart_index: u32,
+    },
+
     /// Use a multidraw indirect buffer without indices.
     MultidrawArray {
         /// The buffer.
@@ -94,6 +107,7 @@ impl<'a> IndicesSource<'a> {
     pub fn get_primitives_type(&self) -> PrimitiveType {
         match self {
             &IndicesSource::IndexBuffer { primitives, .. } => primitives,
+            &IndicesSource::IndexBufferWithRestart { primitives, .. } => primitives,
             &IndicesSource::MultidrawArray { primitives, .. } => primitives,
             &IndicesSource::MultidrawElement { primitives, .. } => primitives,
             &IndicesSource::NoIndices { primitives } => primitives,
@@ -214,6 +228,17 @@ impl PrimitiveType {
             },
         }
     }
+
+    /// Returns true if this type of primitives contains adjacency information. Such primitives
+    /// are meant to be consumed by a geometry shader.
+    #[inline]
+    pub fn has_adjacency(&self) -> bool {
+        match self {
+            &PrimitiveType::LinesListAdjacency | &PrimitiveType::LineStripAdjacency |
+            &PrimitiveType::TrianglesListAdjacency | &PrimitiveType::TriangleStripAdjacency => true,
+            _ => false,
+        }
+    }
 }
 
 impl ToGlEnum for PrimitiveType {
@@ -108,7 +108,7 @@ extern crate takeable_option;
 
 #[cfg(feature = "glutin")]
 pub use backend::glutin::glutin;
-pub use context::Profile;
+pub use context::{Features, Profile};
 pub use draw_parameters::{Blend, BlendingFunction, LinearBlendingFactor, BackfaceCullingMode};
 pub use draw_parameters::{Depth, DepthTest, PolygonMode, DrawParameters, StencilTest, StencilOperation};
 pub use draw_parameters::{Smooth};
@@ -116,11 +116,12 @@ pub use index::IndexBuffer;
 pub use vertex::{VertexBuffer, Vertex, VertexFormat};
 pub use program::{Program, ProgramCreationError};
 pub use program::ProgramCreationError::{CompilationError, LinkingError, ShaderTypeNotSupported};
-pub use sync::{LinearSyncFence, SyncFence};
+pub use sync::{LinearSyncFence, SyncFence, SyncWaitStatus};
 pub use texture::Texture2d;
 pub use version::{Api, Version, get_supported_glsl_version};
 pub use ops::ReadError;
 
+use std::cell::Cell;
 use std::rc::Rc;
 use std::thread;
 use std::error::Error;
@@ -220,6 +221,13 @@ trait BufferExt {
     /// Returns the number of bytes from the start of the buffer to this subbuffer.
     fn get_offset_bytes(&self) -> usize;
 
+    /// Queries the size in bytes of the whole buffer from OpenGL.
+    fn query_size(&self) -> usize;
+
+    /// Returns a number that changes every time the content of the whole buffer may have been
+    /// modified, by the CPU or by the GPU.
+    fn get_modifications_count(&self) -> u64;
+
     /// Calls `glMemoryBarrier(GL_VERTEX_ATTRIB_ARRAY_BARRIER_BIT)` if necessary.
     fn prepare_for_vertex_attrib_array(&self, &mut CommandContext);
 
@@ -283,6 +291,17 @@ trait BufferSliceExt<'a> {
     fn add_fence(&self) -> Option<buffer::Inserter<'a>>;
 }
 
+/// Internal trait for `BufferAny`.
+trait BufferAnyExt {
+    /// Only considers the first `size` bytes of the buffer to be in use, for example to leave
+    /// out the spare capacity of a vertex buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `size` is larger than the current size.
+    fn truncate(&mut self, size: usize);
+}
+
 /// Internal trait for contexts.
 trait ContextExt {
     /// Sets whether the context's debug output callback should take errors into account.
@@ -293,6 +312,12 @@ trait ContextExt {
 
     /// Returns the capabilities of the backend.
     fn capabilities(&self) -> &context::Capabilities;
+
+    /// Calls the hook registered with `on_buffer_alloc`, if any.
+    fn notify_buffer_alloc(&self, id: gl::types::GLuint, old_size: usize, new_size: usize);
+
+    /// Returns the cached result of the instancing divisor probe.
+    fn instancing_divisor_works(&self) -> &Cell<Option<bool>>;
 }
 
 /// Internal trait for programs.
@@ -464,6 +489,41 @@ pub struct Rect {
     pub height: u32,
 }
 
+impl Rect {
+    /// Builds a `Rect` from coordinates whose origin is the top-left corner of the surface, like
+    /// the ones of the windowing system or of the mouse, given the height of the surface.
+    ///
+    /// Parts of the rectangle that go past the top or the bottom of the surface are cut.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // a 100x20 rectangle at the top of a window of height 600
+    /// let rect = glium::Rect::from_top_left(10, 0, 100, 20, 600);
+    /// assert_eq!(rect, glium::Rect { left: 10, bottom: 580, width: 100, height: 20 });
+    /// assert_eq!(rect.top(600), 0);
+    /// ```
+    #[inline]
+    pub fn from_top_left(left: u32, top: u32, width: u32, height: u32, surface_height: u32)
+                         -> Rect
+    {
+        let bottom = surface_height.saturating_sub(top.saturating_add(height));
+        Rect {
+            left: left,
+            bottom: bottom,
+            width: width,
+            height: surface_height.saturating_sub(top).min(height),
+        }
+    }
+
+    /// Returns the number of pixels between the top border of a surface of the given height
+    /// and the top border of the rectangle.
+    #[inline]
+    pub fn top(&self, surface_height: u32) -> u32 {
+        surface_height.saturating_sub(self.bottom + self.height)
+    }
+}
+
 /// Area of a surface in pixels. Similar to a `Rect` except that dimensions can be negative.
 ///
 /// In the OpenGL ecosystem, the (0,0) coordinate is at the bottom-left hand corner of the images.
@@ -751,6 +811,10 @@ pub trait Surface {
     }
 
     /// Returns the dimensions in pixels of the target.
+    ///
+    /// For a `Frame`, this is the size of the window when the frame was started. For a
+    /// framebuffer object, this is the size of its attachments, which depends on the mipmap
+    /// level that is attached. This is the value to use when computing a viewport or a scissor.
     fn get_dimensions(&self) -> (u32, u32);
 
     /// Returns the number of bits of each pixel of the depth buffer.
@@ -758,11 +822,39 @@ pub trait Surface {
     /// Returns `None` if there is no depth buffer.
     fn get_depth_buffer_bits(&self) -> Option<u16>;
 
+    /// Builds a `Rect` from coordinates whose origin is the top-left corner of the surface.
+    ///
+    /// OpenGL places the origin at the bottom-left corner, while windowing systems place it at
+    /// the top-left corner. The result can be used for the viewport, the scissor box, or to
+    /// read pixels. See `Rect::from_top_left`.
+    #[inline]
+    fn rect_from_top_left(&self, left: u32, top: u32, width: u32, height: u32) -> Rect {
+        Rect::from_top_left(left, top, width, height, self.get_dimensions().1)
+    }
+
     /// Returns true if the surface has a depth buffer available.
     fn has_depth_buffer(&self) -> bool {
         self.get_depth_buffer_bits().is_some()
     }
 
+    /// Reads the depth values of a rectangle of the depth buffer with `glReadPixels`.
+    ///
+    /// The values are between `0.0` (near) and `1.0` (far), or the bounds of the depth range of
+    /// the draw parameters, and are returned row by row starting from the bottom of the
+    /// rectangle. With a perspective projection they are not linear. The distance to the
+    /// camera can be computed with `near * far / (far - depth * (far - near))`, or the position
+    /// can be obtained by multiplying the normalized device coordinates
+    /// `(x, y, depth * 2.0 - 1.0)` by the inverse of the projection matrix.
+    ///
+    /// Returns `ReadError::NoDepthBuffer` if the surface doesn't have a depth buffer, and
+    /// `ReadError::AttachmentTypeNotSupported` on OpenGL ES without `GL_NV_read_depth`.
+    ///
+    /// The default implementation always returns `ReadError::AttachmentTypeNotSupported`.
+    #[inline]
+    fn read_depth(&self, _: &Rect) -> Result<Vec<f32>, ReadError> {
+        Err(ReadError::AttachmentTypeNotSupported)
+    }
+
     /// Returns the number of bits of each pixel of the stencil buffer.
     ///
     /// Returns `None` if there is no stencil buffer.
@@ -784,6 +876,27 @@ pub trait Surface {
         V: vertex::MultiVerticesSource<'b>, I: Into<index::IndicesSource<'a>>,
         U: uniforms::Uniforms;
 
+    /// Same as `draw`, but with `check_errors` enabled in the draw parameters.
+    ///
+    /// Instead of panicking, returns `AttributeMissing` or `AttributeTypeMismatch` if the
+    /// vertices sources don't match the program, and returns `OpenGlError` if `glGetError`
+    /// reports an error after the draw command. This is slower than `draw` and is meant for
+    /// tools that want to report diagnostics.
+    fn draw_checked<'a, 'b, V, I, U>(&mut self, vertex_buffer: V, index_buffer: I,
+                                     program: &Program, uniforms: &U,
+                                     draw_parameters: &DrawParameters) -> Result<(), DrawError>
+                                     where V: vertex::MultiVerticesSource<'b>,
+                                           I: Into<index::IndicesSource<'a>>,
+                                           U: uniforms::Uniforms
+    {
+        let draw_parameters = DrawParameters {
+            check_errors: true,
+            .. draw_parameters.clone()
+        };
+
+        self.draw(vertex_buffer, index_buffer, program, uniforms, &draw_parameters)
+    }
+
     /// Blits from the default framebuffer.
     fn blit_from_frame(&self, source_rect: &Rect, target_rect: &BlitTarget,
                        filter: uniforms::MagnifySamplerFilter);
@@ -924,6 +1037,14 @@ pub enum DrawError {
     /// Using a program which contains tessellation shaders, but without submitting patches.
     TessellationWithoutPatches,
 
+    /// Submitting primitives with adjacency information, but the program doesn't contain a
+    /// geometry shader that could make use of it.
+    AdjacencyWithoutGeometryShader,
+
+    /// The size of the index buffer doesn't match its type of indices, or the buffer contains
+    /// too many indices to be drawn in one draw call.
+    IndexBufferSizeMismatch,
+
     /// Trying to use a sampler, but they are not supported by the backend.
     SamplersNotSupported,
 
@@ -959,6 +1080,42 @@ pub enum DrawError {
 
     /// Tried to enable a clip plane that does not exist.
     ClipPlaneIndexOutOfBounds,
+
+    /// The index buffer has a primitive restart index that the backend doesn't support.
+    PrimitiveRestartNotSupported,
+
+    /// The `primitive_restart_index` draw parameter is enabled while the index buffer has a
+    /// primitive restart index other than the maximum value of its index type.
+    PrimitiveRestartIndexConflict,
+
+    /// Two vertex sources use the same vertex buffer binding index. See
+    /// `VerticesSource::with_binding_index`.
+    BindingIndexConflict,
+
+    /// A vertex source has a binding index larger than or equal to
+    /// `GL_MAX_VERTEX_ATTRIB_BINDINGS`. When a source has an explicit binding index, the
+    /// position of the sources without one must also be in range.
+    BindingIndexOutOfRange,
+
+    /// A vertex source has an explicit binding index, but the backend doesn't support the
+    /// separate attribute format API.
+    BindingIndexNotSupported,
+
+    /// The vertices are an `InstancedVertices` whose instances have been expanded on the CPU,
+    /// and they are drawn with an index buffer. The indices would only address the vertices of
+    /// the first instance, so expanded instances must be drawn with `NoIndices`.
+    ExpandedInstancesWithIndices,
+
+    /// The `index_range` of the draw parameters starts after its end, or the index buffer
+    /// contains an index outside of this range. The content of the index buffer is only checked
+    /// if debug assertions are enabled.
+    IndexRangeMismatch,
+
+    /// OpenGL reported an error after the draw command. Only returned if `check_errors` is
+    /// enabled in the draw parameters.
+    ///
+    /// Contains the name of the error returned by `glGetError`.
+    OpenGlError(&'static str),
 }
 
 impl Error for DrawError {
@@ -982,6 +1139,8 @@ impl fmt::Display for DrawError {
                 "The type of a vertex attribute in the vertices source doesn't match what the program requires",
             AttributeMissing =>
                 "One of the attributes required by the program is missing from the vertex format",
+            OpenGlError(_) =>
+                "OpenGL reported an error after the draw command",
             ViewportTooLarge =>
                 "The viewport's dimensions are not supported by the backend",
             InvalidDepthRange =>
@@ -1006,6 +1165,10 @@ impl fmt::Display for DrawError {
                 "Trying to use tessellation, but this is not supported by the underlying hardware",
             TessellationWithoutPatches =>
                 "Using a program which contains tessellation shaders, but without submitting patches",
+            AdjacencyWithoutGeometryShader =>
+                "Submitting primitives with adjacency information, but the program doesn't contain a geometry shader",
+            IndexBufferSizeMismatch =>
+                "The size of the index buffer doesn't match its type of indices",
             SamplersNotSupported => "
                 Trying to use a sampler, but they are not supported by the backend",
             InstancesCountMismatch =>
@@ -1028,6 +1191,21 @@ impl fmt::Display for DrawError {
                 "One the blending parameters is not supported by the backend",
             FixedIndexRestartingNotSupported =>
                 "Restarting indices (multiple objects per draw call) is not supported by the backend",
+            PrimitiveRestartNotSupported =>
+                "The index buffer has a primitive restart index that the backend doesn't support",
+            PrimitiveRestartIndexConflict =>
+                "The index buffer has a primitive restart index that conflicts with the \
+                 `primitive_restart_index` draw parameter",
+            BindingIndexConflict =>
+                "Two vertex sources use the same vertex buffer binding index",
+            BindingIndexOutOfRange =>
+                "A vertex source uses a binding index larger than the maximum supported",
+            BindingIndexNotSupported =>
+                "Explicit vertex buffer binding indices are not supported by the backend",
+            ExpandedInstancesWithIndices =>
+                "Instances expanded on the CPU can't be drawn with indices",
+            IndexRangeMismatch =>
+                "The index buffer contains an index outside of the range given in the draw parameters",
             ClipPlaneIndexOutOfBounds =>
                 "Tried to enable a clip plane that does not exist."
         };
@@ -1169,6 +1347,14 @@ impl Surface for Frame {
         self.context.capabilities().stencil_bits
     }
 
+    fn read_depth(&self, rect: &Rect) -> Result<Vec<f32>, ReadError> {
+        if !self.has_depth_buffer() {
+            return Err(ReadError::NoDepthBuffer);
+        }
+
+        ops::read_depth(&self.context, None, rect)
+    }
+
     fn draw<'a, 'b, V, I, U>(&mut self, vertex_buffer: V,
                          index_buffer: I, program: &Program, uniforms: &U,
                          draw_parameters: &DrawParameters) -> Result<(), DrawError>
@@ -1273,7 +1459,11 @@ fn get_gl_error(ctxt: &mut context::CommandContext) -> Option<&'static str> {
         gl::OUT_OF_MEMORY => Some("GL_OUT_OF_MEMORY"),
         gl::STACK_UNDERFLOW => Some("GL_STACK_UNDERFLOW"),
         gl::STACK_OVERFLOW => Some("GL_STACK_OVERFLOW"),
-        gl::CONTEXT_LOST => Some("GL_CONTEXT_LOST"),
+        gl::CONTEXT_LOST => {
+            // later calls must know that the context was lost, see `Context::is_context_lost`
+            ctxt.state.lost_context = true;
+            Some("GL_CONTEXT_LOST")
+        },
         _ => Some("Unknown glGetError return value")
     }
 }
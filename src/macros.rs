@@ -97,6 +97,35 @@ macro_rules! uniform {
 /// # }
 /// ```
 ///
+/// ## Attribute bindings
+///
+/// By default, the way each field is passed to the shader depends on the type of the attribute
+/// in the shader. You can instead choose it for each field with `binding(...)`, in which case
+/// every field must be given a binding:
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// use glium::vertex::AttributeBinding;
+///
+/// #[derive(Copy, Clone)]
+/// struct Vertex {
+///     material_id: i32,
+///     weight: i32,
+/// }
+///
+/// implement_vertex!(Vertex, material_id binding(AttributeBinding::Integer),
+///                           weight binding(AttributeBinding::FloatNormalized));
+/// # }
+/// ```
+///
+/// ## Attribute order
+///
+/// The attributes of the `VertexFormat` are in the order in which the fields are passed to the
+/// macro, whatever their offsets in the struct. Since attribute locations may be assigned in
+/// this order, you should list the fields in the same order as they are declared.
+///
 /// ## Naming convention
 ///
 /// When it comes to using to using your vertex array in a shader you must make sure that all your attribute variables *match* the field names in the struct you are calling calling this macro for.
@@ -129,7 +158,7 @@ macro_rules! implement_vertex {
                                 let field_option = None::<&$struct_name>.map(|v| &v.$field_name);
                                 attr_type_of_val(field_option)
                             },
-                            false
+                            $crate::vertex::AttributeBinding::Auto
                         )
                     ),+
                 ])
@@ -161,9 +190,39 @@ macro_rules! implement_vertex {
                                 let field_option = None::<&$struct_name>.map(|v| &v.$field_name);
                                 attr_type_of_val(field_option)
                             },
+                            $crate::vertex::AttributeBinding::from($should_normalize)
+                        )
+                    ),+
+                ])
+            }
+        }
+    };
+
+    ($struct_name:ident, $($field_name:ident binding($binding:expr)),+) => {
+        impl $crate::vertex::Vertex for $struct_name {
+            #[inline]
+            fn build_bindings() -> $crate::vertex::VertexFormat {
+                use std::borrow::Cow;
+
+                // TODO: use a &'static [] if possible
+
+                Cow::Owned(vec![
+                    $(
+                        (
+                            Cow::Borrowed(stringify!($field_name)),
+                            $crate::__glium_offset_of!($struct_name, $field_name),
                             {
-                                $should_normalize
-                            }
+                                // Obtain the type of the $field_name field of $struct_name and
+                                // call get_type on it.
+                                fn attr_type_of_val<T: $crate::vertex::Attribute>(_: Option<&T>)
+                                    -> $crate::vertex::AttributeType
+                                {
+                                    <T as $crate::vertex::Attribute>::get_type()
+                                }
+                                let field_option = None::<&$struct_name>.map(|v| &v.$field_name);
+                                attr_type_of_val(field_option)
+                            },
+                            $binding
                         )
                     ),+
                 ])
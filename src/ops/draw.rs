@@ -1,4 +1,5 @@
 use std::ptr;
+use smallvec::SmallVec;
 
 use BufferExt;
 use BufferSliceExt;
@@ -14,9 +15,11 @@ use fbo::{self, ValidatedAttachments};
 
 use uniforms::Uniforms;
 use {Program, ToGlEnum};
-use index::{self, IndicesSource};
-use vertex::{MultiVerticesSource, VerticesSource, TransformFeedbackSession};
-use vertex_array_object::VertexAttributesSystem;
+use index::{self, IndexType, IndicesSource};
+use vertex::{AttributeBinding, AttributeType, MultiVerticesSource, VertexFormat, VerticesSource};
+use vertex::TransformFeedbackSession;
+use vertex_array_object::{self, VertexAttributesSystem};
+use buffer::BufferAnySlice;
 
 use draw_parameters::DrawParameters;
 
@@ -35,6 +38,15 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
     // has started
     let mut fences = Vec::with_capacity(0);
 
+    // instances that have been expanded on the CPU can't be drawn with indices
+    match indices {
+        IndicesSource::NoIndices { .. } => (),
+        _ if !vertex_buffers.supports_indices() => {
+            return Err(DrawError::ExpandedInstancesWithIndices);
+        },
+        _ => (),
+    }
+
     // handling tessellation
     let vertices_per_patch = match indices.get_primitives_type() {
         index::PrimitiveType::Patches { vertices_per_patch } => {
@@ -54,64 +66,115 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
 
             Some(vertices_per_patch)
         },
-        _ => {
+        prim => {
             // TODO: programs created from binaries have the wrong value
             // for `has_tessellation_shaders`
             /*if program.has_tessellation_shaders() {
                 return Err(DrawError::TessellationWithoutPatches);
             }*/
 
+            // the adjacent vertices would be silently ignored without a geometry shader
+            if prim.has_adjacency() && !program.has_geometry_shader() {
+                return Err(DrawError::AdjacencyWithoutGeometryShader);
+            }
+
             None
         },
     };
 
+    // the number of indices passed to `glDrawElements` is derived from the size of the index
+    // buffer, so it must contain a whole number of indices of the right type
+    let index_buffer = match indices {
+        IndicesSource::IndexBuffer { ref buffer, data_type, .. } |
+        IndicesSource::IndexBufferWithRestart { ref buffer, data_type, .. } |
+        IndicesSource::MultidrawElement { indices: ref buffer, data_type, .. } => {
+            Some((buffer, data_type))
+        },
+        _ => None
+    };
+
+    if let Some((buffer, data_type)) = index_buffer {
+        if buffer.get_elements_size() != data_type.get_size() ||
+           buffer.get_size() % data_type.get_size() != 0 ||
+           buffer.get_elements_count() > gl::types::GLsizei::MAX as usize
+        {
+            return Err(DrawError::IndexBufferSizeMismatch);
+        }
+    }
+
+    // `glDrawRangeElements` generates `GL_INVALID_VALUE` if the end is smaller than the start
+    if let Some((start, end)) = draw_parameters.index_range {
+        if start > end {
+            return Err(DrawError::IndexRangeMismatch);
+        }
+    }
+
+    // the indices must be read before the context is made current
+    if cfg!(debug_assertions) {
+        let index_buffer = match indices {
+            IndicesSource::IndexBuffer { ref buffer, data_type, .. } => {
+                Some((buffer, data_type, None))
+            },
+            IndicesSource::IndexBufferWithRestart { ref buffer, data_type, restart_index, .. } => {
+                Some((buffer, data_type, Some(restart_index)))
+            },
+            _ => None
+        };
+
+        if let (Some(range), Some((buffer, data_type, restart_index))) =
+               (draw_parameters.index_range, index_buffer)
+        {
+            if !indices_within_range(buffer, data_type, restart_index,
+                                     draw_parameters.primitive_restart_index, range)
+            {
+                return Err(DrawError::IndexRangeMismatch);
+            }
+        }
+    }
+
+    let vertex_buffers: SmallVec<[VerticesSource; 4]> = vertex_buffers.iter().collect();
+    check_binding_indices(context, &vertex_buffers)?;
+    if draw_parameters.check_errors {
+        check_attributes(&vertex_buffers, program, draw_parameters.constant_attributes)?;
+    }
+
     // starting the state changes
     let mut ctxt = context.make_current();
 
     // handling vertices source
     let (vertices_count, instances_count, base_vertex) = {
-        let index_buffer = match indices {
-            IndicesSource::IndexBuffer { buffer, .. } => Some(buffer),
-            IndicesSource::MultidrawArray { .. } => None,
-            IndicesSource::MultidrawElement { indices, .. } => Some(indices),
-            IndicesSource::NoIndices { .. } => None,
-        };
+        let index_buffer = element_array_buffer(&indices);
 
         // determining whether we can use the `base_vertex` variants for drawing
-        let use_base_vertex = match indices {
-            IndicesSource::MultidrawArray { .. } => false,
-            IndicesSource::MultidrawElement { .. } => false,
-            IndicesSource::NoIndices { .. } => true,
-            _ => ctxt.version >= &Version(Api::Gl, 3, 2) ||
-                 ctxt.version >= &Version(Api::GlEs, 3, 2) ||
-                 ctxt.extensions.gl_arb_draw_elements_base_vertex ||
-                 ctxt.extensions.gl_oes_draw_elements_base_vertex
-        };
+        let use_base_vertex = can_use_base_vertex(&ctxt, &indices);
 
         // object that is used to build the bindings
         let mut binder = VertexAttributesSystem::start(&mut ctxt, program, index_buffer,
-                                                       use_base_vertex);
+                                                       use_base_vertex)
+                                 .add_constants(draw_parameters.constant_attributes);
         // number of vertices in the vertices sources, or `None` if there is a mismatch
         let mut vertices_count: Option<usize> = None;
-        // number of instances to draw
+        // number of instances to draw, given by the sources whose divisor is 1
         let mut instances_count: Option<usize> = None;
+        // number of instances covered by the sources whose divisor is larger than 1
+        let mut divided_instances_count: Option<usize> = None;
 
-        for src in vertex_buffers.iter() {
-            match src {
-                VerticesSource::VertexBuffer(buffer, format, per_instance) => {
-                    // TODO: assert!(buffer.get_elements_size() == total_size(format));
+        for src in vertex_buffers {
+            let parts = vertex_buffer_parts(&src);
 
-                    if let Some(fence) = buffer.add_fence() {
-                        fences.push(fence);
-                    }
+            if let Some((buffer, format, divisor, binding_index)) = parts {
+                // TODO: assert!(buffer.get_elements_size() == total_size(format));
 
-                    binder = binder.add(&buffer, format, if per_instance { Some(1) } else { None });
-                },
-                _ => {}
+                if let Some(fence) = buffer.add_fence() {
+                    fences.push(fence);
+                }
+
+                let divisor = if divisor == 0 { None } else { Some(divisor) };
+                binder = binder.add(&buffer, format, divisor, binding_index);
             }
 
-            match src {
-                VerticesSource::VertexBuffer(ref buffer, _, false) => {
+            match (parts, src) {
+                (Some((ref buffer, _, 0, _)), _) => {
                     if let Some(curr) = vertices_count {
                         if curr != buffer.get_elements_count() {
                             vertices_count = None;
@@ -121,7 +184,7 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                         vertices_count = Some(buffer.get_elements_count());
                     }
                 },
-                VerticesSource::VertexBuffer(ref buffer, _, true) => {
+                (Some((ref buffer, _, 1, _)), _) => {
                     if let Some(curr) = instances_count {
                         if curr != buffer.get_elements_count() {
                             return Err(DrawError::InstancesCountMismatch);
@@ -130,7 +193,14 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                         instances_count = Some(buffer.get_elements_count());
                     }
                 },
-                VerticesSource::Marker { len, per_instance } if !per_instance => {
+                (Some((ref buffer, _, divisor, _)), _) => {
+                    let covered = buffer.get_elements_count() * divisor as usize;
+                    divided_instances_count = Some(match divided_instances_count {
+                        Some(curr) => curr.min(covered),
+                        None => covered,
+                    });
+                },
+                (None, VerticesSource::Marker { len, per_instance }) if !per_instance => {
                     if let Some(curr) = vertices_count {
                         if curr != len {
                             vertices_count = None;
@@ -140,7 +210,7 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                         vertices_count = Some(len);
                     }
                 },
-                VerticesSource::Marker { len, per_instance } if per_instance => {
+                (None, VerticesSource::Marker { len, per_instance }) if per_instance => {
                     if let Some(curr) = instances_count {
                         if curr != len {
                             return Err(DrawError::InstancesCountMismatch);
@@ -153,6 +223,15 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
             }
         }
 
+        // the sources with a larger divisor must have enough elements for all the instances
+        let instances_count = match (instances_count, divided_instances_count) {
+            (Some(count), Some(covered)) if covered < count => {
+                return Err(DrawError::InstancesCountMismatch);
+            },
+            (None, covered) => covered,
+            (count, _) => count,
+        };
+
         (vertices_count, instances_count, binder.bind().unwrap_or(0))
     };
 
@@ -170,6 +249,7 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
     unsafe {
         draw_parameters::sync(&mut ctxt, draw_parameters, dimensions, indices.get_primitives_type())?;
         sync_vertices_per_patch(&mut ctxt, vertices_per_patch);
+        sync_primitive_restart(&mut ctxt, &indices, draw_parameters.primitive_restart_index)?;
 
         // TODO: make sure that the program is the right one
         // TODO: changing the current transform feedback requires pausing/unbinding before changing the program
@@ -184,7 +264,8 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
     // TODO: make this code more readable
     {
         match &indices {
-            &IndicesSource::IndexBuffer { ref buffer, data_type, primitives } => {
+            &IndicesSource::IndexBuffer { ref buffer, data_type, primitives } |
+            &IndicesSource::IndexBufferWithRestart { ref buffer, data_type, primitives, .. } => {
                 let ptr: *const u8 = ptr::null_mut();
                 let ptr = unsafe { ptr.offset(buffer.get_offset_bytes() as isize) };
 
@@ -254,6 +335,17 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
                                 unreachable!();
                             }
 
+                        } else if draw_parameters.index_range.is_some() &&
+                                  (ctxt.version >= &Version(Api::Gl, 1, 2) ||
+                                   ctxt.version >= &Version(Api::GlEs, 3, 0))
+                        {
+                            let (start, end) = draw_parameters.index_range.unwrap();
+                            ctxt.gl.DrawRangeElements(primitives.to_glenum(), start, end,
+                                                      buffer.get_elements_count() as
+                                                      gl::types::GLsizei,
+                                                      data_type.to_glenum(),
+                                                      ptr as *const _);
+
                         } else {
                             ctxt.gl.DrawElements(primitives.to_glenum(),
                                                  buffer.get_elements_count() as gl::types::GLsizei,
@@ -326,9 +418,245 @@ pub fn draw<'a, U, V>(context: &Context, framebuffer: Option<&ValidatedAttachmen
 
     ctxt.state.next_draw_call_id += 1;
 
-    // fulfilling the fences
-    for fence in fences.into_iter() {
-        fence.insert(&mut ctxt);
+    // fulfilling the fences, unless the user synchronizes the buffers manually
+    if context.is_auto_fence_enabled() {
+        for fence in fences.into_iter() {
+            fence.insert(&mut ctxt);
+        }
+    }
+
+    if draw_parameters.check_errors {
+        if let Some(error) = ::get_gl_error(&mut ctxt) {
+            return Err(DrawError::OpenGlError(error));
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the vertex array object that `draw` would use with these vertices, indices and
+/// program, without drawing anything. The VAO is then found in the cache when drawing.
+pub fn prewarm_vao<'a, V>(context: &Context, vertex_buffers: V, indices: IndicesSource,
+                          program: &Program,
+                          constant_attributes: &[(&str, draw_parameters::ConstantAttribute)])
+                          -> Result<(), DrawError>
+                          where V: MultiVerticesSource<'a>
+{
+    let vertex_buffers: SmallVec<[VerticesSource; 4]> = vertex_buffers.iter().collect();
+    check_binding_indices(context, &vertex_buffers)?;
+    check_attributes(&vertex_buffers, program, constant_attributes)?;
+
+    let mut ctxt = context.make_current();
+
+    let index_buffer = element_array_buffer(&indices);
+    let use_base_vertex = can_use_base_vertex(&ctxt, &indices);
+
+    let mut binder = VertexAttributesSystem::start(&mut ctxt, program, index_buffer,
+                                                   use_base_vertex)
+                             .add_constants(constant_attributes);
+    for src in vertex_buffers {
+        if let Some((buffer, format, divisor, binding_index)) = vertex_buffer_parts(&src) {
+            let divisor = if divisor == 0 { None } else { Some(divisor) };
+            binder = binder.add(&buffer, format, divisor, binding_index);
+        }
+    }
+
+    binder.bind();
+    Ok(())
+}
+
+/// Returns the buffer, the format, the instancing divisor and the binding index of a source, or
+/// `None` if it is a marker.
+fn vertex_buffer_parts<'a>(src: &VerticesSource<'a>)
+                           -> Option<(BufferAnySlice<'a>, &'a VertexFormat, u32, Option<u32>)>
+{
+    match *src {
+        VerticesSource::VertexBuffer(buffer, format, per_instance) => {
+            Some((buffer, format, if per_instance { 1 } else { 0 }, None))
+        },
+        VerticesSource::VertexBufferWithBinding { buffer, format, divisor, binding_index } => {
+            Some((buffer, format, divisor, binding_index))
+        },
+        VerticesSource::Marker { .. } => None,
+    }
+}
+
+/// Returns an error if two vertex buffers use the same binding index, or if a buffer has an
+/// explicit binding index that the backend can't honor. Buffers without an explicit binding
+/// index use their position among the vertex buffers.
+fn check_binding_indices(context: &Context, vertex_buffers: &[VerticesSource])
+                         -> Result<(), DrawError>
+{
+    let parts: SmallVec<[Option<u32>; 4]> = vertex_buffers.iter()
+                                                          .filter_map(|src| vertex_buffer_parts(src))
+                                                          .map(|(_, _, _, index)| index)
+                                                          .collect();
+
+    let indices: SmallVec<[u32; 4]> = parts.iter().enumerate()
+                                           .map(|(position, index)| {
+                                               index.unwrap_or(position as u32)
+                                           })
+                                           .collect();
+
+    // explicit binding indices require the separate attribute format API, and all the buffers
+    // must then fit in the binding points of the backend
+    if parts.iter().any(|index| index.is_some()) {
+        let max = match context.capabilities().max_vertex_attrib_bindings {
+            Some(max) => max,
+            None => return Err(DrawError::BindingIndexNotSupported),
+        };
+
+        if indices.iter().any(|&index| index >= max as u32) {
+            return Err(DrawError::BindingIndexOutOfRange);
+        }
+    }
+
+    for (n, index) in indices.iter().enumerate() {
+        if indices[.. n].contains(index) {
+            return Err(DrawError::BindingIndexConflict);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the buffer to bind to `GL_ELEMENT_ARRAY_BUFFER`, if any.
+fn element_array_buffer<'a>(indices: &IndicesSource<'a>) -> Option<BufferAnySlice<'a>> {
+    match *indices {
+        IndicesSource::IndexBuffer { buffer, .. } => Some(buffer),
+        IndicesSource::IndexBufferWithRestart { buffer, .. } => Some(buffer),
+        IndicesSource::MultidrawArray { .. } => None,
+        IndicesSource::MultidrawElement { indices, .. } => Some(indices),
+        IndicesSource::NoIndices { .. } => None,
+    }
+}
+
+/// Returns true if the `base_vertex` variants of the draw commands can be used.
+fn can_use_base_vertex(ctxt: &context::CommandContext, indices: &IndicesSource) -> bool {
+    match *indices {
+        IndicesSource::MultidrawArray { .. } => false,
+        IndicesSource::MultidrawElement { .. } => false,
+        IndicesSource::NoIndices { .. } => true,
+        _ => ctxt.version >= &Version(Api::Gl, 3, 2) ||
+             ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+             ctxt.extensions.gl_arb_draw_elements_base_vertex ||
+             ctxt.extensions.gl_oes_draw_elements_base_vertex
+    }
+}
+
+/// Checks that each attribute of the program is provided by one of the vertices sources or by a
+/// constant attribute, with the right number of components.
+fn check_attributes(vertex_buffers: &[VerticesSource], program: &Program,
+                    constant_attributes: &[(&str, draw_parameters::ConstantAttribute)])
+                    -> Result<(), DrawError>
+{
+    for (name, attribute) in program.attributes() {
+        let binding = vertex_buffers.iter()
+                                    .filter_map(|src| vertex_buffer_parts(src))
+                                    .filter_map(|(_, format, _, _)| {
+                                        format.iter().find(|b| b.0 == *name)
+                                    })
+                                    .next();
+
+        match binding {
+            Some(&(_, _, ty, binding)) => {
+                if ty.get_num_components() != attribute.ty.get_num_components() ||
+                   attribute.size != 1
+                {
+                    return Err(DrawError::AttributeTypeMismatch);
+                }
+
+                // `GL_BGRA` can only be used with normalized values
+                if ty == AttributeType::U8U8U8U8Bgra && binding != AttributeBinding::Auto &&
+                   binding != AttributeBinding::FloatNormalized
+                {
+                    return Err(DrawError::AttributeTypeMismatch);
+                }
+
+                if !vertex_array_object::is_binding_valid(binding, ty, attribute.ty) {
+                    return Err(DrawError::AttributeTypeMismatch);
+                }
+            },
+            None => {
+                match constant_attributes.iter().find(|c| c.0 == name) {
+                    Some(&(_, value)) => {
+                        if !vertex_array_object::is_constant_valid(value, attribute.ty) ||
+                           attribute.size != 1
+                        {
+                            return Err(DrawError::AttributeTypeMismatch);
+                        }
+                    },
+                    None => return Err(DrawError::AttributeMissing),
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Enables or disables primitive restart depending on the restart index of the index buffer.
+///
+/// `fixed_restart` is the value of the `primitive_restart_index` draw parameter, which has
+/// already been applied. Returns an error if it is `true` and the index buffer has a restart
+/// index other than the maximum value of its type, as `GL_PRIMITIVE_RESTART_FIXED_INDEX` would
+/// take precedence over it.
+unsafe fn sync_primitive_restart(ctxt: &mut context::CommandContext, indices: &IndicesSource,
+                                 fixed_restart: bool) -> Result<(), DrawError>
+{
+    let (index, data_type) = match *indices {
+        IndicesSource::IndexBufferWithRestart { restart_index, data_type, .. } => {
+            (Some(restart_index), data_type)
+        },
+        _ => (None, index::IndexType::U32),
+    };
+
+    let max = match data_type {
+        index::IndexType::U8 => 0xff,
+        index::IndexType::U16 => 0xffff,
+        index::IndexType::U32 => 0xffffffff,
+    };
+
+    if let Some(index) = index {
+        if fixed_restart && index != max {
+            return Err(DrawError::PrimitiveRestartIndexConflict);
+        }
+    }
+
+    if ctxt.version >= &Version(Api::Gl, 3, 1) {
+        if let Some(index) = index {
+            if ctxt.state.primitive_restart_index != index {
+                ctxt.gl.PrimitiveRestartIndex(index);
+                ctxt.state.primitive_restart_index = index;
+            }
+
+            if !ctxt.state.enabled_primitive_restart {
+                ctxt.gl.Enable(gl::PRIMITIVE_RESTART);
+                ctxt.state.enabled_primitive_restart = true;
+            }
+
+        } else if ctxt.state.enabled_primitive_restart {
+            ctxt.gl.Disable(gl::PRIMITIVE_RESTART);
+            ctxt.state.enabled_primitive_restart = false;
+        }
+
+        return Ok(());
+    }
+
+    // without `GL_PRIMITIVE_RESTART`, only the maximum value of the index type can be used
+    if let Some(index) = index {
+        if index != max {
+            return Err(DrawError::PrimitiveRestartNotSupported);
+        }
+
+        if !fixed_restart {
+            if !(ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_es3_compatibility) {
+                return Err(DrawError::PrimitiveRestartNotSupported);
+            }
+
+            ctxt.gl.Enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+            ctxt.state.enabled_primitive_fixed_restart = true;
+        }
     }
 
     Ok(())
@@ -343,3 +671,44 @@ unsafe fn sync_vertices_per_patch(ctxt: &mut context::CommandContext, vertices_p
         }
     }
 }
+
+/// Reads the indices of `buffer` and checks that they are all within `range` or equal to a
+/// primitive restart index. `fixed_restart` is the value of the `primitive_restart_index` draw
+/// parameter, which makes the maximum value of the index type a restart index. Returns true if
+/// the buffer can't be read.
+fn indices_within_range(buffer: &BufferAnySlice, data_type: IndexType,
+                        primitive_restart: Option<u32>, fixed_restart: bool,
+                        range: (u32, u32)) -> bool
+{
+    let fixed_restart = if fixed_restart {
+        Some(match data_type {
+            IndexType::U8 => 0xff,
+            IndexType::U16 => 0xffff,
+            IndexType::U32 => 0xffffffff,
+        })
+    } else {
+        None
+    };
+
+    let check = |index: u32| {
+        (index >= range.0 && index <= range.1) || Some(index) == primitive_restart ||
+        Some(index) == fixed_restart
+    };
+
+    unsafe {
+        match data_type {
+            IndexType::U8 => match buffer.read::<[u8]>() {
+                Ok(data) => data.into_iter().all(|i| check(i as u32)),
+                Err(_) => true,
+            },
+            IndexType::U16 => match buffer.read::<[u16]>() {
+                Ok(data) => data.into_iter().all(|i| check(i as u32)),
+                Err(_) => true,
+            },
+            IndexType::U32 => match buffer.read::<[u32]>() {
+                Ok(data) => data.into_iter().all(check),
+                Err(_) => true,
+            },
+        }
+    }
+}
@@ -9,11 +9,13 @@ use image_format::{TextureFormatRequest, TextureFormat};
 
 use fbo;
 use fbo::FramebuffersContainer;
+use fbo::ValidatedAttachments;
 
 use buffer::BufferAny;
 use BufferExt;
 use Rect;
-use context::CommandContext;
+use context::{CommandContext, Context};
+use ContextExt;
 use gl;
 
 use version::Version;
@@ -73,6 +75,9 @@ pub enum ReadError {
     /// Clamping the values is not supported by the implementation.
     ClampingNotSupported,
 
+    /// Tried to read the depth buffer of a surface that doesn't have one.
+    NoDepthBuffer,
+
     // TODO: context lost
 }
 
@@ -86,6 +91,8 @@ impl fmt::Display for ReadError {
                 "The implementation doesn't support reading a depth, depth-stencil or stencil attachment",
             ClampingNotSupported =>
                 "Clamping the values is not supported by the implementation",
+            NoDepthBuffer =>
+                "Tried to read the depth buffer of a surface that doesn't have one",
         };
         fmt.write_str(desc)
     }
@@ -258,6 +265,43 @@ pub fn read<'a, S, D, T>(mut ctxt: &mut CommandContext, source: S, rect: &Rect,
     Ok(())
 }
 
+/// Reads the depth values of a framebuffer with `glReadPixels`.
+///
+/// The caller must check that the framebuffer has a depth buffer. The values are between
+/// `0.0` and `1.0`, row by row starting from the bottom of `rect`.
+pub fn read_depth(context: &Context, framebuffer: Option<&ValidatedAttachments>, rect: &Rect)
+                  -> Result<Vec<f32>, ReadError>
+{
+    let mut ctxt = context.make_current();
+
+    // OpenGL ES doesn't support reading from depth attachments by default
+    if ctxt.version >= &Version(Api::GlEs, 2, 0) && !ctxt.extensions.gl_nv_read_depth {
+        return Err(ReadError::AttachmentTypeNotSupported);
+    }
+
+    let pixels_to_read = (rect.width * rect.height) as usize;
+    let mut buf: Vec<f32> = Vec::with_capacity(pixels_to_read);
+
+    unsafe {
+        let fbo = FramebuffersContainer::get_framebuffer_for_drawing(&mut ctxt, framebuffer);
+        fbo::bind_framebuffer(&mut ctxt, fbo, false, true);
+
+        BufferAny::unbind_pixel_pack(&mut ctxt);
+
+        if ctxt.state.pixel_store_pack_alignment != 4 {
+            ctxt.state.pixel_store_pack_alignment = 4;
+            ctxt.gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+        }
+
+        ctxt.gl.ReadPixels(rect.left as gl::types::GLint, rect.bottom as gl::types::GLint,
+                           rect.width as gl::types::GLsizei, rect.height as gl::types::GLsizei,
+                           gl::DEPTH_COMPONENT, gl::FLOAT, buf.as_mut_ptr() as *mut _);
+        buf.set_len(pixels_to_read);
+    }
+
+    Ok(buf)
+}
+
 fn client_format_to_gl_enum(format: &ClientFormat, integer: bool)
                             -> (gl::types::GLenum, gl::types::GLenum)
 {
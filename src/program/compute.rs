@@ -49,7 +49,8 @@ impl ComputeShader {
         let shader = build_shader(facade, gl::COMPUTE_SHADER, src)?;
 
         Ok(ComputeShader {
-            raw: RawProgram::from_shaders(facade, &[shader], false, false, false, None)?
+            raw: RawProgram::from_shaders(facade, &[shader], false, false, false, None,
+                                          None)?
         })
     }
 
@@ -135,6 +135,10 @@ pub enum ProgramCreationError {
 
     /// The glium-specific binary header was not found or is corrupt.
     BinaryHeaderError,
+
+    /// One of the requested transform feedback varyings is not an output of the last vertex
+    /// processing stage of the program.
+    TransformFeedbackVaryingNotFound(String),
 }
 
 impl fmt::Display for ProgramCreationError {
@@ -163,12 +167,16 @@ impl fmt::Display for ProgramCreationError {
                 "Point size is not supported by the backend.",
             BinaryHeaderError =>
                 "The glium-specific binary header was not found or is corrupt.",
+            TransformFeedbackVaryingNotFound(_) =>
+                "A transform feedback varying is not an output of the program",
         };
         match *self {
             CompilationError(ref s, _) =>
                 write!(fmt, "{}: {}", desc, s),
             LinkingError(ref s) =>
                 write!(fmt, "{}: {}", desc, s),
+            TransformFeedbackVaryingNotFound(ref s) =>
+                write!(fmt, "{}: {}", desc, s),
             _ =>
                 write!(fmt, "{}", desc),
         }
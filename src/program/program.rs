@@ -22,7 +22,7 @@ use program::{COMPILER_GLOBAL_LOCK, ProgramCreationInput, ProgramCreationError,
 use program::GetBinaryError;
 
 use program::reflection::{Uniform, UniformBlock, OutputPrimitives};
-use program::reflection::{Attribute, TransformFeedbackBuffer};
+use program::reflection::{Attribute, TransformFeedbackBuffer, TransformFeedbackMode};
 use program::reflection::{SubroutineData, ShaderStage, SubroutineUniform};
 use program::shader::build_shader;
 
@@ -39,11 +39,69 @@ pub struct Program {
 
 impl Program {
     /// Builds a new program.
+    #[inline]
     pub fn new<'a, F: ?Sized, I>(facade: &F, input: I) -> Result<Program, ProgramCreationError>
                          where I: Into<ProgramCreationInput<'a>>, F: Facade
     {
-        let input = input.into();
+        Program::new_impl(facade, input.into(), None)
+    }
+
+    /// Builds a new program, and assigns locations to its vertex attributes with
+    /// `glBindAttribLocation` before linking it.
+    ///
+    /// The attributes of `attribute_locations` get consecutive locations in the order of the
+    /// format, starting at 0. Matrices use one location per column, and double-precision
+    /// columns of three or four components use two locations. This makes the locations
+    /// deterministic, so that programs built with the same format can share their vertex array
+    /// objects.
+    ///
+    /// Attributes that have an explicit `layout(location = ...)` in the shader keep that
+    /// location, as explicit locations take precedence over `glBindAttribLocation`. Avoid
+    /// mixing both, as the locations could then overlap and the linking would fail.
+    /// Attributes of the format that don't exist in the shader are ignored, and so is the format
+    /// if `input` is a binary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[macro_use] extern crate glium;
+    /// # fn main() {
+    /// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let vertex_source = ""; let fragment_source = "";
+    /// #[derive(Copy, Clone)]
+    /// struct Vertex {
+    ///     position: [f32; 2],
+    ///     color: [f32; 3],
+    /// }
+    ///
+    /// implement_vertex!(Vertex, position, color);
+    ///
+    /// let format = <Vertex as glium::Vertex>::build_bindings();
+    /// let input = glium::program::SourceCode {
+    ///     vertex_shader: vertex_source,
+    ///     fragment_shader: fragment_source,
+    ///     tessellation_control_shader: None,
+    ///     tessellation_evaluation_shader: None,
+    ///     geometry_shader: None,
+    /// };
+    /// let program = glium::Program::new_with_attribute_locations(&display, input, &format);
+    /// # }
+    /// ```
+    ///
+    #[inline]
+    pub fn new_with_attribute_locations<'a, F: ?Sized, I>(facade: &F, input: I,
+                                                          attribute_locations: &VertexFormat)
+                                                          -> Result<Program, ProgramCreationError>
+                                                          where I: Into<ProgramCreationInput<'a>>,
+                                                                F: Facade
+    {
+        Program::new_impl(facade, input.into(), Some(attribute_locations))
+    }
 
+    fn new_impl<F: ?Sized>(facade: &F, input: ProgramCreationInput,
+                           attribute_locations: Option<&VertexFormat>)
+                           -> Result<Program, ProgramCreationError> where F: Facade
+    {
         let (raw, outputs_srgb, uses_point_size) = match input {
             ProgramCreationInput::SourceCode { vertex_shader, tessellation_control_shader,
                                                tessellation_evaluation_shader, geometry_shader,
@@ -98,7 +156,8 @@ impl Program {
 
                 (RawProgram::from_shaders(facade, &shaders_store, has_geometry_shader,
                                                has_tessellation_control_shader, has_tessellation_evaluation_shader,
-                                               transform_feedback_varyings)?,
+                                               transform_feedback_varyings,
+                                               attribute_locations)?,
                  outputs_srgb, uses_point_size)
             },
 
@@ -153,6 +212,52 @@ impl Program {
         })
     }
 
+    /// Builds a new program from GLSL source code, whose outputs are captured by transform
+    /// feedback.
+    ///
+    /// This is the same as `from_source`, except that the transform feedback varyings are
+    /// passed to the linker. Varyings must be specified before linking, and can't be added to
+    /// an existing program. Returns `TransformFeedbackVaryingNotFound` if one of the varyings
+    /// is not an output of the vertex or geometry shader.
+    ///
+    /// The varyings are part of the binary returned by `get_binary`, so a program reloaded from
+    /// this binary can be used for transform feedback as well.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let vertex_source = ""; let fragment_source = "";
+    /// use glium::program::TransformFeedbackMode;
+    ///
+    /// let program = glium::Program::from_source_with_feedback_varyings(&display, vertex_source,
+    ///     fragment_source, None, &["out_position", "out_velocity"],
+    ///     TransformFeedbackMode::Interleaved);
+    /// ```
+    ///
+    #[inline]
+    pub fn from_source_with_feedback_varyings<'a, F: ?Sized>(facade: &F, vertex_shader: &'a str,
+                                                             fragment_shader: &'a str,
+                                                             geometry_shader: Option<&'a str>,
+                                                             varyings: &[&str],
+                                                             mode: TransformFeedbackMode)
+                                                             -> Result<Program, ProgramCreationError>
+                                                             where F: Facade
+    {
+        let varyings = varyings.iter().map(|name| name.to_string()).collect();
+
+        Program::new(facade, ProgramCreationInput::SourceCode {
+            vertex_shader: vertex_shader,
+            fragment_shader: fragment_shader,
+            geometry_shader: geometry_shader,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            transform_feedback_varyings: Some((varyings, mode)),
+            outputs_srgb: false,
+            uses_point_size: false,
+        })
+    }
+
     /// Returns the program's compiled binary.
     ///
     /// You can store the result in a file, then reload it later. This avoids having to compile
@@ -230,6 +335,18 @@ impl Program {
         self.raw.transform_feedback_matches(format, stride)
     }
 
+    /// True if the transform feedback output of this program for the buffer at the given index
+    /// matches the specified `VertexFormat` and `stride`.
+    ///
+    /// In interleaved mode all the varyings are written to the buffer 0. In separate mode each
+    /// varying is written to its own buffer.
+    #[inline]
+    pub fn transform_feedback_buffer_matches(&self, index: usize, format: &VertexFormat,
+                                             stride: usize) -> bool
+    {
+        self.raw.transform_feedback_buffer_matches(index, format, stride)
+    }
+
     /// Returns the type of geometry that transform feedback would generate, or `None` if it
     /// depends on the vertex/index data passed when drawing.
     ///
@@ -75,10 +75,25 @@ impl RawProgram {
     pub fn from_shaders<'a, F: ?Sized, I>(facade: &'a F, shaders: I, has_geometry_shader: bool,
                                   has_tessellation_control_shader: bool,
                                   has_tessellation_evaluation_shader: bool,
-                                  transform_feedback: Option<(Vec<String>, TransformFeedbackMode)>)
+                                  transform_feedback: Option<(Vec<String>, TransformFeedbackMode)>,
+                                  attribute_locations: Option<&VertexFormat>)
                                   -> Result<RawProgram, ProgramCreationError>
                                   where F: Facade, I: IntoIterator<Item = &'a Shader>
     {
+        let varying_names = transform_feedback.as_ref().map(|&(ref names, _)| names.clone());
+
+        // names of the attributes whose locations are bound, which must be valid C strings
+        let mut attribute_names = Vec::new();
+        if let Some(format) = attribute_locations {
+            for &(ref name, _, ty, _) in format.iter() {
+                match ffi::CString::new(name.as_bytes()) {
+                    Ok(c_name) => attribute_names.push((c_name, ty)),
+                    Err(_) => return Err(ProgramCreationError::LinkingError(
+                        format!("The name of the attribute {:?} contains a nul byte", name))),
+                }
+            }
+        }
+
         let mut ctxt = facade.get_context().make_current();
 
         let shaders_ids = shaders.into_iter().map(|s| s.get_id()).collect::<Vec<_>>();
@@ -139,6 +154,21 @@ impl RawProgram {
                 }
             }
 
+            // binding the locations of the attributes
+            let mut location = 0;
+            for (name, ty) in attribute_names {
+                match id {
+                    Handle::Id(id) => {
+                        ctxt.gl.BindAttribLocation(id, location, name.as_ptr());
+                    },
+                    Handle::Handle(id) => {
+                        ctxt.gl.BindAttribLocationARB(id, location, name.as_ptr());
+                    },
+                }
+
+                location += VertexAttributesSystem::get_locations_count(ty);
+            }
+
             // linking
             {
                 ctxt.report_debug_output_errors.set(false);
@@ -183,7 +213,10 @@ impl RawProgram {
             None
         };
 
-        Ok(RawProgram {
+        // the program must be destroyed without the context being borrowed
+        drop(ctxt);
+
+        let program = RawProgram {
             context: facade.get_context().clone(),
             id: id,
             uniforms: uniforms,
@@ -199,7 +232,13 @@ impl RawProgram {
             has_geometry_shader: has_geometry_shader,
             has_tessellation_control_shader: has_tessellation_control_shader,
             has_tessellation_evaluation_shader: has_tessellation_evaluation_shader,
-        })
+        };
+
+        if let Some(names) = varying_names {
+            check_transform_feedback_varyings(&names, &program.tf_buffers)?;
+        }
+
+        Ok(program)
     }
 
     /// Creates a program from binary.
@@ -413,13 +452,24 @@ impl RawProgram {
     ///
     /// The correctness of semantic meaning(i.e. naming) between vertex attributes is the responsibility of user.
     pub fn transform_feedback_matches(&self, format: &VertexFormat, stride: usize) -> bool {
-        // TODO: doesn't support multiple buffers
-
         if self.get_transform_feedback_buffers().len() != 1 {
             return false;
         }
 
-        let buf = &self.get_transform_feedback_buffers()[0];
+        self.transform_feedback_buffer_matches(0, format, stride)
+    }
+
+    /// True if the transform feedback output of this program for the buffer at the given index
+    /// matches the specified `VertexFormat` and `stride`.
+    ///
+    /// Returns false if the program doesn't have a transform feedback buffer at this index.
+    pub fn transform_feedback_buffer_matches(&self, index: usize, format: &VertexFormat,
+                                             stride: usize) -> bool
+    {
+        let buf = match self.get_transform_feedback_buffers().get(index) {
+            Some(b) => b,
+            None => return false
+        };
 
         if buf.stride != stride {
             return false;
@@ -758,6 +808,25 @@ unsafe fn create_program(ctxt: &mut CommandContext) -> Handle {
     id
 }
 
+/// Checks that all the requested transform feedback varyings have been captured by the linker.
+fn check_transform_feedback_varyings(names: &[String], buffers: &[TransformFeedbackBuffer])
+                                     -> Result<(), ProgramCreationError>
+{
+    for name in names {
+        // special names that only change the layout of the buffers
+        if name == "gl_NextBuffer" || name.starts_with("gl_SkipComponents") {
+            continue;
+        }
+
+        let found = buffers.iter().any(|buffer| buffer.elements.iter().any(|e| e.name == *name));
+        if !found {
+            return Err(ProgramCreationError::TransformFeedbackVaryingNotFound(name.clone()));
+        }
+    }
+
+    Ok(())
+}
+
 unsafe fn check_program_link_errors(ctxt: &mut CommandContext, id: Handle)
                                     -> Result<(), ProgramCreationError>
 {
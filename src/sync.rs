@@ -14,6 +14,27 @@ use std::thread;
 #[derive(Copy, Clone, Debug)]
 pub struct SyncNotSupportedError;
 
+/// Timeout in nanoseconds used by `SyncFence::wait`, equal to one year.
+///
+/// The reason why the deadline is so long is because if you attach a GL debugger, the wait can
+/// be blocked during a breaking point of the debugger.
+const DEFAULT_TIMEOUT: u64 = 365 * 24 * 3600 * 1000 * 1000 * 1000;
+
+/// Result of waiting for a `SyncFence` with `wait_timeout`.
+///
+/// Corresponds to the value returned by `glClientWaitSync`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncWaitStatus {
+    /// The fence was already signaled when the function was called.
+    AlreadySignaled,
+    /// The fence became signaled before the timeout expired.
+    ConditionSatisfied,
+    /// The timeout expired before the fence became signaled.
+    TimeoutExpired,
+    /// An error happened while waiting, for example because the context was lost.
+    WaitFailed,
+}
+
 /// Provides a way to wait for a server-side operation to be finished.
 ///
 /// Creating a `SyncFence` injects an element in the commands queue of the backend.
@@ -42,11 +63,16 @@ impl SyncFence {
     }
 
     /// Blocks until the operation has finished on the server.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the wait fails or takes longer than a year. Use `wait_timeout` to handle these
+    /// cases.
     pub fn wait(mut self) {
         let sync = self.id.take().unwrap();
 
         let mut ctxt = self.context.make_current();
-        let result = unsafe { client_wait(&mut ctxt, sync) };
+        let result = unsafe { client_wait(&mut ctxt, sync, DEFAULT_TIMEOUT) };
         unsafe { delete_fence(&mut ctxt, sync) };
 
         match result {
@@ -54,6 +80,23 @@ impl SyncFence {
             _ => panic!("Could not wait for the fence")
         };
     }
+
+    /// Blocks until the operation has finished on the server or until `timeout` nanoseconds
+    /// have elapsed, and returns which of these happened.
+    ///
+    /// Contrary to `wait`, the fence isn't consumed and you can wait again if the timeout
+    /// expired. This lets you detect a stuck GPU or a lost context instead of hanging forever.
+    pub fn wait_timeout(&self, timeout: u64) -> SyncWaitStatus {
+        let sync = self.id.unwrap();
+
+        let mut ctxt = self.context.make_current();
+        match unsafe { client_wait(&mut ctxt, sync, timeout) } {
+            gl::ALREADY_SIGNALED => SyncWaitStatus::AlreadySignaled,
+            gl::CONDITION_SATISFIED => SyncWaitStatus::ConditionSatisfied,
+            gl::TIMEOUT_EXPIRED => SyncWaitStatus::TimeoutExpired,
+            _ => SyncWaitStatus::WaitFailed,
+        }
+    }
 }
 
 impl Drop for SyncFence {
@@ -126,10 +169,40 @@ pub unsafe fn wait_linear_sync_fence_and_drop(mut fence: LinearSyncFence,
                                               ctxt: &mut CommandContext)
 {
     let fence = fence.id.take().unwrap();
-    client_wait(ctxt, fence);
+    client_wait(ctxt, fence, DEFAULT_TIMEOUT);
     delete_fence(ctxt, fence);
 }
 
+/// Returns true if this fence has been signaled, without waiting, from within the commands
+/// context.
+///
+/// # Panic
+///
+/// Panics if `glClientWaitSync` fails, like `SyncFence::wait`.
+pub unsafe fn is_linear_sync_fence_signaled(ctxt: &mut CommandContext, fence: &LinearSyncFence)
+                                            -> bool
+{
+    let fence = fence.id.unwrap();
+
+    // the commands are flushed so that the fence is eventually signaled
+    let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
+                    ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
+    {
+        ctxt.gl.ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, 0)
+    } else if ctxt.extensions.gl_apple_sync {
+        ctxt.gl.ClientWaitSyncAPPLE(fence, gl::SYNC_FLUSH_COMMANDS_BIT_APPLE, 0)
+    } else {
+        unreachable!();
+    };
+
+    match result {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => true,
+        gl::TIMEOUT_EXPIRED => false,
+        gl::WAIT_FAILED => panic!("Could not query the status of the fence"),
+        _ => unreachable!()
+    }
+}
+
 /// Destroys a fence, from within the commands context.
 #[inline]
 pub unsafe fn destroy_linear_sync_fence(ctxt: &mut CommandContext, mut fence: LinearSyncFence) {
@@ -139,13 +212,15 @@ pub unsafe fn destroy_linear_sync_fence(ctxt: &mut CommandContext, mut fence: Li
 
 /// Calls `glClientWaitSync` and returns the result.
 ///
-/// Tries without flushing first, then with flushing.
+/// Tries without flushing first, then with flushing and a deadline of `timeout` nanoseconds.
 ///
 /// # Unsafety
 ///
 /// The fence object must exist.
 ///
-unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl::types::GLenum {
+unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync, timeout: u64)
+                      -> gl::types::GLenum
+{
     // trying without flushing first
     let result = if ctxt.version >= &Version(Api::Gl, 3, 2) ||
                     ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
@@ -164,17 +239,13 @@ unsafe fn client_wait(ctxt: &mut CommandContext, fence: gl::types::GLsync) -> gl
         _ => unreachable!()
     };
 
-    // waiting with a deadline of one year
-    // the reason why the deadline is so long is because if you attach a GL debugger,
-    // the wait can be blocked during a breaking point of the debugger
+    // waiting with the deadline
     if ctxt.version >= &Version(Api::Gl, 3, 2) ||
        ctxt.version >= &Version(Api::GlEs, 3, 0) || ctxt.extensions.gl_arb_sync
     {
-        ctxt.gl.ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT,
-                               365 * 24 * 3600 * 1000 * 1000 * 1000)
+        ctxt.gl.ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, timeout)
     } else if ctxt.extensions.gl_apple_sync {
-        ctxt.gl.ClientWaitSyncAPPLE(fence, gl::SYNC_FLUSH_COMMANDS_BIT_APPLE,
-                                    365 * 24 * 3600 * 1000 * 1000 * 1000)
+        ctxt.gl.ClientWaitSyncAPPLE(fence, gl::SYNC_FLUSH_COMMANDS_BIT_APPLE, timeout)
     } else {
         unreachable!();
     }
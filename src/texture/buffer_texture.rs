@@ -51,6 +51,40 @@ sample from a buffer texture of type `Integral` you need to use a `isamplerBuffe
 to sample from a buffer texture of type `Unsigned` you need to use a `usamplerBuffer`. Using the
 wrong type will result in an error.
 
+# Per-instance data
+
+Instead of passing per-instance attributes in a vertex buffer, you can put them in a buffer
+texture and fetch them in the vertex shader with `texelFetch(tex, gl_InstanceID)`. Contrary to
+attributes, this isn't limited by the number of attribute locations.
+
+Since there is no per-instance vertex buffer in this case, pass an `EmptyInstanceAttributes`
+marker along with your vertices to tell glium how many instances to draw:
+
+```no_run
+# #[macro_use]
+# extern crate glium;
+# use glium::Surface;
+# fn main() {
+# let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+# let program: glium::Program = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+# let vertex_buffer: glium::vertex::VertexBufferAny = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+# let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+use glium::texture::buffer_texture::{BufferTexture, BufferTextureType};
+
+let colors: Vec<(f32, f32, f32, f32)> = vec![(1.0, 0.0, 0.0, 1.0); 4096];
+let colors = BufferTexture::new(&display, &colors, BufferTextureType::Float).unwrap();
+
+let instances = glium::vertex::EmptyInstanceAttributes { len: 4096 };
+let mut frame = display.draw();
+frame.draw((&vertex_buffer, instances), &indices, &program, &uniform!{ colors: &colors },
+           &Default::default()).unwrap();
+# }
+```
+
+Buffer textures require OpenGL 3.0, `GL_ARB_texture_buffer_object`,
+`GL_EXT_texture_buffer_object` or their OpenGL ES equivalents. Use
+`is_buffer_texture_supported` to check for them.
+
 */
 use std::{ mem, fmt };
 use std::marker::PhantomData;
@@ -109,6 +143,12 @@ impl fmt::Display for TextureCreationError {
 
 impl Error for TextureCreationError {}
 
+/// Returns true if buffer textures are supported by the backend.
+#[inline]
+pub fn is_buffer_texture_supported<F: ?Sized>(facade: &F) -> bool where F: Facade {
+    facade.get_context().capabilities().max_texture_buffer_size.is_some()
+}
+
 /// Error that can happen while building a buffer texture.
 #[derive(Copy, Clone, Debug)]
 pub enum CreationError {
@@ -283,174 +323,192 @@ impl<T> BufferTexture<T> where [T]: BufferContent, T: TextureBufferContent + Cop
         let mut ctxt = context.make_current();
 
         // checking capabilities
-        if buffer.get_size() / mem::size_of::<T>() > ctxt.capabilities
-                                                         .max_texture_buffer_size.unwrap() as usize
-        {
+        let max_size = match ctxt.capabilities.max_texture_buffer_size {
+            Some(s) => s as usize,
+            None => return Err((TextureCreationError::NotSupported, buffer))
+        };
+
+        if buffer.get_size() / mem::size_of::<T>() > max_size {
             return Err((TextureCreationError::TooLarge, buffer));
         }
 
-        // before starting, we determine the internal format and check that buffer textures are
-        // supported
-        let internal_format = if ctxt.version >= &Version(Api::Gl, 3, 0) ||
-                                 ctxt.extensions.gl_oes_texture_buffer ||
-                                 ctxt.extensions.gl_ext_texture_buffer
-        {
-            match (T::get_type(), ty) {
-                (TextureBufferContentType::U8, BufferTextureType::Float) => gl::R8,
-                (TextureBufferContentType::U8, BufferTextureType::Unsigned) => gl::R8UI,
-                (TextureBufferContentType::I8, BufferTextureType::Integral) => gl::R8I,
-                (TextureBufferContentType::U16, BufferTextureType::Float) => gl::R16,
-                (TextureBufferContentType::U16, BufferTextureType::Unsigned) => gl::R16UI,
-                (TextureBufferContentType::I16, BufferTextureType::Integral) => gl::R16I,
-                (TextureBufferContentType::U32, BufferTextureType::Unsigned) => gl::R32UI,
-                (TextureBufferContentType::I32, BufferTextureType::Integral) => gl::R32I,
-                (TextureBufferContentType::U8U8, BufferTextureType::Float) => gl::RG8,
-                (TextureBufferContentType::U8U8, BufferTextureType::Unsigned) => gl::RG8UI,
-                (TextureBufferContentType::I8I8, BufferTextureType::Integral) => gl::RG8I,
-                (TextureBufferContentType::U16U16, BufferTextureType::Float) => gl::RG16,
-                (TextureBufferContentType::U16U16, BufferTextureType::Unsigned) => gl::RG16UI,
-                (TextureBufferContentType::I16I16, BufferTextureType::Integral) => gl::RG16I,
-                (TextureBufferContentType::U32U32, BufferTextureType::Unsigned) => gl::RG32UI,
-                (TextureBufferContentType::I32I32, BufferTextureType::Integral) => gl::RG32I,
-                (TextureBufferContentType::U8U8U8U8, BufferTextureType::Float) => gl::RGBA8,
-                (TextureBufferContentType::U8U8U8U8, BufferTextureType::Unsigned) => gl::RGBA8UI,
-                (TextureBufferContentType::I8I8I8I8, BufferTextureType::Integral) => gl::RGBA8I,
-                (TextureBufferContentType::U16U16U16U16, BufferTextureType::Float) => gl::RGBA16,
-                (TextureBufferContentType::U16U16U16U16, BufferTextureType::Unsigned) =>
-                                                                                      gl::RGBA16UI,
-                (TextureBufferContentType::I16I16I16I16, BufferTextureType::Integral) =>
-                                                                                       gl::RGBA16I,
-                (TextureBufferContentType::U32U32U32U32, BufferTextureType::Unsigned) =>
-                                                                                      gl::RGBA32UI,
-                (TextureBufferContentType::I32I32I32I32, BufferTextureType::Integral) =>
-                                                                                       gl::RGBA32I,
-                (TextureBufferContentType::F16, BufferTextureType::Float) => gl::R16F,
-                (TextureBufferContentType::F32, BufferTextureType::Float) => gl::R32F,
-                (TextureBufferContentType::F16F16, BufferTextureType::Float) => gl::RG16F,
-                (TextureBufferContentType::F32F32, BufferTextureType::Float) => gl::RG32F,
-                (TextureBufferContentType::F16F16F16F16, BufferTextureType::Float) => gl::RGBA16F,
-                (TextureBufferContentType::F32F32F32F32, BufferTextureType::Float) => gl::RGBA32F,
-
-                (TextureBufferContentType::U32U32U32, BufferTextureType::Unsigned)
-                                            if ctxt.version >= &Version(Api::Gl, 4, 0) ||
-                                               ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32UI,
-                (TextureBufferContentType::I32I32I32, BufferTextureType::Integral)
-                                            if ctxt.version >= &Version(Api::Gl, 4, 0) ||
-                                               ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32I,
-                (TextureBufferContentType::F32F32F32, BufferTextureType::Float)
-                                            if ctxt.version >= &Version(Api::Gl, 4, 0) ||
-                                               ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32F,
-
-                _ => return Err((TextureCreationError::FormatNotSupported, buffer))
-            }
-
-        } else if ctxt.extensions.gl_arb_texture_buffer_object ||
-                  ctxt.extensions.gl_ext_texture_buffer_object
-        {
-            match (T::get_type(), ty) {
-                (TextureBufferContentType::U8U8U8U8, BufferTextureType::Float) => gl::RGBA8,
-                (TextureBufferContentType::U16U16U16U16, BufferTextureType::Float) => gl::RGBA16,
-                (TextureBufferContentType::F16F16F16F16, BufferTextureType::Float) => gl::RGBA16F,
-                (TextureBufferContentType::F32F32F32F32, BufferTextureType::Float) => gl::RGBA32F,
-                (TextureBufferContentType::I8I8I8I8, BufferTextureType::Integral) => gl::RGBA8I,
-                (TextureBufferContentType::I16I16I16I16, BufferTextureType::Integral) =>
-                                                                                      gl::RGBA16I,
-                (TextureBufferContentType::I32I32I32I32, BufferTextureType::Integral) =>
-                                                                                      gl::RGBA32I,
-                (TextureBufferContentType::U8U8U8U8, BufferTextureType::Unsigned) => gl::RGBA8UI,
-                (TextureBufferContentType::U16U16U16U16, BufferTextureType::Unsigned) =>
-                                                                                      gl::RGBA16UI,
-                (TextureBufferContentType::U32U32U32U32, BufferTextureType::Unsigned) =>
-                                                                                      gl::RGBA32UI,
-
-                (TextureBufferContentType::U32U32U32, BufferTextureType::Unsigned)
-                                            if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32UI,
-                (TextureBufferContentType::I32I32I32, BufferTextureType::Integral)
-                                            if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32I,
-                (TextureBufferContentType::F32F32F32, BufferTextureType::Float)
-                                            if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
-                                                                                    => gl::RGB32F,
-
-                // TODO: intensity?
-
-                _ => return Err((TextureCreationError::FormatNotSupported, buffer))
-            }
-
-        } else {
-            return Err((TextureCreationError::NotSupported, buffer));
+        let internal_format = match texture_buffer_internal_format(&ctxt, T::get_type(), ty) {
+            Ok(f) => f,
+            Err(e) => return Err((e, buffer)),
         };
 
         // now the texture creation
         debug_assert_eq!(buffer.get_offset_bytes(), 0);
-        let id = if ctxt.version >= &Version(Api::Gl, 4, 5) ||
-                    ctxt.extensions.gl_arb_direct_state_access
+        let id = create_texture_buffer(&mut ctxt, internal_format, buffer.get_id());
+
+        Ok(BufferTexture {
+            buffer: buffer,
+            ty: ty,
+            texture: id,
+        })
+    }
+}
+
+/// Determines the internal format to pass to `glTexBuffer` for the given type of content.
+fn texture_buffer_internal_format(ctxt: &CommandContext, content: TextureBufferContentType,
+                                  ty: BufferTextureType)
+                                  -> Result<gl::types::GLenum, TextureCreationError>
+{
+    let internal_format = if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                             ctxt.extensions.gl_oes_texture_buffer ||
+                             ctxt.extensions.gl_ext_texture_buffer
+    {
+        match (content, ty) {
+            (TextureBufferContentType::U8, BufferTextureType::Float) => gl::R8,
+            (TextureBufferContentType::U8, BufferTextureType::Unsigned) => gl::R8UI,
+            (TextureBufferContentType::I8, BufferTextureType::Integral) => gl::R8I,
+            (TextureBufferContentType::U16, BufferTextureType::Float) => gl::R16,
+            (TextureBufferContentType::U16, BufferTextureType::Unsigned) => gl::R16UI,
+            (TextureBufferContentType::I16, BufferTextureType::Integral) => gl::R16I,
+            (TextureBufferContentType::U32, BufferTextureType::Unsigned) => gl::R32UI,
+            (TextureBufferContentType::I32, BufferTextureType::Integral) => gl::R32I,
+            (TextureBufferContentType::U8U8, BufferTextureType::Float) => gl::RG8,
+            (TextureBufferContentType::U8U8, BufferTextureType::Unsigned) => gl::RG8UI,
+            (TextureBufferContentType::I8I8, BufferTextureType::Integral) => gl::RG8I,
+            (TextureBufferContentType::U16U16, BufferTextureType::Float) => gl::RG16,
+            (TextureBufferContentType::U16U16, BufferTextureType::Unsigned) => gl::RG16UI,
+            (TextureBufferContentType::I16I16, BufferTextureType::Integral) => gl::RG16I,
+            (TextureBufferContentType::U32U32, BufferTextureType::Unsigned) => gl::RG32UI,
+            (TextureBufferContentType::I32I32, BufferTextureType::Integral) => gl::RG32I,
+            (TextureBufferContentType::U8U8U8U8, BufferTextureType::Float) => gl::RGBA8,
+            (TextureBufferContentType::U8U8U8U8, BufferTextureType::Unsigned) => gl::RGBA8UI,
+            (TextureBufferContentType::I8I8I8I8, BufferTextureType::Integral) => gl::RGBA8I,
+            (TextureBufferContentType::U16U16U16U16, BufferTextureType::Float) => gl::RGBA16,
+            (TextureBufferContentType::U16U16U16U16, BufferTextureType::Unsigned) =>
+                                                                                  gl::RGBA16UI,
+            (TextureBufferContentType::I16I16I16I16, BufferTextureType::Integral) =>
+                                                                                   gl::RGBA16I,
+            (TextureBufferContentType::U32U32U32U32, BufferTextureType::Unsigned) =>
+                                                                                  gl::RGBA32UI,
+            (TextureBufferContentType::I32I32I32I32, BufferTextureType::Integral) =>
+                                                                                   gl::RGBA32I,
+            (TextureBufferContentType::F16, BufferTextureType::Float) => gl::R16F,
+            (TextureBufferContentType::F32, BufferTextureType::Float) => gl::R32F,
+            (TextureBufferContentType::F16F16, BufferTextureType::Float) => gl::RG16F,
+            (TextureBufferContentType::F32F32, BufferTextureType::Float) => gl::RG32F,
+            (TextureBufferContentType::F16F16F16F16, BufferTextureType::Float) => gl::RGBA16F,
+            (TextureBufferContentType::F32F32F32F32, BufferTextureType::Float) => gl::RGBA32F,
+
+            (TextureBufferContentType::U32U32U32, BufferTextureType::Unsigned)
+                                        if ctxt.version >= &Version(Api::Gl, 4, 0) ||
+                                           ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32UI,
+            (TextureBufferContentType::I32I32I32, BufferTextureType::Integral)
+                                        if ctxt.version >= &Version(Api::Gl, 4, 0) ||
+                                           ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32I,
+            (TextureBufferContentType::F32F32F32, BufferTextureType::Float)
+                                        if ctxt.version >= &Version(Api::Gl, 4, 0) ||
+                                           ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32F,
+
+            _ => return Err(TextureCreationError::FormatNotSupported)
+        }
+
+    } else if ctxt.extensions.gl_arb_texture_buffer_object ||
+              ctxt.extensions.gl_ext_texture_buffer_object
+    {
+        match (content, ty) {
+            (TextureBufferContentType::U8U8U8U8, BufferTextureType::Float) => gl::RGBA8,
+            (TextureBufferContentType::U16U16U16U16, BufferTextureType::Float) => gl::RGBA16,
+            (TextureBufferContentType::F16F16F16F16, BufferTextureType::Float) => gl::RGBA16F,
+            (TextureBufferContentType::F32F32F32F32, BufferTextureType::Float) => gl::RGBA32F,
+            (TextureBufferContentType::I8I8I8I8, BufferTextureType::Integral) => gl::RGBA8I,
+            (TextureBufferContentType::I16I16I16I16, BufferTextureType::Integral) =>
+                                                                                  gl::RGBA16I,
+            (TextureBufferContentType::I32I32I32I32, BufferTextureType::Integral) =>
+                                                                                  gl::RGBA32I,
+            (TextureBufferContentType::U8U8U8U8, BufferTextureType::Unsigned) => gl::RGBA8UI,
+            (TextureBufferContentType::U16U16U16U16, BufferTextureType::Unsigned) =>
+                                                                                  gl::RGBA16UI,
+            (TextureBufferContentType::U32U32U32U32, BufferTextureType::Unsigned) =>
+                                                                                  gl::RGBA32UI,
+
+            (TextureBufferContentType::U32U32U32, BufferTextureType::Unsigned)
+                                        if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32UI,
+            (TextureBufferContentType::I32I32I32, BufferTextureType::Integral)
+                                        if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32I,
+            (TextureBufferContentType::F32F32F32, BufferTextureType::Float)
+                                        if ctxt.extensions.gl_arb_texture_buffer_object_rgb32
+                                                                                => gl::RGB32F,
+
+            // TODO: intensity?
+
+            _ => return Err(TextureCreationError::FormatNotSupported)
+        }
+
+    } else {
+        return Err(TextureCreationError::NotSupported);
+    };
+
+    Ok(internal_format)
+}
+
+/// Creates a texture of type `GL_TEXTURE_BUFFER` that gets its data from `buffer`.
+fn create_texture_buffer(ctxt: &mut CommandContext, internal_format: gl::types::GLenum,
+                         buffer: gl::types::GLuint) -> gl::types::GLuint
+{
+    if ctxt.version >= &Version(Api::Gl, 4, 5) ||
+       ctxt.extensions.gl_arb_direct_state_access
+    {
+        unsafe {
+            let mut id = 0;
+            ctxt.gl.CreateTextures(gl::TEXTURE_BUFFER, 1, &mut id);
+            ctxt.gl.TextureBuffer(id, internal_format, buffer);
+            id
+        }
+
+    } else {
+        // reserving the ID
+        let id = unsafe {
+            let mut id = 0;
+            ctxt.gl.GenTextures(1, &mut id);
+            id
+        };
+
+        // binding the texture
+        unsafe {
+            ctxt.gl.BindTexture(gl::TEXTURE_BUFFER, id);
+            let act = ctxt.state.active_texture as usize;
+            ctxt.state.texture_units[act].texture = id;
+        }
+
+        // binding the buffer
+        if ctxt.version >= &Version(Api::Gl, 3, 0) ||
+           ctxt.version >= &Version(Api::GlEs, 3, 2)
         {
             unsafe {
-                let mut id = 0;
-                ctxt.gl.CreateTextures(gl::TEXTURE_BUFFER, 1, &mut id);
-                ctxt.gl.TextureBuffer(id, internal_format, buffer.get_id());
-                id
+                ctxt.gl.TexBuffer(gl::TEXTURE_BUFFER, internal_format, buffer);
             }
-
-        } else {
-            // reserving the ID
-            let id = unsafe {
-                let mut id = 0;
-                ctxt.gl.GenTextures(1, &mut id);
-                id
-            };
-
-            // binding the texture
+        } else if ctxt.extensions.gl_arb_texture_buffer_object {
             unsafe {
-                ctxt.gl.BindTexture(gl::TEXTURE_BUFFER, id);
-                let act = ctxt.state.active_texture as usize;
-                ctxt.state.texture_units[act].texture = id;
+                ctxt.gl.TexBufferARB(gl::TEXTURE_BUFFER, internal_format, buffer);
             }
-
-            // binding the buffer
-            if ctxt.version >= &Version(Api::Gl, 3, 0) ||
-               ctxt.version >= &Version(Api::GlEs, 3, 2)
-            {
-                unsafe {
-                    ctxt.gl.TexBuffer(gl::TEXTURE_BUFFER, internal_format, buffer.get_id());
-                }
-            } else if ctxt.extensions.gl_arb_texture_buffer_object {
-                unsafe {
-                    ctxt.gl.TexBufferARB(gl::TEXTURE_BUFFER, internal_format,
-                                         buffer.get_id());
-                }
-            } else if ctxt.extensions.gl_ext_texture_buffer_object ||
-                      ctxt.extensions.gl_ext_texture_buffer
-            {
-                unsafe {
-                    ctxt.gl.TexBufferEXT(gl::TEXTURE_BUFFER, internal_format,
-                                         buffer.get_id());
-                }
-            } else if ctxt.extensions.gl_oes_texture_buffer {
-                unsafe {
-                    ctxt.gl.TexBufferOES(gl::TEXTURE_BUFFER, internal_format,
-                                         buffer.get_id());
-                }
-
-            } else {
-                // handled during the choice for the internal format
-                // note that this panic will leak the texture
-                unreachable!();
+        } else if ctxt.extensions.gl_ext_texture_buffer_object ||
+                  ctxt.extensions.gl_ext_texture_buffer
+        {
+            unsafe {
+                ctxt.gl.TexBufferEXT(gl::TEXTURE_BUFFER, internal_format, buffer);
+            }
+        } else if ctxt.extensions.gl_oes_texture_buffer {
+            unsafe {
+                ctxt.gl.TexBufferOES(gl::TEXTURE_BUFFER, internal_format, buffer);
             }
 
-            id
-        };
+        } else {
+            // handled during the choice for the internal format
+            // note that this panic will leak the texture
+            unreachable!();
+        }
 
-        Ok(BufferTexture {
-            buffer: buffer,
-            ty: ty,
-            texture: id,
-        })
+        id
     }
 }
 
@@ -513,6 +571,99 @@ impl<'a, T: 'a> AsUniformValue for &'a BufferTexture<T> where [T]: BufferContent
     }
 }
 
+/// A buffer texture that reads the content of a buffer that it doesn't own.
+///
+/// Built with `BufferTextureView::new` or `VertexBuffer::as_uniform_texel_buffer`. The texture
+/// shares the storage of the buffer, so nothing is copied and modifications of the buffer are
+/// visible when sampling. The texture is destroyed when the view is dropped, and the buffer
+/// can't be dropped while the view exists.
+pub struct BufferTextureView<'a> {
+    context: Rc<Context>,
+    texture: gl::types::GLuint,
+    ty: BufferTextureType,
+    marker: PhantomData<&'a ()>,
+}
+
+impl<'a> BufferTextureView<'a> {
+    /// Builds a buffer texture that reads the content of `buffer` as a list of texels of type
+    /// `E`.
+    ///
+    /// The size of the buffer must be a multiple of the size of `E`, otherwise
+    /// `FormatNotSupported` is returned. Like `BufferTexture::new`, the combination of `E` and
+    /// `ty` must be supported by the backend.
+    pub fn new<T: ?Sized, E>(buffer: &'a Buffer<T>, ty: BufferTextureType)
+                             -> Result<BufferTextureView<'a>, TextureCreationError>
+                             where T: BufferContent, E: TextureBufferContent
+    {
+        let context = buffer.get_context().clone();
+        let texture = {
+            let mut ctxt = context.make_current();
+
+            let max_size = match ctxt.capabilities.max_texture_buffer_size {
+                Some(s) => s as usize,
+                None => return Err(TextureCreationError::NotSupported)
+            };
+
+            if buffer.get_size() % mem::size_of::<E>() != 0 {
+                return Err(TextureCreationError::FormatNotSupported);
+            }
+
+            if buffer.get_size() / mem::size_of::<E>() > max_size {
+                return Err(TextureCreationError::TooLarge);
+            }
+
+            let internal_format = texture_buffer_internal_format(&ctxt, E::get_type(), ty)?;
+            create_texture_buffer(&mut ctxt, internal_format, buffer.get_id())
+        };
+
+        Ok(BufferTextureView {
+            context: context,
+            texture: texture,
+            ty: ty,
+            marker: PhantomData,
+        })
+    }
+
+    /// Builds a `BufferTextureRef`.
+    #[inline]
+    pub fn as_buffer_texture_ref(&self) -> BufferTextureRef {
+        BufferTextureRef {
+            texture: self.texture,
+            ty: self.ty,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Drop for BufferTextureView<'a> {
+    fn drop(&mut self) {
+        let mut ctxt = self.context.make_current();
+
+        // resetting the bindings
+        for tex_unit in ctxt.state.texture_units.iter_mut() {
+            if tex_unit.texture == self.texture {
+                tex_unit.texture = 0;
+            }
+        }
+
+        unsafe { ctxt.gl.DeleteTextures(1, [ self.texture ].as_ptr()); }
+    }
+}
+
+impl<'a> AsUniformValue for BufferTextureView<'a> {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::BufferTexture(self.as_buffer_texture_ref())
+    }
+}
+
+impl<'a, 'b> AsUniformValue for &'b BufferTextureView<'a> {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::BufferTexture(self.as_buffer_texture_ref())
+    }
+}
+
 /// Holds a reference to a `BufferTexture`.
 #[derive(Copy, Clone)]
 pub struct BufferTextureRef<'a> {
@@ -0,0 +1,129 @@
+use buffer::{Buffer, BufferType, BufferMode, BufferCreationError, ReadError};
+use buffer::{BufferSlice, BufferMutSlice};
+use uniforms::{AsUniformValue, UniformBlock, UniformValue, LayoutMismatchError};
+use program;
+
+use gl;
+use GlObject;
+
+use std::ops::{Deref, DerefMut};
+
+use backend::Facade;
+use version::{Api, Version};
+use CapabilitiesSource;
+
+/// Buffer that contains a list of atomic counters.
+///
+/// Each counter is an `u32`. The buffer can be passed to `uniform!` for an `atomic_uint`
+/// uniform, in which case it is bound with `glBindBufferBase(GL_ATOMIC_COUNTER_BUFFER, ...)`
+/// at the binding point declared in the shader:
+///
+///```ignore
+///     layout(binding = 1) uniform atomic_uint counter;
+///```
+///
+/// Atomic counters require OpenGL 4.2, OpenGL ES 3.1 or `GL_ARB_shader_atomic_counters`.
+#[derive(Debug)]
+pub struct AtomicCounterBuffer {
+    buffer: Buffer<[u32]>,
+}
+
+impl GlObject for AtomicCounterBuffer {
+    type Id = gl::types::GLuint;
+
+    #[inline]
+    fn get_id(&self) -> gl::types::GLuint {
+        self.buffer.get_id()
+    }
+}
+
+impl AtomicCounterBuffer {
+    /// Returns true if the backend supports atomic counters.
+    #[inline]
+    pub fn is_supported<F: ?Sized>(facade: &F) -> bool where F: Facade {
+        let context = facade.get_context();
+
+        context.get_version() >= &Version(Api::Gl, 4, 2) ||
+        context.get_version() >= &Version(Api::GlEs, 3, 1) ||
+        context.get_extensions().gl_arb_shader_atomic_counters ||
+        context.get_extensions().gl_nv_shader_atomic_counters
+    }
+
+    /// Builds a buffer containing `counters` atomic counters initialized to zero.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if atomic counters are not supported by the
+    /// backend.
+    pub fn new<F: ?Sized>(facade: &F, counters: usize)
+                          -> Result<AtomicCounterBuffer, BufferCreationError> where F: Facade
+    {
+        if !AtomicCounterBuffer::is_supported(facade) {
+            return Err(BufferCreationError::NotSupported);
+        }
+
+        let data = vec![0u32; counters];
+        let buffer = Buffer::new(facade, &data[..], BufferType::AtomicCounterBuffer,
+                                 BufferMode::Dynamic)?;
+
+        Ok(AtomicCounterBuffer {
+            buffer: buffer,
+        })
+    }
+
+    /// Sets all the counters of the buffer to `value`.
+    ///
+    /// Uses `glClearBufferData` if it is supported.
+    #[inline]
+    pub fn reset(&self, value: u32) {
+        self.buffer.fill(value);
+    }
+
+    /// Reads the current value of the counters.
+    ///
+    /// This waits until all the draw commands that use the buffer have been executed.
+    #[inline]
+    pub fn read(&self) -> Result<Vec<u32>, ReadError> {
+        self.buffer.read()
+    }
+}
+
+impl Deref for AtomicCounterBuffer {
+    type Target = Buffer<[u32]>;
+
+    #[inline]
+    fn deref(&self) -> &Buffer<[u32]> {
+        &self.buffer
+    }
+}
+
+impl DerefMut for AtomicCounterBuffer {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Buffer<[u32]> {
+        &mut self.buffer
+    }
+}
+
+impl<'a> From<&'a AtomicCounterBuffer> for BufferSlice<'a, [u32]> {
+    #[inline]
+    fn from(b: &'a AtomicCounterBuffer) -> BufferSlice<'a, [u32]> {
+        b.buffer.as_slice()
+    }
+}
+
+impl<'a> From<&'a mut AtomicCounterBuffer> for BufferMutSlice<'a, [u32]> {
+    #[inline]
+    fn from(b: &'a mut AtomicCounterBuffer) -> BufferMutSlice<'a, [u32]> {
+        b.buffer.as_mut_slice()
+    }
+}
+
+impl<'a> AsUniformValue for &'a AtomicCounterBuffer {
+    #[inline]
+    fn as_uniform_value(&self) -> UniformValue {
+        #[inline]
+        fn f(block: &program::UniformBlock) -> Result<(), LayoutMismatchError> {
+            u32::matches(&block.layout, 0)
+        }
+
+        UniformValue::Block(self.buffer.as_slice_any(), f)
+    }
+}
@@ -140,6 +140,7 @@ let program = glium::Program::from_source(&display,
 # }
 ```
 */
+pub use self::atomic_counter::AtomicCounterBuffer;
 pub use self::buffer::UniformBuffer;
 pub use self::sampler::{SamplerWrapFunction, MagnifySamplerFilter, MinifySamplerFilter, DepthTextureComparison};
 pub use self::sampler::{Sampler, SamplerBehavior};
@@ -154,6 +155,7 @@ use buffer::Buffer;
 use program;
 use program::BlockLayout;
 
+mod atomic_counter;
 mod bind;
 mod buffer;
 mod sampler;
@@ -1,14 +1,28 @@
+use std::borrow::Cow;
+use std::cmp;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
-use std::ops::{Deref, DerefMut};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::ops::{Deref, DerefMut, Range};
 use utils::range::RangeArgument;
 
 use buffer::{Buffer, BufferSlice, BufferMutSlice, BufferAny, BufferType, BufferMode, BufferCreationError, Content};
-use vertex::{Vertex, VerticesSource, PerInstance};
+use buffer::{BufferAnySlice, Mapping, ReadMapping, WriteMapping, CopyError, ReadError, MapError};
+use buffer::ReadbackFuture;
+use vertex::{Attribute, AttributeBinding, AttributeType, Vertex, VerticesSource, PerInstance};
 use vertex::format::VertexFormat;
+use texture::buffer_texture::{BufferTextureType, BufferTextureView, TextureBufferContent};
+use texture::buffer_texture::TextureCreationError as BufferTextureCreationError;
+use texture::{PixelValue, Texture1dDataSink};
 
 use gl;
 use GlObject;
+use BufferAnyExt;
+use BufferExt;
 
 use backend::Facade;
 use version::{Api, Version};
@@ -68,14 +82,77 @@ impl<T: Copy> GlObject for VertexBuffer<T> {
 pub struct VertexBuffer<T> where T: Copy {
     buffer: Buffer<[T]>,
     bindings: VertexFormat,
+    // number of vertices in use, the rest of the buffer being spare capacity
+    len: usize,
+    // copy of the content of the buffer used by `write_if_changed`, if enabled, with the
+    // modifications count of the buffer when the copy was last updated
+    shadow: Option<(Vec<u8>, u64)>,
 }
 
 /// Represents a slice of a `VertexBuffer`.
+///
+/// A slice can be drawn like the whole buffer, in which case only its vertices are used. This
+/// makes it possible to put several meshes in the same buffer and to draw them separately.
+/// Slices can be sliced again, and the ranges are always relative to the slice:
+/// `buffer.slice(100 .. 200).unwrap().slice(10 .. 20)` contains the vertices `110 .. 120` of
+/// `buffer`.
+///
+/// The methods of `BufferSlice`, like `len`, `read` or `write`, are accessible through `Deref`.
+/// Mapping a range of vertices requires an exclusive access to the buffer and is done with
+/// `VertexBuffer::slice_mut`.
 pub struct VertexBufferSlice<'b, T: 'b> where T: Copy {
     buffer: BufferSlice<'b, [T]>,
     bindings: &'b VertexFormat,
 }
 
+/// Mapping of a vertex buffer that gives access to a single attribute of each vertex.
+///
+/// Obtained with `VertexBuffer::map_attribute`. The other attributes of the vertices are left
+/// untouched, and the type of the attribute has been checked against the `VertexFormat` of
+/// the buffer when the view was created.
+pub struct AttributeView<'a, T: 'a, A> where T: Copy {
+    mapping: Mapping<'a, [T]>,
+    offset: usize,
+    marker: PhantomData<A>,
+}
+
+impl<'a, T: 'a, A> AttributeView<'a, T, A> where T: Copy, A: Attribute + Copy {
+    /// Returns the number of vertices in the view.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.mapping.len()
+    }
+
+    /// Returns the value of the attribute for the vertex at the given index.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    #[inline]
+    pub fn get(&self, index: usize) -> A {
+        let vertex = &self.mapping[index];
+        unsafe {
+            let ptr = (vertex as *const T as *const u8).add(self.offset) as *const A;
+            ptr::read_unaligned(ptr)
+        }
+    }
+
+    /// Changes the value of the attribute for the vertex at the given index.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    #[inline]
+    pub fn set(&mut self, index: usize, value: A) {
+        let offset = self.offset;
+        let vertex = &mut self.mapping[index];
+        unsafe {
+            let ptr = (vertex as *mut T as *mut u8).add(offset) as *mut A;
+            ptr::write_unaligned(ptr, value)
+        }
+    }
+}
+
 impl<'b, T: 'b> VertexBufferSlice<'b, T> where T: Copy + Content {
     /// Creates a marker that instructs glium to use multiple instances.
     ///
@@ -87,14 +164,29 @@ impl<'b, T: 'b> VertexBufferSlice<'b, T> where T: Copy + Content {
     #[inline]
     pub fn per_instance(&'b self) -> Result<PerInstance, InstancingNotSupported> {
         // TODO: don't check this here
-        if !(self.get_context().get_version() >= &Version(Api::Gl, 3, 3)) &&
-            !self.get_context().get_extensions().gl_arb_instanced_arrays
-        {
+        if !is_instancing_supported(self.get_context()) {
             return Err(InstancingNotSupported);
         }
 
         Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings))
     }
+
+    /// Builds a subslice of this slice, with a range relative to the start of this slice.
+    ///
+    /// Returns `None` if the range is out of this slice.
+    #[inline]
+    pub fn slice<R: RangeArgument<usize>>(&self, range: R) -> Option<VertexBufferSlice<'b, T>> {
+        Some(VertexBufferSlice {
+            buffer: self.buffer.slice(range)?,
+            bindings: self.bindings,
+        })
+    }
+
+    /// Returns the index of the first vertex of this slice within the buffer.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.buffer.get_offset_bytes() / mem::size_of::<T>()
+    }
 }
 
 impl<T> VertexBuffer<T> where T: Vertex {
@@ -126,6 +218,12 @@ impl<T> VertexBuffer<T> where T: Vertex {
     /// # }
     /// ```
     ///
+    /// The data is copied before this function returns, so `T` doesn't need to be `'static`.
+    /// Only turning the buffer into a `VertexBufferAny` requires it.
+    ///
+    /// Returns `BufferCreationError::OutOfMemory` if the implementation couldn't allocate the
+    /// buffer, and `BufferCreationError::ContextLost` if the context has been lost.
+    ///
     #[inline]
     pub fn new<F: ?Sized>(facade: &F, data: &[T]) -> Result<VertexBuffer<T>, CreationError>
                   where F: Facade
@@ -136,6 +234,9 @@ impl<T> VertexBuffer<T> where T: Vertex {
     /// Builds a new vertex buffer.
     ///
     /// This function will create a buffer that is intended to be modified frequently.
+    ///
+    /// The buffer is created with `BufferMode::Dynamic`, which means `GL_DYNAMIC_DRAW` instead
+    /// of `GL_STATIC_DRAW` when `glBufferStorage` isn't available. `get_usage` returns the mode.
     #[inline]
     pub fn dynamic<F: ?Sized>(facade: &F, data: &[T]) -> Result<VertexBuffer<T>, CreationError>
                       where F: Facade
@@ -159,6 +260,20 @@ impl<T> VertexBuffer<T> where T: Vertex {
         VertexBuffer::new_impl(facade, data, BufferMode::Immutable)
     }
 
+    /// Builds a new vertex buffer that keeps a copy of its content in memory.
+    ///
+    /// The copy allows `write_if_changed` to skip uploads of data that the buffer already
+    /// contains, at the cost of doubling the memory used by the buffer.
+    pub fn with_shadow_copy<F: ?Sized>(facade: &F, data: &[T], mode: BufferMode)
+                                       -> Result<VertexBuffer<T>, CreationError>
+                                       where F: Facade
+    {
+        let mut buffer = VertexBuffer::new_impl(facade, data, mode)?;
+        let count = buffer.buffer.get_modifications_count();
+        buffer.shadow = Some((as_bytes(data).to_vec(), count));
+        Ok(buffer)
+    }
+
     #[inline]
     fn new_impl<F: ?Sized>(facade: &F, data: &[T], mode: BufferMode)
                    -> Result<VertexBuffer<T>, CreationError>
@@ -213,6 +328,21 @@ impl<T> VertexBuffer<T> where T: Vertex {
         VertexBuffer::empty_impl(facade, elements, BufferMode::Immutable)
     }
 
+    /// Builds a vertex buffer with room for `capacity` vertices, but that contains no vertex.
+    ///
+    /// This is similar to `Vec::with_capacity`. The storage is allocated without any data,
+    /// with `BufferMode::Dynamic`, and vertices are added with `extend`. Only the vertices
+    /// between 0 and `len()` are drawn.
+    #[inline]
+    pub fn with_capacity<F: ?Sized>(facade: &F, capacity: usize)
+                                    -> Result<VertexBuffer<T>, CreationError>
+                                    where F: Facade
+    {
+        let mut buffer = VertexBuffer::empty_impl(facade, capacity, BufferMode::Dynamic)?;
+        buffer.len = 0;
+        Ok(buffer)
+    }
+
     #[inline]
     fn empty_impl<F: ?Sized>(facade: &F, elements: usize, mode: BufferMode)
                      -> Result<VertexBuffer<T>, CreationError>
@@ -230,6 +360,11 @@ impl<T> VertexBuffer<T> where T: Vertex {
 impl<T> VertexBuffer<T> where T: Copy {
     /// Builds a new vertex buffer from an indeterminate data type and bindings.
     ///
+    /// # Panic
+    ///
+    /// In debug mode, panics if the offset of one of the attributes is not a multiple of the
+    /// size of its components (for example an `F32` attribute at offset 6).
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -241,11 +376,11 @@ impl<T> VertexBuffer<T> where T: Copy {
     /// let bindings = Cow::Owned(vec![(
     ///         Cow::Borrowed("position"), 0,
     ///         glium::vertex::AttributeType::F32F32,
-    ///         false,
+    ///         glium::vertex::AttributeBinding::Auto,
     ///     ), (
     ///         Cow::Borrowed("color"), 2 * ::std::mem::size_of::<f32>(),
     ///         glium::vertex::AttributeType::F32,
-    ///         false,
+    ///         glium::vertex::AttributeBinding::Auto,
     ///     ),
     /// ]);
     ///
@@ -268,15 +403,22 @@ impl<T> VertexBuffer<T> where T: Copy {
                              where F: Facade
     {
         // FIXME: check that the format is supported
+        debug_check_alignment(&bindings);
 
         Ok(VertexBuffer {
             buffer: Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Default)?,
             bindings: bindings,
+            len: data.len(),
+            shadow: None,
         })
     }
 
     /// Dynamic version of `new_raw`.
+    ///
+    /// # Panic
+    ///
+    /// Same as `new_raw`.
     #[inline]
     pub unsafe fn new_raw_dynamic<F: ?Sized>(facade: &F, data: &[T],
                                      bindings: VertexFormat, elements_size: usize)
@@ -284,20 +426,43 @@ impl<T> VertexBuffer<T> where T: Copy {
                                      where F: Facade
     {
         // FIXME: check that the format is supported
+        debug_check_alignment(&bindings);
 
         Ok(VertexBuffer {
             buffer: Buffer::new(facade, data, BufferType::ArrayBuffer,
                                          BufferMode::Dynamic)?,
             bindings: bindings,
+            len: data.len(),
+            shadow: None,
         })
     }
 
     /// Accesses a slice of the buffer.
     ///
+    /// The slice can be passed to `draw` like the buffer itself, in which case only the vertices
+    /// within `range` are used. This allows drawing a part of a large buffer, for example a
+    /// single quad of a buffer that contains the quads of many glyphs, without copying it.
+    ///
     /// Returns `None` if the slice is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let vertex_buffer: glium::VertexBuffer<[f32; 2]> = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let program: glium::Program = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # use glium::Surface;
+    /// # let mut frame = display.draw();
+    /// // draws the second quad of the buffer
+    /// let quad = vertex_buffer.slice(4 .. 8).unwrap();
+    /// let indices = glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip);
+    /// frame.draw(quad, &indices, &program, &glium::uniforms::EmptyUniforms,
+    ///            &Default::default()).unwrap();
+    /// # frame.finish().unwrap();
+    /// ```
     #[inline]
     pub fn slice<R: RangeArgument<usize>>(&self, range: R) -> Option<VertexBufferSlice<T>> {
-        let slice = match self.buffer.slice(range) {
+        let slice = match self.used().slice(range) {
             None => return None,
             Some(s) => s
         };
@@ -308,12 +473,618 @@ impl<T> VertexBuffer<T> where T: Copy {
         })
     }
 
+    /// Maps a slice of the buffer in memory for reading and writing.
+    ///
+    /// Only the elements within `range` are mapped. This is the mutable counterpart of `slice`
+    /// and is the same as calling `slice_mut(range).map()` on the underlying `Buffer`.
+    ///
+    /// Returns `Ok(None)` if the slice is out of range, and an error if it can't be mapped.
+    #[inline]
+    pub fn slice_mut<R: RangeArgument<usize>>(&mut self, range: R)
+                                              -> Result<Option<Mapping<[T]>>, MapError>
+    {
+        let len = self.len;
+        self.buffer.slice_mut(0 .. len).unwrap().slice(range).map(|slice| slice.map()).transpose()
+    }
+
+    /// Maps a slice of the buffer in memory for writing only, without reading back or
+    /// preserving its previous content and without waiting for the GPU.
+    ///
+    /// You must write every vertex within `range`. See
+    /// `BufferMutSlice::map_write_invalidate_range` for the caveats of this function.
+    ///
+    /// Returns `Ok(None)` if the slice is out of range, and an error if it can't be mapped.
+    #[inline]
+    pub fn map_mut_range_invalidate<R: RangeArgument<usize>>(&mut self, range: R)
+                                                             -> Result<Option<WriteMapping<[T]>>, MapError>
+    {
+        let len = self.len;
+        self.buffer.slice_mut(0 .. len).unwrap().slice(range)
+                   .map(|slice| slice.map_write_invalidate_range()).transpose()
+    }
+
+    /// Writes the vertices produced by an iterator, starting at `offset`.
+    ///
+    /// The range between `offset` and the end of the buffer is mapped and the vertices are
+    /// written one by one, without building an intermediate `Vec`. Vertices after the last
+    /// one produced by the iterator are left untouched. Returns the number of vertices that
+    /// have been written, or an error if the buffer can't be mapped.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `offset` is out of range, or if the iterator produces more vertices than
+    /// what fits between `offset` and the end of the buffer.
+    pub fn write_iter<I>(&mut self, offset: usize, iter: I) -> Result<usize, MapError>
+                         where I: IntoIterator<Item = T>
+    {
+        let len = self.len;
+        assert!(offset <= len, "Offset {} is out of range (the buffer contains {} vertices)",
+                offset, len);
+
+        let mut iter = iter.into_iter();
+        if offset == len {
+            assert!(iter.next().is_none(), "The iterator produced too many vertices");
+            return Ok(0);
+        }
+
+        let mut mapping = self.buffer.slice_mut(offset .. len).unwrap().map_write()?;
+        let available = mapping.len();
+
+        let mut written = 0;
+        for vertex in iter {
+            assert!(written < available, "The iterator produced more than {} vertices",
+                    available);
+            mapping.set(written, vertex);
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    /// Sets each vertex of the buffer to the value returned by `f` for its index.
+    ///
+    /// The buffer is mapped for writing, then `f(0)`, `f(1)`, etc. are written directly in the
+    /// mapping, without building a `Vec`. This is useful to initialize a buffer created with
+    /// `empty` with a procedural mesh.
+    ///
+    /// Like `map_write`, this waits until the vertices are no longer used by the GPU. Returns an
+    /// error if the buffer can't be mapped.
+    pub fn fill_from_fn<F>(&mut self, mut f: F) -> Result<(), MapError>
+                           where F: FnMut(usize) -> T
+    {
+        let len = self.len;
+        if len == 0 {
+            return Ok(());
+        }
+
+        let mut mapping = self.buffer.slice_mut(0 .. len).unwrap().map_write()?;
+        for index in 0 .. len {
+            mapping.set(index, f(index));
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the vertex at the given index.
+    ///
+    /// This is the same as `slice(index .. index + 1).unwrap().write(&[vertex])`, and uploads
+    /// the vertex with `glBufferSubData` without any allocation.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `index` is out of range.
+    #[inline]
+    pub fn write_one(&mut self, index: usize, vertex: T) {
+        let len = self.len;
+        let slice = match self.used().slice(index .. index + 1) {
+            Some(slice) => slice,
+            None => panic!("Index {} is out of range (the buffer contains {} vertices)",
+                           index, len),
+        };
+
+        slice.write(slice::from_ref(&vertex));
+    }
+
+    /// Sets every vertex of the buffer to `value`.
+    ///
+    /// This uses `glClearBufferData` (OpenGL 4.3 or `GL_ARB_clear_buffer_object`) when possible,
+    /// so that nothing is uploaded. Otherwise the vertices are built on the CPU and uploaded.
+    /// See `Buffer::fill`.
+    #[inline]
+    pub fn clear_with(&self, value: T) {
+        self.fill(value);
+    }
+
+    /// Writes `data` starting at the vertex `offset`, growing the buffer if it is too small.
+    ///
+    /// If `offset + data.len()` exceeds the capacity of the buffer, a new buffer of exactly this
+    /// capacity is allocated with the same mode, the existing vertices are copied to it with
+    /// `glCopyBufferSubData` and the old buffer is destroyed. If it exceeds the length of the
+    /// buffer, the length is increased. Vertices between the old length and `offset` are left
+    /// uninitialized.
+    ///
+    /// Reallocating changes the OpenGL id of the buffer, which invalidates the vertex array
+    /// objects that were using it. They will be rebuilt on the next draw.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if the buffer must grow but the backend
+    /// doesn't support copying between buffers, and `BufferCreationError::FormatTooLarge` if
+    /// the required size overflows. The buffer is left untouched in these cases.
+    pub fn write_or_grow(&mut self, offset: usize, data: &[T]) -> Result<(), CreationError> {
+        let required = match offset.checked_add(data.len()) {
+            Some(required) => required,
+            None => return Err(BufferCreationError::FormatTooLarge.into()),
+        };
+
+        if required > self.capacity() {
+            self.reallocate(required)?;
+        }
+
+        if !data.is_empty() {
+            let shadow_up_to_date = self.is_shadow_up_to_date();
+            self.buffer.slice(offset .. required).unwrap().write(data);
+            self.update_shadow(shadow_up_to_date, offset, data);
+        }
+
+        if required > self.len {
+            self.len = required;
+        }
+
+        Ok(())
+    }
+
+    /// Appends `data` after the last vertex of the buffer.
+    ///
+    /// If the vertices fit in the capacity of the buffer, they are uploaded with a single
+    /// `glBufferSubData` and nothing is reallocated. Otherwise the capacity is doubled, or more
+    /// if that's not enough, like `Vec` does. See `write_or_grow` for what reallocating means
+    /// and for the errors.
+    pub fn extend(&mut self, data: &[T]) -> Result<(), CreationError> {
+        let required = match self.len.checked_add(data.len()) {
+            Some(required) => required,
+            None => return Err(BufferCreationError::FormatTooLarge.into()),
+        };
+
+        if required > self.capacity() {
+            let capacity = self.capacity().saturating_mul(2);
+            self.reallocate(if capacity > required { capacity } else { required })?;
+        }
+
+        let offset = self.len;
+        self.write_or_grow(offset, data)
+    }
+
+    /// Removes all the vertices, without changing the capacity of the buffer.
+    ///
+    /// Nothing is uploaded. The content of the buffer is kept until it is overwritten.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the number of vertices in the buffer.
+    ///
+    /// This is the number of vertices that are drawn. It is smaller than `capacity()` for
+    /// buffers built with `with_capacity`.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the buffer contains no vertex.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of vertices that the buffer can hold without reallocating.
+    ///
+    /// The spare capacity is never drawn, read or written. `VertexBuffer` has its own version
+    /// of the methods of `Buffer` that access the content, like `write`, `read` or `map`, and
+    /// they only operate on the first `len()` vertices. `get_size` is the size of the whole
+    /// storage.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Uploads some vertices in the buffer.
+    ///
+    /// See `Buffer::write`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is different from `len()`.
+    #[inline]
+    pub fn write(&self, data: &[T]) {
+        self.used().write(data)
+    }
+
+    /// Invalidates the vertices of the buffer. Their content becomes undefined.
+    ///
+    /// See `Buffer::invalidate`.
+    #[inline]
+    pub fn invalidate(&self) {
+        self.used().invalidate()
+    }
+
+    /// Reads the vertices of the buffer.
+    #[inline]
+    pub fn read(&self) -> Result<Vec<T>, ReadError> {
+        self.used().read()
+    }
+
+    /// Maps the vertices of the buffer in memory for both reading and writing.
+    ///
+    /// See `Buffer::map`.
+    #[inline]
+    pub fn map(&mut self) -> Result<Mapping<[T]>, MapError> {
+        self.used_mut().map()
+    }
+
+    /// Same as `map`, but returns `Ok(None)` instead of blocking if the GPU is still using the
+    /// buffer.
+    ///
+    /// See `Buffer::try_map`.
+    #[inline]
+    pub fn try_map(&mut self) -> Result<Option<Mapping<[T]>>, MapError> {
+        self.used_mut().try_map()
+    }
+
+    /// Maps the vertices of the buffer in memory for reading.
+    ///
+    /// See `Buffer::map_read`.
+    #[inline]
+    pub fn map_read(&mut self) -> Result<ReadMapping<[T]>, MapError> {
+        self.used_mut().map_read()
+    }
+
+    /// Maps the vertices of the buffer in memory for writing only.
+    ///
+    /// See `Buffer::map_write`.
+    #[inline]
+    pub fn map_write(&mut self) -> Result<WriteMapping<[T]>, MapError> {
+        self.used_mut().map_write()
+    }
+
+    /// Maps the vertices of the buffer in memory for writing only, discarding their previous
+    /// content.
+    ///
+    /// See `Buffer::map_write_invalidate`.
+    pub fn map_write_invalidate(&mut self) -> Result<WriteMapping<[T]>, MapError> {
+        if self.len == self.capacity() {
+            return self.buffer.map_write_invalidate();
+        }
+
+        self.used().invalidate();
+        self.used_mut().map_write()
+    }
+
+    /// Copies the vertices of the buffer to another buffer.
+    ///
+    /// See `Buffer::copy_to`.
+    #[inline]
+    pub fn copy_to<'a, S>(&self, target: S) -> Result<(), CopyError>
+                          where S: Into<BufferSlice<'a, [T]>>, T: 'a
+    {
+        let target: BufferSlice<[T]> = target.into();
+        self.used().copy_to(target)
+    }
+
+    /// Builds a slice that contains the vertices of the buffer.
+    #[inline]
+    pub fn as_slice(&self) -> BufferSlice<[T]> {
+        self.used()
+    }
+
+    /// Builds a mutable slice that contains the vertices of the buffer.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> BufferMutSlice<[T]> {
+        self.used_mut()
+    }
+
+    /// Builds a slice-any that contains the vertices of the buffer.
+    #[inline]
+    pub fn as_slice_any(&self) -> BufferAnySlice {
+        self.used().as_slice_any()
+    }
+
+    /// Sets every vertex of the buffer to `value`.
+    ///
+    /// See `Buffer::fill`.
+    pub fn fill(&self, value: T) {
+        if self.len == self.capacity() {
+            self.buffer.fill(value);
+        } else {
+            self.used().write(&vec![value; self.len]);
+        }
+    }
+
+    /// Reads the vertices within each of `ranges`, and returns them in the same order.
+    ///
+    /// See `Buffer::read_ranges`.
+    pub fn read_ranges(&self, ranges: &[Range<usize>]) -> Result<Vec<Vec<T>>, ReadError> {
+        if ranges.iter().any(|range| range.end > self.len) {
+            return Err(ReadError::OutOfRange);
+        }
+
+        self.buffer.read_ranges(ranges)
+    }
+
+    /// Reallocates the buffer so that its capacity is equal to its length.
+    ///
+    /// The vertices are copied to the new buffer with `glCopyBufferSubData` and the old buffer
+    /// is destroyed. This invalidates the vertex array objects that were using it, like
+    /// `write_or_grow`. Does nothing if the buffer has no spare capacity.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if the backend doesn't support copying
+    /// between buffers, in which case the buffer is left untouched.
+    pub fn shrink_to_fit(&mut self) -> Result<(), CreationError> {
+        if self.len == self.capacity() {
+            return Ok(());
+        }
+
+        let len = self.len;
+        self.reallocate(len)
+    }
+
+    /// Replaces the buffer with one of `capacity` vertices, and copies the existing vertices
+    /// that fit in it.
+    fn reallocate(&mut self, capacity: usize) -> Result<(), CreationError> {
+        let shadow_up_to_date = self.is_shadow_up_to_date();
+        let context = self.buffer.get_context().clone();
+        let new_buffer = Buffer::empty_array(&context, BufferType::ArrayBuffer, capacity,
+                                             self.buffer.get_mode())?;
+
+        let copied = cmp::min(self.capacity(), capacity);
+        if copied != 0 {
+            let source = self.buffer.slice(0 .. copied).unwrap();
+            if source.copy_to(new_buffer.slice(0 .. copied).unwrap()).is_err() {
+                return Err(BufferCreationError::NotSupported.into());
+            }
+        }
+
+        self.buffer = new_buffer;
+
+        // the copy only moved the vertices that the shadow copy already knows about
+        if shadow_up_to_date {
+            let count = self.buffer.get_modifications_count();
+            if let Some((ref mut shadow, ref mut shadow_count)) = self.shadow {
+                shadow.truncate(copied * mem::size_of::<T>());
+                *shadow_count = count;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` starting at the vertex `offset`, unless the buffer already contains it.
+    ///
+    /// This requires the buffer to have been created with `with_shadow_copy`. The data is
+    /// compared byte by byte with the copy kept in memory, and nothing is uploaded if they
+    /// match. Otherwise, or if the buffer has no shadow copy, this is the same as `write`.
+    ///
+    /// The shadow copy is updated by `write_if_changed` and `write_or_grow`. Modifying the
+    /// buffer by other means, for example with `write`, `map`, `slice_mut` or from the GPU,
+    /// discards the shadow copy, and the next call uploads the data unconditionally.
+    ///
+    /// Returns true if the data has been uploaded.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `offset + data.len()` is out of range.
+    pub fn write_if_changed(&mut self, offset: usize, data: &[T]) -> bool {
+        let end = offset + data.len();
+        let len = self.len;
+        let slice = match self.used().slice(offset .. end) {
+            Some(slice) => slice,
+            None => panic!("Range {}..{} is out of range (the buffer contains {} vertices)",
+                           offset, end, len),
+        };
+
+        let shadow_up_to_date = self.is_shadow_up_to_date();
+        if shadow_up_to_date {
+            let shadow = &self.shadow.as_ref().unwrap().0;
+            let start = offset * mem::size_of::<T>();
+            let end = end * mem::size_of::<T>();
+            if end <= shadow.len() && &shadow[start .. end] == as_bytes(data) {
+                return false;
+            }
+        }
+
+        slice.write(data);
+        self.update_shadow(shadow_up_to_date, offset, data);
+        true
+    }
+
+    /// Returns true if the buffer has a shadow copy and hasn't been modified since the copy was
+    /// last updated.
+    fn is_shadow_up_to_date(&self) -> bool {
+        match self.shadow {
+            Some((_, count)) => count == self.buffer.get_modifications_count(),
+            None => false,
+        }
+    }
+
+    /// Writes `data` at the vertex `offset` in the shadow copy, if any. Must be called right
+    /// after `data` has been uploaded, with the value that `is_shadow_up_to_date` returned
+    /// before the upload.
+    ///
+    /// If the buffer had been modified by other means, the content of the shadow copy is
+    /// discarded first.
+    fn update_shadow(&mut self, up_to_date: bool, offset: usize, data: &[T]) {
+        let count = self.buffer.get_modifications_count();
+
+        let shadow = match self.shadow {
+            Some((ref mut shadow, ref mut shadow_count)) => {
+                if !up_to_date {
+                    shadow.clear();
+                }
+                *shadow_count = count;
+                shadow
+            },
+            None => return,
+        };
+
+        let start = offset * mem::size_of::<T>();
+        let data = as_bytes(data);
+        if start > shadow.len() {
+            // we don't know the content of the vertices in between
+            return;
+        }
+
+        let overlap = (shadow.len() - start).min(data.len());
+        shadow[start .. start + overlap].copy_from_slice(&data[.. overlap]);
+        shadow.extend_from_slice(&data[overlap ..]);
+    }
+
+    /// Checks that the number of vertices matches the size of the buffer allocated by OpenGL.
+    ///
+    /// This is cheap enough to be called after each operation in tests, for example after a
+    /// `write` or a reallocation. It does nothing if debug assertions are disabled.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `len() * size_of::<T>()` is larger than the size of the buffer in OpenGL, or if
+    /// the capacity doesn't match the size of the buffer in OpenGL.
+    #[inline]
+    pub fn assert_valid(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        self.buffer.assert_valid();
+
+        let obtained = self.buffer.query_size();
+        let used = self.len * mem::size_of::<T>();
+        assert!(used <= obtained, "The {} vertices of the buffer ({} bytes) don't fit in the \
+                 size of the buffer in OpenGL ({} bytes)", self.len, used, obtained);
+    }
+
+    /// Returns the mode that the buffer was created with, for example `BufferMode::Dynamic` for
+    /// a buffer created with `dynamic` or `empty_dynamic`.
+    ///
+    /// This is the usage hint that glium passed to OpenGL. It is recorded at creation and isn't
+    /// queried from OpenGL, so this is cheap enough to be displayed every frame.
+    #[inline]
+    pub fn get_usage(&self) -> BufferMode {
+        self.buffer.get_mode()
+    }
+
     /// Returns the associated `VertexFormat`.
     #[inline]
     pub fn get_bindings(&self) -> &VertexFormat {
         &self.bindings
     }
 
+    /// Reads the content of the buffer and extracts the values of a single attribute.
+    ///
+    /// The whole buffer is downloaded, then the attribute named `name` is extracted from each
+    /// vertex using the offset found in the `VertexFormat`. This is mostly useful for debugging,
+    /// for example to dump the positions of a buffer that also contains normals and texture
+    /// coordinates.
+    ///
+    /// Returns `None` if there is no attribute with this name, if its type is not the same as
+    /// `A::get_type()`, or if the backend doesn't support reading from a buffer.
+    pub fn read_attribute<A>(&self, name: &str) -> Option<Vec<A>> where A: Attribute {
+        let offset = match self.bindings.iter().find(|b| b.0 == name) {
+            Some(&(_, offset, ty, _)) if ty == A::get_type() => offset,
+            _ => return None,
+        };
+
+        let stride = mem::size_of::<T>();
+        if offset + mem::size_of::<A>() > stride {
+            return None;
+        }
+
+        let data = match self.used().read() {
+            Ok(data) => data,
+            Err(_) => return None,
+        };
+
+        Some(data.iter().map(|vertex| unsafe {
+            let ptr = (vertex as *const T as *const u8).add(offset) as *const A;
+            ptr::read_unaligned(ptr)
+        }).collect())
+    }
+
+    /// Maps the buffer and returns a view of the attribute named `name`.
+    ///
+    /// Contrary to `read_attribute`, nothing is downloaded in advance and the values can be
+    /// modified with `AttributeView::set`, which is useful to edit the positions of an
+    /// interleaved buffer without touching the other attributes.
+    ///
+    /// Returns `Ok(None)` if there is no attribute with this name or if its type is not the same
+    /// as `A::get_type()`, and an error if the buffer can't be mapped.
+    pub fn map_attribute<A>(&mut self, name: &str) -> Result<Option<AttributeView<T, A>>, MapError>
+                            where A: Attribute + Copy
+    {
+        let offset = match self.bindings.iter().find(|b| b.0 == name) {
+            Some(&(_, offset, ty, _)) if ty == A::get_type() => offset,
+            _ => return Ok(None),
+        };
+
+        if offset + mem::size_of::<A>() > mem::size_of::<T>() {
+            return Ok(None);
+        }
+
+        let len = self.len;
+        Ok(Some(AttributeView {
+            mapping: self.buffer.slice_mut(0 .. len).unwrap().map()?,
+            offset: offset,
+            marker: PhantomData,
+        }))
+    }
+
+    /// Reads the content of the buffer and computes the axis-aligned bounding box of the
+    /// attribute named `position_attribute`.
+    ///
+    /// Returns the minimum and the maximum corners of the box. The attribute must be of type
+    /// `F32F32`, `F32F32F32` or `F32F32F32F32`. The Z coordinate of 2D positions is 0 and the
+    /// W coordinate of 4D positions is ignored.
+    ///
+    /// Returns `None` if there is no attribute with this name or of one of these types, if the
+    /// buffer is empty, or if the backend doesn't support reading from a buffer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let vertex_buffer: glium::VertexBuffer<[f32; 3]> = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// if let Some((min, max)) = vertex_buffer.compute_aabb("position") {
+    ///     println!("the mesh goes from {:?} to {:?}", min, max);
+    /// }
+    /// ```
+    pub fn compute_aabb(&self, position_attribute: &str) -> Option<([f32; 3], [f32; 3])> {
+        let ty = self.bindings.iter().find(|b| b.0 == position_attribute)?.2;
+
+        let positions: Vec<[f32; 3]> = match ty {
+            AttributeType::F32F32 => {
+                self.read_attribute::<[f32; 2]>(position_attribute)?
+                    .into_iter().map(|p| [p[0], p[1], 0.0]).collect()
+            },
+            AttributeType::F32F32F32 => self.read_attribute::<[f32; 3]>(position_attribute)?,
+            AttributeType::F32F32F32F32 => {
+                self.read_attribute::<[f32; 4]>(position_attribute)?
+                    .into_iter().map(|p| [p[0], p[1], p[2]]).collect()
+            },
+            _ => return None,
+        };
+
+        if positions.is_empty() {
+            return None;
+        }
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for position in positions {
+            for i in 0 .. 3 {
+                min[i] = min[i].min(position[i]);
+                max[i] = max[i].max(position[i]);
+            }
+        }
+
+        Some((min, max))
+    }
+
     /// Creates a marker that instructs glium to use multiple instances.
     ///
     /// Instead of calling `surface.draw(&vertex_buffer, ...)` you can call
@@ -323,16 +1094,117 @@ impl<T> VertexBuffer<T> where T: Copy {
     #[inline]
     pub fn per_instance(&self) -> Result<PerInstance, InstancingNotSupported> {
         // TODO: don't check this here
-        if !(self.buffer.get_context().get_version() >= &Version(Api::Gl, 3, 3)) &&
-            !self.buffer.get_context().get_extensions().gl_arb_instanced_arrays
-        {
+        if !is_instancing_supported(self.buffer.get_context()) {
             return Err(InstancingNotSupported);
         }
 
-        Ok(PerInstance(self.buffer.as_slice_any(), &self.bindings))
+        Ok(PerInstance(self.used().as_slice_any(), &self.bindings))
+    }
+
+    /// Builds a buffer texture that reads the content of this buffer as a list of texels of
+    /// type `E`, without copying it.
+    ///
+    /// This makes it possible to fetch the vertices from a `samplerBuffer` in another pass,
+    /// for example with `texelFetch(positions, gl_VertexID)`. For a buffer of `[f32; 4]`
+    /// positions, use `as_uniform_texel_buffer::<(f32, f32, f32, f32)>(BufferTextureType::Float)`.
+    /// The whole capacity of the buffer is used.
+    ///
+    /// Returns `FormatNotSupported` if the size of a vertex is not a multiple of the size of
+    /// `E`, or if the backend doesn't support this combination of `E` and `ty`.
+    #[inline]
+    pub fn as_uniform_texel_buffer<E>(&self, ty: BufferTextureType)
+                                      -> Result<BufferTextureView, BufferTextureCreationError>
+                                      where E: TextureBufferContent
+    {
+        if mem::size_of::<T>() % mem::size_of::<E>() != 0 {
+            return Err(BufferTextureCreationError::FormatNotSupported);
+        }
+
+        BufferTextureView::new::<_, E>(&self.buffer, ty)
+    }
+
+    /// Starts reading the vertices without waiting for the GPU.
+    ///
+    /// This is useful to get back the vertices written by transform feedback or by a compute
+    /// shader. Poll the returned `ReadbackFuture` until it returns the vertices. See
+    /// `BufferSlice::read_async`.
+    #[inline]
+    pub fn read_async(&self) -> Result<ReadbackFuture<[T]>, ReadError> {
+        self.used().read_async()
+    }
+
+    /// Returns the vertices that are in use, without the spare capacity.
+    #[inline]
+    fn used(&self) -> BufferSlice<[T]> {
+        self.buffer.slice(0 .. self.len).unwrap()
+    }
+
+    /// Same as `used`, but returns a mutable slice.
+    #[inline]
+    fn used_mut(&mut self) -> BufferMutSlice<[T]> {
+        let len = self.len;
+        self.buffer.slice_mut(0 .. len).unwrap()
+    }
+}
+
+impl<T> VertexBuffer<T> where T: PixelValue {
+    /// Reads the vertices of the buffer as if they were the pixels of a one-dimensional
+    /// texture.
+    ///
+    /// See `Buffer::read_as_texture_1d`.
+    #[inline]
+    pub fn read_as_texture_1d<S>(&self) -> Result<S, ReadError> where S: Texture1dDataSink<T> {
+        self.used().read_as_texture_1d()
     }
 }
 
+impl VertexBuffer<[f32; 3]> {
+    /// Builds a vertex buffer whose only attribute is a 3D `position`.
+    ///
+    /// This is a shortcut for prototyping that avoids declaring a vertex type. The attribute is
+    /// named `position` and is of type `F32F32F32`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let display: glium::Display = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+    /// let vertex_buffer = glium::VertexBuffer::positions(&display, &[
+    ///     [-0.5, -0.5, 0.0], [0.0, 0.5, 0.0], [0.5, -0.25, 0.0]
+    /// ]).unwrap();
+    /// ```
+    #[inline]
+    pub fn positions<F: ?Sized>(facade: &F, data: &[[f32; 3]])
+                                -> Result<VertexBuffer<[f32; 3]>, CreationError>
+                                where F: Facade
+    {
+        unsafe {
+            VertexBuffer::new_raw(facade, data, position_bindings(AttributeType::F32F32F32),
+                                  mem::size_of::<[f32; 3]>())
+        }
+    }
+}
+
+impl VertexBuffer<[f32; 2]> {
+    /// Builds a vertex buffer whose only attribute is a 2D `position`.
+    ///
+    /// Same as `positions`, except that the attribute is of type `F32F32`.
+    #[inline]
+    pub fn positions_2d<F: ?Sized>(facade: &F, data: &[[f32; 2]])
+                                   -> Result<VertexBuffer<[f32; 2]>, CreationError>
+                                   where F: Facade
+    {
+        unsafe {
+            VertexBuffer::new_raw(facade, data, position_bindings(AttributeType::F32F32),
+                                  mem::size_of::<[f32; 2]>())
+        }
+    }
+}
+
+/// Returns the format of a vertex whose only attribute is a `position` of type `ty`.
+fn position_bindings(ty: AttributeType) -> VertexFormat {
+    Cow::Owned(vec![(Cow::Borrowed("position"), 0, ty, AttributeBinding::Auto)])
+}
+
 impl<T> VertexBuffer<T> where T: Copy + Send + 'static {
     /// Discard the type information and turn the vertex buffer into a `VertexBufferAny`.
     #[inline]
@@ -342,8 +1214,13 @@ impl<T> VertexBuffer<T> where T: Copy + Send + 'static {
     }
     #[inline]
     fn into_vertex_buffer_any_inner(self) -> VertexBufferAny {
+        // the spare capacity is left out, but is still there if the buffer is turned back into
+        // a `VertexBuffer`
+        let mut buffer: BufferAny = self.buffer.into();
+        buffer.truncate(self.len * mem::size_of::<T>());
+
         VertexBufferAny {
-            buffer: self.buffer.into(),
+            buffer: buffer,
             bindings: self.bindings,
         }
     }
@@ -357,12 +1234,24 @@ impl<T> From<Buffer<[T]>> for VertexBuffer<T> where T: Vertex + Copy {
         let bindings = <T as Vertex>::build_bindings();
 
         VertexBuffer {
+            len: buffer.len(),
             buffer: buffer,
             bindings: bindings,
+            shadow: None,
         }
     }
 }
 
+/// Builds a vertex buffer with `VertexBuffer::new`.
+impl<'a, F: ?Sized, T> TryFrom<(&'a F, Vec<T>)> for VertexBuffer<T> where F: Facade, T: Vertex {
+    type Error = CreationError;
+
+    #[inline]
+    fn try_from((facade, data): (&'a F, Vec<T>)) -> Result<VertexBuffer<T>, CreationError> {
+        VertexBuffer::new(facade, &data)
+    }
+}
+
 impl<T> Deref for VertexBuffer<T> where T: Copy {
     type Target = Buffer<[T]>;
 
@@ -382,7 +1271,6 @@ impl<T> DerefMut for VertexBuffer<T> where T: Copy {
 impl<'a, T> From<&'a VertexBuffer<T>> for BufferSlice<'a, [T]> where T: Copy {
     #[inline]
     fn from(b: &'a VertexBuffer<T>) -> BufferSlice<'a, [T]> {
-        let b: &Buffer<[T]> = &*b;
         b.as_slice()
     }
 }
@@ -390,7 +1278,6 @@ impl<'a, T> From<&'a VertexBuffer<T>> for BufferSlice<'a, [T]> where T: Copy {
 impl<'a, T> From<&'a mut VertexBuffer<T>> for BufferMutSlice<'a, [T]> where T: Copy {
     #[inline]
     fn from(b: &'a mut VertexBuffer<T>) -> BufferMutSlice<'a, [T]> {
-        let b: &mut Buffer<[T]> = &mut *b;
         b.as_mut_slice()
     }
 }
@@ -398,7 +1285,7 @@ impl<'a, T> From<&'a mut VertexBuffer<T>> for BufferMutSlice<'a, [T]> where T: C
 impl<'a, T> Into<VerticesSource<'a>> for &'a VertexBuffer<T> where T: Copy {
     #[inline]
     fn into(self) -> VerticesSource<'a> {
-        VerticesSource::VertexBuffer(self.buffer.as_slice_any(), &self.bindings, false)
+        VerticesSource::VertexBuffer(self.used().as_slice_any(), &self.bindings, false)
     }
 }
 
@@ -458,16 +1345,188 @@ impl VertexBufferAny {
         self.buffer.get_elements_count()
     }
 
+    /// Returns true if the buffer contains no element.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the mode that the buffer was created with, for example `BufferMode::Dynamic` for
+    /// a buffer created with `dynamic` or `empty_dynamic`.
+    ///
+    /// This is the usage hint that glium passed to OpenGL. It is recorded at creation and isn't
+    /// queried from OpenGL, so this is cheap enough to be displayed every frame.
+    #[inline]
+    pub fn get_usage(&self) -> BufferMode {
+        self.buffer.get_mode()
+    }
+
     /// Returns the associated `VertexFormat`.
     #[inline]
     pub fn get_bindings(&self) -> &VertexFormat {
         &self.bindings
     }
 
+    /// Reads the whole content of the buffer as raw bytes, regardless of the type of the
+    /// vertices.
+    ///
+    /// Together with `get_bindings` and `get_elements_size`, this can be used to save the buffer
+    /// and rebuild it later with `VertexBuffer::new_raw`.
+    ///
+    /// Returns `None` if the backend doesn't support reading from a buffer.
+    #[inline]
+    pub fn read_raw_bytes(&self) -> Option<Vec<u8>> {
+        unsafe { self.buffer.read::<[u8]>() }.ok()
+    }
+
+    /// Reads the content of the buffer as vertices of type `T`.
+    ///
+    /// Contrary to `into_vertex_buffer`, this checks that the bindings of the buffer are the
+    /// ones of `T` and that its elements are large enough to contain a `T`. If the elements are
+    /// larger than `T`, for example for a buffer created with `new_with_stride`, the padding
+    /// after each vertex is skipped.
+    ///
+    /// Returns `None` if they don't match or if the backend doesn't support reading from a
+    /// buffer.
+    pub fn read_as_if_supported<T>(&self) -> Option<Vec<T>> where T: Vertex {
+        let stride = self.get_elements_size();
+
+        if stride < mem::size_of::<T>() || *self.bindings != *<T as Vertex>::build_bindings() {
+            return None;
+        }
+
+        if stride == mem::size_of::<T>() {
+            return unsafe { self.buffer.read::<[T]>() }.ok();
+        }
+
+        let bytes = self.read_raw_bytes()?;
+
+        Some(bytes.chunks(stride).map(|vertex| unsafe {
+            ptr::read_unaligned(vertex.as_ptr() as *const T)
+        }).collect())
+    }
+
+    /// Builds a new vertex buffer whose vertices are `stride` bytes apart, with padding after
+    /// each vertex.
+    ///
+    /// This is useful when the buffer is also accessed by something that has alignment
+    /// requirements, for example a compute shader that reads each vertex as `vec4`s. The
+    /// stride is passed to `glVertexAttribPointer`, and the padding is filled with zeroes.
+    ///
+    /// Use `read_as_if_supported` and `write_with_stride` to access the content of the buffer.
+    ///
+    /// Returns `BufferCreationError::FormatTooLarge` if the size of the buffer overflows.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `stride` is smaller than the size of `T`.
+    pub fn new_with_stride<F: ?Sized, T>(facade: &F, data: &[T], stride: usize)
+                                         -> Result<VertexBufferAny, CreationError>
+                                         where F: Facade, T: Vertex
+    {
+        if !T::is_supported(facade) {
+            return Err(CreationError::FormatNotSupported);
+        }
+
+        let bytes = match pad_vertices(data, stride) {
+            Some(bytes) => bytes,
+            None => return Err(BufferCreationError::FormatTooLarge.into()),
+        };
+
+        unsafe {
+            VertexBufferAny::new_raw_bytes(facade, &bytes, <T as Vertex>::build_bindings(), stride)
+        }
+    }
+
+    /// Replaces the content of the buffer with `data`, taking the padding between the vertices
+    /// into account. This is the counterpart of `new_with_stride`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the bindings of the buffer are not the ones of `T`, if the elements of the
+    /// buffer are smaller than `T` or if the length of `data` is not the length of the buffer.
+    pub fn write_with_stride<T>(&self, data: &[T]) where T: Vertex {
+        assert!(*self.bindings == *<T as Vertex>::build_bindings(),
+                "The bindings of the buffer don't match the vertex type");
+        assert_eq!(data.len(), self.len());
+
+        let bytes = pad_vertices(data, self.get_elements_size()).unwrap();
+        unsafe { self.buffer.as_typed_slice::<[u8]>().write(&bytes) };
+    }
+
+    /// Builds a new vertex buffer from raw bytes, for example a slice of a memory-mapped file.
+    ///
+    /// Each vertex is `elements_size` bytes long and is described by `bindings`. The bytes are
+    /// copied to the video memory before this function returns, so the slice doesn't need to
+    /// outlive the buffer.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the length of `data` is not a multiple of `elements_size`.
+    #[inline]
+    pub unsafe fn new_raw_bytes<F: ?Sized>(facade: &F, data: &[u8], bindings: VertexFormat,
+                                           elements_size: usize)
+                                           -> Result<VertexBufferAny, CreationError>
+                                           where F: Facade
+    {
+        // FIXME: check that the format is supported
+        debug_check_alignment(&bindings);
+
+        Ok(VertexBufferAny {
+            buffer: BufferAny::new_raw_bytes(facade, data, elements_size,
+                                             BufferType::ArrayBuffer, BufferMode::Default)?,
+            bindings: bindings,
+        })
+    }
+
+    /// Wraps a buffer that was created outside of glium, for example by a video decoder,
+    /// without reallocating it or copying its content.
+    ///
+    /// The buffer contains `len` vertices of `elements_size` bytes each, described by
+    /// `bindings`. If `owned` is true, glium destroys the buffer with `glDeleteBuffers` when the
+    /// `VertexBufferAny` is dropped. Otherwise its creator remains responsible for destroying
+    /// it, after the `VertexBufferAny` has been dropped.
+    ///
+    /// # Safety
+    ///
+    /// - `id` must be the name of a buffer created with `glBufferData` that contains at least
+    ///   `len * elements_size` bytes.
+    /// - The buffer must belong to the OpenGL context of `display`, or to a context that shares
+    ///   its objects with it.
+    /// - The buffer must not be written, resized or destroyed outside of glium while glium may
+    ///   be using it, as glium can't synchronize with these operations.
+    pub unsafe fn from_raw_id<F: ?Sized>(display: &F, id: gl::types::GLuint,
+                                         bindings: VertexFormat, elements_size: usize,
+                                         len: usize, owned: bool)
+                                         -> VertexBufferAny where F: Facade
+    {
+        debug_check_alignment(&bindings);
+
+        VertexBufferAny {
+            buffer: BufferAny::from_raw_id(display, id, BufferType::ArrayBuffer, elements_size,
+                                           len, owned),
+            bindings: bindings,
+        }
+    }
+
+    /// Destroys the `VertexBufferAny` without destroying the underlying OpenGL buffer, and
+    /// returns its name. This is the opposite of `from_raw_id`.
+    ///
+    /// The buffer is leaked unless it is destroyed by someone else.
+    #[inline]
+    pub fn into_raw_id(self) -> gl::types::GLuint {
+        self.buffer.into_raw_id()
+    }
+
     /// Turns the vertex buffer into a `VertexBuffer` without checking the type.
     #[inline]
     pub unsafe fn into_vertex_buffer<T: Copy>(self) -> VertexBuffer<T> {
-        unimplemented!();
+        VertexBuffer {
+            len: self.len(),
+            buffer: self.buffer.into_buffer(),
+            bindings: self.bindings,
+            shadow: None,
+        }
     }
 
     /// Creates a marker that instructs glium to use multiple instances.
@@ -479,9 +1538,7 @@ impl VertexBufferAny {
     #[inline]
     pub fn per_instance(&self) -> Result<PerInstance, InstancingNotSupported> {
         // TODO: don't check this here
-        if !(self.buffer.get_context().get_version() >= &Version(Api::Gl, 3, 3)) &&
-            !self.buffer.get_context().get_extensions().gl_arb_instanced_arrays
-        {
+        if !is_instancing_supported(self.buffer.get_context()) {
             return Err(InstancingNotSupported);
         }
 
@@ -489,6 +1546,35 @@ impl VertexBufferAny {
     }
 }
 
+/// Copies each vertex into a list of bytes, `stride` bytes apart. The padding is filled with
+/// zeroes.
+///
+/// Returns `None` if the size of the list is too large to be passed to OpenGL.
+///
+/// # Panic
+///
+/// Panics if `stride` is smaller than the size of `T`.
+fn pad_vertices<T>(data: &[T], stride: usize) -> Option<Vec<u8>> where T: Copy {
+    let size = mem::size_of::<T>();
+    assert!(stride >= size, "The stride ({}) is smaller than the size of a vertex ({})",
+            stride, size);
+
+    let len = data.len().checked_mul(stride).filter(|&len| len <= isize::MAX as usize)?;
+
+    let mut bytes = vec![0u8; len];
+    for (vertex, dest) in data.iter().zip(bytes.chunks_mut(stride)) {
+        unsafe {
+            ptr::copy_nonoverlapping(vertex as *const T as *const u8, dest.as_mut_ptr(), size);
+        }
+    }
+    Some(bytes)
+}
+
+/// Returns the bytes that make up a list of vertices.
+fn as_bytes<T>(data: &[T]) -> &[u8] where T: Copy {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
+
 impl<T> From<VertexBuffer<T>> for VertexBufferAny where T: Copy + Send + 'static {
     #[inline]
     fn from(buf: VertexBuffer<T>) -> VertexBufferAny {
@@ -527,6 +1613,27 @@ impl<'a> Into<VerticesSource<'a>> for &'a VertexBufferAny {
     }
 }
 
+/// Checks, in debug mode only, that the offset of each attribute is a multiple of the size of
+/// its components. Misaligned attributes are slow on some hardware and rejected by strict
+/// drivers.
+fn debug_check_alignment(bindings: &VertexFormat) {
+    for &(ref name, offset, ty, _) in bindings.iter() {
+        debug_assert!(offset % ty.get_alignment() == 0,
+                      "The offset of the attribute `{}` ({} bytes) is not a multiple of {}, the \
+                       size of its components", name, offset, ty.get_alignment());
+    }
+}
+
+/// Returns true if the backend supports per-instance attributes with `glVertexAttribDivisor`.
+///
+/// This is the case with OpenGL 3.3, OpenGL ES 3.0 or `GL_ARB_instanced_arrays`.
+#[inline]
+pub fn is_instancing_supported<C: ?Sized>(ctxt: &C) -> bool where C: CapabilitiesSource {
+    ctxt.get_version() >= &Version(Api::Gl, 3, 3) ||
+    ctxt.get_version() >= &Version(Api::GlEs, 3, 0) ||
+    ctxt.get_extensions().gl_arb_instanced_arrays
+}
+
 /// Instancing is not supported by the backend.
 #[derive(Debug, Copy, Clone)]
 pub struct InstancingNotSupported;
@@ -0,0 +1,205 @@
+use std::borrow::Cow;
+
+use backend::Facade;
+use context::CommandContext;
+use version::{Api, Version};
+use ContextExt;
+use Surface;
+
+use index::{NoIndices, PrimitiveType};
+use texture::{MipmapsOption, Texture2d, UncompressedFloatFormat};
+use uniforms::EmptyUniforms;
+use vertex::{is_instancing_supported, AttributeBinding, AttributeType, VertexBuffer};
+
+use gl;
+
+/// Returns true if the backend honors the divisor of per-instance attributes.
+///
+/// Some old drivers silently ignore `glVertexAttribDivisor`, which results in wrong instanced
+/// rendering without any error. This function draws a tiny instanced scene to a 1x1 texture and
+/// reads it back in order to check that per-instance attributes really are per-instance.
+///
+/// The probe is never run by glium itself. It only runs the first time you call this function,
+/// and its result is then cached in the context. If the divisor doesn't work, a message of type
+/// `Portability` is also sent to the debug output, if the backend supports it.
+///
+/// Returns false if instancing isn't supported at all, in which case the probe isn't run and
+/// the message says that instancing is not supported. Returns true if the probe can't be run,
+/// for example because the backend doesn't support rendering to a texture.
+pub fn is_instancing_divisor_working<F: ?Sized>(facade: &F) -> bool where F: Facade {
+    let context = facade.get_context();
+
+    if let Some(works) = context.instancing_divisor_works().get() {
+        return works;
+    }
+
+    if !is_instancing_supported(&**context) {
+        context.instancing_divisor_works().set(Some(false));
+        let mut ctxt = context.make_current();
+        unsafe {
+            send_portability_message(&mut ctxt, "glium: the OpenGL implementation doesn't \
+                                                 support instanced rendering");
+        }
+        return false;
+    }
+
+    let works = probe(facade);
+    context.instancing_divisor_works().set(Some(works));
+
+    if !works {
+        let mut ctxt = context.make_current();
+        unsafe {
+            send_portability_message(&mut ctxt, "glium: the OpenGL implementation ignores the \
+                                                 divisor of per-instance attributes; instanced \
+                                                 rendering will be wrong");
+        }
+    }
+
+    works
+}
+
+/// Draws two points at the same location in one instance. The second vertex only reads the
+/// first element of the per-instance buffer if the divisor is honored.
+fn probe<F: ?Sized>(facade: &F) -> bool where F: Facade {
+    let program = program!(facade,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in float value;
+                out float v_value;
+
+                void main() {
+                    v_value = value;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                in float v_value;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(v_value, 0.0, 0.0, 1.0);
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute float value;
+                varying float v_value;
+
+                void main() {
+                    v_value = value;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying float v_value;
+
+                void main() {
+                    gl_FragColor = vec4(v_value, 0.0, 0.0, 1.0);
+                }
+            "
+        },
+        100 es => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp float value;
+                varying lowp float v_value;
+
+                void main() {
+                    v_value = value;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    gl_PointSize = 1.0;
+                }
+            ",
+            fragment: "
+                #version 100
+
+                varying lowp float v_value;
+
+                void main() {
+                    gl_FragColor = vec4(v_value, 0.0, 0.0, 1.0);
+                }
+            "
+        },
+    );
+
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return true,
+    };
+
+    let vertices = unsafe {
+        VertexBuffer::new_raw(facade, &[[0.0f32, 0.0], [0.0, 0.0]],
+                              Cow::Owned(vec![(Cow::Borrowed("position"), 0,
+                                               AttributeType::F32F32, AttributeBinding::Auto)]),
+                              8)
+    };
+
+    let instances = unsafe {
+        VertexBuffer::new_raw(facade, &[1.0f32, 0.0],
+                              Cow::Owned(vec![(Cow::Borrowed("value"), 0,
+                                               AttributeType::F32, AttributeBinding::Auto)]),
+                              4)
+    };
+
+    let (vertices, instances) = match (vertices, instances) {
+        (Ok(v), Ok(i)) => (v, i),
+        _ => return true,
+    };
+
+    // only the first instance is drawn, but the buffer contains a second element that is read
+    // by the second vertex if the divisor is ignored
+    let instances = instances.slice(0 .. 1).unwrap();
+    let instances = match instances.per_instance() {
+        Ok(i) => i,
+        Err(_) => return false,
+    };
+
+    let texture = match Texture2d::empty_with_format(facade, UncompressedFloatFormat::U8U8U8U8,
+                                                     MipmapsOption::NoMipmap, 1, 1)
+    {
+        Ok(t) => t,
+        Err(_) => return true,
+    };
+
+    let mut surface = texture.as_surface();
+    surface.clear_color(0.0, 0.0, 0.0, 0.0);
+    if surface.draw((&vertices, instances), NoIndices(PrimitiveType::Points), &program,
+                    &EmptyUniforms, &Default::default()).is_err()
+    {
+        return true;
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    data[0][0].0 != 0
+}
+
+/// Sends a message of type `Portability` to the debug output, if the backend supports it.
+unsafe fn send_portability_message(ctxt: &mut CommandContext, message: &str) {
+    if ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 2) ||
+       (ctxt.version >= &Version(Api::Gl, 1, 0) && ctxt.extensions.gl_khr_debug)
+    {
+        ctxt.gl.DebugMessageInsert(gl::DEBUG_SOURCE_APPLICATION, gl::DEBUG_TYPE_PORTABILITY, 0,
+                                   gl::DEBUG_SEVERITY_MEDIUM,
+                                   message.len() as gl::types::GLsizei,
+                                   message.as_ptr() as *const _);
+
+    } else if ctxt.extensions.gl_khr_debug {
+        ctxt.gl.DebugMessageInsertKHR(gl::DEBUG_SOURCE_APPLICATION, gl::DEBUG_TYPE_PORTABILITY, 0,
+                                      gl::DEBUG_SEVERITY_MEDIUM,
+                                      message.len() as gl::types::GLsizei,
+                                      message.as_ptr() as *const _);
+    }
+}
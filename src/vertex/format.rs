@@ -11,8 +11,13 @@ use cgmath;
 #[cfg(feature = "nalgebra")]
 use nalgebra;
 
+/// The type of an attribute in a vertex buffer.
+///
+/// The type only describes the layout of the data. Whether integral values such as `U8U8U8U8`
+/// are normalized, converted to floats or passed as integers is chosen with the
+/// `AttributeBinding` of the attribute.
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AttributeType {
     I8,
     I8I8,
@@ -147,6 +152,13 @@ pub enum AttributeType {
     ///
     /// Corresponds to `GL_FIXED`.
     FixedFloatI16U16,
+    /// Four `u8`s stored in the blue, green, red, alpha order, as exported by some DirectX
+    /// tools. The shader receives them as a normalized `vec4` in the red, green, blue, alpha
+    /// order.
+    ///
+    /// Corresponds to a size of `GL_BGRA` with `GL_UNSIGNED_BYTE`. The binding of the attribute
+    /// must be `Auto` or `FloatNormalized`.
+    U8U8U8U8Bgra,
 }
 
 impl AttributeType {
@@ -233,6 +245,12 @@ impl AttributeType {
                 caps.get_version() >= &Version(Api::Gl, 4, 0) ||
                 caps.get_extensions().gl_arb_vertex_type_10f_11f_11f_rev
             },
+
+            &AttributeType::U8U8U8U8Bgra => {
+                caps.get_version() >= &Version(Api::Gl, 3, 2) ||
+                caps.get_extensions().gl_arb_vertex_array_bgra ||
+                caps.get_extensions().gl_ext_vertex_array_bgra
+            },
         }
     }
 
@@ -247,6 +265,7 @@ impl AttributeType {
             AttributeType::U8U8 => 2 * mem::size_of::<u8>(),
             AttributeType::U8U8U8 => 3 * mem::size_of::<u8>(),
             AttributeType::U8U8U8U8 => 4 * mem::size_of::<u8>(),
+            AttributeType::U8U8U8U8Bgra => 4 * mem::size_of::<u8>(),
             AttributeType::I16 => 1 * mem::size_of::<i16>(),
             AttributeType::I16I16 => 2 * mem::size_of::<i16>(),
             AttributeType::I16I16I16 => 3 * mem::size_of::<i16>(),
@@ -319,6 +338,19 @@ impl AttributeType {
         }
     }
 
+    /// Returns the alignment in bytes that the offset of an attribute of this type should have.
+    ///
+    /// This is the size of a single component, except for packed types which must be aligned
+    /// to the size of the integer they are packed into.
+    pub fn get_alignment(&self) -> usize {
+        match *self {
+            AttributeType::I2I10I10I10Reversed | AttributeType::U2U10U10U10Reversed |
+            AttributeType::I10I10I10I2 | AttributeType::U10U10U10U2 |
+            AttributeType::F10F11F11UnsignedIntReversed | AttributeType::FixedFloatI16U16 => 4,
+            _ => self.get_size_bytes() / self.get_num_components(),
+        }
+    }
+
     /// Returns the number of values for this type.
     pub fn get_num_components(&self) -> usize {
         match *self {
@@ -330,6 +362,7 @@ impl AttributeType {
             AttributeType::U8U8 => 2,
             AttributeType::U8U8U8 => 3,
             AttributeType::U8U8U8U8 => 4,
+            AttributeType::U8U8U8U8Bgra => 4,
             AttributeType::I16 => 1,
             AttributeType::I16I16 => 2,
             AttributeType::I16I16I16 => 3,
@@ -401,16 +434,250 @@ impl AttributeType {
             AttributeType::FixedFloatI16U16 => 1,
         }
     }
+
+    /// Returns the name of the GLSL type that corresponds to this type, for example `vec3`,
+    /// `ivec4` or `mat4`.
+    ///
+    /// Integers are mapped to the integer types of GLSL, and 64-bit integers to the types of
+    /// `GL_ARB_gpu_shader_int64`. Half floats, packed types and fixed floating points are
+    /// converted to single-precision floats when they are read by the shader, and are therefore
+    /// mapped to `float` or `vecN`. Matrices are named after their number of columns then rows,
+    /// like in `matCxR`.
+    pub fn glsl_type_name(&self) -> &'static str {
+        match *self {
+            AttributeType::I8 => "int",
+            AttributeType::I8I8 => "ivec2",
+            AttributeType::I8I8I8 => "ivec3",
+            AttributeType::I8I8I8I8 => "ivec4",
+            AttributeType::U8 => "uint",
+            AttributeType::U8U8 => "uvec2",
+            AttributeType::U8U8U8 => "uvec3",
+            AttributeType::U8U8U8U8 => "uvec4",
+            AttributeType::U8U8U8U8Bgra => "vec4",
+            AttributeType::I16 => "int",
+            AttributeType::I16I16 => "ivec2",
+            AttributeType::I16I16I16 => "ivec3",
+            AttributeType::I16I16I16I16 => "ivec4",
+            AttributeType::U16 => "uint",
+            AttributeType::U16U16 => "uvec2",
+            AttributeType::U16U16U16 => "uvec3",
+            AttributeType::U16U16U16U16 => "uvec4",
+            AttributeType::I32 => "int",
+            AttributeType::I32I32 => "ivec2",
+            AttributeType::I32I32I32 => "ivec3",
+            AttributeType::I32I32I32I32 => "ivec4",
+            AttributeType::U32 => "uint",
+            AttributeType::U32U32 => "uvec2",
+            AttributeType::U32U32U32 => "uvec3",
+            AttributeType::U32U32U32U32 => "uvec4",
+            AttributeType::I64 => "int64_t",
+            AttributeType::I64I64 => "i64vec2",
+            AttributeType::I64I64I64 => "i64vec3",
+            AttributeType::I64I64I64I64 => "i64vec4",
+            AttributeType::U64 => "uint64_t",
+            AttributeType::U64U64 => "u64vec2",
+            AttributeType::U64U64U64 => "u64vec3",
+            AttributeType::U64U64U64U64 => "u64vec4",
+            AttributeType::F16 => "float",
+            AttributeType::F16F16 => "vec2",
+            AttributeType::F16F16F16 => "vec3",
+            AttributeType::F16F16F16F16 => "vec4",
+            AttributeType::F16x2x2 => "mat2",
+            AttributeType::F16x2x3 => "mat2x3",
+            AttributeType::F16x2x4 => "mat2x4",
+            AttributeType::F16x3x2 => "mat3x2",
+            AttributeType::F16x3x3 => "mat3",
+            AttributeType::F16x3x4 => "mat3x4",
+            AttributeType::F16x4x2 => "mat4x2",
+            AttributeType::F16x4x3 => "mat4x3",
+            AttributeType::F16x4x4 => "mat4",
+            AttributeType::F32 => "float",
+            AttributeType::F32F32 => "vec2",
+            AttributeType::F32F32F32 => "vec3",
+            AttributeType::F32F32F32F32 => "vec4",
+            AttributeType::F32x2x2 => "mat2",
+            AttributeType::F32x2x3 => "mat2x3",
+            AttributeType::F32x2x4 => "mat2x4",
+            AttributeType::F32x3x2 => "mat3x2",
+            AttributeType::F32x3x3 => "mat3",
+            AttributeType::F32x3x4 => "mat3x4",
+            AttributeType::F32x4x2 => "mat4x2",
+            AttributeType::F32x4x3 => "mat4x3",
+            AttributeType::F32x4x4 => "mat4",
+            AttributeType::F64 => "double",
+            AttributeType::F64F64 => "dvec2",
+            AttributeType::F64F64F64 => "dvec3",
+            AttributeType::F64F64F64F64 => "dvec4",
+            AttributeType::F64x2x2 => "dmat2",
+            AttributeType::F64x2x3 => "dmat2x3",
+            AttributeType::F64x2x4 => "dmat2x4",
+            AttributeType::F64x3x2 => "dmat3x2",
+            AttributeType::F64x3x3 => "dmat3",
+            AttributeType::F64x3x4 => "dmat3x4",
+            AttributeType::F64x4x2 => "dmat4x2",
+            AttributeType::F64x4x3 => "dmat4x3",
+            AttributeType::F64x4x4 => "dmat4",
+            AttributeType::I2I10I10I10Reversed => "vec4",
+            AttributeType::U2U10U10U10Reversed => "vec4",
+            AttributeType::I10I10I10I2 => "vec4",
+            AttributeType::U10U10U10U2 => "vec4",
+            AttributeType::F10F11F11UnsignedIntReversed => "vec3",
+            AttributeType::FixedFloatI16U16 => "float",
+        }
+    }
+}
+
+/// How the data of an attribute is delivered to the vertex shader.
+///
+/// This chooses between `glVertexAttribPointer`, `glVertexAttribIPointer` and
+/// `glVertexAttribLPointer` when binding the attribute. An explicit binding that doesn't match
+/// the type of the data or of the attribute in the shader makes `draw_checked` return
+/// `DrawError::AttributeTypeMismatch`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeBinding {
+    /// Picks the function depending on the type of the attribute in the shader. Integral values
+    /// are passed as integers to `int`/`ivec`/`uint`/`uvec` attributes and converted to floats
+    /// without normalization for `float`/`vec` attributes.
+    Auto,
+
+    /// The data is converted to floating-point values without normalization.
+    Float,
+
+    /// The data is converted to floating-point values with fixed-point normalization. For
+    /// example a `u8` of `255` becomes `1.0`.
+    FloatNormalized,
+
+    /// The data is passed as integers. The attribute must be declared as an integral type in
+    /// the shader.
+    Integer,
+
+    /// The data is passed as 64bits values. The attribute must be declared as a `double` or
+    /// `dvec` in the shader.
+    Double,
+}
+
+impl Default for AttributeBinding {
+    #[inline]
+    fn default() -> AttributeBinding {
+        AttributeBinding::Auto
+    }
+}
+
+impl From<bool> for AttributeBinding {
+    /// Converts the old `normalize` flag. `true` is `FloatNormalized` and `false` is `Auto`.
+    #[inline]
+    fn from(normalize: bool) -> AttributeBinding {
+        if normalize {
+            AttributeBinding::FloatNormalized
+        } else {
+            AttributeBinding::Auto
+        }
+    }
 }
 
 /// Describes the layout of each vertex in a vertex buffer.
 ///
 /// The first element is the name of the binding, the second element
 /// is the offset from the start of each vertex to this element, the
-/// third element is the type and the fourth element indicates whether
-/// or not the element should use fixed-point normalization when
-/// binding in a VAO.
-pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType, bool)]>;
+/// third element is the type and the fourth element indicates how the
+/// element is delivered to the shader when binding in a VAO.
+pub type VertexFormat = Cow<'static, [(Cow<'static, str>, usize, AttributeType, AttributeBinding)]>;
+
+/// Three positive floating-point values packed in 32 bits, for example an HDR color.
+///
+/// From LSB to MSB: an 11-bit float for the first component, an 11-bit float for the second
+/// and a 10-bit float for the third. These floats have no sign bit, a 5-bit exponent, and a
+/// 6-bit or 5-bit mantissa. This is the attribute type `F10F11F11UnsignedIntReversed`, which
+/// is a `vec3` in the shader and requires OpenGL 4.0 or
+/// `GL_ARB_vertex_type_10f_11f_11f_rev`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[repr(C)]
+pub struct PackedF10F11F11(pub u32);
+
+impl PackedF10F11F11 {
+    /// Packs three values. Negative values become `0.0` and values that are too large become
+    /// the largest representable value.
+    #[inline]
+    pub fn new(value: [f32; 3]) -> PackedF10F11F11 {
+        PackedF10F11F11(pack_unsigned_float(value[0], 6) |
+                        (pack_unsigned_float(value[1], 6) << 11) |
+                        (pack_unsigned_float(value[2], 5) << 22))
+    }
+
+    /// Unpacks the three values.
+    #[inline]
+    pub fn get(&self) -> [f32; 3] {
+        [
+            unpack_unsigned_float(self.0 & 0x7ff, 6),
+            unpack_unsigned_float((self.0 >> 11) & 0x7ff, 6),
+            unpack_unsigned_float(self.0 >> 22, 5),
+        ]
+    }
+}
+
+impl From<[f32; 3]> for PackedF10F11F11 {
+    #[inline]
+    fn from(value: [f32; 3]) -> PackedF10F11F11 {
+        PackedF10F11F11::new(value)
+    }
+}
+
+unsafe impl Attribute for PackedF10F11F11 {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F10F11F11UnsignedIntReversed
+    }
+}
+
+/// Converts a `f32` to an unsigned float with a 5-bit exponent and `mantissa` bits of mantissa,
+/// rounding to the nearest value.
+fn pack_unsigned_float(value: f32, mantissa: u32) -> u32 {
+    let max = (30 << mantissa) | ((1 << mantissa) - 1);
+
+    if value.is_nan() {
+        return (31 << mantissa) | 1;
+    }
+    if value <= 0.0 {
+        return 0;
+    }
+    if value.is_infinite() {
+        return 31 << mantissa;
+    }
+
+    let bits = value.to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127 + 15;
+    let significand = bits & 0x7f_ffff;
+    if exponent >= 31 {
+        return max;
+    }
+
+    // exponent and significand of the result, with 23 bits of significand
+    let combined = if exponent > 0 {
+        ((exponent as u32) << 23) | significand
+    } else if exponent > -24 {
+        // denormalized value
+        (significand | 0x80_0000) >> (1 - exponent)
+    } else {
+        return 0;
+    };
+
+    let shift = 23 - mantissa;
+    let rounded = (combined + (1 << (shift - 1))) >> shift;
+    if rounded > max { max } else { rounded }
+}
+
+/// Converts an unsigned float with a 5-bit exponent and `mantissa` bits of mantissa to a `f32`.
+fn unpack_unsigned_float(value: u32, mantissa: u32) -> f32 {
+    let exponent = value >> mantissa;
+    let significand = (value & ((1 << mantissa) - 1)) as f32 / (1 << mantissa) as f32;
+
+    match exponent {
+        0 => significand * 2.0f32.powi(-14),
+        31 if significand == 0.0 => f32::INFINITY,
+        31 => f32::NAN,
+        e => (1.0 + significand) * 2.0f32.powi(e as i32 - 15),
+    }
+}
 
 unsafe impl Attribute for i8 {
     #[inline]
@@ -874,6 +1141,23 @@ unsafe impl Attribute for [[f32; 4]; 4] {
     }
 }
 
+/// A 3x3 matrix stored as a flat array. Uses three `vec3` locations.
+unsafe impl Attribute for [f32; 9] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F32x3x3
+    }
+}
+
+/// A 3x4 matrix, for example an affine transform, stored as a flat array. Bound like a
+/// `F32x3x4` attribute.
+unsafe impl Attribute for [f32; 12] {
+    #[inline]
+    fn get_type() -> AttributeType {
+        AttributeType::F32x3x4
+    }
+}
+
 unsafe impl Attribute for f64 {
     #[inline]
     fn get_type() -> AttributeType {
@@ -2103,6 +2387,20 @@ mod tests {
     #[allow(unused_imports)]
     use std::mem;
 
+    use super::AttributeType;
+
+    #[test]
+    fn attribute_alignment() {
+        assert_eq!(AttributeType::U8U8U8.get_alignment(), 1);
+        assert_eq!(AttributeType::I16I16.get_alignment(), 2);
+        assert_eq!(AttributeType::F16x3x3.get_alignment(), 2);
+        assert_eq!(AttributeType::F32F32F32.get_alignment(), 4);
+        assert_eq!(AttributeType::F32x4x4.get_alignment(), 4);
+        assert_eq!(AttributeType::F64F64.get_alignment(), 8);
+        assert_eq!(AttributeType::U2U10U10U10Reversed.get_alignment(), 4);
+        assert_eq!(AttributeType::F10F11F11UnsignedIntReversed.get_alignment(), 4);
+    }
+
     #[cfg(feature="cgmath")]
     macro_rules! test_layout_val {
         ($from_val:path, $ety:ty, $ncomps:expr, $literal:expr) => {{
@@ -0,0 +1,163 @@
+use std::ops::Deref;
+
+use backend::Facade;
+
+use vertex::{Vertex, VertexBuffer, PerInstance};
+use buffer::BufferCreationError;
+use vertex::buffer::{CreationError, InstancingNotSupported, is_instancing_supported};
+
+/// A buffer of per-instance attributes that is meant to be rewritten every frame.
+///
+/// This is a wrapper around a dynamic `VertexBuffer` that keeps track of the number of
+/// instances that were uploaded with the latest call to `update`. The underlying buffer is only
+/// reallocated if the data doesn't fit in it, otherwise its storage is orphaned and the new
+/// data is uploaded with a single write.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate glium;
+/// # extern crate glutin;
+/// # use glium::Surface;
+/// # fn main() {
+/// #[derive(Copy, Clone)]
+/// struct Instance {
+///     transform: [[f32; 4]; 4],
+/// }
+///
+/// implement_vertex!(Instance, transform);
+///
+/// # let display: glium::Display = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+/// # let program: glium::Program = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+/// # let vertex_buffer: glium::VertexBuffer<Instance> = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+/// # let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+/// # let transforms: Vec<Instance> = Vec::new();
+/// # let mut frame = display.draw();
+/// let mut instances = glium::vertex::InstanceBuffer::empty(&display, 128).unwrap();
+///
+/// // every frame
+/// instances.update(&transforms).unwrap();
+/// frame.draw((&vertex_buffer, instances.per_instance().unwrap()), &indices, &program,
+///            &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InstanceBuffer<T> where T: Copy {
+    buffer: VertexBuffer<T>,
+    len: usize,
+}
+
+impl<T> InstanceBuffer<T> where T: Vertex {
+    /// Builds a new instance buffer containing the given instances.
+    #[inline]
+    pub fn new<F: ?Sized>(facade: &F, data: &[T]) -> Result<InstanceBuffer<T>, CreationError>
+                          where F: Facade
+    {
+        Ok(InstanceBuffer {
+            buffer: VertexBuffer::dynamic(facade, data)?,
+            len: data.len(),
+        })
+    }
+
+    /// Builds a new instance buffer with room for `capacity` instances and no instance in it.
+    #[inline]
+    pub fn empty<F: ?Sized>(facade: &F, capacity: usize)
+                            -> Result<InstanceBuffer<T>, CreationError>
+                            where F: Facade
+    {
+        Ok(InstanceBuffer {
+            buffer: VertexBuffer::empty_dynamic(facade, capacity)?,
+            len: 0,
+        })
+    }
+
+    /// Replaces the content of the buffer with the given instances.
+    ///
+    /// If the instances fit in the current buffer, its previous content is invalidated and the
+    /// data is uploaded in one operation. Otherwise a new buffer is allocated, whose capacity
+    /// is the next power of two.
+    pub fn update(&mut self, data: &[T]) -> Result<(), CreationError> {
+        if data.len() > self.buffer.len() {
+            let context = self.buffer.get_context().clone();
+            self.buffer = VertexBuffer::empty_dynamic(&context, data.len().next_power_of_two())?;
+        } else {
+            self.buffer.invalidate();
+        }
+
+        if !data.is_empty() {
+            self.buffer.slice(0 .. data.len()).unwrap().write(data);
+        }
+
+        self.len = data.len();
+        Ok(())
+    }
+
+    /// Reallocates the buffer so that its capacity is equal to its length.
+    ///
+    /// The instances are copied to the new buffer with `glCopyBufferSubData` and the old buffer
+    /// is destroyed, along with the vertex array objects that use it. This is a no-op if the
+    /// buffer is already full.
+    ///
+    /// Returns `BufferCreationError::NotSupported` if the backend doesn't support copying
+    /// between buffers, in which case the buffer is left untouched.
+    pub fn shrink_to_fit(&mut self) -> Result<(), CreationError> {
+        if self.len == self.buffer.len() {
+            return Ok(());
+        }
+
+        let context = self.buffer.get_context().clone();
+        let new_buffer = VertexBuffer::empty_dynamic(&context, self.len)?;
+
+        if self.len != 0 {
+            let source = self.buffer.slice(0 .. self.len).unwrap();
+            if source.copy_to(&*new_buffer).is_err() {
+                return Err(BufferCreationError::NotSupported.into());
+            }
+        }
+
+        self.buffer = new_buffer;
+        Ok(())
+    }
+}
+
+impl<T> InstanceBuffer<T> where T: Copy {
+    /// Returns the number of instances that were uploaded with the latest update.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the buffer doesn't contain any instance.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of instances that the buffer can hold without being reallocated.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Creates a marker that instructs glium to draw one instance for each element that was
+    /// uploaded with the latest update.
+    pub fn per_instance(&self) -> Result<PerInstance, InstancingNotSupported> {
+        // TODO: don't check this here
+        if !is_instancing_supported(self.buffer.get_context()) {
+            return Err(InstancingNotSupported);
+        }
+
+        let slice = self.buffer.slice(0 .. self.len).unwrap();
+        Ok(PerInstance(slice.as_slice_any(), self.buffer.get_bindings()))
+    }
+}
+
+impl<T> Deref for InstanceBuffer<T> where T: Copy {
+    type Target = VertexBuffer<T>;
+
+    #[inline]
+    fn deref(&self) -> &VertexBuffer<T> {
+        &self.buffer
+    }
+}
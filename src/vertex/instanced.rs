@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+use std::cmp;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::vec::IntoIter;
+
+use backend::Facade;
+use buffer::BufferCreationError;
+
+use vertex::{MultiVerticesSource, Vertex, VertexBuffer, VertexBufferAny, VerticesSource};
+use vertex::is_instancing_divisor_working;
+use vertex::buffer::CreationError;
+
+/// How `InstancedVertices` draws multiple instances.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InstancingMode {
+    /// Uses the instanced arrays of the backend, with one buffer of vertices and one buffer of
+    /// instances. Fails if the backend doesn't support them.
+    Hardware,
+
+    /// Same as `Hardware` if the backend supports instanced arrays and honors their divisor.
+    /// Otherwise the instances are expanded on the CPU: the vertices are duplicated for each
+    /// instance and the attributes of the instance are copied next to each vertex.
+    ///
+    /// The expanded buffer uses `vertices * instances` times more memory than the vertices,
+    /// and `gl_InstanceID` is always 0 in the shader.
+    CpuFallback,
+
+    /// Always expands the instances on the CPU like `CpuFallback` does, even if the backend
+    /// supports instanced arrays. This is mostly useful to test the fallback.
+    Cpu,
+}
+
+/// Error that can happen when creating an `InstancedVertices`.
+#[derive(Debug, Copy, Clone)]
+pub enum InstancedVerticesError {
+    /// The backend doesn't support instancing and the mode is `InstancingMode::Hardware`.
+    InstancingNotSupported,
+
+    /// Error while creating the vertex buffers.
+    CreationError(CreationError),
+}
+
+impl fmt::Display for InstancedVerticesError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::InstancedVerticesError::*;
+        match *self {
+            InstancingNotSupported => fmt.write_str("Instancing is not supported by the backend"),
+            CreationError(ref err) => write!(fmt, "Error while creating the vertex buffers: {}",
+                                             err),
+        }
+    }
+}
+
+impl Error for InstancedVerticesError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use self::InstancedVerticesError::*;
+        match *self {
+            CreationError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CreationError> for InstancedVerticesError {
+    #[inline]
+    fn from(err: CreationError) -> InstancedVerticesError {
+        InstancedVerticesError::CreationError(err)
+    }
+}
+
+/// A list of vertices drawn once for each element of a list of instances.
+///
+/// This can be passed to `draw` instead of `(&vertex_buffer, instances.per_instance())`. The
+/// vertices must be drawn with `NoIndices` and a list primitive type, like `TrianglesList`,
+/// because the expanded vertices of `InstancingMode::CpuFallback` are simply put one after
+/// another. Drawing expanded instances with an index buffer returns
+/// `DrawError::ExpandedInstancesWithIndices`. The names of the attributes of the vertices and of
+/// the instances must be different.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[macro_use]
+/// # extern crate glium;
+/// # use glium::Surface;
+/// # fn main() {
+/// # let display: glium::Display = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+/// # let program: glium::Program = unsafe { ::std::mem::MaybeUninit::uninit().assume_init() };
+/// # let mut frame = display.draw();
+/// use glium::vertex::{InstancedVertices, InstancingMode};
+///
+/// #[derive(Copy, Clone)]
+/// struct Vertex {
+///     position: [f32; 2],
+/// }
+///
+/// implement_vertex!(Vertex, position);
+///
+/// #[derive(Copy, Clone)]
+/// struct Instance {
+///     offset: [f32; 2],
+/// }
+///
+/// implement_vertex!(Instance, offset);
+///
+/// let triangle = [
+///     Vertex { position: [0.0, 0.0] },
+///     Vertex { position: [1.0, 0.0] },
+///     Vertex { position: [0.0, 1.0] },
+/// ];
+/// let instances = [Instance { offset: [0.0, 0.0] }, Instance { offset: [-1.0, -1.0] }];
+/// let vertices = InstancedVertices::new(&display, &triangle, &instances,
+///                                       InstancingMode::CpuFallback).unwrap();
+///
+/// let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+/// frame.draw(&vertices, &indices, &program, &glium::uniforms::EmptyUniforms,
+///            &Default::default()).unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InstancedVertices {
+    vertices: VertexBufferAny,
+    // `None` if the instances have been expanded in `vertices`
+    instances: Option<VertexBufferAny>,
+}
+
+impl InstancedVertices {
+    /// Builds the buffers of `vertices` and `instances`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if an attribute of the vertices has the same name as an attribute of the
+    /// instances.
+    pub fn new<F: ?Sized, V, I>(facade: &F, vertices: &[V], instances: &[I], mode: InstancingMode)
+                                -> Result<InstancedVertices, InstancedVerticesError>
+                                where F: Facade, V: Vertex + Send + 'static,
+                                      I: Vertex + Send + 'static
+    {
+        let vertices_format = <V as Vertex>::build_bindings();
+        let instances_format = <I as Vertex>::build_bindings();
+        for &(ref name, _, _, _) in instances_format.iter() {
+            assert!(vertices_format.iter().all(|&(ref n, _, _, _)| n != name),
+                    "The attribute `{}` exists in both the vertices and the instances", name);
+        }
+
+        let vertex_buffer = VertexBuffer::new(facade, vertices)?;
+        let instance_buffer = VertexBuffer::new(facade, instances)?;
+
+        let hardware = instance_buffer.per_instance().is_ok();
+        let expand = match mode {
+            InstancingMode::Hardware if !hardware => {
+                return Err(InstancedVerticesError::InstancingNotSupported);
+            },
+            InstancingMode::Hardware => false,
+            InstancingMode::CpuFallback => !hardware || !is_instancing_divisor_working(facade),
+            InstancingMode::Cpu => true,
+        };
+
+        if !expand {
+            return Ok(InstancedVertices {
+                vertices: vertex_buffer.into(),
+                instances: Some(instance_buffer.into()),
+            });
+        }
+
+        // the attributes of the instance are put after the vertex, with the right alignment
+        let offset = round_up(mem::size_of::<V>(), mem::align_of::<I>());
+        let stride = round_up(offset + mem::size_of::<I>(),
+                              cmp::max(mem::align_of::<V>(), mem::align_of::<I>()));
+
+        let len = vertices.len().checked_mul(instances.len())
+                                .and_then(|len| len.checked_mul(stride))
+                                .filter(|&len| len <= isize::MAX as usize);
+        let len = match len {
+            Some(len) => len,
+            None => return Err(CreationError::BufferCreationError(
+                                        BufferCreationError::FormatTooLarge).into()),
+        };
+
+        let mut data = vec![0u8; len];
+        let mut chunks = data.chunks_mut(stride);
+        for instance in instances {
+            for vertex in vertices {
+                let chunk = chunks.next().unwrap();
+                unsafe {
+                    ptr::copy_nonoverlapping(vertex as *const V as *const u8, chunk.as_mut_ptr(),
+                                             mem::size_of::<V>());
+                    ptr::copy_nonoverlapping(instance as *const I as *const u8,
+                                             chunk[offset ..].as_mut_ptr(), mem::size_of::<I>());
+                }
+            }
+        }
+
+        let format = vertices_format.iter().cloned()
+            .chain(instances_format.iter().map(|&(ref name, attr_offset, ty, binding)| {
+                (name.clone(), offset + attr_offset, ty, binding)
+            }))
+            .collect::<Vec<_>>();
+
+        let expanded = unsafe {
+            VertexBufferAny::new_raw_bytes(facade, &data, Cow::Owned(format), stride)?
+        };
+
+        Ok(InstancedVertices {
+            vertices: expanded,
+            instances: None,
+        })
+    }
+
+    /// Returns true if the instances have been expanded on the CPU.
+    #[inline]
+    pub fn is_expanded(&self) -> bool {
+        self.instances.is_none()
+    }
+}
+
+impl<'a> MultiVerticesSource<'a> for &'a InstancedVertices {
+    type Iterator = IntoIter<VerticesSource<'a>>;
+
+    fn iter(self) -> IntoIter<VerticesSource<'a>> {
+        let mut sources = vec![(&self.vertices).into()];
+        if let Some(ref instances) = self.instances {
+            sources.push(instances.per_instance().unwrap().into());
+        }
+        sources.into_iter()
+    }
+
+    #[inline]
+    fn supports_indices(&self) -> bool {
+        !self.is_expanded()
+    }
+}
+
+/// Rounds `value` up to a multiple of `alignment`, which must be a power of two.
+#[inline]
+fn round_up(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) & !(alignment - 1)
+}
@@ -69,6 +69,8 @@ Each source can be:
  - A vertex buffer where each element corresponds to an instance, by
    calling `vertex_buffer.per_instance()`.
  - The same with a slice, by calling `vertex_buffer.slice(start .. end).unwrap().per_instance()`.
+ - An `InstanceBuffer` whose content is replaced every frame, by
+   calling `instance_buffer.per_instance()`.
  - A marker indicating a number of vertex sources, with `glium::vertex::EmptyVertexAttributes`.
  - A marker indicating a number of instances, with `glium::vertex::EmptyInstanceAttributes`.
 
@@ -136,29 +138,80 @@ use std::iter::Chain;
 use std::option::IntoIter;
 
 pub use self::buffer::{VertexBuffer, VertexBufferAny};
-pub use self::buffer::VertexBufferSlice;
+pub use self::buffer::{AttributeView, VertexBufferSlice};
 pub use self::buffer::CreationError as BufferCreationError;
-pub use self::format::{AttributeType, VertexFormat};
+pub use self::buffer::is_instancing_supported;
+pub use self::divisor::is_instancing_divisor_working;
+pub use self::instance::InstanceBuffer;
+pub use self::instanced::{InstancedVertices, InstancedVerticesError, InstancingMode};
+pub use self::format::{AttributeBinding, AttributeType, PackedF10F11F11, VertexFormat};
 pub use self::transform_feedback::{is_transform_feedback_supported, TransformFeedbackSession};
+pub use self::transform_feedback::TransformFeedbackTarget;
+pub use self::winding::{recompute_winding, WindingError};
 
 use buffer::BufferAnySlice;
 use CapabilitiesSource;
 
 mod buffer;
+mod divisor;
 mod format;
+mod instance;
+mod instanced;
 mod transform_feedback;
+mod winding;
 
 /// Describes the source to use for the vertices when drawing.
+///
+/// A `VerticesSource` borrows the buffer it refers to, so the buffer can't be dropped while the
+/// source still exists, for example while it waits in a queue of deferred draw commands. Draw
+/// commands are submitted to OpenGL before `draw` returns, and OpenGL itself keeps a deleted
+/// buffer alive until the commands that use it are finished, so dropping the buffer after the
+/// draw is always safe.
+///
+/// ```compile_fail
+/// # #[macro_use]
+/// # extern crate glium;
+/// # fn main() {
+/// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+/// # #[derive(Copy, Clone)]
+/// # struct Vertex { position: [f32; 2] }
+/// # implement_vertex!(Vertex, position);
+/// let buffer = glium::VertexBuffer::new(&display, &[Vertex { position: [0.0, 0.0] }]).unwrap();
+/// let source: glium::vertex::VerticesSource = (&buffer).into();
+/// drop(buffer);   // error: `buffer` is still borrowed by `source`
+/// let queue = vec![source];
+/// # }
+/// ```
 #[derive(Clone)]
 pub enum VerticesSource<'a> {
     /// A buffer uploaded in the video memory.
     ///
-    /// The second parameter is the number of vertices in the buffer.
+    /// The second parameter is the format of the vertices in the buffer.
     ///
     /// The third parameter tells whether or not this buffer is "per instance" (true) or
     /// "per vertex" (false).
     VertexBuffer(BufferAnySlice<'a>, &'a VertexFormat, bool),
 
+    /// A buffer uploaded in the video memory, with an instancing divisor or a vertex buffer
+    /// binding index. See `PerInstance::with_divisor` and `VerticesSource::with_binding_index`.
+    VertexBufferWithBinding {
+        /// The buffer.
+        buffer: BufferAnySlice<'a>,
+
+        /// The format of the vertices in the buffer.
+        format: &'a VertexFormat,
+
+        /// The instancing divisor of the buffer. `0` means that the buffer is "per vertex", `1`
+        /// that each element is used for one instance, and `n` that each element is used for
+        /// `n` consecutive instances.
+        divisor: u32,
+
+        /// The vertex buffer binding index to use, if the backend supports the separate
+        /// attribute format API. If `None`, the position of the source in the list of sources
+        /// is used.
+        binding_index: Option<u32>,
+    },
+
     /// A marker indicating a "phantom list of attributes".
     Marker {
         /// Number of attributes.
@@ -169,6 +222,99 @@ pub enum VerticesSource<'a> {
     },
 }
 
+impl<'a> VerticesSource<'a> {
+    /// Adds `offset` bytes to the pointers of all the attributes of this source.
+    ///
+    /// This is useful when the vertices of multiple meshes are suballocated in the same buffer
+    /// at offsets that are not a multiple of the size of a vertex. The number of vertices is
+    /// not modified. Markers are returned unchanged.
+    ///
+    /// Returns `None` if the attributes would be read past the end of the buffer.
+    #[inline]
+    pub fn with_base_offset(self, offset: usize) -> Option<VerticesSource<'a>> {
+        match self {
+            VerticesSource::VertexBuffer(buffer, format, per_instance) => {
+                buffer.with_base_offset(offset)
+                      .map(|buffer| VerticesSource::VertexBuffer(buffer, format, per_instance))
+            },
+            VerticesSource::VertexBufferWithBinding { buffer, format, divisor, binding_index } => {
+                buffer.with_base_offset(offset).map(|buffer| {
+                    VerticesSource::VertexBufferWithBinding {
+                        buffer: buffer,
+                        format: format,
+                        divisor: divisor,
+                        binding_index: binding_index,
+                    }
+                })
+            },
+            marker @ VerticesSource::Marker { .. } => Some(marker),
+        }
+    }
+
+    /// Assigns the attributes of this source to a specific vertex buffer binding index.
+    ///
+    /// When multiple buffers feed the same draw call, glium uses the separate attribute format
+    /// API if it is available. By default each buffer gets the binding index corresponding to
+    /// its position in the list of sources, so a single buffer uses the binding index 0. This
+    /// function lets you choose the index instead, for example to keep per-instance data at
+    /// the same binding point regardless of the other sources. Markers are returned unchanged.
+    ///
+    /// Two sources of the same draw call must not use the same binding index, otherwise drawing
+    /// returns `DrawError::BindingIndexConflict`. Drawing returns
+    /// `DrawError::BindingIndexOutOfRange` if the index isn't smaller than
+    /// `Capabilities::max_vertex_attrib_bindings`, and `DrawError::BindingIndexNotSupported` if
+    /// the backend doesn't support the separate attribute format API.
+    #[inline]
+    pub fn with_binding_index(self, index: u32) -> VerticesSource<'a> {
+        match self {
+            VerticesSource::VertexBuffer(buffer, format, per_instance) => {
+                VerticesSource::VertexBufferWithBinding {
+                    buffer: buffer,
+                    format: format,
+                    divisor: if per_instance { 1 } else { 0 },
+                    binding_index: Some(index),
+                }
+            },
+            VerticesSource::VertexBufferWithBinding { buffer, format, divisor, .. } => {
+                VerticesSource::VertexBufferWithBinding {
+                    buffer: buffer,
+                    format: format,
+                    divisor: divisor,
+                    binding_index: Some(index),
+                }
+            },
+            marker @ VerticesSource::Marker { .. } => marker,
+        }
+    }
+
+    /// Returns the divisor of the attribute named `name`, or `None` if this source doesn't
+    /// contain this attribute.
+    ///
+    /// The divisor is the value passed to `glVertexAttribDivisor`: `0` for an attribute that
+    /// is read once per vertex, `1` for an attribute that is read once per instance, and `n`
+    /// for an attribute that is read once every `n` instances. Markers don't contain any
+    /// attribute and always return `None`.
+    pub fn divisor_of(&self, name: &str) -> Option<u32> {
+        match *self {
+            VerticesSource::VertexBuffer(_, format, per_instance) => {
+                if format.iter().any(|&(ref n, _, _, _)| n == name) {
+                    Some(if per_instance { 1 } else { 0 })
+                } else {
+                    None
+                }
+            },
+            VerticesSource::VertexBufferWithBinding { format, divisor, .. } => {
+                if format.iter().any(|&(ref n, _, _, _)| n == name) {
+                    Some(divisor)
+                } else {
+                    None
+                }
+            },
+            VerticesSource::Marker { .. } => None,
+        }
+    }
+}
+
 /// Marker that can be passed instead of a buffer to indicate an empty list of buffers.
 pub struct EmptyVertexAttributes {
     /// Number of phantom vertices.
@@ -198,6 +344,31 @@ impl<'a> Into<VerticesSource<'a>> for EmptyInstanceAttributes {
 /// Marker that instructs glium that the buffer is to be used per instance.
 pub struct PerInstance<'a>(BufferAnySlice<'a>, &'a VertexFormat);
 
+impl<'a> PerInstance<'a> {
+    /// Uses each element of the buffer for `divisor` consecutive instances instead of one.
+    ///
+    /// This is the value passed to `glVertexAttribDivisor`. For example a buffer of colors
+    /// with a divisor of `4` gives the same color to the instances `0` to `3`, then the next
+    /// color to the instances `4` to `7`, etc. It can be drawn together with per-vertex
+    /// buffers and with buffers of other divisors. The number of instances is given by the
+    /// buffers whose divisor is `1`, and the buffers with a larger divisor must contain at
+    /// least one element for every `divisor` instances.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `divisor` is `0`.
+    #[inline]
+    pub fn with_divisor(self, divisor: u32) -> VerticesSource<'a> {
+        assert!(divisor != 0, "The divisor of a per-instance buffer can't be 0");
+        VerticesSource::VertexBufferWithBinding {
+            buffer: self.0,
+            format: self.1,
+            divisor: divisor,
+            binding_index: None,
+        }
+    }
+}
+
 impl<'a> Into<VerticesSource<'a>> for PerInstance<'a> {
     #[inline]
     fn into(self) -> VerticesSource<'a> {
@@ -212,6 +383,14 @@ pub trait MultiVerticesSource<'a> {
 
     /// Iterates over the `VerticesSource`.
     fn iter(self) -> Self::Iterator;
+
+    /// Returns false if the sources can only be drawn with `NoIndices`.
+    ///
+    /// Drawing them with an index buffer returns `DrawError::ExpandedInstancesWithIndices`.
+    #[inline]
+    fn supports_indices(&self) -> bool {
+        true
+    }
 }
 
 impl<'a, T> MultiVerticesSource<'a> for T
@@ -289,6 +468,11 @@ impl_for_tuple!(A, B, C, D, E, F, G);
 // TODO: this should be `unsafe`, but that would break the syntax extension
 pub trait Vertex: Copy + Sized {
     /// Builds the `VertexFormat` representing the layout of this element.
+    ///
+    /// The attributes must always be returned in the same order, which should be the order in
+    /// which the fields are declared. Glium assigns some things by index in the format, for
+    /// example the locations of `Program::new_with_attribute_locations`, so
+    /// the order must not change between two calls.
     fn build_bindings() -> VertexFormat;
 
     /// Returns true if the backend supports this vertex format.
@@ -14,7 +14,7 @@ use buffer::{Buffer, BufferAnySlice};
 use index::PrimitiveType;
 use program::OutputPrimitives;
 use program::Program;
-use vertex::Vertex;
+use vertex::{Vertex, VertexFormat};
 
 use gl;
 
@@ -92,10 +92,34 @@ use gl;
 /// ```
 #[derive(Debug)]
 pub struct TransformFeedbackSession<'a> {
-    buffer: BufferAnySlice<'a>,
+    buffers: Vec<BufferAnySlice<'a>>,
     program: &'a Program,
 }
 
+/// A buffer that receives the vertices written to one of the transform feedback buffers of a
+/// program. Used with `TransformFeedbackSession::new_multiple`.
+#[derive(Debug)]
+pub struct TransformFeedbackTarget<'a> {
+    buffer: BufferAnySlice<'a>,
+    format: VertexFormat,
+    stride: usize,
+}
+
+impl<'a, V> From<&'a mut Buffer<[V]>> for TransformFeedbackTarget<'a>
+    where V: Vertex + Copy + Send + 'static
+{
+    #[inline]
+    fn from(buffer: &'a mut Buffer<[V]>) -> TransformFeedbackTarget<'a> {
+        let buffer: &'a Buffer<[V]> = buffer;
+
+        TransformFeedbackTarget {
+            buffer: buffer.as_slice_any(),
+            format: <V as Vertex>::build_bindings(),
+            stride: mem::size_of::<V>(),
+        }
+    }
+}
+
 /// Error that can happen when creating a `TransformFeedbackSession`.
 #[derive(Debug, Clone)]
 pub enum TransformFeedbackSessionCreationError {
@@ -104,6 +128,13 @@ pub enum TransformFeedbackSessionCreationError {
 
     /// The format of the output doesn't match what the program is expected to output.
     WrongVertexFormat,
+
+    /// The number of buffers doesn't match the number of transform feedback buffers of the
+    /// program.
+    ///
+    /// A program that uses the interleaved mode writes all its varyings to one buffer, while a
+    /// program that uses the separate mode writes each varying to its own buffer.
+    BuffersCountMismatch,
 }
 
 impl fmt::Display for TransformFeedbackSessionCreationError {
@@ -114,6 +145,9 @@ impl fmt::Display for TransformFeedbackSessionCreationError {
                 "Transform feedback is not supported by the OpenGL implementation",
             WrongVertexFormat =>
                 "The format of the output doesn't match what the program is expected to output",
+            BuffersCountMismatch =>
+                "The number of buffers doesn't match the number of transform feedback buffers \
+                 of the program",
         };
         fmt.write_str(desc)
     }
@@ -134,8 +168,8 @@ pub fn is_transform_feedback_supported<F: ?Sized>(facade: &F) -> bool where F: F
 impl<'a> TransformFeedbackSession<'a> {
     /// Builds a new transform feedback session.
     ///
-    /// TODO: this constructor should ultimately support passing multiple buffers of different
-    ///       types
+    /// The program must write all its varyings to a single buffer. Use `new_multiple` if the
+    /// program uses the separate mode.
     pub fn new<F: ?Sized, V>(facade: &F, program: &'a Program, buffer: &'a mut Buffer<[V]>)
                      -> Result<TransformFeedbackSession<'a>, TransformFeedbackSessionCreationError>
                      where F: Facade, V: Vertex + Copy + Send + 'static
@@ -151,7 +185,66 @@ impl<'a> TransformFeedbackSession<'a> {
         }
 
         Ok(TransformFeedbackSession {
-            buffer: buffer.as_slice_any(),
+            buffers: vec![buffer.as_slice_any()],
+            program: program,
+        })
+    }
+
+    /// Builds a new transform feedback session that writes to multiple buffers at once.
+    ///
+    /// The buffer at index `n` of the list is bound to the transform feedback binding point `n`.
+    /// If the program uses the interleaved mode, there must be exactly one buffer. If it uses
+    /// the separate mode (`GL_SEPARATE_ATTRIBS`), there must be one buffer per varying, in the
+    /// order in which the varyings were passed when building the program. The format of each
+    /// buffer must match the varyings written to it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[macro_use]
+    /// # extern crate glium;
+    /// # fn main() {
+    /// # let display: glium::Display = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// # let program: glium::Program = unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+    /// #[derive(Copy, Clone)]
+    /// struct Position { position: [f32; 3] }
+    /// implement_vertex!(Position, position);
+    ///
+    /// #[derive(Copy, Clone)]
+    /// struct Velocity { velocity: [f32; 3] }
+    /// implement_vertex!(Velocity, velocity);
+    ///
+    /// let mut positions: glium::VertexBuffer<Position> =
+    ///     glium::VertexBuffer::empty(&display, 128).unwrap();
+    /// let mut velocities: glium::VertexBuffer<Velocity> =
+    ///     glium::VertexBuffer::empty(&display, 128).unwrap();
+    ///
+    /// let session = glium::vertex::TransformFeedbackSession::new_multiple(&display, &program,
+    ///     vec![(&mut *positions).into(), (&mut *velocities).into()]).unwrap();
+    /// # }
+    /// ```
+    pub fn new_multiple<F: ?Sized>(facade: &F, program: &'a Program,
+                                   targets: Vec<TransformFeedbackTarget<'a>>)
+                                   -> Result<TransformFeedbackSession<'a>,
+                                             TransformFeedbackSessionCreationError>
+                                   where F: Facade
+    {
+        if !is_transform_feedback_supported(facade) {
+            return Err(TransformFeedbackSessionCreationError::NotSupported);
+        }
+
+        if targets.is_empty() || targets.len() != program.get_transform_feedback_buffers().len() {
+            return Err(TransformFeedbackSessionCreationError::BuffersCountMismatch);
+        }
+
+        for (index, target) in targets.iter().enumerate() {
+            if !program.transform_feedback_buffer_matches(index, &target.format, target.stride) {
+                return Err(TransformFeedbackSessionCreationError::WrongVertexFormat);
+            }
+        }
+
+        Ok(TransformFeedbackSession {
+            buffers: targets.into_iter().map(|t| t.buffer).collect(),
             program: program,
         })
     }
@@ -165,7 +258,9 @@ impl<'a> TransformFeedbackSessionExt for TransformFeedbackSession<'a> {
         }
 
         // FIXME: use the memory barrier system
-        self.buffer.bind_to_transform_feedback(ctxt, 0);
+        for (index, buffer) in self.buffers.iter().enumerate() {
+            buffer.bind_to_transform_feedback(ctxt, index as gl::types::GLuint);
+        }
 
         unsafe {
             let primitives = match (self.program.get_output_primitives(), draw_primitives) {
@@ -231,7 +326,9 @@ impl<'a> Drop for TransformFeedbackSession<'a> {
         // Since the session can be mem::forget'ed, the code in buffer/alloc.rs ensures that the
         // buffer isn't used by transform feedback.
         // However we end the session now anyway.
-        let mut ctxt = self.buffer.get_context().make_current();
-        Self::ensure_buffer_out_of_transform_feedback(&mut ctxt, self.buffer.get_id());
+        let mut ctxt = self.buffers[0].get_context().make_current();
+        for buffer in self.buffers.iter() {
+            Self::ensure_buffer_out_of_transform_feedback(&mut ctxt, buffer.get_id());
+        }
     }
 }
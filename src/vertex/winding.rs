@@ -0,0 +1,180 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::ptr;
+
+use vertex::{AttributeType, Vertex};
+
+/// Error that can happen when recomputing the winding of a list of triangles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindingError {
+    /// The vertex format doesn't contain an attribute with this name.
+    AttributeNotFound,
+
+    /// The position attribute must be made of two, three or four `f32`s.
+    WrongAttributeType(AttributeType),
+
+    /// The number of vertices is not a multiple of three.
+    NotTriangles,
+}
+
+impl fmt::Display for WindingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::WindingError::*;
+        let desc = match *self {
+            AttributeNotFound => "The position attribute was not found in the vertex format",
+            WrongAttributeType(_) => "The position attribute must be made of two, three or \
+                                      four f32s",
+            NotTriangles => "The number of vertices is not a multiple of three",
+        };
+        fmt.write_str(desc)
+    }
+}
+
+impl Error for WindingError {}
+
+/// Flips triangles so that they all have a consistent winding, before uploading them.
+///
+/// `vertices` must be a list of triangles, as with `PrimitiveType::TrianglesList`, and
+/// `position` the name of their position attribute. Triangles that share an edge are given the
+/// same orientation. Then each group of connected triangles is oriented so that its faces are
+/// counter-clockwise when seen from the outside of the mesh, or from the positive Z axis if
+/// the mesh is flat. This corresponds to the default front face of OpenGL. For meshes that are
+/// not closed, the outside is determined relatively to the origin.
+///
+/// Vertices are considered to be shared if their positions are exactly equal. The triangles
+/// of meshes that can't be oriented, like a Möbius strip, keep the orientation of the first
+/// triangle that reaches them.
+///
+/// Returns the number of triangles that have been flipped.
+pub fn recompute_winding<T>(vertices: &mut [T], position: &str) -> Result<usize, WindingError>
+                            where T: Vertex
+{
+    let bindings = <T as Vertex>::build_bindings();
+    let (offset, ty) = match bindings.iter().find(|&&(ref n, _, _, _)| {
+        Borrow::<str>::borrow(n) == position
+    }) {
+        Some(&(_, offset, ty, _)) => (offset, ty),
+        None => return Err(WindingError::AttributeNotFound),
+    };
+
+    if vertices.len() % 3 != 0 {
+        return Err(WindingError::NotTriangles);
+    }
+
+    let positions = vertices.iter().map(|vertex| unsafe {
+        let ptr = (vertex as *const T as *const u8).add(offset);
+        match ty {
+            AttributeType::F32F32 => {
+                let p = ptr::read_unaligned(ptr as *const [f32; 2]);
+                Ok([p[0], p[1], 0.0])
+            },
+            AttributeType::F32F32F32 => Ok(ptr::read_unaligned(ptr as *const [f32; 3])),
+            AttributeType::F32F32F32F32 => {
+                let p = ptr::read_unaligned(ptr as *const [f32; 4]);
+                Ok([p[0], p[1], p[2]])
+            },
+            ty => Err(WindingError::WrongAttributeType(ty)),
+        }
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    let flips = compute_flips(&positions);
+
+    let mut flipped = 0;
+    for (triangle, &flip) in flips.iter().enumerate() {
+        if flip {
+            vertices.swap(triangle * 3 + 1, triangle * 3 + 2);
+            flipped += 1;
+        }
+    }
+
+    Ok(flipped)
+}
+
+/// Returns, for each triangle, whether it must be flipped.
+fn compute_flips(positions: &[[f32; 3]]) -> Vec<bool> {
+    let num_triangles = positions.len() / 3;
+    let key = |p: [f32; 3]| [p[0].to_bits(), p[1].to_bits(), p[2].to_bits()];
+
+    // for each edge, the list of triangles that contain it and whether they go through the
+    // edge from its smaller key to its larger key
+    let mut edges: HashMap<_, Vec<(usize, bool)>> = HashMap::new();
+    for triangle in 0 .. num_triangles {
+        for &(a, b) in &[(0, 1), (1, 2), (2, 0)] {
+            let a = key(positions[triangle * 3 + a]);
+            let b = key(positions[triangle * 3 + b]);
+            if a == b {
+                continue;
+            }
+
+            let edge = if a < b { (a, b) } else { (b, a) };
+            edges.entry(edge).or_insert_with(Vec::new).push((triangle, a < b));
+        }
+    }
+
+    let mut triangle_edges = vec![Vec::with_capacity(3); num_triangles];
+    for (edge, triangles) in &edges {
+        for &(triangle, forward) in triangles {
+            triangle_edges[triangle].push((*edge, forward));
+        }
+    }
+
+    let mut flips = vec![false; num_triangles];
+    let mut visited = vec![false; num_triangles];
+
+    for first in 0 .. num_triangles {
+        if visited[first] {
+            continue;
+        }
+
+        // propagating the orientation of `first` to the triangles connected to it
+        let mut component = vec![first];
+        let mut queue = vec![first];
+        visited[first] = true;
+
+        while let Some(triangle) = queue.pop() {
+            for &(edge, forward) in &triangle_edges[triangle] {
+                for &(neighbour, neighbour_forward) in &edges[&edge] {
+                    if visited[neighbour] {
+                        continue;
+                    }
+
+                    // two neighbours must go through their shared edge in opposite directions
+                    flips[neighbour] = !(neighbour_forward ^ forward ^ flips[triangle]);
+                    visited[neighbour] = true;
+                    component.push(neighbour);
+                    queue.push(neighbour);
+                }
+            }
+        }
+
+        // orienting the component towards the outside
+        let mut volume = 0.0;
+        let mut area = 0.0;
+        let mut flat = true;
+        let z = positions[first * 3][2];
+        for &triangle in &component {
+            let a = positions[triangle * 3];
+            let (b, c) = if flips[triangle] {
+                (positions[triangle * 3 + 2], positions[triangle * 3 + 1])
+            } else {
+                (positions[triangle * 3 + 1], positions[triangle * 3 + 2])
+            };
+
+            volume += a[0] * (b[1] * c[2] - b[2] * c[1]) +
+                      a[1] * (b[2] * c[0] - b[0] * c[2]) +
+                      a[2] * (b[0] * c[1] - b[1] * c[0]);
+            area += (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+            flat = flat && a[2] == z && b[2] == z && c[2] == z;
+        }
+
+        if (flat && area < 0.0) || (!flat && volume < 0.0) {
+            for &triangle in &component {
+                flips[triangle] = !flips[triangle];
+            }
+        }
+    }
+
+    flips
+}
@@ -7,7 +7,9 @@ use smallvec::SmallVec;
 
 use Handle;
 use buffer::BufferAnySlice;
+use draw_parameters::ConstantAttribute;
 use program::Program;
+use vertex::AttributeBinding;
 use vertex::AttributeType;
 use vertex::VertexFormat;
 use GlObject;
@@ -20,9 +22,68 @@ use version::Version;
 
 /// Stores and handles vertex attributes.
 pub struct VertexAttributesSystem {
-    // we maintain a list of VAOs for each vertexbuffer-indexbuffer-program association
-    // the key is a (buffers-list-with-offset, program) ; the buffers list must be sorted
-    vaos: RefCell<HashMap<(Vec<(gl::types::GLuint, usize)>, Handle), VertexArrayObject>>,
+    // we maintain a list of VAOs for each vertexbuffer-indexbuffer-attributes association
+    vaos: RefCell<HashMap<VaoKey, VertexArrayObject>>,
+    // maximum number of VAOs in the cache, the least recently used ones are destroyed first
+    max_vaos: Cell<Option<usize>>,
+    // incremented every time a VAO is bound, used to find the least recently used VAO
+    clock: Cell<u64>,
+}
+
+/// Key of the VAOs cache.
+///
+/// The first element is the list of buffers with their offset, their stride, their instancing
+/// divisor and their vertex buffer binding index. The index buffer is part of this list, with a
+/// stride of 0 and no divisor nor binding index. The second element contains, for each
+/// attribute of each buffer, the buffer, its offset, the location of the attribute in the
+/// program, the OpenGL type of the attribute in the program, and the offset, type and binding
+/// of the attribute in the vertex format. Both lists must be sorted.
+///
+/// The format, the stride, the divisor and the binding index are part of the key so that
+/// drawing the same buffer with another format (for example with normalization toggled) or
+/// with other instancing parameters doesn't reuse a VAO built for the old ones.
+///
+/// The program itself is not part of the key, so that programs that use the same attribute
+/// locations share the same VAOs.
+///
+/// The third list contains the location and the kind of the constant attributes that are
+/// applied, as the VAO keeps their attribute arrays disabled.
+type VaoKey = (Vec<(gl::types::GLuint, usize, usize, Option<u32>, Option<u32>)>,
+               Vec<(gl::types::GLuint, usize, gl::types::GLint, gl::types::GLenum,
+                    usize, AttributeType, AttributeBinding)>,
+               Vec<(gl::types::GLint, gl::types::GLenum)>);
+
+/// Description of a vertex array object of the cache, as returned by
+/// `Context::dump_vao_cache`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaoCacheEntry {
+    /// The OpenGL identifier of the vertex array object.
+    pub id: gl::types::GLuint,
+    /// The OpenGL identifiers of the vertex buffers, with their offset in bytes.
+    pub vertex_buffers: Vec<(gl::types::GLuint, usize)>,
+    /// The OpenGL identifier of the index buffer bound to the vertex array object, if any.
+    pub index_buffer: Option<gl::types::GLuint>,
+    /// The programs that have been checked against the attributes of this vertex array object.
+    pub programs: Vec<Handle>,
+    /// The attributes bound in the vertex array object.
+    pub attributes: Vec<VaoCacheAttribute>,
+}
+
+/// Description of an attribute of a vertex array object of the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaoCacheAttribute {
+    /// The OpenGL identifier of the vertex buffer that contains the attribute.
+    pub buffer: gl::types::GLuint,
+    /// The offset in bytes of the vertex buffer.
+    pub buffer_offset: usize,
+    /// The location of the attribute in the program.
+    pub location: gl::types::GLint,
+    /// The offset in bytes of the attribute within a vertex.
+    pub offset: usize,
+    /// The type of the attribute in the vertex buffer.
+    pub ty: AttributeType,
+    /// How the attribute is delivered to the shader.
+    pub binding: AttributeBinding,
 }
 
 /// Object allowing one to bind vertex attributes to the current context.
@@ -30,7 +91,9 @@ pub struct Binder<'a, 'b, 'c: 'b> {
     context: &'b mut CommandContext<'c>,
     program: &'a Program,
     element_array_buffer: Option<BufferAnySlice<'a>>,
-    vertex_buffers: SmallVec<[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>); 2]>,
+    vertex_buffers: SmallVec<[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>,
+                               Option<u32>); 2]>,
+    constant_attributes: &'a [(&'a str, ConstantAttribute)],
     base_vertex: bool,
 }
 
@@ -40,6 +103,8 @@ impl VertexAttributesSystem {
     pub fn new() -> VertexAttributesSystem {
         VertexAttributesSystem {
             vaos: RefCell::new(HashMap::with_hasher(Default::default())),
+            max_vaos: Cell::new(None),
+            clock: Cell::new(0),
         }
     }
 
@@ -61,6 +126,7 @@ impl VertexAttributesSystem {
             program: program,
             element_array_buffer: indices,
             vertex_buffers: SmallVec::new(),
+            constant_attributes: &[],
             base_vertex: base_vertex,
         }
     }
@@ -69,8 +135,8 @@ impl VertexAttributesSystem {
     /// purge its VAOs cache.
     #[inline]
     pub fn purge_buffer(ctxt: &mut CommandContext, id: gl::types::GLuint) {
-        VertexAttributesSystem::purge_if(ctxt, |&(ref buffers, _)| {
-            buffers.iter().find(|&&(b, _)| b == id).is_some()
+        VertexAttributesSystem::purge_if(ctxt, |&(ref buffers, _, _)| {
+            buffers.iter().find(|&&(b, _, _, _, _)| b == id).is_some()
         })
     }
 
@@ -78,7 +144,21 @@ impl VertexAttributesSystem {
     /// purge its VAOs cache.
     #[inline]
     pub fn purge_program(ctxt: &mut CommandContext, program: Handle) {
-        VertexAttributesSystem::purge_if(ctxt, |&(_, p)| p == program)
+        let mut vaos = ctxt.vertex_array_objects.vaos.borrow_mut();
+
+        let mut keys = Vec::with_capacity(4);
+        for (key, vao) in &*vaos {
+            let mut programs = vao.programs.borrow_mut();
+            programs.retain(|p| *p != program);
+            if programs.is_empty() {
+                keys.push(key.clone());
+            }
+        }
+
+        // destroying the VAOs that are no longer used by any program
+        for key in keys {
+            vaos.remove(&key).unwrap().destroy(ctxt);
+        }
     }
 
     /// Purges the VAOs cache.
@@ -102,6 +182,76 @@ impl VertexAttributesSystem {
         }
     }
 
+    /// Returns the number of VAOs in the cache.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vaos.borrow().len()
+    }
+
+    /// Returns a description of each VAO of the cache.
+    pub fn dump(&self) -> Vec<VaoCacheEntry> {
+        self.vaos.borrow().iter().map(|(&(ref buffers, ref attributes, _), vao)| {
+            VaoCacheEntry {
+                id: vao.id,
+                vertex_buffers: buffers.iter().map(|&(b, o, _, _, _)| (b, o)).collect(),
+                index_buffer: if vao.element_array_buffer == 0 {
+                    None
+                } else {
+                    Some(vao.element_array_buffer)
+                },
+                programs: vao.programs.borrow().iter().cloned().collect(),
+                attributes: attributes.iter().map(|&(buffer, buffer_offset, location, _,
+                                                     offset, ty, binding)|
+                {
+                    VaoCacheAttribute {
+                        buffer: buffer,
+                        buffer_offset: buffer_offset,
+                        location: location,
+                        offset: offset,
+                        ty: ty,
+                        binding: binding,
+                    }
+                }).collect(),
+            }
+        }).collect()
+    }
+
+    /// Sets the maximum number of VAOs in the cache, and destroys the least recently used VAOs
+    /// if there are too many of them.
+    pub fn set_max_len(ctxt: &mut CommandContext, max: Option<usize>) {
+        ctxt.vertex_array_objects.max_vaos.set(max);
+
+        if let Some(max) = max {
+            VertexAttributesSystem::evict(ctxt, max);
+        }
+    }
+
+    /// Destroys the least recently used VAOs until there are no more than `max` VAOs.
+    fn evict(ctxt: &mut CommandContext, max: usize) {
+        let mut vaos = ctxt.vertex_array_objects.vaos.borrow_mut();
+
+        while vaos.len() > max {
+            let key = vaos.iter().min_by_key(|&(_, vao)| vao.last_used.get())
+                                 .map(|(key, _)| key.clone()).unwrap();
+            vaos.remove(&key).unwrap().destroy(ctxt);
+        }
+    }
+
+    /// Returns a new value to store in `last_used`.
+    #[inline]
+    fn tick(&self) -> u64 {
+        let value = self.clock.get() + 1;
+        self.clock.set(value);
+        value
+    }
+
+    /// Returns the number of consecutive locations that an attribute of this type uses.
+    #[inline]
+    pub fn get_locations_count(ty: AttributeType) -> gl::types::GLuint {
+        let (_, _, columns) = vertex_binding_type_to_gl(ty);
+        (columns * locations_per_column(ty)) as gl::types::GLuint
+    }
+
     /// Tells the VAOs system that the currently bound element array buffer will change.
     pub fn hijack_current_element_array_buffer(ctxt: &mut CommandContext) {
         let vaos = ctxt.vertex_array_objects.vaos.borrow_mut();
@@ -116,7 +266,7 @@ impl VertexAttributesSystem {
 
     /// Purges VAOs that match a certain condition.
     fn purge_if<F>(ctxt: &mut CommandContext, mut condition: F)
-                   where F: FnMut(&(Vec<(gl::types::GLuint, usize)>, Handle)) -> bool
+                   where F: FnMut(&VaoKey) -> bool
     {
         let mut vaos = ctxt.vertex_array_objects.vaos.borrow_mut();
 
@@ -141,9 +291,11 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
     /// - `buffer`: The buffer to bind.
     /// - `first`: Offset of the first element of the buffer in number of elements.
     /// - `divisor`: If `Some`, use this value for `glVertexAttribDivisor` (instancing-related).
+    /// - `binding_index`: If `Some`, the vertex buffer binding index to use with the separate
+    ///   attribute format API. Otherwise the position of the buffer in the list is used.
     #[inline]
-    pub fn add(mut self, buffer: &BufferAnySlice, bindings: &VertexFormat, divisor: Option<u32>)
-               -> Binder<'a, 'b, 'c>
+    pub fn add(mut self, buffer: &BufferAnySlice, bindings: &VertexFormat, divisor: Option<u32>,
+               binding_index: Option<u32>) -> Binder<'a, 'b, 'c>
     {
         let offset = buffer.get_offset_bytes();
 
@@ -152,7 +304,16 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
         let (buffer, format, stride) = (buffer.get_id(), bindings.clone(),
                                         buffer.get_elements_size());
 
-        self.vertex_buffers.push((buffer, format, offset, stride, divisor));
+        self.vertex_buffers.push((buffer, format, offset, stride, divisor, binding_index));
+        self
+    }
+
+    /// Sets the values of the attributes that aren't provided by any buffer.
+    #[inline]
+    pub fn add_constants(mut self, constants: &'a [(&'a str, ConstantAttribute)])
+                         -> Binder<'a, 'b, 'c>
+    {
+        self.constant_attributes = constants;
         self
     }
 
@@ -171,8 +332,8 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
             // finding the base vertex
             let base_vertex = if self.base_vertex {
                 Some(self.vertex_buffers.iter()
-                                        .filter(|&&(_, _, _, _, div)| div.is_none())
-                                        .map(|&(_, _, off, stride, _)| off / stride)
+                                        .filter(|&&(_, _, _, _, div, _)| div.is_none())
+                                        .map(|&(_, _, off, stride, _, _)| off / stride)
                                         .min().unwrap_or(0))
             } else {
                 None
@@ -180,35 +341,95 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
 
             // removing the offset corresponding to the base vertex
             if let Some(base_vertex) = base_vertex {
-                for &mut (_, _, ref mut off, stride, _) in self.vertex_buffers.iter_mut() {
+                for &mut (_, _, ref mut off, stride, _, _) in self.vertex_buffers.iter_mut() {
                     *off -= base_vertex * stride;
                 }
             }
 
-            let mut buffers_list: Vec<_> = self.vertex_buffers.iter()
-                                                              .map(|&(v, _, o, s, _)| (v, o))
-                                                              .collect();
-            buffers_list.push((self.element_array_buffer.map(|b| b.get_id()).unwrap_or(0), 0));
+            // the binding index of a buffer without an explicit one is its position in the list
+            let mut buffers_list: Vec<_> = self.vertex_buffers.iter().enumerate()
+                                               .map(|(position, &(v, _, o, s, div, index))| {
+                                                   let index = index.unwrap_or(position as u32);
+                                                   (v, o, s, div, Some(index))
+                                               })
+                                               .collect();
+            buffers_list.push((self.element_array_buffer.map(|b| b.get_id()).unwrap_or(0), 0, 0,
+                               None, None));
             buffers_list.sort();
 
             let program_id = self.program.get_id();
 
+            // the locations of the attributes in the program
+            let mut locations: Vec<_> = Vec::new();
+            for &(buffer, ref bindings, offset, _, _, _) in self.vertex_buffers.iter() {
+                for &(ref name, attr_offset, attr_ty, binding) in bindings.iter() {
+                    let (location, ty) = match self.program
+                                                   .get_attribute(Borrow::<str>::borrow(name))
+                    {
+                        Some(a) => (a.location, vertex_binding_type_to_gl(a.ty).0),
+                        None => (-1, 0),
+                    };
+
+                    locations.push((buffer, offset, location, ty, attr_offset, attr_ty, binding));
+                }
+            }
+            locations.sort_by_key(|&(buffer, offset, location, ty, attr_offset, _, _)| {
+                (buffer, offset, location, ty, attr_offset)
+            });
+
+            // the constant attributes that are applied, as they disable their attribute arrays
+            let (vertex_buffers, program) = (&self.vertex_buffers, self.program);
+            let mut constants: Vec<_> = self.constant_attributes.iter()
+                .filter(|&&(name, _)| {
+                    !vertex_buffers.iter().any(|b| b.1.iter().any(|a| a.0 == name))
+                })
+                .filter_map(|&(name, value)| {
+                    program.get_attribute(name)
+                           .map(|a| (a.location, constant_attribute_kind(value)))
+                })
+                .collect();
+            constants.sort();
+
+            let key = (buffers_list, locations, constants);
+
             // trying to find an existing VAO in the cache
-            if let Some(value) = ctxt.vertex_array_objects.vaos.borrow_mut()
-                                     .get(&(buffers_list.clone(), program_id))
-            {
+            if let Some(value) = ctxt.vertex_array_objects.vaos.borrow_mut().get(&key) {
+                // the VAO may have been built for another program with the same locations
+                if !value.programs.borrow().contains(&program_id) {
+                    check_attributes(ctxt, &self.vertex_buffers, self.program,
+                                     self.constant_attributes);
+                    value.programs.borrow_mut().push(program_id);
+                }
+
+                value.last_used.set(ctxt.vertex_array_objects.tick());
                 value.bind(ctxt);
+                unsafe {
+                    bind_constant_attributes(ctxt, self.program, &self.vertex_buffers,
+                                             self.constant_attributes);
+                }
                 return base_vertex.map(|v| v as gl::types::GLint);
             }
 
             // if not found, building a new one
             let new_vao = unsafe {
                 VertexArrayObject::new(ctxt, &self.vertex_buffers,
-                                       self.element_array_buffer, self.program)
+                                       self.element_array_buffer, self.program,
+                                       self.constant_attributes)
             };
 
+            // making room for the new VAO
+            if let Some(max) = ctxt.vertex_array_objects.max_vaos.get() {
+                VertexAttributesSystem::evict(ctxt, max.saturating_sub(1));
+            }
+
+            new_vao.last_used.set(ctxt.vertex_array_objects.tick());
             new_vao.bind(ctxt);
-            ctxt.vertex_array_objects.vaos.borrow_mut().insert((buffers_list, program_id), new_vao);
+            ctxt.vertex_array_objects.vaos.borrow_mut().insert(key, new_vao);
+
+            unsafe {
+                bind_constant_attributes(ctxt, self.program, &self.vertex_buffers,
+                                         self.constant_attributes);
+            }
 
             base_vertex.map(|v| v as gl::types::GLint)
 
@@ -218,17 +439,25 @@ impl<'a, 'b, 'c> Binder<'a, 'b, 'c> {
             // just in case
             bind_vao(ctxt, 0);
 
+            // without a VAO cache, the attributes are checked on each draw
+            check_attributes(ctxt, &self.vertex_buffers, self.program, self.constant_attributes);
+
             if let Some(element_array_buffer) = self.element_array_buffer {
                 element_array_buffer.bind_to_element_array(ctxt);
             }
 
-            for (vertex_buffer, bindings, offset, stride, divisor) in self.vertex_buffers.into_iter() {
+            for &(vertex_buffer, ref bindings, offset, stride, divisor, _) in self.vertex_buffers.iter() {
                 unsafe {
-                    bind_attribute(ctxt, self.program, vertex_buffer, &bindings, offset, stride,
+                    bind_attribute(ctxt, self.program, vertex_buffer, bindings, offset, stride,
                                    divisor);
                 }
             }
 
+            unsafe {
+                bind_constant_attributes(ctxt, self.program, &self.vertex_buffers,
+                                         self.constant_attributes);
+            }
+
             // TODO: it is unlikely that a backend supports base vertex but not VAOs, so we just
             //       ignore this case ; however it would ideally be better to handle it
             if self.base_vertex {
@@ -246,6 +475,10 @@ struct VertexArrayObject {
     destroyed: bool,
     element_array_buffer: gl::types::GLuint,
     element_array_buffer_hijacked: Cell<bool>,
+    // programs that have been checked against the attributes of this VAO
+    programs: RefCell<SmallVec<[Handle; 2]>>,
+    // value of the clock of the system the last time this VAO was used
+    last_used: Cell<u64>,
 }
 
 impl VertexArrayObject {
@@ -254,39 +487,12 @@ impl VertexArrayObject {
     /// The vertex buffer, index buffer and program must not outlive the
     /// VAO, and the VB & program attributes must not change.
     unsafe fn new(mut ctxt: &mut CommandContext,
-                  vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>)],
-                  index_buffer: Option<BufferAnySlice>, program: &Program) -> VertexArrayObject
+                  vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>,
+                                     Option<u32>)],
+                  index_buffer: Option<BufferAnySlice>, program: &Program,
+                  constant_attributes: &[(&str, ConstantAttribute)]) -> VertexArrayObject
     {
-        // checking the attributes types
-        for &(_, ref bindings, _, _, _) in vertex_buffers {
-            for &(ref name, _, ty, _) in bindings.iter() {
-                let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
-                    Some(a) => a,
-                    None => continue
-                };
-
-                if ty.get_num_components() != attribute.ty.get_num_components() ||
-                    attribute.size != 1
-                {
-                    panic!("The program attribute `{}` does not match the vertex format. \
-                            Program expected {:?}, got {:?}.", name, attribute.ty, ty);
-                }
-            }
-        }
-
-        // checking for missing attributes
-        for (&ref name, _) in program.attributes() {
-            let mut found = false;
-            for &(_, ref bindings, _, _, _) in vertex_buffers {
-                if bindings.iter().find(|&&(ref n, _, _, _)| n == name).is_some() {
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                panic!("The program attribute `{}` is missing in the vertex bindings", name);
-            }
-        };
+        check_attributes(ctxt, vertex_buffers, program, constant_attributes);
 
         // TODO: check for collisions between the vertices sources
 
@@ -317,8 +523,15 @@ impl VertexArrayObject {
             index_buffer.bind_to_element_array(&mut ctxt);
         }
 
-        for &(vertex_buffer, ref bindings, offset, stride, divisor) in vertex_buffers {
-            bind_attribute(ctxt, program, vertex_buffer, bindings, offset, stride, divisor);
+        // explicit binding indices have been checked by the draw function, so they always fit
+        // and only the sources without one can fall back to the classic attribute API
+        let max_bindings = ctxt.capabilities.max_vertex_attrib_bindings;
+        if max_bindings.map_or(false, |max| bindings_count(vertex_buffers) <= max as usize) {
+            bind_attributes_separate(ctxt, program, vertex_buffers);
+        } else {
+            for &(vertex_buffer, ref bindings, offset, stride, divisor, _) in vertex_buffers {
+                bind_attribute(ctxt, program, vertex_buffer, bindings, offset, stride, divisor);
+            }
         }
 
         VertexArrayObject {
@@ -326,6 +539,12 @@ impl VertexArrayObject {
             destroyed: false,
             element_array_buffer: index_buffer.map(|b| b.get_id()).unwrap_or(0),
             element_array_buffer_hijacked: Cell::new(false),
+            programs: RefCell::new({
+                let mut programs = SmallVec::new();
+                programs.push(program.get_id());
+                programs
+            }),
+            last_used: Cell::new(0),
         }
     }
 
@@ -393,6 +612,14 @@ impl GlObject for VertexArrayObject {
     }
 }
 
+/// Returns the number of locations that each column of an attribute of this type uses.
+///
+/// Double-precision columns of three or four components use two locations.
+fn locations_per_column(ty: AttributeType) -> gl::types::GLint {
+    let (data_type, elements_count, _) = vertex_binding_type_to_gl(ty);
+    if data_type == gl::DOUBLE && elements_count >= 3 { 2 } else { 1 }
+}
+
 fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types::GLint, gl::types::GLint) {
     match ty {
         AttributeType::I8 => (gl::BYTE, 1, 1),
@@ -472,9 +699,80 @@ fn vertex_binding_type_to_gl(ty: AttributeType) -> (gl::types::GLenum, gl::types
         AttributeType::U10U10U10U2 => (gl::UNSIGNED_INT_10_10_10_2_OES, 4, 1),
         AttributeType::F10F11F11UnsignedIntReversed => (gl::UNSIGNED_INT_10F_11F_11F_REV, 3, 1),
         AttributeType::FixedFloatI16U16 => (gl::FIXED, 1, 1),
+        AttributeType::U8U8U8U8Bgra => (gl::UNSIGNED_BYTE, gl::BGRA as gl::types::GLint, 1),
     }
 }
 
+/// Checks that the attributes of the program match the vertex sources.
+///
+/// ## Panic
+///
+/// Panics if an attribute has the wrong type or is missing.
+fn check_attributes(ctxt: &CommandContext,
+                    vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize, Option<u32>,
+                                       Option<u32>)],
+                    program: &Program, constant_attributes: &[(&str, ConstantAttribute)])
+{
+    // checking the attributes types
+    for &(_, ref bindings, _, _, _, _) in vertex_buffers {
+        for &(ref name, _, ty, binding) in bindings.iter() {
+            let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+                Some(a) => a,
+                None => continue
+            };
+
+            // `GL_BGRA` can only be used with normalized values
+            if ty == AttributeType::U8U8U8U8Bgra && binding != AttributeBinding::Auto &&
+               binding != AttributeBinding::FloatNormalized
+            {
+                panic!("The attribute `{}` of type `U8U8U8U8Bgra` must use the `Auto` or \
+                        `FloatNormalized` binding, got `{:?}`.", name, binding);
+            }
+
+            if ty.get_num_components() != attribute.ty.get_num_components() ||
+                attribute.size != 1
+            {
+                panic!("The program attribute `{}` does not match the vertex format. \
+                        Program expected {:?} (`{}`), got {:?} (`{}`).", name, attribute.ty,
+                       attribute.ty.glsl_type_name(), ty, ty.glsl_type_name());
+            }
+
+            // matrices use one location per column, or two for large double-precision columns
+            let locations = VertexAttributesSystem::get_locations_count(ty) as i32;
+            if attribute.location + locations > ctxt.capabilities.max_vertex_attribs {
+                panic!("The program attribute `{}` uses {} locations starting at {}, but \
+                        the backend only supports {}.", name, locations, attribute.location,
+                        ctxt.capabilities.max_vertex_attribs);
+            }
+        }
+    }
+
+    // checking for missing attributes
+    for (&ref name, attribute) in program.attributes() {
+        let mut found = false;
+        for &(_, ref bindings, _, _, _, _) in vertex_buffers {
+            if bindings.iter().find(|&&(ref n, _, _, _)| n == name).is_some() {
+                found = true;
+                break;
+            }
+        }
+        if found {
+            continue;
+        }
+
+        match constant_attributes.iter().find(|&&(n, _)| n == name) {
+            Some(&(_, value)) => {
+                if !is_constant_valid(value, attribute.ty) || attribute.size != 1 {
+                    panic!("The constant attribute `{}` does not match the program attribute \
+                            of type {:?} (`{}`).", name, attribute.ty,
+                           attribute.ty.glsl_type_name());
+                }
+            },
+            None => panic!("The program attribute `{}` is missing in the vertex bindings", name),
+        }
+    };
+}
+
 /// Binds the vertex array object as the current one. Unbinds if `0` is passed.
 ///
 /// ## Panic
@@ -520,7 +818,7 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
     }
 
     // binding attributes
-    for &(ref name, offset, ty, normalize) in bindings.iter() {
+    for &(ref name, offset, ty, binding) in bindings.iter() {
         let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
 
         let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
@@ -530,49 +828,324 @@ unsafe fn bind_attribute(ctxt: &mut CommandContext, program: &Program,
 
         if attribute.location != -1 {
             let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
-            if normalize {
-                for i in 0..instances_count {
-                    ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
-                                                elements_count as gl::types::GLint, data_type, 1,
-                                                stride as i32,
-                                                (buffer_offset + offset + (i * elements_count * 4) as usize) as *const _)
-                }
-            } else {
-                match attribute_ty {
-                    gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
-                    gl::INT | gl::UNSIGNED_INT =>
-                        ctxt.gl.VertexAttribIPointer(attribute.location as u32,
+            let locations_per_column = locations_per_column(ty);
+            match resolve_binding(binding, ty, attribute_ty) {
+                AttributeBinding::FloatNormalized => {
+                    for i in 0..instances_count {
+                        ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
+                                                    elements_count as gl::types::GLint, data_type, 1,
+                                                    stride as i32,
+                                                    (buffer_offset + offset + (i * elements_count * 4) as usize) as *const _)
+                    }
+                },
+
+                AttributeBinding::Integer =>
+                    ctxt.gl.VertexAttribIPointer(attribute.location as u32,
+                                                 elements_count as gl::types::GLint, data_type,
+                                                 stride as i32,
+                                                 (buffer_offset + offset) as *const _),
+
+                AttributeBinding::Float => {
+                    for i in 0..instances_count {
+                        ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
+                                                    elements_count as gl::types::GLint, data_type, 0,
+                                                    stride as i32,
+                                                    (buffer_offset + offset + (i * elements_count * 4) as usize) as *const _)
+                    }
+                },
+
+                AttributeBinding::Double => {
+                    for i in 0..instances_count {
+                        ctxt.gl.VertexAttribLPointer((attribute.location + i * locations_per_column) as u32,
                                                      elements_count as gl::types::GLint, data_type,
                                                      stride as i32,
-                                                     (buffer_offset + offset) as *const _),
-
-                    gl::FLOAT => {
-                        for i in 0..instances_count {
-                            ctxt.gl.VertexAttribPointer((attribute.location + i) as u32,
-                                                        elements_count as gl::types::GLint, data_type, 0,
-                                                        stride as i32,
-                                                        (buffer_offset + offset + (i * elements_count * 4) as usize) as *const _)
-                        }
-                    },
-
-                    gl::DOUBLE | gl::INT64_NV | gl::UNSIGNED_INT64_NV => {
-                        for i in 0..instances_count {
-                            ctxt.gl.VertexAttribLPointer((attribute.location + i) as u32,
-                                                         elements_count as gl::types::GLint, data_type,
-                                                         stride as i32,
-                                                         (buffer_offset + offset + (i * elements_count * 8) as usize) as *const _)
-                        }
-                    },
-
-                    _ => unreachable!()
-                }
+                                                     (buffer_offset + offset + (i * elements_count * 8) as usize) as *const _)
+                    }
+                },
+
+                AttributeBinding::Auto => unreachable!()
             }
 
             for i in 0..instances_count {
+                let location = (attribute.location + i * locations_per_column) as u32;
                 if let Some(divisor) = divisor {
-                    ctxt.gl.VertexAttribDivisor((attribute.location + i) as u32, divisor);
+                    ctxt.gl.VertexAttribDivisor(location, divisor);
+                }
+                ctxt.gl.EnableVertexAttribArray(location);
+            }
+        }
+    }
+}
+
+/// Replaces `AttributeBinding::Auto` with the binding that matches the type of the attribute in
+/// the program.
+///
+/// `ty` is the type of the attribute in the vertex buffer. `GL_BGRA` attributes are always
+/// normalized.
+fn resolve_binding(binding: AttributeBinding, ty: AttributeType,
+                   attribute_ty: gl::types::GLenum) -> AttributeBinding
+{
+    match binding {
+        AttributeBinding::Auto if ty == AttributeType::U8U8U8U8Bgra => {
+            AttributeBinding::FloatNormalized
+        },
+        AttributeBinding::Auto => match attribute_ty {
+            gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
+            gl::INT | gl::UNSIGNED_INT => AttributeBinding::Integer,
+            gl::FLOAT => AttributeBinding::Float,
+            gl::DOUBLE | gl::INT64_NV | gl::UNSIGNED_INT64_NV => AttributeBinding::Double,
+            _ => unreachable!()
+        },
+        binding => binding,
+    }
+}
+
+/// Returns false if `binding` can't pass data of type `ty` to an attribute of type
+/// `attribute_ty` in the program.
+///
+/// `Float` and `FloatNormalized` require a floating-point attribute, `Integer` requires integral
+/// data and an integral attribute, and `Double` requires 64bits data and a 64bits attribute.
+/// `Auto` is always valid.
+pub fn is_binding_valid(binding: AttributeBinding, ty: AttributeType,
+                        attribute_ty: AttributeType) -> bool
+{
+    fn is_integral(ty: gl::types::GLenum) -> bool {
+        match ty {
+            gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT |
+            gl::INT | gl::UNSIGNED_INT => true,
+            _ => false,
+        }
+    }
+
+    fn is_64bits(ty: gl::types::GLenum) -> bool {
+        match ty {
+            gl::DOUBLE | gl::INT64_NV | gl::UNSIGNED_INT64_NV => true,
+            _ => false,
+        }
+    }
+
+    let (data_type, _, _) = vertex_binding_type_to_gl(ty);
+    let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute_ty);
+
+    match binding {
+        AttributeBinding::Auto => true,
+        AttributeBinding::Float | AttributeBinding::FloatNormalized => attribute_ty == gl::FLOAT,
+        AttributeBinding::Integer => is_integral(data_type) && is_integral(attribute_ty),
+        AttributeBinding::Double => is_64bits(data_type) && is_64bits(attribute_ty),
+    }
+}
+
+/// Returns false if `constant` can't be the value of an attribute of type `attribute_ty` in the
+/// program.
+///
+/// `Float` requires a single-precision scalar or vector, `Matrix` a single-precision matrix,
+/// and `Int` and `UnsignedInt` a signed or unsigned integral attribute. Integral attributes
+/// don't exist before OpenGL 3.0 and OpenGL ES 3.0, so integral constants are always rejected
+/// there.
+pub fn is_constant_valid(constant: ConstantAttribute, attribute_ty: AttributeType) -> bool {
+    let (ty, _, columns) = vertex_binding_type_to_gl(attribute_ty);
+
+    match constant {
+        ConstantAttribute::Float(_) => ty == gl::FLOAT && columns == 1,
+        ConstantAttribute::Matrix(_) => ty == gl::FLOAT && columns > 1,
+        ConstantAttribute::Int(_) => ty == gl::INT,
+        ConstantAttribute::UnsignedInt(_) => ty == gl::UNSIGNED_INT,
+    }
+}
+
+/// Returns the kind of a constant attribute, as stored in the VAO keys.
+fn constant_attribute_kind(constant: ConstantAttribute) -> gl::types::GLenum {
+    match constant {
+        ConstantAttribute::Float(_) => gl::FLOAT,
+        ConstantAttribute::Matrix(_) => gl::FLOAT_MAT4,
+        ConstantAttribute::Int(_) => gl::INT,
+        ConstantAttribute::UnsignedInt(_) => gl::UNSIGNED_INT,
+    }
+}
+
+/// Sets the current value of the program attributes that are not provided by any vertex buffer.
+///
+/// These values are part of the context state and not of the VAO, so this must be called after
+/// each binding.
+unsafe fn bind_constant_attributes(ctxt: &mut CommandContext, program: &Program,
+                                   vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize,
+                                                      usize, Option<u32>, Option<u32>)],
+                                   constant_attributes: &[(&str, ConstantAttribute)])
+{
+    for &(name, value) in constant_attributes {
+        if vertex_buffers.iter().any(|b| b.1.iter().any(|a| a.0 == name)) {
+            continue;
+        }
+
+        let (location, ty) = match program.get_attribute(name) {
+            Some(a) => (a.location as gl::types::GLuint, a.ty),
+            None => continue
+        };
+
+        // a matrix uses one location per column
+        let (_, _, columns) = vertex_binding_type_to_gl(ty);
+
+        // the attribute may still be enabled if VAOs are not supported
+        for column in 0 .. columns as gl::types::GLuint {
+            ctxt.gl.DisableVertexAttribArray(location + column);
+        }
+
+        // integral attributes don't exist before GL 3.0 and GLES 3.0, and `check_attributes`
+        // rejects integral constants there
+        let integral_supported = ctxt.version >= &Version(Api::Gl, 3, 0) ||
+                                 ctxt.version >= &Version(Api::GlEs, 3, 0);
+
+        match value {
+            ConstantAttribute::Float(v) => {
+                ctxt.gl.VertexAttrib4f(location, v[0], v[1], v[2], v[3]);
+            },
+            ConstantAttribute::Matrix(m) => {
+                for column in 0 .. columns as usize {
+                    let v = m[column];
+                    ctxt.gl.VertexAttrib4f(location + column as gl::types::GLuint,
+                                           v[0], v[1], v[2], v[3]);
                 }
-                ctxt.gl.EnableVertexAttribArray((attribute.location + i) as u32);
+            },
+            ConstantAttribute::Int(v) => {
+                if !integral_supported {
+                    panic!("Integral constant attributes are not supported by the backend");
+                }
+                ctxt.gl.VertexAttribI4i(location, v[0], v[1], v[2], v[3]);
+            },
+            ConstantAttribute::UnsignedInt(v) => {
+                if !integral_supported {
+                    panic!("Integral constant attributes are not supported by the backend");
+                }
+                ctxt.gl.VertexAttribI4ui(location, v[0], v[1], v[2], v[3]);
+            },
+        }
+    }
+}
+
+/// Returns true if the backend supports the separate attribute format API, where the format of
+/// the attributes is specified independently from the vertex buffer binding points.
+fn is_attrib_binding_supported(ctxt: &CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.version >= &Version(Api::GlEs, 3, 1) ||
+    ctxt.extensions.gl_arb_vertex_attrib_binding
+}
+
+/// Returns true if the backend supports binding all the vertex buffers of a VAO with a single
+/// call to `glBindVertexBuffers`.
+fn is_multi_bind_supported(ctxt: &CommandContext) -> bool {
+    ctxt.version >= &Version(Api::Gl, 4, 4) ||
+    (ctxt.extensions.gl_arb_multi_bind &&
+     (ctxt.version >= &Version(Api::Gl, 4, 3) || ctxt.extensions.gl_arb_vertex_attrib_binding))
+}
+
+/// Returns the number of vertex buffer binding points used by a list of vertex buffers.
+fn bindings_count(vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize, usize,
+                                     Option<u32>, Option<u32>)]) -> usize
+{
+    vertex_buffers.iter().enumerate()
+                  .map(|(position, b)| b.5.map(|i| i as usize).unwrap_or(position) + 1)
+                  .max().unwrap_or(0)
+}
+
+/// Binds all the attributes of all the vertex buffers to the current VAO, by using the separate
+/// attribute format API.
+///
+/// Each vertex buffer is assigned its explicit binding index if it has one, or the binding index
+/// corresponding to its position in the list otherwise. The instancing divisor is set once per
+/// binding index with `glVertexBindingDivisor`, and is thus shared by all the attributes of a
+/// buffer. The buffers are then bound with a single call to `glBindVertexBuffers` if it is
+/// supported, in which case unused binding points are bound to 0, or with `glBindVertexBuffer`
+/// otherwise.
+///
+/// ## Panic
+///
+/// Panics if `is_attrib_binding_supported` returns false, or if two buffers use the same binding
+/// index. The binding indices must be smaller than `max_vertex_attrib_bindings`, which is checked
+/// by the draw function.
+unsafe fn bind_attributes_separate(ctxt: &mut CommandContext, program: &Program,
+                                   vertex_buffers: &[(gl::types::GLuint, VertexFormat, usize,
+                                                      usize, Option<u32>, Option<u32>)])
+{
+    assert!(is_attrib_binding_supported(ctxt));
+
+    let count = bindings_count(vertex_buffers);
+    let mut ids: SmallVec<[gl::types::GLuint; 4]> = (0 .. count).map(|_| 0).collect();
+    let mut offsets: SmallVec<[gl::types::GLintptr; 4]> = (0 .. count).map(|_| 0).collect();
+    let mut strides: SmallVec<[gl::types::GLsizei; 4]> = (0 .. count).map(|_| 0).collect();
+
+    for (position, &(vertex_buffer, ref bindings, buffer_offset, stride, divisor,
+                     binding_index)) in vertex_buffers.iter().enumerate()
+    {
+        let binding_index = binding_index.unwrap_or(position as u32);
+        if ids[binding_index as usize] != 0 {
+            panic!("Two vertex sources use the binding index {}", binding_index);
+        }
+
+        for &(ref name, offset, ty, binding) in bindings.iter() {
+            let (data_type, elements_count, instances_count) = vertex_binding_type_to_gl(ty);
+
+            let attribute = match program.get_attribute(Borrow::<str>::borrow(name)) {
+                Some(a) => a,
+                None => continue
+            };
+
+            if attribute.location == -1 {
+                continue;
+            }
+
+            let (attribute_ty, _, _) = vertex_binding_type_to_gl(attribute.ty);
+            let column_size = ty.get_size_bytes() / instances_count as usize;
+
+            for i in 0..instances_count {
+                let location = (attribute.location + i * locations_per_column(ty))
+                                    as gl::types::GLuint;
+                let relative_offset = (offset + i as usize * column_size) as gl::types::GLuint;
+
+                match resolve_binding(binding, ty, attribute_ty) {
+                    AttributeBinding::FloatNormalized =>
+                        ctxt.gl.VertexAttribFormat(location, elements_count, data_type, gl::TRUE,
+                                                   relative_offset),
+
+                    AttributeBinding::Integer =>
+                        ctxt.gl.VertexAttribIFormat(location, elements_count, data_type,
+                                                    relative_offset),
+
+                    AttributeBinding::Float =>
+                        ctxt.gl.VertexAttribFormat(location, elements_count, data_type,
+                                                   gl::FALSE, relative_offset),
+
+                    AttributeBinding::Double =>
+                        ctxt.gl.VertexAttribLFormat(location, elements_count, data_type,
+                                                    relative_offset),
+
+                    AttributeBinding::Auto => unreachable!()
+                }
+
+                ctxt.gl.VertexAttribBinding(location, binding_index);
+                ctxt.gl.EnableVertexAttribArray(location);
+            }
+        }
+
+        if let Some(divisor) = divisor {
+            ctxt.gl.VertexBindingDivisor(binding_index, divisor);
+        }
+
+        ids[binding_index as usize] = vertex_buffer;
+        offsets[binding_index as usize] = buffer_offset as gl::types::GLintptr;
+        strides[binding_index as usize] = stride as gl::types::GLsizei;
+    }
+
+    if ids.is_empty() {
+        return;
+    }
+
+    if is_multi_bind_supported(ctxt) {
+        ctxt.gl.BindVertexBuffers(0, ids.len() as gl::types::GLsizei, ids.as_ptr(),
+                                  offsets.as_ptr(), strides.as_ptr());
+    } else {
+        for (binding_index, &id) in ids.iter().enumerate() {
+            if id != 0 {
+                ctxt.gl.BindVertexBuffer(binding_index as gl::types::GLuint, id,
+                                         offsets[binding_index], strides[binding_index]);
             }
         }
     }
@@ -61,3 +61,60 @@ fn basic() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn atomic_counter_buffer() {
+    let display = support::build_display();
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 430
+
+            out vec4 f_color;
+
+            layout(binding = 2) uniform atomic_uint counter;
+
+            void main() {
+                f_color = vec4(0.0, 0.0, 0.0, 1.0);
+                atomicCounterIncrement(counter);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let counters = match glium::uniforms::AtomicCounterBuffer::new(&display, 1) {
+        Err(_) => return,
+        Ok(b) => b
+    };
+    assert_eq!(counters.read().unwrap(), vec![0]);
+
+    counters.reset(5);
+
+    let uniforms = uniform!{
+        counter: &counters,
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &uniforms, &Default::default()).unwrap();
+
+    assert_eq!(counters.read().unwrap(), vec![1024 * 1024 + 5]);
+
+    display.assert_no_error(None);
+}
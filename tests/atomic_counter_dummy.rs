@@ -0,0 +1,34 @@
+/*!
+
+Runs atomic counter buffers against dummy OpenGL functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::GlObject;
+use glium::buffer::BufferCreationError;
+use glium::uniforms::AtomicCounterBuffer;
+
+mod dummy;
+
+#[test]
+fn atomic_counters_not_supported() {
+    let context = dummy::build_context(b"3.3.0\0", &[]);
+
+    match AtomicCounterBuffer::new(&context, 4) {
+        Err(BufferCreationError::NotSupported) => (),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn atomic_counters_reset() {
+    let context = dummy::build_context(b"3.3.0\0", &[b"GL_ARB_shader_atomic_counters\0"]);
+
+    let counters = AtomicCounterBuffer::new(&context, 2).unwrap();
+    assert_eq!(dummy::buffer_content(counters.get_id()), vec![0; 8]);
+
+    counters.reset(5);
+    assert_eq!(dummy::buffer_content(counters.get_id()), vec![5, 0, 0, 0, 5, 0, 0, 0]);
+}
@@ -166,6 +166,465 @@ fn missing_attribute() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn missing_attribute_checked() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 4],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vertex_buffer = glium::VertexBuffer::new(&display, &Vec::<Vertex>::new()).unwrap();
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::Points,
+                                               &Vec::<u16>::new()).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 field2;
+
+                void main() {
+                    gl_Position = vec4(field2, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                void main() {
+                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            "
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute mediump vec2 field2;
+
+                void main() {
+                    gl_Position = vec4(field2, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+                void main() {
+                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            "
+        }
+    ).unwrap();
+
+    let mut target = display.draw();
+    match target.draw_checked(&vertex_buffer, &index_buffer, &program,
+                              &glium::uniforms::EmptyUniforms, &Default::default())
+    {
+        Err(glium::DrawError::AttributeMissing) => (),
+        a => panic!("{:?}", a)
+    }
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn constant_attribute() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec4 color;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                in vec4 v_color;
+                out vec4 f_color;
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+                varying vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec4 color;
+                varying lowp vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec4 v_color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        }
+    ).unwrap();
+
+    let constants = [("color", glium::draw_parameters::ConstantAttribute::Float([1.0, 0.0, 0.0, 1.0]))];
+    let params = glium::DrawParameters {
+        constant_attributes: &constants,
+        .. Default::default()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn constant_attribute_direction() {
+    use glium::draw_parameters::ConstantAttribute;
+
+    assert_eq!(ConstantAttribute::direction([1.0, 2.0, 3.0]),
+               ConstantAttribute::Float([1.0, 2.0, 3.0, 0.0]));
+    assert_eq!(ConstantAttribute::position([1.0, 2.0, 3.0]),
+               ConstantAttribute::Float([1.0, 2.0, 3.0, 1.0]));
+}
+
+#[test]
+fn constant_attribute_identity() {
+    use glium::draw_parameters::ConstantAttribute;
+
+    assert_eq!(ConstantAttribute::identity(),
+               ConstantAttribute::Matrix([[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0],
+                                          [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]));
+}
+
+#[test]
+fn constant_attribute_matrix() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in mat4 transform;
+
+                void main() {
+                    gl_Position = transform * vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                out vec4 f_color;
+                void main() {
+                    f_color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute mat4 transform;
+
+                void main() {
+                    gl_Position = transform * vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            "
+        }
+    ).unwrap();
+
+    // every column must be set, otherwise the rectangle doesn't cover the whole texture
+    let constants = [("transform", glium::draw_parameters::ConstantAttribute::identity())];
+    let params = glium::DrawParameters {
+        constant_attributes: &constants,
+        .. Default::default()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &ib, &program, &glium::uniforms::EmptyUniforms,
+                              &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn constant_attribute_wrong_kind() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec4 color;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                in vec4 v_color;
+                out vec4 f_color;
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+                varying vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        }
+    ).unwrap();
+
+    // an integral constant can't be the value of a `vec4`, and doesn't exist before GL 3.0
+    let constants = [("color", glium::draw_parameters::ConstantAttribute::Int([1, 0, 0, 1]))];
+    let params = glium::DrawParameters {
+        constant_attributes: &constants,
+        .. Default::default()
+    };
+
+    let mut target = display.draw();
+    match target.draw_checked(&vb, &ib, &program, &glium::uniforms::EmptyUniforms, &params) {
+        Err(glium::DrawError::AttributeTypeMismatch) => (),
+        a => panic!("{:?}", a)
+    }
+    target.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn constant_attribute_vao_not_shared() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let with_color = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec4 color;
+                out vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                in vec4 v_color;
+                out vec4 f_color;
+                void main() {
+                    f_color = v_color;
+                }
+            "
+        }
+    ).unwrap();
+
+    let without_color = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+                out vec4 f_color;
+                void main() {
+                    f_color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            "
+        }
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().draw(&vb, &ib, &without_color, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let vaos = display.dump_vao_cache().len();
+    if vaos == 0 {
+        // VAOs are not supported or debug assertions are disabled
+        return;
+    }
+
+    // the constants that are applied are part of the key of the VAO cache, so the VAO built
+    // without them isn't reused
+    let constants = [("color", glium::draw_parameters::ConstantAttribute::Float([1.0, 0.0, 0.0, 1.0]))];
+    let params = glium::DrawParameters {
+        constant_attributes: &constants,
+        .. Default::default()
+    };
+    texture.as_surface().draw(&vb, &ib, &with_color, &glium::uniforms::EmptyUniforms,
+                              &params).unwrap();
+
+    assert_eq!(display.dump_vao_cache().len(), vaos + 1);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn per_attribute_binding() {
+    use glium::vertex::AttributeBinding;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        id: i32,
+        weight: u8,
+    }
+
+    implement_vertex!(Vertex, position binding(AttributeBinding::Float),
+                              id binding(AttributeBinding::Integer),
+                              weight binding(AttributeBinding::FloatNormalized));
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], id: 1, weight: 255 },
+        Vertex { position: [ 1.0,  1.0], id: 1, weight: 255 },
+        Vertex { position: [-1.0, -1.0], id: 1, weight: 255 },
+        Vertex { position: [ 1.0, -1.0], id: 1, weight: 255 },
+    ]).unwrap();
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 130
+
+            in vec2 position;
+            in int id;
+            in float weight;
+            out vec4 v_color;
+
+            void main() {
+                v_color = vec4(float(id), weight, 0.0, 1.0);
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+
+            in vec4 v_color;
+            out vec4 f_color;
+
+            void main() {
+                f_color = v_color;
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 255, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
 macro_rules! attribute_test(
     ($name:ident, $attr_ty:ty, $glsl_ty:expr, $value:expr, $gl_pos:expr) => (
         #[test]
@@ -259,6 +718,11 @@ attribute_test!(attribute_vec3_f32, [f32; 3], "vec3", [0.0, 0.0, 0.0], "vec4(fie
 attribute_test!(attribute_vec3_tuple_f32, (f32, f32, f32), "vec3", (0.0, 0.0, 0.0), "vec4(field1, 1.0)");
 attribute_test!(attribute_vec4_f32, [f32; 4], "vec4", [0.0, 0.0, 0.0, 0.0], "field1");
 attribute_test!(attribute_vec4_tuple_f32, (f32, f32, f32, f32), "vec4", (0.0, 0.0, 0.0, 0.0), "field1");
+attribute_test!(attribute_mat3_flat_f32, [f32; 9], "mat3", [0.0; 9], "vec4(field1[0], 1.0)");
+attribute_test!(attribute_mat3x4_flat_f32, [f32; 12], "mat3x4", [0.0; 12], "field1[2]");
+attribute_test!(attribute_mat2_f32, [[f32; 2]; 2], "mat2", [[0.0; 2]; 2], "vec4(field1[1], 0.0, 1.0)");
+attribute_test!(attribute_mat3_f32, [[f32; 3]; 3], "mat3", [[0.0; 3]; 3], "vec4(field1[2], 1.0)");
+attribute_test!(attribute_mat4_f32, [[f32; 4]; 4], "mat4", [[0.0; 4]; 4], "field1[3]");
 
 attribute_test!(attribute_float_u8, u8, "float", 0, "vec4(field1, 0.0, 0.0, 1.0)");
 attribute_test!(attribute_vec2_u8, [u8; 2], "vec2", [0, 0], "vec4(field1, 0.0, 1.0)");
@@ -307,3 +771,370 @@ attribute_test!(attribute_vec3_i32, [i32; 3], "vec3", [0, 0, 0], "vec4(field1, 1
 attribute_test!(attribute_vec3_tuple_i32, (i32, i32, i32), "vec3", (0, 0, 0), "vec4(field1, 1.0)");
 attribute_test!(attribute_vec4_i32, [i32; 4], "vec4", [0, 0, 0, 0], "field1");
 attribute_test!(attribute_vec4_tuple_i32, (i32, i32, i32, i32), "vec4", (0, 0, 0, 0), "field1");
+
+#[test]
+fn attribute_type_glsl_name() {
+    use glium::vertex::AttributeType;
+
+    assert_eq!(AttributeType::F32.glsl_type_name(), "float");
+    assert_eq!(AttributeType::F32F32F32.glsl_type_name(), "vec3");
+    assert_eq!(AttributeType::I32I32I32I32.glsl_type_name(), "ivec4");
+    assert_eq!(AttributeType::U8U8.glsl_type_name(), "uvec2");
+    assert_eq!(AttributeType::F64F64.glsl_type_name(), "dvec2");
+    assert_eq!(AttributeType::F32x4x4.glsl_type_name(), "mat4");
+    assert_eq!(AttributeType::F32x2x3.glsl_type_name(), "mat2x3");
+    assert_eq!(AttributeType::F64x3x3.glsl_type_name(), "dmat3");
+}
+
+#[test]
+fn vertex_format_declaration_order() {
+    use glium::vertex::{AttributeType, Vertex as VertexTrait};
+
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    struct Vertex {
+        weight: f32,
+        position: [f32; 3],
+        color: [f32; 4],
+        id: i32,
+    }
+
+    implement_vertex!(Vertex, weight, position, color, id);
+
+    let bindings = <Vertex as VertexTrait>::build_bindings();
+    let names = bindings.iter().map(|b| &b.0[..]).collect::<Vec<_>>();
+    assert_eq!(names, ["weight", "position", "color", "id"]);
+
+    let types = bindings.iter().map(|b| b.2).collect::<Vec<_>>();
+    assert_eq!(types, [AttributeType::F32, AttributeType::F32F32F32, AttributeType::F32F32F32F32,
+                       AttributeType::I32]);
+
+    let offsets = bindings.iter().map(|b| b.1).collect::<Vec<_>>();
+    assert_eq!(offsets, [0, 4, 16, 32]);
+
+    assert_eq!(<Vertex as VertexTrait>::build_bindings(), bindings);
+}
+
+#[test]
+fn packed_f10f11f11_round_trip() {
+    use glium::vertex::PackedF10F11F11;
+
+    for &value in &[[0.0, 0.0, 0.0], [1.0, 0.5, 0.25], [3.14, 100.0, 0.001], [65000.0, 2.0, 7.5]] {
+        let unpacked = PackedF10F11F11::new(value).get();
+        for (&a, &b) in value.iter().zip(unpacked.iter()) {
+            assert!((a - b).abs() <= a * 0.02 + 0.0001, "{} != {}", a, b);
+        }
+    }
+
+    assert_eq!(PackedF10F11F11::new([-1.0, 0.0, 0.0]).get(), [0.0, 0.0, 0.0]);
+    assert_eq!(PackedF10F11F11::new([1.0, 1.0, 1.0]).0, 0x3c0 | (0x3c0 << 11) | (0x1e0 << 22));
+}
+
+#[test]
+fn packed_f10f11f11_readback() {
+    use glium::vertex::PackedF10F11F11;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: PackedF10F11F11,
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let color = PackedF10F11F11::new([2.5, 0.125, 40.0]);
+    let vb = match glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], color: color },
+        Vertex { position: [ 1.0,  1.0], color: color },
+        Vertex { position: [-1.0, -1.0], color: color },
+        Vertex { position: [ 1.0, -1.0], color: color },
+    ]) {
+        Ok(vb) => vb,
+        Err(glium::vertex::BufferCreationError::FormatNotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 130
+
+            in vec2 position;
+            in vec3 color;
+            out vec3 v_color;
+
+            void main() {
+                v_color = color;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 130
+
+            in vec3 v_color;
+            out vec4 f_color;
+
+            void main() {
+                f_color = vec4(v_color, 1.0);
+            }
+        ",
+        None);
+
+    // ignoring test in case of compilation error (version may not be supported)
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let texture = match glium::Texture2d::empty_with_format(&display,
+                                glium::texture::UncompressedFloatFormat::F32F32F32F32,
+                                glium::texture::MipmapsOption::NoMipmap, 16, 16)
+    {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let image = texture.main_level().first_layer().into_image(None).unwrap();
+    let data: Vec<Vec<(f32, f32, f32, f32)>> = image.raw_read(&glium::Rect {
+        left: 0, bottom: 0, width: 16, height: 16
+    });
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert!((pixel.0 - 2.5).abs() < 0.05);
+            assert!((pixel.1 - 0.125).abs() < 0.005);
+            assert!((pixel.2 - 40.0).abs() < 0.8);
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn bgra_attribute_readback() {
+    use std::borrow::Cow;
+    use glium::vertex::{AttributeBinding, AttributeType};
+
+    let display = support::build_display();
+
+    // the fields are only read by the GPU
+    #[allow(dead_code)]
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [u8; 4],
+    }
+
+    // the color is stored in the blue, green, red, alpha order
+    let color = [0, 128, 255, 255];
+    let bindings = Cow::Owned(vec![
+        (Cow::Borrowed("position"), 0, AttributeType::F32F32, AttributeBinding::Auto),
+        (Cow::Borrowed("color"), 8, AttributeType::U8U8U8U8Bgra, AttributeBinding::Auto),
+    ]);
+
+    let vb = match unsafe {
+        glium::VertexBuffer::new_raw(&display, &[
+            Vertex { position: [-1.0,  1.0], color: color },
+            Vertex { position: [ 1.0,  1.0], color: color },
+            Vertex { position: [-1.0, -1.0], color: color },
+            Vertex { position: [ 1.0, -1.0], color: color },
+        ], bindings, 12)
+    } {
+        Ok(vb) => vb,
+        Err(glium::vertex::BufferCreationError::FormatNotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                              &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 128, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn bgra_attribute_integer_binding() {
+    use std::borrow::Cow;
+    use glium::vertex::{AttributeBinding, AttributeType};
+
+    let display = support::build_display();
+
+    let bindings = Cow::Owned(vec![
+        (Cow::Borrowed("color"), 0, AttributeType::U8U8U8U8Bgra, AttributeBinding::Integer),
+    ]);
+
+    let vb = match unsafe {
+        glium::VertexBuffer::new_raw(&display, &[[0u8, 0, 0, 0]; 3], bindings, 4)
+    } {
+        Ok(vb) => vb,
+        Err(glium::vertex::BufferCreationError::FormatNotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec4 color;
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw_checked(&vb,
+                                            &glium::index::NoIndices(PrimitiveType::TrianglesList),
+                                            &program, &glium::uniforms::EmptyUniforms,
+                                            &Default::default())
+    {
+        Err(glium::DrawError::AttributeTypeMismatch) => (),
+        res => panic!("{:?}", res),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn integer_binding_float_attribute() {
+    use glium::vertex::AttributeBinding;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [i32; 2],
+    }
+
+    implement_vertex!(Vertex, position binding(AttributeBinding::Integer));
+
+    let vb = glium::VertexBuffer::new(&display, &[Vertex { position: [0, 0] }; 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw_checked(&vb,
+                                            &glium::index::NoIndices(PrimitiveType::TrianglesList),
+                                            &program, &glium::uniforms::EmptyUniforms,
+                                            &Default::default())
+    {
+        Err(glium::DrawError::AttributeTypeMismatch) => (),
+        res => panic!("{:?}", res),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn double_binding_f32_data() {
+    use glium::vertex::AttributeBinding;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position binding(AttributeBinding::Double));
+
+    let vb = glium::VertexBuffer::new(&display, &[Vertex { position: [0.0, 0.0] }; 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw_checked(&vb,
+                                            &glium::index::NoIndices(PrimitiveType::TrianglesList),
+                                            &program, &glium::uniforms::EmptyUniforms,
+                                            &Default::default())
+    {
+        Err(glium::DrawError::AttributeTypeMismatch) => (),
+        res => panic!("{:?}", res),
+    }
+
+    display.assert_no_error(None);
+}
@@ -108,7 +108,7 @@ fn buffer_immutable_mapping_read() {
         ]
     ).unwrap();
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(mapping[0].field1, [2, 3]);
     assert_eq!(mapping[1].field2, [15, 17]);
 
@@ -134,7 +134,7 @@ fn buffer_dynamic_mapping_read() {
         ]
     ).unwrap();
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(mapping[0].field1, [2, 3]);
     assert_eq!(mapping[1].field2, [15, 17]);
 
@@ -161,11 +161,11 @@ fn buffer_immutable_mapping_write() {
     ).unwrap();
 
     {
-        let mut mapping = vb.map();
+        let mut mapping = vb.map().unwrap();
         mapping[0].field1 = [0, 1];
     }
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(mapping[0].field1, [0, 1]);
     assert_eq!(mapping[1].field2, [15, 17]);
 
@@ -192,17 +192,60 @@ fn buffer_dynamic_mapping_write() {
     ).unwrap();
 
     {
-        let mut mapping = vb.map();
+        let mut mapping = vb.map().unwrap();
         mapping[0].field1 = [0, 1];
     }
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(mapping[0].field1, [0, 1]);
     assert_eq!(mapping[1].field2, [15, 17]);
 
     display.assert_no_error(None);
 }
 
+#[test]
+fn buffer_slice_mapping_keeps_the_rest() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1u32, 2, 3, 4, 5][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Default).unwrap();
+
+    {
+        let mut mapping = buf.slice_mut(1 .. 3).unwrap().map().unwrap();
+        assert_eq!(&*mapping, &[2, 3]);
+        mapping[0] = 6;
+        mapping[1] = 7;
+    }
+
+    // only the mapping is used to read the content back
+    let mapping = buf.map().unwrap();
+    assert_eq!(&*mapping, &[1, 6, 7, 4, 5]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_dynamic_mapping_write_invalidate() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1u32, 2, 3][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Dynamic).unwrap();
+
+    {
+        let mut mapping = buf.map_write_invalidate().unwrap();
+        mapping.set(0, 4);
+        mapping.set(1, 5);
+        mapping.set(2, 6);
+    }
+
+    let mapping = buf.map().unwrap();
+    assert_eq!(&*mapping, &[4, 5, 6]);
+
+    display.assert_no_error(None);
+}
+
 // TODO: uncomment after std::thread::scoped has been stabilized
 /*#[test]
 fn buffer_mapping_multithread() {
@@ -219,14 +262,14 @@ fn buffer_mapping_multithread() {
     let mut vb = glium::VertexBuffer::empty(&display, 2).unwrap();
 
     {
-        let mut mapping = vb.map();
+        let mut mapping = vb.map().unwrap();
         std::thread::scoped(|| {
             mapping[0].field1 = [0, 1];
             mapping[1].field2 = [15, 17];
         });
     }
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(mapping[0].field1, [0, 1]);
     assert_eq!(mapping[1].field2, [15, 17]);
 
@@ -379,6 +422,103 @@ fn buffer_slice_out_of_bounds() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn buffer_slice_mut_mapping() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [u8; 2],
+        field2: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field1, field2);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display,
+        &[
+            Vertex { field1: [ 2,  3], field2: [ 5,  7] },
+            Vertex { field1: [12, 13], field2: [15, 17] },
+            Vertex { field1: [22, 23], field2: [25, 27] },
+        ]
+    ).unwrap();
+
+    {
+        let mut mapping = vb.slice_mut(1 .. 2).unwrap().unwrap();
+        assert_eq!(mapping.len(), 1);
+        assert_eq!(mapping[0].field1, [12, 13]);
+        mapping[0].field1 = [0, 1];
+    }
+
+    let mapping = vb.map().unwrap();
+    assert_eq!(mapping[0].field1, [2, 3]);
+    assert_eq!(mapping[1].field1, [0, 1]);
+    assert_eq!(mapping[2].field1, [22, 23]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_map_mut_range_invalidate() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [u8; 2],
+        field2: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field1, field2);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display,
+        &[
+            Vertex { field1: [ 2,  3], field2: [ 5,  7] },
+            Vertex { field1: [12, 13], field2: [15, 17] },
+            Vertex { field1: [22, 23], field2: [25, 27] },
+        ]
+    ).unwrap();
+
+    {
+        let mut mapping = vb.map_mut_range_invalidate(1 .. 3).unwrap().unwrap();
+        assert_eq!(mapping.len(), 2);
+        mapping.set(0, Vertex { field1: [0, 1], field2: [2, 3] });
+        mapping.set(1, Vertex { field1: [4, 5], field2: [6, 7] });
+    }
+
+    assert!(vb.map_mut_range_invalidate(2 .. 4).unwrap().is_none());
+
+    let mapping = vb.map().unwrap();
+    assert_eq!(mapping[0].field1, [2, 3]);
+    assert_eq!(mapping[1].field1, [0, 1]);
+    assert_eq!(mapping[2].field2, [6, 7]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_slice_mut_out_of_bounds() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [u8; 2],
+        field2: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, field1, field2);
+
+    let mut vb = glium::VertexBuffer::new(&display,
+        &[
+            Vertex { field1: [ 2,  3], field2: [ 5,  7] },
+            Vertex { field1: [12, 13], field2: [15, 17] },
+        ]
+    ).unwrap();
+
+    assert!(vb.slice_mut(0 .. 3).unwrap().is_none());
+    assert!(vb.slice_mut(2 .. 1).unwrap().is_none());
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn buffer_immutable_write() {
     let display = support::build_display();
@@ -559,6 +699,36 @@ fn zero_sized_dynamic_buffer() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn zero_sized_buffer_map() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::Buffer::<[u32]>::empty_array(&display,
+                                                 glium::buffer::BufferType::ArrayBuffer, 0,
+                                                 BufferMode::Default).unwrap();
+
+    assert_eq!(buf.map().unwrap().len(), 0);
+    assert_eq!(buf.map_read().unwrap().len(), 0);
+    assert_eq!(buf.map_write().unwrap().len(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_slice_map() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::Buffer::new(&display, &[1u32, 2, 3][..],
+                                             glium::buffer::BufferType::ArrayBuffer,
+                                             BufferMode::Immutable).unwrap();
+
+    assert_eq!(buf.slice_mut(1 .. 1).unwrap().map().unwrap().len(), 0);
+    assert_eq!(buf.slice_mut(3 .. 3).unwrap().map_write().unwrap().len(), 0);
+    assert_eq!(buf.read().unwrap(), vec![1, 2, 3]);
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn invalidate() {
     let display = support::build_display();
@@ -603,12 +773,12 @@ fn immutable_mapping_forget_then_remap() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
 
-    let mapping = buf.map();
+    let mapping = buf.map().unwrap();
     assert_eq!(mapping[1], 2);
     assert_eq!(mapping[2], 3);
 
@@ -625,7 +795,7 @@ fn immutable_mapping_forget_then_read() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
@@ -652,7 +822,7 @@ fn immutable_mapping_forget_then_invalidate() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
@@ -661,6 +831,71 @@ fn immutable_mapping_forget_then_invalidate() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn immutable_storage_map_write() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1, 2, 3][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Immutable)
+                                                 .unwrap();
+
+    {
+        let mut mapping = buf.map_write().unwrap();
+        mapping.set(0, 5);
+    }
+
+    let mapping = buf.map_read().unwrap();
+    assert_eq!(mapping[0], 5);
+    assert_eq!(mapping[1], 2);
+    assert_eq!(mapping[2], 3);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn immutable_storage_slice_map() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1, 2, 3, 4][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Immutable)
+                                                 .unwrap();
+
+    {
+        let mut mapping = buf.slice_mut(1 .. 3).unwrap().map().unwrap();
+        mapping[0] = 7;
+        mapping[1] = 8;
+    }
+
+    let mapping = buf.map_read().unwrap();
+    assert_eq!(mapping[0], 1);
+    assert_eq!(mapping[1], 7);
+    assert_eq!(mapping[2], 8);
+    assert_eq!(mapping[3], 4);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn immutable_storage_write_then_map() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1, 2, 3][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Immutable)
+                                                 .unwrap();
+
+    buf.write(&[4, 5, 6]);
+
+    let mapping = buf.map().unwrap();
+    assert_eq!(mapping[0], 4);
+    assert_eq!(mapping[1], 5);
+    assert_eq!(mapping[2], 6);
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn immutable_mapping_forget_then_draw() {
     let display = support::build_display();
@@ -740,7 +975,7 @@ fn immutable_mapping_forget_then_draw() {
     ).unwrap();
 
     {
-        let mapping = vb.map();
+        let mapping = vb.map().unwrap();
         mem::forget(mapping);
     }
 
@@ -767,12 +1002,12 @@ fn persistent_mapping_forget_then_remap() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
 
-    let mapping = buf.map();
+    let mapping = buf.map().unwrap();
     assert_eq!(mapping[1], 2);
     assert_eq!(mapping[2], 3);
 
@@ -789,7 +1024,7 @@ fn persistent_mapping_forget_then_read() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
@@ -816,7 +1051,7 @@ fn persistent_mapping_forget_then_invalidate() {
                                                  .unwrap();
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping[0] = 3;
         mem::forget(mapping);
     }
@@ -826,112 +1061,346 @@ fn persistent_mapping_forget_then_invalidate() {
 }
 
 #[test]
-fn dynamic_mapping_forget_then_draw() {
+fn coherent_only_if_requested() {
     let display = support::build_display();
 
-    #[derive(Copy, Clone)]
-    struct Vertex {
-        position: [f32; 2]
-    }
-
-    implement_vertex!(Vertex, position);
-
-    let mut vb = glium::VertexBuffer::dynamic(&display,
-        &[
-            Vertex { position: [-1.0,  1.0] },
-            Vertex { position: [ 1.0,  1.0] },
-            Vertex { position: [-1.0, -1.0] },
-            Vertex { position: [ 1.0, -1.0] },
-        ]
-    ).unwrap();
+    for &mode in &[BufferMode::Default, BufferMode::Dynamic, BufferMode::Immutable,
+                   BufferMode::Persistent, BufferMode::ImmutableClientStorage,
+                   BufferMode::PersistentClientStorage, BufferMode::PersistentCoherent]
+    {
+        let buf = glium::buffer::BufferView::new(&display, &[1, 2, 3],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 mode).unwrap();
 
-    let program = program!(&display,
-        140 => {
-            vertex: "
-                #version 140
+        assert_eq!(buf.is_coherent(),
+                   buf.is_persistent() && mode == BufferMode::PersistentCoherent);
+    }
 
-                in vec2 position;
+    display.assert_no_error(None);
+}
 
-                void main() {
-                    gl_Position = vec4(position, 0.0, 1.0);
-                }
-            ",
-            fragment: "
-                #version 140
+#[test]
+fn persistent_if_supported() {
+    let display = support::build_display();
 
-                out vec4 color;
-                void main() {
-                    color = vec4(1.0, 0.0, 0.0, 1.0);
-                }
-            ",
-        },
-        110 => {
-            vertex: "
-                #version 110
+    let buf = glium::buffer::BufferView::new(&display, &[1, 2, 3],
+                                             glium::buffer::BufferType::ArrayBuffer,
+                                             BufferMode::Persistent).unwrap();
 
-                attribute vec2 position;
+    assert_eq!(buf.is_persistent(), glium::buffer::is_persistent_mapping_supported(&display));
 
-                void main() {
-                    gl_Position = vec4(position, 0.0, 1.0);
-                }
-            ",
-            fragment: "
-                #version 110
+    display.assert_no_error(None);
+}
 
-                void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
-                }
-            ",
-        },
-        100 => {
-            vertex: "
-                #version 100
+#[test]
+fn client_storage() {
+    let display = support::build_display();
 
-                attribute lowp vec2 position;
+    let mut persistent = glium::buffer::Buffer::new(&display, &[1u32, 2, 3],
+                                                    glium::buffer::BufferType::ArrayBuffer,
+                                                    BufferMode::PersistentClientStorage).unwrap();
+    assert_eq!(persistent.is_persistent(),
+               glium::buffer::is_persistent_mapping_supported(&display));
+    persistent.map().unwrap()[1] = 5;
+    assert_eq!(*persistent.map_read().unwrap(), [1, 5, 3]);
+
+    let immutable = glium::buffer::Buffer::new(&display, &[1u32, 2, 3],
+                                               glium::buffer::BufferType::ArrayBuffer,
+                                               BufferMode::ImmutableClientStorage).unwrap();
+    immutable.write(&[4, 5, 6]);
+    if let Ok(data) = immutable.read() {
+        assert_eq!(data, [4, 5, 6]);
+    }
 
-                void main() {
-                    gl_Position = vec4(position, 0.0, 1.0);
-                }
-            ",
-            fragment: "
-                #version 100
+    display.assert_no_error(None);
+}
 
-                void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
-                }
-            ",
-        },
-    ).unwrap();
+#[test]
+fn sparse_commit_then_write() {
+    let display = support::build_display();
 
+    let buf: glium::buffer::Buffer<[u32]> = match glium::buffer::Buffer::empty_array_sparse_if_supported(
+        &display, glium::buffer::BufferType::ArrayBuffer, 1 << 20)
     {
-        let mapping = vb.map();
-        mem::forget(mapping);
-    }
+        Some(b) => b,
+        None => return
+    };
 
-    let texture = support::build_renderable_texture(&display);
-    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw(&vb, &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
-                              &program, &uniform!{}, &Default::default()).unwrap();
+    assert!(buf.is_sparse());
+    let page = buf.get_sparse_page_size().unwrap() / 4;
 
-    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    for pixel in data.iter().flat_map(|e| e.iter()) {
-        assert_eq!(*pixel, (255, 0, 0, 255));
-    }
+    buf.commit_range(0 .. page);
+    buf.slice(0 .. 3).unwrap().write(&[1, 2, 3]);
+    assert_eq!(&buf.slice(0 .. 3).unwrap().read().unwrap(), &[1, 2, 3]);
+    buf.decommit_range(0 .. page);
 
     display.assert_no_error(None);
 }
 
 #[test]
-fn copy_to() {
+fn read_ranges() {
     let display = support::build_display();
 
-    let buf1 = glium::buffer::BufferView::new(&display, &[1, 2, 3],
-                                              glium::buffer::BufferType::ArrayBuffer,
-                                              BufferMode::Persistent);
-    let buf1 = if let Ok(buf) = buf1 { buf } else { return };
+    let buf = glium::buffer::Buffer::<[u32]>::new(&display, &[0u32, 1, 2, 3, 4, 5, 6, 7][..],
+                                                  glium::buffer::BufferType::ArrayBuffer,
+                                                  glium::buffer::BufferMode::Default).unwrap();
 
-    let buf2 = glium::buffer::BufferView::new(&display, &[0, 0, 0],
-                                              glium::buffer::BufferType::ArrayBuffer,
+    match buf.read_ranges(&[0 .. 2, 7 .. 9]) {
+        Err(glium::buffer::ReadError::OutOfRange) => (),
+        r => panic!("{:?}", r)
+    }
+
+    let data = match buf.read_ranges(&[6 .. 8, 1 .. 3, 4 .. 4]) {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    assert_eq!(data, vec![vec![6, 7], vec![1, 2], vec![]]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn write_from_slice_like_sources() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::Buffer::<[u32]>::new(&display, &[0u32; 3][..],
+                                                  glium::buffer::BufferType::ArrayBuffer,
+                                                  glium::buffer::BufferMode::Default).unwrap();
+
+    buf.write(&[1, 2, 3]);
+    assert_eq!(buf.read().unwrap(), vec![1, 2, 3]);
+
+    buf.write(&vec![4, 5, 6]);
+    assert_eq!(buf.read().unwrap(), vec![4, 5, 6]);
+
+    buf.write(vec![10, 11, 12]);
+    assert_eq!(buf.read().unwrap(), vec![10, 11, 12]);
+
+    let data = [0, 7, 8, 9];
+    buf.write(&data[1 ..]);
+    assert_eq!(buf.read().unwrap(), vec![7, 8, 9]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_array_too_large() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::Buffer::<[u32]>::empty_array(&display,
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          ::std::usize::MAX / 2,
+                                                          BufferMode::Default);
+
+    match buf {
+        Err(glium::buffer::BufferCreationError::FormatTooLarge) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_array_size_boundary() {
+    let display = support::build_display();
+
+    // one byte more than what a `GLsizeiptr` can hold
+    let buf = glium::buffer::Buffer::<[u8]>::empty_array(&display,
+                                                         glium::buffer::BufferType::ArrayBuffer,
+                                                         ::std::isize::MAX as usize + 1,
+                                                         BufferMode::Default);
+    match buf {
+        Err(glium::buffer::BufferCreationError::FormatTooLarge) => (),
+        _ => panic!()
+    }
+
+    let buf = glium::buffer::Buffer::<[u32]>::empty_array(&display,
+                                                          glium::buffer::BufferType::ArrayBuffer,
+                                                          ::std::isize::MAX as usize / 4 + 1,
+                                                          BufferMode::Default);
+    match buf {
+        Err(glium::buffer::BufferCreationError::FormatTooLarge) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn slice_overflowing_range() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::Buffer::new(&display, &[1u32, 2, 3][..],
+                                         glium::buffer::BufferType::ArrayBuffer,
+                                         BufferMode::Default).unwrap();
+
+    assert!(buf.slice(3 .. 3).is_some());
+    assert!(buf.slice(3 .. 4).is_none());
+    assert!(buf.slice(::std::usize::MAX / 2 ..).is_none());
+    assert!(buf.slice(.. ::std::usize::MAX).is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn try_sparse_error() {
+    let display = support::build_display();
+
+    let buf = glium::buffer::Buffer::<[u32]>::try_empty_array_sparse(
+        &display, glium::buffer::BufferType::ArrayBuffer, 1024);
+
+    match buf {
+        Ok(_) => assert!(glium::buffer::is_sparse_buffer_supported(&display)),
+        Err(glium::buffer::BufferCreationError::NotSupported) => {
+            assert!(!glium::buffer::is_sparse_buffer_supported(&display))
+        },
+        Err(e) => panic!("{:?}", e)
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn sparse_empty() {
+    let display = support::build_display();
+
+    let buf = match glium::buffer::Buffer::<[u32]>::empty_array_sparse_if_supported(
+        &display, glium::buffer::BufferType::ArrayBuffer, 0)
+    {
+        Some(b) => b,
+        None => return
+    };
+
+    assert_eq!(buf.len(), 0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn sparse_larger_than_2gib() {
+    let display = support::build_display();
+
+    // nothing is committed, so this doesn't use any memory
+    let buf = match glium::buffer::Buffer::<[u8]>::empty_array_sparse_if_supported(
+        &display, glium::buffer::BufferType::ArrayBuffer, 3 << 30)
+    {
+        Some(b) => b,
+        None => return
+    };
+
+    assert_eq!(buf.get_size(), 3 << 30);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn dynamic_mapping_forget_then_draw() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2]
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display,
+        &[
+            Vertex { position: [-1.0,  1.0] },
+            Vertex { position: [ 1.0,  1.0] },
+            Vertex { position: [-1.0, -1.0] },
+            Vertex { position: [ 1.0, -1.0] },
+        ]
+    ).unwrap();
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    {
+        let mapping = vb.map().unwrap();
+        mem::forget(mapping);
+    }
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for pixel in data.iter().flat_map(|e| e.iter()) {
+        assert_eq!(*pixel, (255, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn copy_to() {
+    let display = support::build_display();
+
+    let buf1 = glium::buffer::BufferView::new(&display, &[1, 2, 3],
+                                              glium::buffer::BufferType::ArrayBuffer,
+                                              BufferMode::Persistent);
+    let buf1 = if let Ok(buf) = buf1 { buf } else { return };
+
+    let buf2 = glium::buffer::BufferView::new(&display, &[0, 0, 0],
+                                              glium::buffer::BufferType::ArrayBuffer,
                                               BufferMode::Persistent);
     let buf2 = if let Ok(buf) = buf2 { buf } else { return };
 
@@ -976,3 +1445,415 @@ fn copy_to_slice() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn persistent_write_then_draw_other_part() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2]
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut vb = glium::VertexBuffer::empty_persistent(&display, 8).unwrap();
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    for part in 0 .. 2 {
+        let range = part * 4 .. (part + 1) * 4;
+
+        {
+            let mut mapping = vb.slice_mut(range.clone()).unwrap().unwrap();
+            mapping[0] = Vertex { position: [-1.0,  1.0] };
+            mapping[1] = Vertex { position: [ 1.0,  1.0] };
+            mapping[2] = Vertex { position: [-1.0, -1.0] };
+            mapping[3] = Vertex { position: [ 1.0, -1.0] };
+        }
+
+        texture.as_surface().draw(vb.slice(range).unwrap(),
+                                  &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                                  &program, &uniform!{}, &Default::default()).unwrap();
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn map_mut_range_invalidate_while_drawing() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2]
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let quad = [
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [ 1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [ 1.0, -1.0] },
+    ];
+
+    let mut vb = glium::VertexBuffer::persistent(&display, &[quad, quad].concat()).unwrap();
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 140
+
+                out vec4 color;
+                void main() {
+                    color = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    // the first half is still used by this draw when we map it again
+    texture.as_surface().draw(vb.slice(0 .. 4).unwrap(),
+                              &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    {
+        let mut mapping = vb.map_mut_range_invalidate(0 .. 4).unwrap().unwrap();
+        for (index, vertex) in quad.iter().enumerate() {
+            mapping.set(index, *vertex);
+        }
+    }
+
+    texture.as_surface().draw(vb.slice(0 .. 4).unwrap(),
+                              &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    assert_eq!(vb.read().unwrap()[5].position, [1.0, 1.0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_map_then_draw_twice() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let mut vb = match glium::VertexBuffer::empty_persistent(&display, 4) {
+        Ok(vb) => vb,
+        Err(_) => return
+    };
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in vec3 color;
+                out vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 140
+
+                in vec3 v_color;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+    ).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+
+    for &(color, expected) in &[([1.0, 0.0, 0.0], (255, 0, 0, 255)),
+                                ([0.0, 1.0, 0.0], (0, 255, 0, 255))]
+    {
+        // the second mapping must wait for the first draw to be finished
+        {
+            let mut mapping = vb.map().unwrap();
+            mapping[0] = Vertex { position: [-1.0,  1.0], color: color };
+            mapping[1] = Vertex { position: [ 1.0,  1.0], color: color };
+            mapping[2] = Vertex { position: [-1.0, -1.0], color: color };
+            mapping[3] = Vertex { position: [ 1.0, -1.0], color: color };
+        }
+
+        texture.as_surface().draw(&vb,
+                                  &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                                  &program, &uniform!{}, &Default::default()).unwrap();
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+        assert_eq!(data[0][0], expected);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn try_map_after_finish() {
+    let display = support::build_display();
+
+    let mut buf = glium::buffer::BufferView::new(&display, &[1u32, 2, 3][..],
+                                                 glium::buffer::BufferType::ArrayBuffer,
+                                                 BufferMode::Persistent).unwrap();
+
+    {
+        let mut mapping = buf.try_map().unwrap().unwrap();
+        mapping[1] = 5;
+    }
+
+    // once all the commands are finished, the buffer is never in use
+    display.finish();
+    assert_eq!(&*buf.try_map().unwrap().unwrap(), &[1, 5, 3]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_readback_latest() {
+    let display = support::build_display();
+
+    let mut readback = match glium::buffer::PersistentReadback::<u32>::new(&display,
+                                                glium::buffer::BufferType::ArrayBuffer, 4, 3)
+    {
+        Ok(r) => r,
+        Err(glium::buffer::BufferCreationError::NotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert!(readback.latest().is_none());
+
+    readback.write_frame(|target| target.write(&[1, 2, 3, 4]));
+    readback.write_frame(|target| target.write(&[5, 6, 7, 8]));
+    display.finish();
+
+    assert_eq!(&*readback.latest().unwrap(), &[5, 6, 7, 8]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn persistent_readback_gpu_write() {
+    let display = support::build_display();
+
+    let mut readback = match glium::buffer::PersistentReadback::<u32>::new(&display,
+                                                glium::buffer::BufferType::ArrayBuffer, 4, 3)
+    {
+        Ok(r) => r,
+        Err(glium::buffer::BufferCreationError::NotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    };
+
+    let source = glium::buffer::Buffer::new(&display, &[9u32, 10, 11, 12][..],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            BufferMode::Default).unwrap();
+
+    // the copy is done by the GPU and must be visible through the persistent mapping
+    let mut copied = true;
+    readback.write_frame(|target| copied = source.copy_to(target).is_ok());
+    if !copied {
+        return;
+    }
+    display.finish();
+
+    assert_eq!(&*readback.latest().unwrap(), &[9, 10, 11, 12]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn buffer_any_raw_bind() {
+    let display = support::build_display();
+
+    // GL_ARRAY_BUFFER and GL_COPY_READ_BUFFER
+    const ARRAY_BUFFER: u32 = 0x8892;
+    const COPY_READ_BUFFER: u32 = 0x8F36;
+
+    let vb: glium::vertex::VertexBufferAny = glium::VertexBuffer::positions_2d(&display, &[
+        [1.0, 2.0], [3.0, 4.0]
+    ]).unwrap().into();
+
+    unsafe {
+        vb.bind(ARRAY_BUFFER);
+        vb.unbind(ARRAY_BUFFER);
+        vb.bind(COPY_READ_BUFFER);
+    }
+
+    // glium must rebind the buffers it uses itself
+    let buf1 = glium::buffer::BufferView::new(&display, &[1, 2, 3],
+                                              glium::buffer::BufferType::ArrayBuffer,
+                                              BufferMode::Default).unwrap();
+    let buf2 = glium::buffer::BufferView::new(&display, &[0, 0, 0],
+                                              glium::buffer::BufferType::ArrayBuffer,
+                                              BufferMode::Default).unwrap();
+
+    if buf1.copy_to(buf2.as_slice()).is_ok() {
+        if let Ok(result) = buf2.read() {
+            assert_eq!(result, [1, 2, 3]);
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn buffer_any_raw_bind_element_array() {
+    let display = support::build_display();
+
+    let vb: glium::vertex::VertexBufferAny = glium::VertexBuffer::positions(&display, &[
+        [0.0, 0.0, 0.0]
+    ]).unwrap().into();
+
+    // GL_ELEMENT_ARRAY_BUFFER
+    unsafe { vb.bind(0x8893); }
+}
@@ -0,0 +1,50 @@
+/*!
+
+Runs buffer fills against dummy OpenGL 3.3 functions that support `glClearBufferData`.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::GlObject;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+
+mod dummy;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"3.3.0\0", &[b"GL_ARB_clear_buffer_object\0"])
+}
+
+#[test]
+fn fill() {
+    let context = build_context();
+
+    let buffer = Buffer::<[u16]>::empty_unsized(&context, BufferType::ArrayBuffer, 8,
+                                                BufferMode::Default).unwrap();
+    buffer.fill(0x0102);
+
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![2, 1, 2, 1, 2, 1, 2, 1]);
+    context.assert_no_error(None);
+}
+
+#[test]
+fn fill_empty() {
+    let context = build_context();
+
+    let buffer = Buffer::<[u32]>::empty_unsized(&context, BufferType::ArrayBuffer, 0,
+                                                BufferMode::Default).unwrap();
+    buffer.fill(5);
+
+    context.assert_no_error(None);
+}
+
+#[test]
+fn fill_zero_sized() {
+    let context = build_context();
+
+    let buffer = Buffer::new(&context, &[(); 4][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+    buffer.fill(());
+
+    context.assert_no_error(None);
+}
@@ -0,0 +1,114 @@
+/*!
+
+Runs the fence checks of persistent-mapped buffers against dummy OpenGL 3.3 functions.
+
+*/
+#[macro_use]
+extern crate glium;
+extern crate libc;
+
+use glium::Surface;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+use glium::index::{NoIndices, PrimitiveType};
+
+mod dummy;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"3.3.0\0", &[b"GL_ARB_buffer_storage\0"])
+}
+
+/// Builds a persistent-mapped buffer that is used by a copy command, so that it has a fence.
+fn build_fenced_buffer(context: &std::rc::Rc<glium::backend::Context>) -> Buffer<[u32]> {
+    let buffer = Buffer::new(context, &[1u32, 2, 3][..], BufferType::ArrayBuffer,
+                             BufferMode::Persistent).unwrap();
+    let target = Buffer::<[u32]>::empty_unsized(context, BufferType::ArrayBuffer, 12,
+                                                BufferMode::Default).unwrap();
+    buffer.copy_to(&target).unwrap();
+    buffer
+}
+
+#[test]
+fn try_map_in_use() {
+    let context = build_context();
+    let mut buffer = build_fenced_buffer(&context);
+
+    dummy::set_client_wait_result(dummy::TIMEOUT_EXPIRED);
+    assert!(buffer.try_map().unwrap().is_none());
+
+    dummy::set_client_wait_result(dummy::ALREADY_SIGNALED);
+    assert_eq!(&*buffer.try_map().unwrap().unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+#[should_panic]
+fn try_map_wait_failed() {
+    let context = build_context();
+    let mut buffer = build_fenced_buffer(&context);
+
+    dummy::set_client_wait_result(dummy::WAIT_FAILED);
+    let _ = buffer.try_map();
+}
+
+#[test]
+fn auto_fence_disabled() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let context = build_context();
+    let program = glium::Program::from_source(&context, "", "", None).unwrap();
+    let mut vertex_buffer = glium::VertexBuffer::persistent(&context, &[
+        Vertex { position: [0.0, 0.0] }, Vertex { position: [1.0, 0.0] },
+        Vertex { position: [0.0, 1.0] },
+    ]).unwrap();
+
+    // the GPU never finishes, so the buffer can only be mapped if no fence was inserted
+    dummy::set_client_wait_result(dummy::TIMEOUT_EXPIRED);
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+
+    unsafe { context.set_auto_fence(false) };
+    frame.draw(&vertex_buffer, NoIndices(PrimitiveType::TrianglesList), &program,
+               &uniform!{}, &Default::default()).unwrap();
+    assert!(vertex_buffer.try_map().unwrap().is_some());
+
+    unsafe { context.set_auto_fence(true) };
+    frame.draw(&vertex_buffer, NoIndices(PrimitiveType::TrianglesList), &program,
+               &uniform!{}, &Default::default()).unwrap();
+    assert!(vertex_buffer.try_map().unwrap().is_none());
+
+    dummy::set_client_wait_result(dummy::ALREADY_SIGNALED);
+    frame.finish().unwrap();
+}
+
+#[test]
+fn fill_from_fn_waits_for_draws() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let context = build_context();
+    let program = glium::Program::from_source(&context, "", "", None).unwrap();
+    let mut vertex_buffer = glium::VertexBuffer::<Vertex>::empty_persistent(&context, 3).unwrap();
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    frame.draw(&vertex_buffer, NoIndices(PrimitiveType::TrianglesList), &program,
+               &uniform!{}, &Default::default()).unwrap();
+
+    let waits = dummy::client_wait_count();
+    vertex_buffer.fill_from_fn(|index| Vertex { position: [index as f32, 0.0] }).unwrap();
+    assert!(dummy::client_wait_count() > waits);
+
+    // the fence has been consumed by the wait
+    dummy::set_client_wait_result(dummy::TIMEOUT_EXPIRED);
+    assert!(vertex_buffer.try_map().unwrap().is_some());
+    dummy::set_client_wait_result(dummy::ALREADY_SIGNALED);
+
+    frame.finish().unwrap();
+}
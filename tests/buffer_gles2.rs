@@ -0,0 +1,66 @@
+/*!
+
+Runs buffer mappings against dummy OpenGL ES 2.0 functions, which support neither
+`glMapBufferRange` nor `glGetBufferSubData`.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::GlObject;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+
+mod dummy;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"OpenGL ES 2.0\0", &[])
+}
+
+#[test]
+fn map_without_map_buffer_range() {
+    let context = build_context();
+
+    let mut buffer = Buffer::new(&context, &[1u8, 2, 3, 4][..], BufferType::ArrayBuffer,
+                                 BufferMode::Default).unwrap();
+
+    {
+        let mut mapping = buffer.map().unwrap();
+        assert_eq!(&*mapping, &[1, 2, 3, 4]);
+        mapping[1] = 5;
+    }
+
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![1, 5, 3, 4]);
+    assert_eq!(&*buffer.map_read().unwrap(), &[1, 5, 3, 4]);
+}
+
+#[test]
+fn map_without_map_buffer_range_after_write() {
+    let context = build_context();
+
+    let mut buffer = Buffer::new(&context, &[1u8, 2, 3, 4][..], BufferType::ArrayBuffer,
+                                 BufferMode::Default).unwrap();
+
+    buffer.write(&[8, 7, 6, 5][..]);
+    buffer.slice_mut(1 .. 3).unwrap().write(&[0, 0][..]);
+
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![8, 0, 0, 5]);
+    assert_eq!(&*buffer.map_read().unwrap(), &[8, 0, 0, 5]);
+}
+
+#[test]
+fn map_write_without_map_buffer_range() {
+    let context = build_context();
+
+    let mut buffer = Buffer::<[u8]>::empty_unsized(&context, BufferType::ArrayBuffer, 4,
+                                                   BufferMode::Default).unwrap();
+
+    {
+        let mut mapping = buffer.map_write().unwrap();
+        for (index, value) in [4, 3, 2, 1].iter().enumerate() {
+            mapping.set(index, *value);
+        }
+    }
+
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![4, 3, 2, 1]);
+    assert_eq!(&*buffer.map_read().unwrap(), &[4, 3, 2, 1]);
+}
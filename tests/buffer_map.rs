@@ -0,0 +1,65 @@
+/*!
+
+Checks how buffers are mapped, against dummy OpenGL 3.3 functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::GlObject;
+use glium::buffer::{Buffer, BufferMode, BufferType, MapError, ReadError};
+
+mod dummy;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"3.3.0\0", &[b"GL_ARB_buffer_storage\0"])
+}
+
+#[test]
+fn map_failure_returns_error() {
+    let context = build_context();
+
+    let mut buffer = Buffer::new(&context, &[1u8, 2, 3][..], BufferType::ArrayBuffer,
+                                 BufferMode::Dynamic).unwrap();
+
+    dummy::set_map_fails(true);
+    match buffer.map() {
+        Err(MapError::MappingFailed) => (),
+        _ => panic!("mapping should have failed"),
+    }
+    match buffer.map_read() {
+        Err(MapError::MappingFailed) => (),
+        _ => panic!("mapping should have failed"),
+    }
+
+    dummy::set_map_fails(false);
+    assert_eq!(&*buffer.map_read().unwrap(), &[1, 2, 3]);
+}
+
+#[test]
+fn immutable_maps_temporary_buffer() {
+    let context = build_context();
+
+    let mut buffer = Buffer::new(&context, &[1u8, 2, 3][..], BufferType::ArrayBuffer,
+                                 BufferMode::Immutable).unwrap();
+
+    buffer.map().unwrap()[1] = 5;
+    assert!(dummy::last_mapped_buffer() != 0);
+    assert!(dummy::last_mapped_buffer() != buffer.get_id());
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![1, 5, 3]);
+}
+
+#[test]
+fn read_ranges_out_of_range() {
+    let context = build_context();
+
+    let buffer = Buffer::new(&context, &[1u8, 2, 3, 4][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+
+    match buffer.read_ranges(&[0 .. 2, 3 .. 5]) {
+        Err(ReadError::OutOfRange) => (),
+        r => panic!("{:?}", r)
+    }
+
+    assert_eq!(buffer.read_ranges(&[2 .. 4, 0 .. 1]).unwrap(), vec![vec![3, 4], vec![1]]);
+}
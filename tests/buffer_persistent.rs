@@ -0,0 +1,71 @@
+/*!
+
+Checks how persistent-mapped buffers are created, against dummy OpenGL 3.3 functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::buffer::{Buffer, BufferCreationError, BufferMode, BufferType, PersistentReadback};
+
+mod dummy;
+
+/// `GL_MAP_FLUSH_EXPLICIT_BIT`
+const MAP_FLUSH_EXPLICIT_BIT: u32 = 0x0010;
+/// `GL_MAP_COHERENT_BIT`
+const MAP_COHERENT_BIT: u32 = 0x0080;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"3.3.0\0", &[b"GL_ARB_buffer_storage\0"])
+}
+
+#[test]
+fn persistent_not_coherent() {
+    let context = build_context();
+
+    for &mode in &[BufferMode::Persistent, BufferMode::PersistentClientStorage] {
+        let buffer = Buffer::new(&context, &[1u8, 2, 3][..], BufferType::ArrayBuffer,
+                                 mode).unwrap();
+
+        assert!(buffer.is_persistent());
+        assert!(!buffer.is_coherent());
+        assert_eq!(dummy::last_map_flags() & MAP_COHERENT_BIT, 0);
+        assert!(dummy::last_map_flags() & MAP_FLUSH_EXPLICIT_BIT != 0);
+    }
+}
+
+#[test]
+fn persistent_coherent() {
+    let context = build_context();
+
+    let buffer = Buffer::new(&context, &[1u8, 2, 3][..], BufferType::ArrayBuffer,
+                             BufferMode::PersistentCoherent).unwrap();
+
+    assert!(buffer.is_persistent());
+    assert!(buffer.is_coherent());
+    assert!(dummy::last_map_flags() & MAP_COHERENT_BIT != 0);
+    assert_eq!(dummy::last_map_flags() & MAP_FLUSH_EXPLICIT_BIT, 0);
+}
+
+#[test]
+fn persistent_coherent_not_supported() {
+    let context = dummy::build_context(b"3.3.0\0", &[]);
+
+    let buffer = Buffer::new(&context, &[1u8, 2, 3][..], BufferType::ArrayBuffer,
+                             BufferMode::PersistentCoherent).unwrap();
+
+    assert!(!buffer.is_persistent());
+    assert!(!buffer.is_coherent());
+}
+
+#[test]
+fn persistent_readback_too_large() {
+    let context = build_context();
+
+    match PersistentReadback::<u32>::new(&context, BufferType::ArrayBuffer,
+                                         usize::max_value() / 2, 3)
+    {
+        Err(BufferCreationError::FormatTooLarge) => (),
+        _ => panic!("the size of the ring should have overflowed"),
+    }
+}
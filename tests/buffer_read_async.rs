@@ -0,0 +1,27 @@
+/*!
+
+Runs asynchronous reads against dummy OpenGL 3.3 functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::buffer::{Buffer, BufferMode, BufferType, ReadError};
+
+mod dummy;
+
+#[test]
+fn read_async_staging_out_of_memory() {
+    let context = dummy::build_context(b"3.3.0\0", &[]);
+
+    let buffer = Buffer::new(&context, &[1u32, 2, 3][..], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+
+    dummy::set_max_buffer_size(0);
+
+    match buffer.read_async() {
+        Err(ReadError::OutOfMemory) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("the staging buffer was allocated"),
+    }
+}
@@ -0,0 +1,66 @@
+/*!
+
+Runs buffer uploads against dummy OpenGL 3.3 functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::GlObject;
+use glium::buffer::{Buffer, BufferMode, BufferType};
+
+mod dummy;
+
+fn build_context() -> std::rc::Rc<glium::backend::Context> {
+    dummy::build_context(b"3.3.0\0", &[])
+}
+
+#[test]
+fn write_from_vec() {
+    let context = build_context();
+
+    let buffer = Buffer::<[u8]>::empty_array(&context, BufferType::ArrayBuffer, 3,
+                                             BufferMode::Default).unwrap();
+
+    buffer.write(vec![1, 2, 3]);
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![1, 2, 3]);
+
+    let data = vec![4, 5, 6];
+    buffer.write(&data);
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![4, 5, 6]);
+}
+
+#[test]
+fn write_from_array() {
+    let context = build_context();
+
+    let buffer = Buffer::<[u8]>::empty_array(&context, BufferType::ArrayBuffer, 3,
+                                             BufferMode::Default).unwrap();
+
+    buffer.write([7, 8, 9]);
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![7, 8, 9]);
+
+    buffer.write(&[1, 2, 3][..]);
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![1, 2, 3]);
+}
+
+#[test]
+fn write_sized() {
+    let context = build_context();
+
+    let buffer = Buffer::new(&context, &[0u8; 2], BufferType::ArrayBuffer,
+                             BufferMode::Default).unwrap();
+
+    buffer.write(&[3, 4]);
+    assert_eq!(dummy::buffer_content(buffer.get_id()), vec![3, 4]);
+}
+
+#[test]
+#[should_panic]
+fn write_wrong_length() {
+    let context = build_context();
+
+    let buffer = Buffer::<[u8]>::empty_array(&context, BufferType::ArrayBuffer, 3,
+                                             BufferMode::Default).unwrap();
+    buffer.write(vec![1, 2]);
+}
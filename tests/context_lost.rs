@@ -0,0 +1,37 @@
+/*!
+
+Simulates the loss of the context with dummy OpenGL 3.3 functions.
+
+*/
+extern crate glium;
+extern crate libc;
+
+use glium::buffer::{Buffer, BufferCreationError, BufferMode, BufferType};
+
+mod dummy;
+
+#[test]
+fn buffer_creation_context_lost() {
+    let context = dummy::build_context(b"3.3.0\0", &[]);
+    assert!(!context.is_context_lost());
+
+    // the allocation fails and `glGetError` reports that the context was lost
+    dummy::set_max_buffer_size(0);
+    dummy::set_next_error(dummy::CONTEXT_LOST);
+
+    match Buffer::new(&context, &[1u32, 2, 3][..], BufferType::ArrayBuffer, BufferMode::Default) {
+        Err(BufferCreationError::ContextLost) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("the buffer was created"),
+    }
+
+    // the loss is remembered by the context
+    dummy::set_max_buffer_size(isize::max_value());
+    assert!(context.is_context_lost());
+
+    match Buffer::new(&context, &[1u32, 2, 3][..], BufferType::ArrayBuffer, BufferMode::Default) {
+        Err(BufferCreationError::ContextLost) => (),
+        Err(e) => panic!("{:?}", e),
+        Ok(_) => panic!("the buffer was created"),
+    }
+}
@@ -94,6 +94,25 @@ fn sync() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn sync_timeout() {
+    let display = support::build_display();
+
+    let fence = glium::SyncFence::new(&display);
+    if let Ok(fence) = fence {
+        match fence.wait_timeout(1000 * 1000 * 1000) {
+            glium::SyncWaitStatus::AlreadySignaled => (),
+            glium::SyncWaitStatus::ConditionSatisfied => (),
+            s => panic!("{:?}", s)
+        }
+
+        // waiting again on a signaled fence returns immediately
+        assert_eq!(fence.wait_timeout(0), glium::SyncWaitStatus::AlreadySignaled);
+    }
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn multiple_displays() {
     let display1 = support::build_display();
@@ -149,3 +168,386 @@ fn is_context_lost() {
     display.is_context_lost();
     display.assert_no_error(None);
 }
+
+#[test]
+fn context_lost_hook() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+
+    let called = Rc::new(Cell::new(false));
+    {
+        let called = called.clone();
+        display.on_context_lost(move || called.set(true));
+    }
+
+    // the context of the tests is never lost
+    assert!(!display.is_context_lost());
+    assert!(!called.get());
+
+    display.remove_context_lost_hook();
+    display.assert_no_error(None);
+}
+
+#[test]
+fn context_lost_hook_called_once() {
+    use std::cell::{Cell, RefCell};
+    use std::os::raw::c_void;
+    use std::rc::Rc;
+    use glium::glutin;
+
+    // a headless context whose buffer swaps always report that the context is lost
+    struct LostBackend(RefCell<Option<glutin::Context<glutin::PossiblyCurrent>>>);
+
+    unsafe impl glium::backend::Backend for LostBackend {
+        fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+            Err(glium::SwapBuffersError::ContextLost)
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+            self.0.borrow().as_ref().unwrap().get_proc_address(symbol) as *const _
+        }
+
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (1, 1)
+        }
+
+        fn is_current(&self) -> bool {
+            self.0.borrow().as_ref().unwrap().is_current()
+        }
+
+        unsafe fn make_current(&self) {
+            let context = self.0.borrow_mut().take().unwrap();
+            *self.0.borrow_mut() = Some(context.make_current().unwrap());
+        }
+    }
+
+    let event_loop = glutin::event_loop::EventLoop::new();
+    let context = glutin::ContextBuilder::new()
+        .build_headless(&event_loop, glutin::dpi::PhysicalSize::new(1, 1))
+        .unwrap();
+    let context = unsafe { context.make_current().unwrap() };
+    let backend = LostBackend(RefCell::new(Some(context)));
+    let context = unsafe {
+        glium::backend::Context::new(backend, true, Default::default()).unwrap()
+    };
+
+    let first = Rc::new(Cell::new(0));
+    let second = Rc::new(Cell::new(0));
+    {
+        let first = first.clone();
+        let second = second.clone();
+        let weak = Rc::downgrade(&context);
+
+        // replacing the hook from inside the hook must not panic
+        context.on_context_lost(move || {
+            first.set(first.get() + 1);
+            let second = second.clone();
+            weak.upgrade().unwrap().on_context_lost(move || second.set(second.get() + 1));
+        });
+    }
+
+    assert!(!context.is_context_lost());
+    assert_eq!(first.get(), 0);
+
+    assert!(context.swap_buffers().is_err());
+    assert_eq!(first.get(), 1);
+
+    // the loss has already been reported
+    assert!(context.swap_buffers().is_err());
+    assert!(context.is_context_lost());
+    assert_eq!(first.get(), 1);
+    assert_eq!(second.get(), 0);
+}
+
+#[test]
+fn buffer_alloc_hook() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let display = support::build_display();
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    {
+        let events = events.clone();
+        display.on_buffer_alloc(move |_, old, new| events.borrow_mut().push((old, new)));
+    }
+
+    let buffer = glium::buffer::Buffer::new(&display, &[0u32; 4],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+    assert_eq!(*events.borrow(), vec![(0, 16)]);
+
+    drop(buffer);
+    assert_eq!(*events.borrow(), vec![(0, 16), (16, 0)]);
+
+    display.remove_buffer_alloc_hook();
+    let _buffer = glium::buffer::Buffer::new(&display, &[0u32; 2],
+                                             glium::buffer::BufferType::ArrayBuffer,
+                                             glium::buffer::BufferMode::Default).unwrap();
+    assert_eq!(events.borrow().len(), 2);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn features_match_helpers() {
+    let display = support::build_display();
+
+    let features = *display.get_features();
+    assert_eq!(features, *display.get_features());
+    assert_eq!(features.persistent_mapping,
+               glium::buffer::is_persistent_mapping_supported(&display));
+    assert_eq!(features.compute_shaders, glium::program::ComputeShader::is_supported(&display));
+    assert_eq!(features.transform_feedback,
+               glium::vertex::is_transform_feedback_supported(&display));
+    assert_eq!(features.buffer_textures,
+               glium::texture::buffer_texture::is_buffer_texture_supported(&display));
+    assert_eq!(features.buffer_storage, glium::buffer::is_buffer_storage_supported(&display));
+    assert_eq!(features.instancing, glium::vertex::is_instancing_supported(&display));
+
+    let buffer = glium::VertexBuffer::positions_2d(&display, &[[0.0, 0.0]]).unwrap();
+    assert_eq!(features.instancing, buffer.per_instance().is_ok());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vao_cache_limit() {
+    let display = support::build_display();
+    display.set_vao_cache_limit(Some(2));
+
+    let texture = support::build_renderable_texture(&display);
+    let pipelines = (0 .. 3).map(|_| support::build_fullscreen_red_pipeline(&display))
+                            .collect::<Vec<_>>();
+
+    for &(ref vb, ref ib, ref program) in pipelines.iter() {
+        texture.as_surface().draw(vb, ib, program, &uniform!{}, &Default::default()).unwrap();
+        assert!(display.vao_cache_len() <= 2);
+    }
+
+    display.clear_vao_cache();
+    assert_eq!(display.vao_cache_len(), 0);
+
+    // the VAOs are rebuilt when needed
+    let (ref vb, ref ib, ref program) = pipelines[0];
+    texture.as_surface().draw(vb, ib, program, &uniform!{}, &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vao_shared_between_programs() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let (vb, ib, program1) = support::build_fullscreen_red_pipeline(&display);
+    let (_, _, program2) = support::build_fullscreen_red_pipeline(&display);
+
+    for program in &[&program1, &program2] {
+        texture.as_surface().draw(&vb, &ib, program, &uniform!{}, &Default::default()).unwrap();
+    }
+
+    // both programs only have a `position` attribute, the second draw must reuse the VAO
+    // of the first one if its location is the same
+    if program1.get_attribute("position").unwrap().location ==
+       program2.get_attribute("position").unwrap().location
+    {
+        assert!(display.vao_cache_len() <= 1);
+    }
+
+    // destroying one of the programs must not break the VAO of the other one
+    drop(program1);
+    texture.as_surface().draw(&vb, &ib, &program2, &uniform!{}, &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vao_not_shared_between_binding_indices() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    let source: glium::vertex::VerticesSource = (&vb).into();
+    texture.as_surface().draw(source.clone(), &ib, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    let count = display.vao_cache_len();
+
+    // the same buffer with another binding index must not reuse the VAO
+    texture.as_surface().draw(source.with_binding_index(1), &ib, &program, &uniform!{},
+                              &Default::default()).unwrap();
+    if count != 0 {
+        assert_eq!(display.vao_cache_len(), count + 1);
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn dump_vao_cache() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    let entries = display.dump_vao_cache();
+    if !cfg!(debug_assertions) {
+        assert!(entries.is_empty());
+        return;
+    }
+
+    assert_eq!(entries.len(), display.vao_cache_len());
+    for entry in entries.iter() {
+        let position = entry.attributes.iter().find(|a| {
+            a.location == program.get_attribute("position").unwrap().location
+        });
+        assert!(position.is_some());
+        assert!(entry.vertex_buffers.iter().any(|&(b, _)| b == position.unwrap().buffer));
+    }
+
+    display.clear_vao_cache();
+    assert!(display.dump_vao_cache().is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn prewarm_vao() {
+    let display = support::build_display();
+
+    let texture = support::build_renderable_texture(&display);
+    let (vb, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    display.prewarm_vao(&vb, &ib, &program, &[]).unwrap();
+    let count = display.vao_cache_len();
+    assert!(count <= 1);
+
+    // prewarming again or drawing reuses the VAO
+    display.prewarm_vao(&vb, &ib, &program, &[]).unwrap();
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+    assert_eq!(display.vao_cache_len(), count);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn prewarm_vao_constant_attribute() {
+    let display = support::build_display();
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+                varying vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec4 color;
+                varying lowp vec4 v_color;
+
+                void main() {
+                    v_color = color;
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec4 v_color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            "
+        }
+    ).unwrap();
+
+    match display.prewarm_vao(&vb, &ib, &program, &[]) {
+        Err(glium::DrawError::AttributeMissing) => (),
+        r => panic!("{:?}", r)
+    };
+
+    let constants = [("color", glium::draw_parameters::ConstantAttribute::Float([1.0, 0.0, 0.0, 1.0]))];
+    display.prewarm_vao(&vb, &ib, &program, &constants).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn max_texture_sizes() {
+    let display = support::build_display();
+
+    // every version of OpenGL and OpenGL ES allows at least 64 texels
+    assert!(display.max_texture_size() >= 64);
+
+    if let Some(size) = display.max_3d_texture_size() {
+        assert!(size >= 16);
+    }
+
+    if let Some(layers) = display.max_array_texture_layers() {
+        assert!(layers >= 1);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn auto_fence_disabled() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let display = support::build_display();
+    assert!(display.is_auto_fence_enabled());
+
+    let texture = support::build_renderable_texture(&display);
+    let (_, ib, program) = support::build_fullscreen_red_pipeline(&display);
+
+    // only the buffers that use persistent mapping get fences
+    let vertices = [
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ];
+    let vb = glium::VertexBuffer::persistent(&display, &vertices).unwrap();
+
+    unsafe { display.set_auto_fence(false) };
+    assert!(!display.is_auto_fence_enabled());
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    // the write doesn't wait for the draw command, so we synchronize manually
+    display.finish();
+    vb.write(&vertices);
+
+    unsafe { display.set_auto_fence(true) };
+    texture.as_surface().draw(&vb, &ib, &program, &uniform!{}, &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
@@ -893,8 +893,9 @@ fn provoking_vertex_first() {
         });
 
     match res {
-        Ok(_) => (),
+        Ok(_) => assert!(glium::draw_parameters::is_provoking_vertex_supported(&display)),
         Err(glium::DrawError::ProvokingVertexNotSupported) => {
+            assert!(!glium::draw_parameters::is_provoking_vertex_supported(&display));
             display.assert_no_error(None);
             return;
         },
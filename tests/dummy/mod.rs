@@ -0,0 +1,502 @@
+/*!
+Dummy OpenGL functions, for the tests that check how glium drives the backend.
+
+Contrary to the `support` module, this doesn't need a GPU. Buffers are stored in memory and
+fences report the status set with `set_client_wait_result`.
+
+*/
+
+#![allow(dead_code)]
+
+use glium;
+use libc;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+
+/// `GL_ALREADY_SIGNALED`
+pub const ALREADY_SIGNALED: u32 = 0x911A;
+/// `GL_TIMEOUT_EXPIRED`
+pub const TIMEOUT_EXPIRED: u32 = 0x911B;
+/// `GL_WAIT_FAILED`
+pub const WAIT_FAILED: u32 = 0x911D;
+/// `GL_CONTEXT_LOST`
+pub const CONTEXT_LOST: u32 = 0x0507;
+
+thread_local! {
+    /// Value returned by `glGetString(GL_VERSION)`.
+    static VERSION: Cell<&'static [u8]> = Cell::new(b"\0");
+
+    /// Values returned by `glGetStringi(GL_EXTENSIONS, ..)`.
+    static EXTENSIONS: Cell<&'static [&'static [u8]]> = Cell::new(&[]);
+
+    /// Content of the buffers, indexed by their name.
+    static BUFFERS: RefCell<HashMap<u32, Vec<u8>>> = RefCell::new(HashMap::new());
+
+    /// Buffer bound to each bind point.
+    static BINDINGS: RefCell<HashMap<u32, u32>> = RefCell::new(HashMap::new());
+
+    /// Value returned by `glClientWaitSync`.
+    static CLIENT_WAIT_RESULT: Cell<u32> = Cell::new(ALREADY_SIGNALED);
+
+    /// Access flags passed to the last call to `glMapBufferRange`.
+    static LAST_MAP_FLAGS: Cell<u32> = Cell::new(0);
+
+    /// Buffer mapped by the last call to `glMapBufferRange`.
+    static LAST_MAPPED_BUFFER: Cell<u32> = Cell::new(0);
+
+    /// If true, `glMapBufferRange` returns a null pointer.
+    static MAP_FAILS: Cell<bool> = Cell::new(false);
+
+    /// Number of calls to `glClientWaitSync`.
+    static CLIENT_WAIT_COUNT: Cell<usize> = Cell::new(0);
+
+    /// Value returned by the next call to `glGetError`.
+    static NEXT_ERROR: Cell<u32> = Cell::new(0);
+
+    /// Maximum size of a buffer. Larger allocations fail like when out of memory.
+    static MAX_BUFFER_SIZE: Cell<isize> = Cell::new(isize::max_value());
+}
+
+/// Builds a context with dummy OpenGL functions. `version` and `extensions` are the
+/// nul-terminated strings returned by `glGetString(GL_VERSION)` and `glGetStringi`.
+pub fn build_context(version: &'static [u8], extensions: &'static [&'static [u8]])
+                     -> Rc<glium::backend::Context>
+{
+    VERSION.with(|v| v.set(version));
+    EXTENSIONS.with(|e| e.set(extensions));
+
+    unsafe {
+        glium::backend::Context::new::<_>(DummyBackend, false, Default::default()).unwrap()
+    }
+}
+
+/// Returns the content of a buffer as seen by the dummy driver.
+pub fn buffer_content(id: u32) -> Vec<u8> {
+    BUFFERS.with(|buffers| buffers.borrow()[&id].clone())
+}
+
+/// Sets the value that `glClientWaitSync` returns from now on.
+pub fn set_client_wait_result(result: u32) {
+    CLIENT_WAIT_RESULT.with(|r| r.set(result));
+}
+
+/// Returns the access flags passed to the last call to `glMapBufferRange`.
+pub fn last_map_flags() -> u32 {
+    LAST_MAP_FLAGS.with(|f| f.get())
+}
+
+/// Returns the name of the buffer mapped by the last call to `glMapBufferRange`.
+pub fn last_mapped_buffer() -> u32 {
+    LAST_MAPPED_BUFFER.with(|b| b.get())
+}
+
+/// Makes `glMapBufferRange` return a null pointer from now on if `fails` is true.
+pub fn set_map_fails(fails: bool) {
+    MAP_FAILS.with(|m| m.set(fails));
+}
+
+/// Returns the number of calls to `glClientWaitSync` so far.
+pub fn client_wait_count() -> usize {
+    CLIENT_WAIT_COUNT.with(|c| c.get())
+}
+
+/// Sets the value that the next call to `glGetError` returns. The following calls return
+/// `GL_NO_ERROR`.
+pub fn set_next_error(error: u32) {
+    NEXT_ERROR.with(|e| e.set(error));
+}
+
+/// Makes the allocation of buffers larger than `size` bytes fail from now on.
+pub fn set_max_buffer_size(size: isize) {
+    MAX_BUFFER_SIZE.with(|m| m.set(size));
+}
+
+fn bound_buffer(target: u32) -> u32 {
+    BINDINGS.with(|bindings| bindings.borrow()[&target])
+}
+
+fn with_bound_buffer<R, F>(target: u32, f: F) -> R where F: FnOnce(&mut Vec<u8>) -> R {
+    let id = bound_buffer(target);
+    BUFFERS.with(|buffers| f(buffers.borrow_mut().get_mut(&id).unwrap()))
+}
+
+struct DummyBackend;
+
+unsafe impl glium::backend::Backend for DummyBackend {
+    fn swap_buffers(&self) -> Result<(), glium::SwapBuffersError> {
+        Ok(())
+    }
+
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const libc::c_void {
+        match symbol {
+            "glAttachShader" => {
+                extern "system" fn attach(_: u32, _: u32) {}
+                attach as *const _
+            },
+
+            "glBindBuffer" => {
+                extern "system" fn bind_buffer(target: u32, id: u32) {
+                    BINDINGS.with(|bindings| bindings.borrow_mut().insert(target, id));
+                }
+                bind_buffer as *const _
+            },
+
+            "glBindTexture" | "glBindFramebuffer" | "glBindVertexArray" => {
+                extern "system" fn bind(_: u32, _: u32) {}
+                bind as *const _
+            },
+
+            "glBufferData" => {
+                extern "system" fn buffer_data(target: u32, size: isize, data: *const u8, _: u32) {
+                    if size > MAX_BUFFER_SIZE.with(|m| m.get()) {
+                        return;
+                    }
+
+                    let new_content = if data.is_null() {
+                        vec![0; size as usize]
+                    } else {
+                        unsafe { slice::from_raw_parts(data, size as usize).to_vec() }
+                    };
+
+                    with_bound_buffer(target, |content| *content = new_content);
+                }
+                buffer_data as *const _
+            },
+
+            "glBufferStorage" => {
+                extern "system" fn buffer_storage(target: u32, size: isize, data: *const u8,
+                                                  _: u32)
+                {
+                    if size > MAX_BUFFER_SIZE.with(|m| m.get()) {
+                        return;
+                    }
+
+                    let new_content = if data.is_null() {
+                        vec![0; size as usize]
+                    } else {
+                        unsafe { slice::from_raw_parts(data, size as usize).to_vec() }
+                    };
+
+                    with_bound_buffer(target, |content| *content = new_content);
+                }
+                buffer_storage as *const _
+            },
+
+            "glBufferSubData" => {
+                extern "system" fn buffer_sub_data(target: u32, offset: isize, size: isize,
+                                                   data: *const u8)
+                {
+                    let data = unsafe { slice::from_raw_parts(data, size as usize) };
+                    let range = offset as usize .. (offset + size) as usize;
+                    with_bound_buffer(target, |content| content[range].copy_from_slice(data));
+                }
+                buffer_sub_data as *const _
+            },
+
+            "glClear" => {
+                extern "system" fn clear(_: u32) {}
+                clear as *const _
+            },
+
+            "glClearBufferData" => {
+                extern "system" fn clear_buffer_data(target: u32, _: u32, format: u32, ty: u32,
+                                                     data: *const u8)
+                {
+                    let components = match format {
+                        0x8D94 /* GL_RED_INTEGER */ => 1,
+                        0x8228 /* GL_RG_INTEGER */ => 2,
+                        0x8D98 /* GL_RGB_INTEGER */ => 3,
+                        0x8D99 /* GL_RGBA_INTEGER */ => 4,
+                        _ => unreachable!()
+                    };
+                    let len = components * match ty {
+                        0x1401 /* GL_UNSIGNED_BYTE */ => 1,
+                        0x1403 /* GL_UNSIGNED_SHORT */ => 2,
+                        0x1405 /* GL_UNSIGNED_INT */ => 4,
+                        _ => unreachable!()
+                    };
+                    let pattern = unsafe { slice::from_raw_parts(data, len) };
+
+                    with_bound_buffer(target, |content| {
+                        if content.len() % len != 0 {
+                            // GL_INVALID_VALUE
+                            NEXT_ERROR.with(|e| e.set(0x0501));
+                            return;
+                        }
+
+                        for chunk in content.chunks_mut(len) {
+                            chunk.copy_from_slice(pattern);
+                        }
+                    });
+                }
+                clear_buffer_data as *const _
+            },
+
+            "glClearColor" => {
+                extern "system" fn clear_color(_: f32, _: f32, _: f32, _: f32) {}
+                clear_color as *const _
+            },
+
+            "glClientWaitSync" => {
+                extern "system" fn client_wait_sync(_: *const (), _: u32, _: u64) -> u32 {
+                    CLIENT_WAIT_COUNT.with(|c| c.set(c.get() + 1));
+                    CLIENT_WAIT_RESULT.with(|r| r.get())
+                }
+                client_wait_sync as *const _
+            },
+
+            "glCompileShader" => {
+                extern "system" fn compile(_: u32) {}
+                compile as *const _
+            },
+
+            "glCopyBufferSubData" => {
+                extern "system" fn copy_buffer_sub_data(read_target: u32, write_target: u32,
+                                                        read_offset: isize, write_offset: isize,
+                                                        size: isize)
+                {
+                    let read = read_offset as usize .. (read_offset + size) as usize;
+                    let data = with_bound_buffer(read_target, |content| content[read].to_vec());
+                    let write = write_offset as usize .. (write_offset + size) as usize;
+                    with_bound_buffer(write_target, |content| {
+                        content[write].copy_from_slice(&data)
+                    });
+                }
+                copy_buffer_sub_data as *const _
+            },
+
+            "glDeleteBuffers" | "glDeleteFramebuffers" | "glDeleteTextures" |
+            "glDeleteVertexArrays" => {
+                extern "system" fn delete_mult(_: isize, _: *const u32) {}
+                delete_mult as *const _
+            },
+
+            "glCreateProgram" => {
+                extern "system" fn create_program() -> u32 { 1 }
+                create_program as *const _
+            },
+
+            "glCreateShader" => {
+                extern "system" fn create_shader(_: u32) -> u32 { 1 }
+                create_shader as *const _
+            },
+
+            "glDeleteProgram" | "glDeleteShader" => {
+                extern "system" fn delete(_: u32) {}
+                delete as *const _
+            },
+
+            "glDeleteSync" => {
+                extern "system" fn delete_sync(_: *const ()) {}
+                delete_sync as *const _
+            },
+
+            "glDrawArrays" => {
+                extern "system" fn draw_arrays(_: u32, _: i32, _: i32) {}
+                draw_arrays as *const _
+            },
+
+            "glEnable" | "glDisable" => {
+                extern "system" fn enable(_: u32) {}
+                enable as *const _
+            },
+
+            "glFenceSync" => {
+                extern "system" fn fence_sync(_: u32, _: u32) -> *const () {
+                    1 as *const ()
+                }
+                fence_sync as *const _
+            },
+
+            "glFinish" | "glFlush" => {
+                extern "system" fn finish() {}
+                finish as *const _
+            },
+
+            "glFlushMappedBufferRange" => {
+                extern "system" fn flush_mapped_buffer_range(_: u32, _: isize, _: isize) {}
+                flush_mapped_buffer_range as *const _
+            },
+
+            "glGenBuffers" => {
+                extern "system" fn gen_buffers(num: usize, bufs: *mut u32) {
+                    BUFFERS.with(|buffers| {
+                        let mut buffers = buffers.borrow_mut();
+                        for i in 0 .. num {
+                            let id = buffers.len() as u32 + 1;
+                            buffers.insert(id, Vec::new());
+                            unsafe { *bufs.offset(i as isize) = id; }
+                        }
+                    });
+                }
+                gen_buffers as *const _
+            },
+
+            "glGenTextures" | "glGenFramebuffers" | "glGenVertexArrays" => {
+                extern "system" fn gen(num: usize, names: *mut u32) {
+                    for i in 0 .. num { unsafe { *names.offset(i as isize) = 1; } }
+                }
+                gen as *const _
+            },
+
+            "glGetBooleanv" => {
+                extern "system" fn get_booleanv(_: u32, out: *mut u8) {
+                    unsafe { *out = 0; }
+                }
+                get_booleanv as *const _
+            },
+
+            "glGetBufferSubData" => {
+                extern "system" fn get_buffer_sub_data(target: u32, offset: isize, size: isize,
+                                                       data: *mut u8)
+                {
+                    let data = unsafe { slice::from_raw_parts_mut(data, size as usize) };
+                    let range = offset as usize .. (offset + size) as usize;
+                    with_bound_buffer(target, |content| data.copy_from_slice(&content[range]));
+                }
+                get_buffer_sub_data as *const _
+            },
+
+            "glGetBufferParameteriv" => {
+                extern "system" fn get_buf_paramiv(target: u32, param: u32, out: *mut i32) {
+                    match param {
+                        0x8764 /* GL_BUFFER_SIZE */ => unsafe {
+                            *out = with_bound_buffer(target, |content| content.len()) as i32;
+                        },
+                        _ => unsafe { *out = 0; }
+                    }
+                }
+                get_buf_paramiv as *const _
+            },
+
+            "glGetError" => {
+                extern "system" fn get_error() -> u32 {
+                    NEXT_ERROR.with(|e| e.replace(0))
+                }
+                get_error as *const _
+            },
+
+            "glGetFramebufferAttachmentParameteriv" => {
+                extern "system" fn get_fbap(_: u32, _: u32, _: u32, out: *mut i32) {
+                    unsafe { *out = 0; }
+                }
+                get_fbap as *const _
+            },
+
+            "glGetIntegerv" => {
+                extern "system" fn get_integerv(name: u32, out: *mut i32) {
+                    match name {
+                        0x821D /* GL_NUM_EXTENSIONS */ => unsafe {
+                            *out = EXTENSIONS.with(|e| e.get().len()) as i32;
+                        },
+                        0x0D3A /* GL_MAX_VIEWPORT_DIMS */ => unsafe {
+                            *out = 16384;
+                            *out.offset(1) = 16384;
+                        },
+                        0x82DA /* GL_MAX_VERTEX_ATTRIB_BINDINGS */ => unsafe { *out = 16; },
+                        _ => unsafe { *out = 0; },
+                    }
+                }
+                get_integerv as *const _
+            },
+
+            "glGetProgramiv" => {
+                extern "system" fn get_programiv(_: u32, param: u32, out: *mut i32) {
+                    match param {
+                        0x8B82 /* GL_LINK_STATUS */ => unsafe { *out = 1; },
+                        _ => unsafe { *out = 0; }
+                    }
+                }
+                get_programiv as *const _
+            },
+
+            "glGetShaderiv" => {
+                extern "system" fn get_shaderiv(_: u32, param: u32, out: *mut i32) {
+                    match param {
+                        0x8B81 /* GL_COMPILE_STATUS */ => unsafe { *out = 1; },
+                        _ => unsafe { *out = 0; }
+                    }
+                }
+                get_shaderiv as *const _
+            },
+
+            "glGetString" => {
+                extern "system" fn get_string(name: u32) -> *const i8 {
+                    match name {
+                        0x1F02 /* GL_VERSION */ => VERSION.with(|v| v.get()).as_ptr() as *const _,
+                        _ => b"\0".as_ptr() as *const _,
+                    }
+                }
+                get_string as *const _
+            },
+
+            "glGetStringi" => {
+                extern "system" fn get_stringi(_: u32, index: u32) -> *const i8 {
+                    EXTENSIONS.with(|e| e.get()[index as usize]).as_ptr() as *const _
+                }
+                get_stringi as *const _
+            },
+
+            "glLinkProgram" | "glUseProgram" => {
+                extern "system" fn program_op(_: u32) {}
+                program_op as *const _
+            },
+
+            "glMapBufferRange" => {
+                extern "system" fn map_buffer_range(target: u32, offset: isize, _: isize,
+                                                    flags: u32) -> *mut u8
+                {
+                    LAST_MAP_FLAGS.with(|f| f.set(flags));
+                    LAST_MAPPED_BUFFER.with(|b| b.set(bound_buffer(target)));
+
+                    if MAP_FAILS.with(|m| m.get()) {
+                        return ptr::null_mut();
+                    }
+
+                    with_bound_buffer(target, |content| unsafe {
+                        content.as_mut_ptr().offset(offset)
+                    })
+                }
+                map_buffer_range as *const _
+            },
+
+            "glMemoryBarrier" => {
+                extern "system" fn memory_barrier(_: u32) {}
+                memory_barrier as *const _
+            },
+
+            "glShaderSource" => {
+                extern "system" fn shader_source(_: u32, _: isize, _: *const *const i8,
+                                                 _: *const i32) {}
+                shader_source as *const _
+            },
+
+            "glUnmapBuffer" => {
+                extern "system" fn unmap_buffer(_: u32) -> u8 { 1 }
+                unmap_buffer as *const _
+            },
+
+            "glViewport" => {
+                extern "system" fn viewport(_: i32, _: i32, _: i32, _: i32) {}
+                viewport as *const _
+            },
+
+            _name => ptr::null()
+        }
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        (800, 600)
+    }
+
+    fn is_current(&self) -> bool {
+        true
+    }
+
+    unsafe fn make_current(&self) {
+    }
+}
@@ -76,6 +76,46 @@ fn simple_dimensions() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn render_to_mipmap_level() {
+    let display = support::build_display();
+
+    let texture = glium::Texture2d::empty_with_format(&display,
+                                              glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                              glium::texture::MipmapsOption::EmptyMipmapsMax(2),
+                                              128, 128).unwrap();
+    assert!(texture.mipmap(3).is_none());
+
+    let level = texture.mipmap(1).unwrap();
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, level).unwrap();
+    assert_eq!(framebuffer.get_dimensions(), (64, 64));
+
+    framebuffer.clear_color(1.0, 0.0, 0.0, 1.0);
+
+    let image = texture.mipmap(1).unwrap().first_layer().into_image(None).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = image.raw_read(&glium::Rect {
+        left: 0, bottom: 0, width: 64, height: 64
+    });
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn default_framebuffer_dimensions() {
+    let display = support::build_display();
+
+    let frame = display.draw();
+    assert_eq!(frame.get_dimensions(), display.get_framebuffer_dimensions());
+    frame.finish().unwrap();
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn simple_render_to_texture() {
     let display = support::build_display();
@@ -304,6 +344,117 @@ fn cubemap_layer() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn layered_cubemap_instanced() {
+    use glium::texture::CubeLayer;
+
+    let display = support::build_display();
+
+    if !glium::framebuffer::is_layered_supported(&display) {
+        return;
+    }
+
+    let cubemap = match glium::texture::Cubemap::empty(&display, 64) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let (vb, ib, _) = support::build_fullscreen_red_pipeline(&display);
+
+    // each instance is routed to the face whose index is `gl_InstanceID`
+    let program = program!(&display,
+        150 => {
+            vertex: "
+                #version 150
+
+                in vec2 position;
+                flat out int v_layer;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_layer = gl_InstanceID;
+                }
+            ",
+            geometry: "
+                #version 150
+
+                layout(triangles) in;
+                layout(triangle_strip, max_vertices = 3) out;
+
+                flat in int v_layer[];
+                flat out int g_layer;
+
+                void main() {
+                    for (int i = 0; i < 3; i++) {
+                        gl_Layer = v_layer[0];
+                        g_layer = v_layer[0];
+                        gl_Position = gl_in[i].gl_Position;
+                        EmitVertex();
+                    }
+                    EndPrimitive();
+                }
+            ",
+            fragment: "
+                #version 150
+
+                flat in int g_layer;
+                out vec4 f_color;
+
+                void main() {
+                    f_color = vec4(float(g_layer) / 5.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        });
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::layered(&display,
+                                                                         *cubemap.main_level())
+                                                                         .unwrap();
+    assert_eq!(framebuffer.get_dimensions(), (64, 64));
+
+    framebuffer.clear_color(0.0, 0.0, 0.0, 0.0);
+    framebuffer.draw((&vb, glium::vertex::EmptyInstanceAttributes { len: 6 }), &ib, &program,
+                     &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let faces = [CubeLayer::PositiveX, CubeLayer::NegativeX, CubeLayer::PositiveY,
+                 CubeLayer::NegativeY, CubeLayer::PositiveZ, CubeLayer::NegativeZ];
+
+    for (index, &face) in faces.iter().enumerate() {
+        let output = glium::texture::Texture2d::empty(&display, 64, 64).unwrap();
+        let source = glium::framebuffer::SimpleFrameBuffer::new(&display,
+                                                    cubemap.main_level().image(face)).unwrap();
+        source.fill(&output.as_surface(), glium::uniforms::MagnifySamplerFilter::Nearest);
+
+        let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+        assert_eq!(data[0][0], ((index * 51) as u8, 0, 0, 255));
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn layered_attachment_not_layered() {
+    use glium::framebuffer::ValidationError;
+
+    let display = support::build_display();
+
+    if !glium::framebuffer::is_layered_supported(&display) {
+        return;
+    }
+
+    let texture = support::build_renderable_texture(&display);
+
+    match glium::framebuffer::SimpleFrameBuffer::layered(&display, *texture.main_level()) {
+        Err(ValidationError::AttachmentNotLayered) => (),
+        _ => panic!()
+    };
+
+    display.assert_no_error(None);
+}
+
 #[test]
 #[should_panic]
 fn multi_color_attachments_maximum() {
@@ -447,3 +598,44 @@ fn empty_framebuffer_simple_draw() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn read_depth() {
+    let display = support::build_display();
+
+    let color = glium::texture::Texture2d::empty_with_format(&display,
+                                            glium::texture::UncompressedFloatFormat::U8U8U8U8,
+                                            glium::texture::MipmapsOption::NoMipmap,
+                                            16, 16).unwrap();
+
+    let depth = match glium::framebuffer::DepthRenderBuffer::new(&display,
+                                            glium::texture::DepthFormat::I24, 16, 16)
+    {
+        Err(_) => return,
+        Ok(d) => d
+    };
+
+    let mut framebuffer = glium::framebuffer::SimpleFrameBuffer::with_depth_buffer(&display,
+                                                                                   &color, &depth).unwrap();
+    framebuffer.clear_depth(0.25);
+
+    let rect = glium::Rect { left: 2, bottom: 2, width: 4, height: 3 };
+    match framebuffer.read_depth(&rect) {
+        Ok(values) => {
+            assert_eq!(values.len(), 12);
+            for value in values {
+                assert!((value - 0.25).abs() < 0.001);
+            }
+        },
+        Err(glium::ReadError::AttachmentTypeNotSupported) => return,
+        Err(e) => panic!("{:?}", e),
+    }
+
+    let framebuffer = glium::framebuffer::SimpleFrameBuffer::new(&display, &color).unwrap();
+    match framebuffer.read_depth(&rect) {
+        Err(glium::ReadError::NoDepthBuffer) => (),
+        a => panic!("{:?}", a),
+    }
+
+    display.assert_no_error(None);
+}
@@ -108,6 +108,83 @@ fn triangle_strip() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn triangle_strip_primitive_restart() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                          &[0u16, 1, 2, 7, 2, 1, 3]).unwrap()
+                                     .with_primitive_restart(7);
+    assert_eq!(indices.get_primitive_restart(), Some(7));
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    match texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                                    &Default::default())
+    {
+        Ok(()) => (),
+        Err(glium::DrawError::PrimitiveRestartNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+    assert_eq!(data.last().unwrap().last().unwrap(), &(255, 0, 0, 255));
+
+    // restarting must be disabled again for a buffer without a restart index
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                          &[0u16, 1, 2, 3]).unwrap();
+    texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                              &Default::default()).unwrap();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn primitive_restart_conflicts_with_fixed_index() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                          &[0u16, 1, 2, 7, 2, 1, 3]).unwrap()
+                                     .with_primitive_restart(7);
+
+    let params = glium::DrawParameters {
+        primitive_restart_index: true,
+        .. Default::default()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                                    &params)
+    {
+        Err(glium::DrawError::PrimitiveRestartIndexConflict) => (),
+        Err(glium::DrawError::FixedIndexRestartingNotSupported) => return,
+        r => panic!("{:?}", r)
+    }
+
+    // the maximum value is the fixed restart index, so there's no conflict
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                          &[0u16, 1, 2, 0xffff, 2, 1, 3]).unwrap()
+                                     .with_primitive_restart(0xffff);
+    texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                              &params).unwrap();
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn triangle_fan() {
     let display = support::build_display();
@@ -271,6 +348,66 @@ fn triangle_fan_noindices() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn adjacency_without_geometry_shader() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] },
+        Vertex { position: [ 0.0,  1.0] },
+        Vertex { position: [ 1.0,  1.0] },
+        Vertex { position: [ 1.0,  0.0] },
+        Vertex { position: [-1.0, -1.0] },
+        Vertex { position: [-1.0,  0.0] },
+    ]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    let result = texture.as_surface().draw(&vb,
+                                           &index::NoIndices(PrimitiveType::TrianglesListAdjacency),
+                                           &program, &glium::uniforms::EmptyUniforms,
+                                           &Default::default());
+
+    match result {
+        Err(glium::DrawError::AdjacencyWithoutGeometryShader) => (),
+        a => panic!("{:?}", a)
+    };
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn index_type_mismatch() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2, 2, 1, 3]).unwrap();
+
+    let source = glium::index::IndicesSource::IndexBuffer {
+        buffer: indices.as_slice_any(),
+        data_type: glium::index::IndexType::U32,
+        primitives: PrimitiveType::TrianglesList,
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    let result = texture.as_surface().draw(&vb, source, &program,
+                                           &glium::uniforms::EmptyUniforms,
+                                           &Default::default());
+
+    match result {
+        Err(glium::DrawError::IndexBufferSizeMismatch) => (),
+        a => panic!("{:?}", a)
+    };
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn empty_index_buffer() {
     let display = support::build_display();
@@ -295,6 +432,21 @@ fn indexbuffer_slice_out_of_range() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn indexbuffer_slice_offset() {
+    let display = support::build_display();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2, 2, 1, 3]).unwrap();
+
+    let slice = indices.slice(2 .. 6).unwrap();
+    assert_eq!(slice.offset(), 2);
+    assert_eq!(slice.slice(1 .. 3).unwrap().offset(), 3);
+    assert!(slice.slice(3 .. 5).is_none());
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn indexbuffer_slice_draw() {
     let display = support::build_display();
@@ -414,3 +566,113 @@ fn multidraw_elements() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn index_range_hint() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [0.0, 0.0] }, Vertex { position: [0.0, 0.0] },
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[2u16, 3, 4, 4, 3, 5]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+
+    let params = glium::DrawParameters {
+        index_range: Some((2, 5)),
+        .. Default::default()
+    };
+    texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                              &params).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    if cfg!(debug_assertions) {
+        let params = glium::DrawParameters {
+            index_range: Some((2, 4)),
+            .. Default::default()
+        };
+
+        match texture.as_surface().draw(&vb, &indices, &program,
+                                        &glium::uniforms::EmptyUniforms, &params)
+        {
+            Err(glium::DrawError::IndexRangeMismatch) => (),
+            // the backend can't read buffers
+            Ok(()) => (),
+            a => panic!("{:?}", a)
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn index_range_hint_fixed_restart() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                          &[0u16, 1, 2, 0xffff, 2, 1, 3]).unwrap();
+
+    let params = glium::DrawParameters {
+        index_range: Some((0, 3)),
+        primitive_restart_index: true,
+        .. Default::default()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    match texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                                    &params)
+    {
+        Ok(()) => (),
+        Err(glium::DrawError::FixedIndexRestartingNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    }
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn index_range_hint_reversed() {
+    let display = support::build_display();
+    let program = build_program(&display);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2, 2, 1, 3]).unwrap();
+
+    let params = glium::DrawParameters {
+        index_range: Some((3, 0)),
+        .. Default::default()
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw(&vb, &indices, &program, &glium::uniforms::EmptyUniforms,
+                                    &params)
+    {
+        Err(glium::DrawError::IndexRangeMismatch) => (),
+        a => panic!("{:?}", a)
+    }
+
+    display.assert_no_error(None);
+}
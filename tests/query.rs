@@ -159,6 +159,24 @@ fn time_elapsed() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn buffer_write_timed() {
+    let display = support::build_display();
+
+    let buffer = glium::buffer::Buffer::new(&display, &[1u8, 2, 3, 4][..],
+                                            glium::buffer::BufferType::ArrayBuffer,
+                                            glium::buffer::BufferMode::Default).unwrap();
+
+    let query = buffer.as_slice().write_timed(&[5u8, 6, 7, 8][..]);
+    if let Some(query) = query {
+        query.get();
+    }
+
+    assert_eq!(buffer.read().unwrap(), vec![5, 6, 7, 8]);
+
+    display.assert_no_error(None);
+}
+
 #[test]
 #[ignore]       // not sure about the interaction between primitives_generated and no geometry shader
 fn primitives_generated() {
@@ -304,7 +322,7 @@ fn query_to_buffer() {
         return;
     }
 
-    let mapping = buffer.map();
+    let mapping = buffer.map().unwrap();
     assert!(*mapping == 1024 * 1024); // texture dimensions
 
     display.assert_no_error(None);
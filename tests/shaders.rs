@@ -430,6 +430,85 @@ fn get_transform_feedback_varyings() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn feedback_varyings_survive_binary() {
+    let display = support::build_display();
+
+    let vertex_shader = "
+        #version 110
+
+        varying vec2 normal;
+        varying float color;
+
+        void main() {
+            normal = vec2(0.0, 0.0);
+            color = 5.0;
+
+            gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+        }
+    ";
+    let fragment_shader = "
+        #version 110
+
+        void main() {
+            gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+        }
+    ";
+
+    let program = match glium::Program::from_source_with_feedback_varyings(&display,
+        vertex_shader, fragment_shader, None, &["normal", "color"],
+        glium::program::TransformFeedbackMode::Interleaved)
+    {
+        Ok(p) => p,
+        Err(glium::program::ProgramCreationError::TransformFeedbackNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    assert_eq!(program.get_transform_feedback_buffers().len(), 1);
+    assert_eq!(program.get_transform_feedback_buffers()[0].elements.len(), 2);
+
+    let binary = match program.get_binary() {
+        Err(_) => return,
+        Ok(bin) => bin
+    };
+
+    let reloaded = glium::Program::new(&display, binary).unwrap();
+    assert_eq!(reloaded.get_transform_feedback_buffers(), program.get_transform_feedback_buffers());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn feedback_varyings_missing() {
+    let display = support::build_display();
+
+    let result = glium::Program::from_source_with_feedback_varyings(&display,
+        "
+            #version 110
+
+            void main() {
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+        None, &["missing"], glium::program::TransformFeedbackMode::Interleaved);
+
+    match result {
+        Err(glium::program::ProgramCreationError::TransformFeedbackNotSupported) => (),
+        Err(glium::program::ProgramCreationError::LinkingError(_)) => (),
+        Err(glium::program::ProgramCreationError::TransformFeedbackVaryingNotFound(name)) => {
+            assert_eq!(name, "missing");
+        },
+        r => panic!("{:?}", r.err())
+    }
+}
+
 #[test]
 fn get_output_primitives_simple() {
     let display = support::build_display();
@@ -856,3 +935,145 @@ fn array_layout_offsets() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn bind_attribute_locations() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        color: [f32; 3],
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, color, position);
+
+    let source = glium::program::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            attribute vec2 position;
+            attribute vec3 color;
+
+            varying vec3 v_color;
+
+            void main() {
+                v_color = color;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            varying vec3 v_color;
+
+            void main() {
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+    };
+
+    let format = <Vertex as glium::Vertex>::build_bindings();
+    let program = glium::Program::new_with_attribute_locations(&display, source, &format).unwrap();
+
+    assert_eq!(program.get_attribute("color").unwrap().location, 0);
+    assert_eq!(program.get_attribute("position").unwrap().location, 1);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn bind_attribute_locations_double() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        transform: [[f64; 3]; 3],
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, transform, position);
+
+    let source = glium::program::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 410
+
+            in dmat3 transform;
+            in vec2 position;
+
+            void main() {
+                gl_Position = vec4(vec3(transform * dvec3(position, 1.0)), 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 410
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+    };
+
+    let format = <Vertex as glium::Vertex>::build_bindings();
+    let program = match glium::Program::new_with_attribute_locations(&display, source, &format) {
+        Ok(program) => program,
+        Err(glium::program::ProgramCreationError::CompilationError(..)) => return,
+        Err(err) => panic!("{:?}", err),
+    };
+
+    // each column of a `dmat3` uses two locations
+    assert_eq!(program.get_attribute("transform").unwrap().location, 0);
+    assert_eq!(program.get_attribute("position").unwrap().location, 6);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn bind_attribute_locations_nul_name() {
+    let display = support::build_display();
+
+    let format = ::std::borrow::Cow::Owned(vec![
+        (::std::borrow::Cow::Borrowed("posi\0tion"), 0, glium::vertex::AttributeType::F32F32,
+         glium::vertex::AttributeBinding::Auto),
+    ]);
+
+    let source = glium::program::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+
+        vertex_shader: "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 110
+
+            void main() {
+                gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+    };
+
+    match glium::Program::new_with_attribute_locations(&display, source, &format) {
+        Err(glium::program::ProgramCreationError::LinkingError(_)) => (),
+        _ => panic!(),
+    }
+
+    display.assert_no_error(None);
+}
@@ -75,6 +75,68 @@ fn sample() {
     display.assert_no_error(None);
 }
 
+#[test]
+fn per_instance_data() {
+    let display = support::build_display();
+
+    if !glium::texture::buffer_texture::is_buffer_texture_supported(&display) {
+        return;
+    }
+
+    let data = &[(255, 0, 0, 255), (0, 255, 0, 255)];
+    let buf_tex = BufferTexture::new(&display, data, BufferTextureType::Float);
+    let buf_tex: BufferTexture<(u8, u8, u8, u8)> = match buf_tex {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 140
+
+            in vec2 position;
+            out vec4 v_color;
+
+            uniform samplerBuffer tex;
+
+            void main() {
+                float offset = gl_InstanceID == 0 ? -0.5 : 0.5;
+                gl_Position = vec4(position.x * 0.5 + offset, position.y, 0.0, 1.0);
+                v_color = texelFetch(tex, gl_InstanceID);
+            }
+        ",
+        "
+            #version 140
+
+            in vec4 v_color;
+            out vec4 color;
+
+            void main() {
+                color = v_color;
+            }
+        ",
+        None);
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let instances = glium::vertex::EmptyInstanceAttributes { len: 2 };
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    output.as_surface().draw((&vb, instances), &ib, &program, &uniform!{ tex: &buf_tex },
+                             &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[0][1023], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
 #[test]
 fn wrong_type() {
     let display = support::build_display();
@@ -125,3 +187,73 @@ fn wrong_type() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn vertex_buffer_as_texel_buffer() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        color: [f32; 4],
+        weight: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, color, weight);
+
+    let vertices = glium::VertexBuffer::new(&display, &[
+        Vertex { color: [1.0, 0.0, 1.0, 1.0], weight: [0.0, 0.0] },
+    ]).unwrap();
+
+    // a vertex is 24 bytes, which is not a multiple of 16 bytes
+    match vertices.as_uniform_texel_buffer::<(f32, f32, f32, f32)>(BufferTextureType::Float) {
+        Err(glium::texture::buffer_texture::TextureCreationError::FormatNotSupported) => (),
+        _ => panic!()
+    };
+
+    let texels = match vertices.as_uniform_texel_buffer::<(f32, f32)>(BufferTextureType::Float) {
+        Ok(t) => t,
+        Err(_) => return
+    };
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let program = glium::Program::from_source(&display,
+        "
+            #version 110
+
+            attribute vec2 position;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        ",
+        "
+            #version 140
+
+            uniform samplerBuffer tex;
+
+            void main() {
+                gl_FragColor = vec4(texelFetch(tex, 0).rg, texelFetch(tex, 1).rg);
+            }
+        ",
+        None);
+    let program = match program {
+        Ok(p) => p,
+        Err(_) => return
+    };
+
+    let output = support::build_renderable_texture(&display);
+    output.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    output.as_surface().draw(&vb, &ib, &program, &uniform!{ tex: &texels },
+                             &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = output.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 255, 255));
+        }
+    }
+
+    drop(texels);
+    display.assert_no_error(None);
+}
@@ -24,7 +24,7 @@ fn uniform_buffer_mapping_read() {
         Ok(b) => b
     };
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(*mapping, 12);
 
     display.assert_no_error(None);
@@ -40,11 +40,11 @@ fn uniform_buffer_mapping_write() {
     };
 
     {
-        let mut mapping = vb.map();
+        let mut mapping = vb.map().unwrap();
         *mapping = 15;
     }
 
-    let mapping = vb.map();
+    let mapping = vb.map().unwrap();
     assert_eq!(*mapping, 15);
 
     display.assert_no_error(None);
@@ -227,11 +227,11 @@ fn buffer_write() {
     };
 
     {
-        let mut mapping = buf.map();
+        let mut mapping = buf.map().unwrap();
         mapping.1 = 8;
     }
 
-    let mapping = buf.map();
+    let mapping = buf.map().unwrap();
     assert_eq!(*mapping, (5, 8));
 
     display.assert_no_error(None);
@@ -291,7 +291,7 @@ fn persistent_block_race_condition() {
     target.clear_color(0.0, 0.0, 0.0, 0.0);
     for _ in 0 .. 1000 {
         {
-            let mut mapping = buffer.map();
+            let mut mapping = buffer.map().unwrap();
             mapping.color.0 = rand::random();
             mapping.color.1 = rand::random();
             mapping.color.2 = rand::random();
@@ -302,7 +302,7 @@ fn persistent_block_race_condition() {
         }, &Default::default()).unwrap();
     }
     {
-        let mut mapping = buffer.map();
+        let mut mapping = buffer.map().unwrap();
         mapping.color.0 = 1.0;
         mapping.color.1 = 1.0;
         mapping.color.2 = 1.0;
@@ -311,7 +311,7 @@ fn persistent_block_race_condition() {
         MyBlock: &buffer
     }, &Default::default()).unwrap();
     {
-        let mut mapping = buffer.map();
+        let mut mapping = buffer.map().unwrap();
         mapping.color.0 = 0.0;
         mapping.color.1 = 0.0;
         mapping.color.2 = 0.0;
@@ -0,0 +1,103 @@
+#![cfg(not(feature = "test_headless"))]
+
+#[macro_use]
+extern crate glium;
+
+use glium::glutin;
+use glium::backend::glutin::upload::{UploadQueue, UploadError};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+/// Builds a display and an upload queue whose context shares its objects with the display.
+fn build_display_and_queue() -> (glium::Display, UploadQueue) {
+    let event_loop = glutin::event_loop::EventLoop::new();
+    let wb = glutin::window::WindowBuilder::new().with_visible(false);
+    let cb = glutin::ContextBuilder::new().with_gl_debug_flag(true);
+    let display = glium::Display::new(wb, cb, &event_loop).unwrap();
+
+    let upload_context = {
+        let gl_window = display.gl_window();
+        glutin::ContextBuilder::new()
+            .with_shared_lists(gl_window.context())
+            .build_headless(&event_loop, glutin::dpi::PhysicalSize::new(1, 1))
+            .unwrap()
+    };
+
+    let queue = UploadQueue::new(upload_context).unwrap();
+    (display, queue)
+}
+
+#[test]
+fn upload_wait() {
+    let (display, queue) = build_display_and_queue();
+
+    let data = vec![Vertex { position: [1.0, 2.0] }, Vertex { position: [3.0, 4.0] }];
+    let vb = queue.upload(data.clone()).wait(&display).unwrap();
+    assert_eq!(vb.len(), 2);
+
+    if let Ok(read) = vb.read() {
+        assert_eq!(read, data);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn upload_try_get() {
+    let (display, queue) = build_display_and_queue();
+
+    let mut pending = queue.uploader().upload(vec![Vertex { position: [1.0, 2.0] }; 16]);
+
+    let vb = loop {
+        if let Some(vb) = pending.try_get(&display) {
+            break vb.unwrap();
+        }
+    };
+    assert_eq!(vb.len(), 16);
+
+    // the buffer is only returned once
+    assert!(pending.try_get(&display).is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn upload_dropped_before_retrieval() {
+    let (display, queue) = build_display_and_queue();
+
+    let pending = queue.upload(vec![Vertex { position: [1.0, 2.0] }; 16]);
+    drop(pending);
+
+    // the buffer is destroyed by the upload thread, which must still stop when dropped
+    let pending = queue.upload(vec![Vertex { position: [1.0, 2.0] }; 16]);
+    drop(queue);
+    drop(pending);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn upload_after_queue_dropped() {
+    let (display, queue) = build_display_and_queue();
+
+    let uploader = queue.uploader();
+    drop(queue);
+
+    let mut pending = uploader.upload(vec![Vertex { position: [1.0, 2.0] }; 16]);
+    match pending.try_get(&display) {
+        Some(Err(UploadError::ThreadStopped)) => (),
+        _ => panic!("the upload thread should have stopped"),
+    }
+
+    match uploader.upload(vec![Vertex { position: [1.0, 2.0] }; 16]).wait(&display) {
+        Err(UploadError::ThreadStopped) => (),
+        _ => panic!("the upload thread should have stopped"),
+    }
+
+    display.assert_no_error(None);
+}
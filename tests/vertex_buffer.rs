@@ -1,6 +1,8 @@
 #[macro_use]
 extern crate glium;
 
+use std::convert::TryFrom;
+
 use glium::Surface;
 
 mod support;
@@ -88,3 +90,1084 @@ fn transform_feedback() {
 
     display.assert_no_error(None);
 }
+
+#[test]
+fn transform_feedback_separate_buffers() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone, PartialEq)]
+    struct Position {
+        output_pos: (f32, f32),
+    }
+
+    implement_vertex!(Position, output_pos);
+
+    #[derive(Copy, Clone, PartialEq)]
+    struct Sum {
+        output_sum: f32,
+    }
+
+    implement_vertex!(Sum, output_sum);
+
+    let (vb, ib) = support::build_rectangle_vb_ib(&display);
+
+    let source = glium::program::ProgramCreationInput::SourceCode {
+        tessellation_control_shader: None,
+        tessellation_evaluation_shader: None,
+        geometry_shader: None,
+        outputs_srgb: false,
+        uses_point_size: false,
+
+        vertex_shader: "
+            #version 110
+
+            attribute vec2 position;
+
+            varying vec2 output_pos;
+            varying float output_sum;
+
+            void main() {
+                output_pos = position;
+                output_sum = position.x + position.y;
+                gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        fragment_shader: "
+            #version 130
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 1.0, 1.0, 1.0);
+            }
+        ",
+
+        transform_feedback_varyings: Some((
+            vec!["output_pos".to_string(), "output_sum".to_string()],
+            glium::program::TransformFeedbackMode::Separate
+        )),
+    };
+
+    let program = match glium::Program::new(&display, source) {
+        Ok(p) => p,
+        Err(glium::program::ProgramCreationError::TransformFeedbackNotSupported) => return,
+        Err(e) => panic!("{:?}", e)
+    };
+
+    let mut positions: glium::VertexBuffer<Position> =
+        glium::VertexBuffer::empty(&display, 6).unwrap();
+    let mut sums: glium::VertexBuffer<Sum> = glium::VertexBuffer::empty(&display, 6).unwrap();
+
+    assert!(glium::vertex::TransformFeedbackSession::new(&display, &program,
+                                                         &mut positions).is_err());
+    assert!(glium::vertex::TransformFeedbackSession::new_multiple(&display, &program,
+        vec![(&mut *sums).into(), (&mut *positions).into()]).is_err());
+
+    {
+        let session = glium::vertex::TransformFeedbackSession::new_multiple(&display, &program,
+            vec![(&mut *positions).into(), (&mut *sums).into()]).unwrap();
+
+        let params = glium::DrawParameters {
+            transform_feedback: Some(&session),
+            .. Default::default()
+        };
+
+        display.draw().draw(&vb, &ib, &program, &uniform!{}, &params).unwrap();
+    }
+
+    let (positions, sums) = match (positions.read(), sums.read()) {
+        (Ok(p), Ok(s)) => (p, s),
+        _ => return,
+    };
+
+    assert_eq!(positions[0].output_pos, (-1.0, 1.0));
+    assert_eq!(positions[5].output_pos, (1.0, -1.0));
+    assert_eq!(sums[0].output_sum, 0.0);
+    assert_eq!(sums[1].output_sum, 2.0);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn new_raw_misaligned_attribute() {
+    use std::borrow::Cow;
+
+    let display = support::build_display();
+
+    let bindings = Cow::Owned(vec![(
+            Cow::Borrowed("flag"), 0,
+            glium::vertex::AttributeType::U8,
+            glium::vertex::AttributeBinding::Auto,
+        ), (
+            Cow::Borrowed("value"), 1,
+            glium::vertex::AttributeType::F32,
+            glium::vertex::AttributeBinding::Auto,
+        ),
+    ]);
+
+    let data = vec![0u8; 10];
+
+    let _ = unsafe { glium::VertexBuffer::new_raw(&display, &data, bindings, 5) };
+}
+
+#[test]
+fn read_attribute() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [1.0, 2.0], color: [0.0, 0.5, 1.0] },
+        Vertex { position: [3.0, 4.0], color: [1.0, 0.5, 0.0] },
+    ]).unwrap();
+
+    let colors = match vb.read_attribute::<[f32; 3]>("color") {
+        Some(c) => c,
+        None => return      // reading not supported
+    };
+
+    assert_eq!(colors, vec![[0.0, 0.5, 1.0], [1.0, 0.5, 0.0]]);
+    assert_eq!(vb.read_attribute::<[f32; 2]>("position").unwrap(), vec![[1.0, 2.0], [3.0, 4.0]]);
+    assert!(vb.read_attribute::<[f32; 2]>("color").is_none());
+    assert!(vb.read_attribute::<[f32; 2]>("normal").is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn compute_aabb() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 3],
+        uv: [f32; 2],
+        id: u32,
+    }
+
+    implement_vertex!(Vertex, position, uv, id);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [1.0, -2.0, 3.0], uv: [0.0, 1.0], id: 0 },
+        Vertex { position: [-1.0, 4.0, 0.5], uv: [1.0, 0.0], id: 1 },
+        Vertex { position: [0.0, 0.0, -3.0], uv: [0.5, 0.5], id: 2 },
+    ]).unwrap();
+
+    let aabb = match vb.compute_aabb("position") {
+        Some(aabb) => aabb,
+        None => return      // reading not supported
+    };
+
+    assert_eq!(aabb, ([-1.0, -2.0, -3.0], [1.0, 4.0, 3.0]));
+    assert_eq!(vb.compute_aabb("uv").unwrap(), ([0.0, 0.0, 0.0], [1.0, 1.0, 0.0]));
+    assert!(vb.compute_aabb("id").is_none());
+    assert!(vb.compute_aabb("normal").is_none());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn from_display_and_vec() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let data = vec![Vertex { position: [0.0, 1.0] }, Vertex { position: [2.0, 3.0] }];
+    let vb = glium::VertexBuffer::try_from((&display, data)).unwrap();
+    assert_eq!(vb.len(), 2);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn write_one() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display, &[
+        Vertex { position: [0.0, 1.0] },
+        Vertex { position: [2.0, 3.0] },
+    ]).unwrap();
+
+    vb.write_one(1, Vertex { position: [4.0, 5.0] });
+
+    let data = match vb.read() {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    assert_eq!(data, vec![Vertex { position: [0.0, 1.0] }, Vertex { position: [4.0, 5.0] }]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn write_one_out_of_range() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let mut vb = glium::VertexBuffer::new(&display, &[Vertex { position: [0.0, 1.0] }]).unwrap();
+    vb.write_one(1, Vertex { position: [4.0, 5.0] });
+}
+
+#[test]
+fn vertex_buffer_any_read_raw_bytes() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        value: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, value);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { value: [1, 2] },
+        Vertex { value: [3, 4] },
+    ]).unwrap();
+    let vb: glium::vertex::VertexBufferAny = vb.into();
+
+    match vb.read_raw_bytes() {
+        Some(bytes) => assert_eq!(bytes, vec![1, 2, 3, 4]),
+        None => assert!(!glium::buffer::is_buffer_read_supported(&display)),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_any_read_as() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Vertex {
+        value: [u8; 2],
+    }
+
+    implement_vertex!(Vertex, value);
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Other {
+        other: [u8; 2],
+    }
+
+    implement_vertex!(Other, other);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { value: [1, 2] },
+        Vertex { value: [3, 4] },
+    ]).unwrap();
+    let vb: glium::vertex::VertexBufferAny = vb.into();
+
+    assert!(vb.read_as_if_supported::<Other>().is_none());
+
+    match vb.read_as_if_supported::<Vertex>() {
+        Some(data) => assert_eq!(data, vec![Vertex { value: [1, 2] }, Vertex { value: [3, 4] }]),
+        None => assert!(!glium::buffer::is_buffer_read_supported(&display)),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_any_from_borrowed_bytes() {
+    use std::borrow::Cow;
+
+    let display = support::build_display();
+
+    let file = vec![0xffu8, 1, 2, 3, 4, 5, 6, 0xff];
+
+    let vb = {
+        let bytes = &file[1 .. 7];
+        let bindings = Cow::Owned(vec![(Cow::Borrowed("value"), 0,
+                                        glium::vertex::AttributeType::U8U8,
+                                        glium::vertex::AttributeBinding::Auto)]);
+        unsafe { glium::vertex::VertexBufferAny::new_raw_bytes(&display, bytes, bindings, 2) }
+            .unwrap()
+    };
+
+    assert_eq!(vb.len(), 3);
+    assert_eq!(vb.get_elements_size(), 2);
+    drop(file);
+
+    match vb.read_raw_bytes() {
+        Some(bytes) => assert_eq!(bytes, vec![1, 2, 3, 4, 5, 6]),
+        None => assert!(!glium::buffer::is_buffer_read_supported(&display)),
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_len_is_empty() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ]).unwrap();
+    assert_eq!(vb.len(), 2);
+    assert!(!vb.is_empty());
+
+    let vb: glium::vertex::VertexBufferAny = vb.into();
+    assert_eq!(vb.len(), 2);
+    assert!(!vb.is_empty());
+
+    let empty = glium::VertexBuffer::<Vertex>::empty(&display, 0).unwrap();
+    assert_eq!(empty.len(), 0);
+    assert!(empty.is_empty());
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_assert_valid() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::dynamic(&display, &[
+        Vertex { field1: [2.0, 3.0, 4.0] },
+        Vertex { field1: [12.0, 13.0, 14.0] },
+    ]).unwrap();
+    vb.assert_valid();
+
+    vb.write(&[
+        Vertex { field1: [5.0, 6.0, 7.0] },
+        Vertex { field1: [15.0, 16.0, 17.0] },
+    ]);
+    vb.assert_valid();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn empty_vertex_buffer_assert_valid() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::<Vertex>::empty_dynamic(&display, 0).unwrap();
+    vb.assert_valid();
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_write_or_grow() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+    ]).unwrap();
+
+    vb.write_or_grow(1, &[Vertex { field1: [3.0, 4.0] }, Vertex { field1: [5.0, 6.0] }]).unwrap();
+    assert_eq!(vb.len(), 3);
+    vb.assert_valid();
+
+    vb.write_or_grow(0, &[Vertex { field1: [7.0, 8.0] }]).unwrap();
+    assert_eq!(vb.len(), 3);
+
+    let data = match vb.read() {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    assert_eq!(data[0].field1, [7.0, 8.0]);
+    assert_eq!(data[1].field1, [3.0, 4.0]);
+    assert_eq!(data[2].field1, [5.0, 6.0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_write_if_changed() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::with_shadow_copy(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ], glium::buffer::BufferMode::Dynamic).unwrap();
+
+    assert!(!vb.write_if_changed(1, &[Vertex { field1: [3.0, 4.0] }]));
+    assert!(vb.write_if_changed(0, &[Vertex { field1: [5.0, 6.0] }]));
+    assert!(!vb.write_if_changed(0, &[Vertex { field1: [5.0, 6.0] }]));
+
+    vb.write_or_grow(2, &[Vertex { field1: [7.0, 8.0] }]).unwrap();
+    assert!(!vb.write_if_changed(2, &[Vertex { field1: [7.0, 8.0] }]));
+
+    let data = match vb.read() {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    assert_eq!(data[0].field1, [5.0, 6.0]);
+    assert_eq!(data[1].field1, [3.0, 4.0]);
+    assert_eq!(data[2].field1, [7.0, 8.0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_write_if_changed_after_write() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::with_shadow_copy(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ], glium::buffer::BufferMode::Dynamic).unwrap();
+
+    // modifying the buffer without `write_if_changed` must not let it skip the next upload
+    vb.write(&[Vertex { field1: [5.0, 6.0] }, Vertex { field1: [7.0, 8.0] }]);
+    assert!(vb.write_if_changed(0, &[Vertex { field1: [1.0, 2.0] }]));
+
+    vb.map().unwrap()[1] = Vertex { field1: [5.0, 6.0] };
+    assert!(vb.write_if_changed(0, &[Vertex { field1: [1.0, 2.0] }]));
+
+    vb.slice_mut(0 .. 1).unwrap().unwrap()[0] = Vertex { field1: [5.0, 6.0] };
+    assert!(vb.write_if_changed(0, &[Vertex { field1: [1.0, 2.0] }]));
+    assert!(!vb.write_if_changed(0, &[Vertex { field1: [1.0, 2.0] }]));
+
+    let data = match vb.read() {
+        Ok(d) => d,
+        Err(_) => return
+    };
+
+    assert_eq!(data[0].field1, [1.0, 2.0]);
+    assert_eq!(data[1].field1, [5.0, 6.0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_clear_with() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    #[derive(Copy, Clone)]
+    struct OddVertex {
+        field1: [u16; 3],
+    }
+
+    implement_vertex!(OddVertex, field1);
+
+    let vb = glium::VertexBuffer::dynamic(&display, &[Vertex { field1: [1.0, 2.0] }; 16]).unwrap();
+    vb.clear_with(Vertex { field1: [3.0, -4.0] });
+
+    let odd = glium::VertexBuffer::dynamic(&display, &[OddVertex { field1: [1, 2, 3] }; 5]).unwrap();
+    odd.clear_with(OddVertex { field1: [7, 8, 9] });
+
+    match vb.read() {
+        Ok(data) => assert!(data.iter().all(|v| v.field1 == [3.0, -4.0])),
+        Err(_) => return
+    };
+
+    let data = odd.read().unwrap();
+    assert!(data.iter().all(|v| v.field1 == [7, 8, 9]));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_any_from_raw_id() {
+    use glium::GlObject;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let original = glium::VertexBuffer::new(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ]).unwrap();
+
+    let adopted = unsafe {
+        glium::vertex::VertexBufferAny::from_raw_id(&display, original.get_id(),
+                                                    <Vertex as glium::Vertex>::build_bindings(),
+                                                    8, 2, false)
+    };
+    assert_eq!(adopted.len(), 2);
+
+    if let Some(data) = adopted.read_as_if_supported::<Vertex>() {
+        assert_eq!(data[1].field1, [3.0, 4.0]);
+    }
+
+    // the buffer isn't owned by `adopted` and must still be usable after this
+    drop(adopted);
+    original.write(&[Vertex { field1: [5.0, 6.0] }, Vertex { field1: [7.0, 8.0] }]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_any_into_raw_id() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let original: glium::vertex::VertexBufferAny = glium::VertexBuffer::new(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ]).unwrap().into();
+
+    let id = original.into_raw_id();
+
+    let buffer: glium::VertexBuffer<Vertex> = unsafe {
+        glium::vertex::VertexBufferAny::from_raw_id(&display, id,
+                                                    <Vertex as glium::Vertex>::build_bindings(),
+                                                    8, 2, true).into_vertex_buffer()
+    };
+    assert_eq!(buffer.len(), 2);
+
+    if let Ok(data) = buffer.read() {
+        assert_eq!(data[0].field1, [1.0, 2.0]);
+        assert_eq!(data[1].field1, [3.0, 4.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_get_usage() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::new(&display, &[Vertex { field1: [0.0, 0.0] }]).unwrap();
+    assert_eq!(vb.get_usage(), glium::buffer::BufferMode::Default);
+
+    let vb = glium::VertexBuffer::dynamic(&display, &[Vertex { field1: [0.0, 0.0] }]).unwrap();
+    assert_eq!(vb.get_usage(), glium::buffer::BufferMode::Dynamic);
+
+    let vb: glium::vertex::VertexBufferAny = vb.into();
+    assert_eq!(vb.get_usage(), glium::buffer::BufferMode::Dynamic);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn recompute_winding() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let vertex = |x, y| Vertex { position: [x, y], color: [1.0, 1.0, 1.0] };
+
+    // a quad whose first triangle is clockwise and second triangle is counter-clockwise
+    let mut vertices = vec![
+        vertex(0.0, 0.0), vertex(0.0, 1.0), vertex(1.0, 0.0),
+        vertex(1.0, 0.0), vertex(1.0, 1.0), vertex(0.0, 1.0),
+    ];
+
+    assert_eq!(glium::vertex::recompute_winding(&mut vertices, "position"), Ok(1));
+
+    for triangle in vertices.chunks(3) {
+        let (a, b, c) = (triangle[0].position, triangle[1].position, triangle[2].position);
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        assert!(area > 0.0);
+    }
+
+    assert_eq!(glium::vertex::recompute_winding(&mut vertices, "position"), Ok(0));
+    assert_eq!(glium::vertex::recompute_winding(&mut vertices, "normal"),
+               Err(glium::vertex::WindingError::AttributeNotFound));
+    assert_eq!(glium::vertex::recompute_winding(&mut vertices[.. 4], "position"),
+               Err(glium::vertex::WindingError::NotTriangles));
+}
+
+#[test]
+fn vertex_buffer_any_with_stride() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let buffer = glium::vertex::VertexBufferAny::new_with_stride(&display, &[
+        Vertex { position: [1.0, 2.0, 3.0] },
+        Vertex { position: [4.0, 5.0, 6.0] },
+    ], 16).unwrap();
+
+    assert_eq!(buffer.get_elements_size(), 16);
+    assert_eq!(buffer.len(), 2);
+
+    if let Some(bytes) = buffer.read_raw_bytes() {
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(&bytes[12 .. 16], &[0, 0, 0, 0]);
+    }
+
+    buffer.write_with_stride(&[
+        Vertex { position: [7.0, 8.0, 9.0] },
+        Vertex { position: [10.0, 11.0, 12.0] },
+    ]);
+
+    if let Some(data) = buffer.read_as_if_supported::<Vertex>() {
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].position, [7.0, 8.0, 9.0]);
+        assert_eq!(data[1].position, [10.0, 11.0, 12.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_positions() {
+    let display = support::build_display();
+
+    let vb = glium::VertexBuffer::positions(&display, &[[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]).unwrap();
+    assert_eq!(vb.len(), 2);
+    assert_eq!(vb.get_bindings()[0].0, "position");
+    assert_eq!(vb.get_bindings()[0].2, glium::vertex::AttributeType::F32F32F32);
+
+    let vb = glium::VertexBuffer::positions_2d(&display, &[[1.0, 2.0]]).unwrap();
+    assert_eq!(vb.len(), 1);
+    assert_eq!(vb.get_bindings()[0].2, glium::vertex::AttributeType::F32F32);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_size_overflow() {
+    use glium::vertex::BufferCreationError;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::dynamic(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+    ]).unwrap();
+
+    match vb.write_or_grow(::std::usize::MAX, &[Vertex { field1: [3.0, 4.0] }]) {
+        Err(BufferCreationError::BufferCreationError(
+            glium::buffer::BufferCreationError::FormatTooLarge)) => (),
+        _ => panic!()
+    }
+    assert_eq!(vb.len(), 1);
+
+    let vertices = [Vertex { field1: [1.0, 2.0] }; 3];
+    match glium::vertex::VertexBufferAny::new_with_stride(&display, &vertices,
+                                                          ::std::isize::MAX as usize / 2)
+    {
+        Err(BufferCreationError::BufferCreationError(
+            glium::buffer::BufferCreationError::FormatTooLarge)) => (),
+        _ => panic!()
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_with_capacity() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::<Vertex>::with_capacity(&display, 4).unwrap();
+    assert_eq!(vb.len(), 0);
+    assert_eq!(vb.capacity(), 4);
+    assert!(vb.slice(0 .. 1).is_none());
+
+    vb.extend(&[Vertex { field1: [1.0, 2.0] }, Vertex { field1: [3.0, 4.0] }]).unwrap();
+    vb.extend(&[Vertex { field1: [5.0, 6.0] }]).unwrap();
+    assert_eq!(vb.len(), 3);
+    assert_eq!(vb.capacity(), 4);
+    vb.assert_valid();
+
+    // growing beyond the capacity doubles it
+    vb.extend(&[Vertex { field1: [7.0, 8.0] }, Vertex { field1: [9.0, 10.0] }]).unwrap();
+    assert_eq!(vb.len(), 5);
+    assert_eq!(vb.capacity(), 8);
+    vb.assert_valid();
+
+    if let Ok(data) = vb.slice(..).unwrap().read() {
+        assert_eq!(data.len(), 5);
+        assert_eq!(data[0].field1, [1.0, 2.0]);
+        assert_eq!(data[2].field1, [5.0, 6.0]);
+        assert_eq!(data[4].field1, [9.0, 10.0]);
+    }
+
+    vb.clear();
+    assert_eq!(vb.len(), 0);
+    assert_eq!(vb.capacity(), 8);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_with_capacity_ignores_spare_capacity() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::<Vertex>::with_capacity(&display, 4).unwrap();
+    vb.extend(&[Vertex { field1: [1.0, 2.0] }, Vertex { field1: [3.0, 4.0] }]).unwrap();
+
+    // these only see the two vertices in use
+    vb.write(&[Vertex { field1: [5.0, 6.0] }, Vertex { field1: [7.0, 8.0] }]);
+    assert_eq!(vb.map().unwrap().len(), 2);
+    assert_eq!(vb.map_write().unwrap().len(), 2);
+    assert_eq!(vb.as_slice().len(), 2);
+
+    if let Ok(data) = vb.read() {
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].field1, [5.0, 6.0]);
+        assert_eq!(data[1].field1, [7.0, 8.0]);
+    }
+
+    let vb: glium::vertex::VertexBufferAny = vb.into();
+    assert_eq!(vb.len(), 2);
+
+    let vb = unsafe { vb.into_vertex_buffer::<Vertex>() };
+    assert_eq!(vb.len(), 2);
+    assert_eq!(vb.capacity(), 4);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_shrink_to_fit() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::<Vertex>::with_capacity(&display, 8).unwrap();
+    vb.extend(&[Vertex { field1: [1.0, 2.0] }, Vertex { field1: [3.0, 4.0] }]).unwrap();
+
+    match vb.shrink_to_fit() {
+        Ok(()) => (),
+        Err(glium::vertex::BufferCreationError::BufferCreationError(
+            glium::buffer::BufferCreationError::NotSupported)) =>
+        {
+            assert_eq!(vb.capacity(), 8);
+            return;
+        },
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert_eq!(vb.len(), 2);
+    assert_eq!(vb.capacity(), 2);
+    vb.assert_valid();
+
+    if let Ok(data) = vb.read() {
+        assert_eq!(data[0].field1, [1.0, 2.0]);
+        assert_eq!(data[1].field1, [3.0, 4.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_read_async() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { field1: [1.0, 2.0] },
+        Vertex { field1: [3.0, 4.0] },
+    ]).unwrap();
+
+    let readback = match vb.read_async() {
+        Ok(r) => r,
+        Err(glium::buffer::ReadError::NotSupported) => return,
+        e => e.unwrap(),
+    };
+
+    let data = readback.wait().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[0].field1, [1.0, 2.0]);
+    assert_eq!(data[1].field1, [3.0, 4.0]);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_nested_slices() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let data = (0 .. 100).map(|i| Vertex { field1: [i as f32, 0.0] }).collect::<Vec<_>>();
+    let vb = glium::VertexBuffer::new(&display, &data).unwrap();
+
+    let outer = vb.slice(50 .. 100).unwrap();
+    let inner = outer.slice(10 .. 20).unwrap();
+    assert_eq!(outer.offset(), 50);
+    assert_eq!(inner.offset(), 60);
+    assert_eq!(inner.len(), 10);
+    assert_eq!(inner.slice(5 ..).unwrap().offset(), 65);
+    assert!(inner.slice(5 .. 11).is_none());
+    assert!(outer.slice(40 .. 51).is_none());
+
+    if let Ok(read) = inner.read() {
+        assert_eq!(read[0].field1, [60.0, 0.0]);
+        assert_eq!(read[9].field1, [69.0, 0.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_write_iter() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::new(&display, &[Vertex { field1: [0.0, 0.0] }; 4]).unwrap();
+
+    let written = vb.write_iter(1, (0 .. 2).map(|i| Vertex { field1: [i as f32, 1.0] })).unwrap();
+    assert_eq!(written, 2);
+
+    if let Ok(data) = vb.read() {
+        assert_eq!(data[0].field1, [0.0, 0.0]);
+        assert_eq!(data[1].field1, [0.0, 1.0]);
+        assert_eq!(data[2].field1, [1.0, 1.0]);
+        assert_eq!(data[3].field1, [0.0, 0.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+#[should_panic]
+fn vertex_buffer_write_iter_overflow() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::new(&display, &[Vertex { field1: [0.0, 0.0] }; 2]).unwrap();
+    vb.write_iter(1, (0 .. 2).map(|_| Vertex { field1: [1.0, 1.0] })).unwrap();
+}
+
+#[test]
+fn vertex_buffer_map_attribute() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let mut vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [1.0, 2.0], color: [0.5, 0.5, 0.5] },
+        Vertex { position: [3.0, 4.0], color: [0.25, 0.25, 0.25] },
+    ]).unwrap();
+
+    assert!(vb.map_attribute::<[f32; 3]>("position").unwrap().is_none());
+    assert!(vb.map_attribute::<[f32; 2]>("normal").unwrap().is_none());
+
+    {
+        let mut view = vb.map_attribute::<[f32; 2]>("position").unwrap().unwrap();
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.get(1), [3.0, 4.0]);
+        view.set(0, [5.0, 6.0]);
+    }
+
+    if let Ok(data) = vb.read() {
+        assert_eq!(data[0].position, [5.0, 6.0]);
+        assert_eq!(data[0].color, [0.5, 0.5, 0.5]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_fill_from_fn() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        field1: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, field1);
+
+    let mut vb = glium::VertexBuffer::<Vertex>::empty(&display, 3).unwrap();
+    vb.fill_from_fn(|i| Vertex { field1: [i as f32, 2.0 * i as f32] }).unwrap();
+
+    if let Ok(data) = vb.read() {
+        assert_eq!(data[0].field1, [0.0, 0.0]);
+        assert_eq!(data[1].field1, [1.0, 2.0]);
+        assert_eq!(data[2].field1, [2.0, 4.0]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_buffer_non_static() {
+    use std::borrow::Cow;
+    use std::marker::PhantomData;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex<'a> {
+        position: [f32; 2],
+        source: PhantomData<&'a [f32; 2]>,
+    }
+
+    impl<'a> Vertex<'a> {
+        // the vertex borrows the point it is built from
+        fn new(source: &'a [f32; 2]) -> Vertex<'a> {
+            Vertex { position: *source, source: PhantomData }
+        }
+    }
+
+    impl<'a> glium::vertex::Vertex for Vertex<'a> {
+        fn build_bindings() -> glium::VertexFormat {
+            Cow::Owned(vec![(Cow::Borrowed("position"), 0, glium::vertex::AttributeType::F32F32,
+                             glium::vertex::AttributeBinding::Auto)])
+        }
+    }
+
+    {
+        // the vertices borrow `scene`, so the buffer can't outlive this scope
+        let scene = vec![[1.0, 2.0], [3.0, 4.0]];
+        let vertices = scene.iter().map(Vertex::new).collect::<Vec<_>>();
+        let vb = glium::VertexBuffer::new(&display, &vertices).unwrap();
+        assert_eq!(vb.len(), 2);
+
+        if let Ok(data) = vb.read() {
+            assert_eq!(data[0].position, [1.0, 2.0]);
+            assert_eq!(data[1].position, [3.0, 4.0]);
+        }
+    }
+
+    display.assert_no_error(None);
+}
@@ -113,31 +113,69 @@ fn multiple_buffers_source() {
 }
 
 #[test]
-fn slice_draw_indices() {
-    #[derive(Copy, Clone)]
-    struct Vertex {
-        position: [f32; 2],
-    }
+fn multiple_buffers_binding_index() {
+    let display = support::build_display();
 
-    implement_vertex!(Vertex, position);
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+            ]
+        ).unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
 
-    let display = support::build_display();
     let program = program!(&display,
         110 => {
             vertex: "
                 #version 110
 
                 attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
 
                 void main() {
                     gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
                 }
             ",
             fragment: "
                 #version 110
+                varying vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         },
@@ -146,66 +184,108 @@ fn slice_draw_indices() {
                 #version 100
 
                 attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
 
                 void main() {
                     gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
                 }
             ",
             fragment: "
                 #version 100
+                varying lowp vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         }).unwrap();
 
-    let vb = glium::VertexBuffer::new(&display, &[
-        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
-        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
-    ]).unwrap();
-
-    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
-                                          &[0u16, 1, 2]).unwrap();
-
     let texture = support::build_renderable_texture(&display);
     texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw(vb.slice(1 .. 4).unwrap(), &indices, &program,
-                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+    let source1: glium::vertex::VerticesSource = (&buffer1).into();
+    let source2: glium::vertex::VerticesSource = (&buffer2).into();
+    texture.as_surface().draw((source1.with_binding_index(3), source2.with_binding_index(0)),
+                              &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
 
     let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
-    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
 
     display.assert_no_error(None);
 }
 
 #[test]
-fn slice_draw_noindices() {
-    #[derive(Copy, Clone)]
-    struct Vertex {
-        position: [f32; 2],
+fn multiple_buffers_structure_of_arrays() {
+    let display = support::build_display();
+
+    macro_rules! attribute_buffer {
+        ($name:ident, $value:expr) => ({
+            #[derive(Copy, Clone)]
+            struct Vertex {
+                $name: f32,
+            }
+
+            implement_vertex!(Vertex, $name);
+
+            glium::VertexBuffer::new(&display, &[Vertex { $name: $value }; 4]).unwrap()
+        })
     }
 
-    implement_vertex!(Vertex, position);
+    let position = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let red = attribute_buffer!(red, 1.0);
+    let green = attribute_buffer!(green, 0.0);
+    let blue = attribute_buffer!(blue, 1.0);
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
 
-    let display = support::build_display();
     let program = program!(&display,
         110 => {
             vertex: "
                 #version 110
 
                 attribute vec2 position;
+                attribute float red;
+                attribute float green;
+                attribute float blue;
+
+                varying vec3 v_color;
 
                 void main() {
                     gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = vec3(red, green, blue);
                 }
             ",
             fragment: "
                 #version 110
+                varying vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         },
@@ -214,228 +294,1495 @@ fn slice_draw_noindices() {
                 #version 100
 
                 attribute lowp vec2 position;
+                attribute lowp float red;
+                attribute lowp float green;
+                attribute lowp float blue;
+
+                varying lowp vec3 v_color;
 
                 void main() {
                     gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = vec3(red, green, blue);
                 }
             ",
             fragment: "
                 #version 100
+                varying lowp vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         }).unwrap();
 
-    let vb = glium::VertexBuffer::new(&display, &[
-        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
-        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
-    ]).unwrap();
+    let texture = support::build_renderable_texture(&display);
 
-    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+    // with `GL_ARB_multi_bind` all the buffers are bound at once, including the binding points
+    // that are left unused in the second draw
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&position, &red, &green, &blue), &index_buffer, &program,
+                              &uniform!{}, &Default::default()).unwrap();
 
-    let texture = support::build_renderable_texture(&display);
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 255, 255));
+        }
+    }
+
+    let position_source: glium::vertex::VerticesSource = (&position).into();
+    let red_source: glium::vertex::VerticesSource = (&red).into();
+    let green_source: glium::vertex::VerticesSource = (&green).into();
+    let blue_source: glium::vertex::VerticesSource = (&blue).into();
     texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw(vb.slice(1 .. 4).unwrap(), &indices, &program,
-                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+    texture.as_surface().draw((position_source.with_binding_index(5), red_source.with_binding_index(0),
+                               green_source.with_binding_index(3),
+                               blue_source.with_binding_index(1)),
+                              &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
 
     let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
-    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 255, 255));
+        }
+    }
 
     display.assert_no_error(None);
 }
 
 #[test]
-fn slice_draw_multiple() {
-    #[derive(Copy, Clone)]
-    struct Vertex {
-        position: [f32; 2],
-    }
+fn multiple_buffers_binding_index_conflict() {
+    let display = support::build_display();
 
-    implement_vertex!(Vertex, position);
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
 
-    #[derive(Copy, Clone)]
-    struct Vertex2 {
-        position2: [f32; 2],
-    }
+        implement_vertex!(Vertex, position);
 
-    implement_vertex!(Vertex2, position2);
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+            ]
+        ).unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
 
-    let display = support::build_display();
     let program = program!(&display,
         110 => {
             vertex: "
                 #version 110
 
                 attribute vec2 position;
-                attribute vec2 position2;
+                attribute vec3 color;
+
+                varying vec3 v_color;
 
                 void main() {
-                    if (position != position2) {
-                        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
-                    } else {
-                        gl_Position = vec4(position, 0.0, 1.0);
-                    }
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
                 }
             ",
             fragment: "
                 #version 110
+                varying vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         },
-
         100 => {
             vertex: "
                 #version 100
 
                 attribute lowp vec2 position;
-                attribute lowp vec2 position2;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
 
                 void main() {
-                    if (position != position2) {
-                        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
-                    } else {
-                        gl_Position = vec4(position, 0.0, 1.0);
-                    }
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
                 }
             ",
             fragment: "
                 #version 100
+                varying lowp vec3 v_color;
 
                 void main() {
-                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                    gl_FragColor = vec4(v_color, 1.0);
                 }
             ",
         }).unwrap();
 
-    // the 3 last elements will be drawn
-    let vb1 = glium::VertexBuffer::new(&display, &[
-        Vertex { position: [-1.0,  1.0] }, Vertex { position: [-1.0, 1.0] },
-        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
-        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
-    ]).unwrap();
-
-    // the 3 last elements will be drawn
-    let vb2 = glium::VertexBuffer::new(&display, &[
-        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [-1.0, 1.0] },
-        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [-1.0, 1.0] },
-        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [1.0,  1.0] },
-        Vertex2 { position2: [-1.0, -1.0] }, Vertex2 { position2: [1.0, -1.0] },
-    ]).unwrap();
-
-    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
-                                          &[2u16, 3, 4]).unwrap();
-
     let texture = support::build_renderable_texture(&display);
-    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw((vb1.slice(1 .. 4).unwrap(), vb2.slice(3 .. 6).unwrap()), &indices,
-                &program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+    let source1: glium::vertex::VerticesSource = (&buffer1).into();
+    let source2: glium::vertex::VerticesSource = (&buffer2).into();
 
-    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
-    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+    // the first buffer implicitly uses the binding index 0
+    match texture.as_surface().draw((source1, source2.with_binding_index(0)),
+                                    &index_buffer, &program, &uniform!{},
+                                    &Default::default())
+    {
+        Err(glium::DrawError::BindingIndexConflict) => (),
+        r => panic!("{:?}", r)
+    }
 
     display.assert_no_error(None);
 }
 
 #[test]
-fn attributes_marker() {
-    let display = support::build_display();
+fn slice_draw_indices() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
 
-    let program = match glium::Program::from_source(&display,
-        "
-            #version 140
+    implement_vertex!(Vertex, position);
+
+    let display = support::build_display();
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(vb.slice(1 .. 4).unwrap(), &indices, &program,
+                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
+    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn base_offset_draw() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let display = support::build_display();
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let source: glium::vertex::VerticesSource = vb.slice(0 .. 3).unwrap().into();
+    assert!(source.clone().with_base_offset(3 * 8).is_none());
+    let source = source.with_base_offset(8).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(source, &indices, &program,
+                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
+    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn slice_draw_noindices() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    let display = support::build_display();
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(vb.slice(1 .. 4).unwrap(), &indices, &program,
+                &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
+    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn slice_draw_multiple() {
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    #[derive(Copy, Clone)]
+    struct Vertex2 {
+        position2: [f32; 2],
+    }
+
+    implement_vertex!(Vertex2, position2);
+
+    let display = support::build_display();
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec2 position2;
+
+                void main() {
+                    if (position != position2) {
+                        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                    } else {
+                        gl_Position = vec4(position, 0.0, 1.0);
+                    }
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec2 position2;
+
+                void main() {
+                    if (position != position2) {
+                        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                    } else {
+                        gl_Position = vec4(position, 0.0, 1.0);
+                    }
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(1.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    // the 3 last elements will be drawn
+    let vb1 = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [-1.0, 1.0] },
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [1.0, -1.0] },
+    ]).unwrap();
+
+    // the 3 last elements will be drawn
+    let vb2 = glium::VertexBuffer::new(&display, &[
+        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [-1.0, 1.0] },
+        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [-1.0, 1.0] },
+        Vertex2 { position2: [-1.0,  1.0] }, Vertex2 { position2: [1.0,  1.0] },
+        Vertex2 { position2: [-1.0, -1.0] }, Vertex2 { position2: [1.0, -1.0] },
+    ]).unwrap();
+
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[2u16, 3, 4]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((vb1.slice(1 .. 4).unwrap(), vb2.slice(3 .. 6).unwrap()), &indices,
+                &program, &glium::uniforms::EmptyUniforms, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data.last().unwrap()[0], (0, 0, 0, 0));
+    assert_eq!(data[0].last().unwrap(), &(255, 0, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn attributes_marker() {
+    let display = support::build_display();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            void main() {
+                if (gl_VertexID == 0) {
+                    gl_Position = vec4(-1.0, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 1) {
+                    gl_Position = vec4(1.0, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 2) {
+                    gl_Position = vec4(-1.0, -1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 3) {
+                    gl_Position = vec4(1.0, -1.0, 0.0, 1.0);
+                }
+            }
+        ",
+        "
+            #version 140
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(glium::vertex::EmptyVertexAttributes { len: 4 },
+                              &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
+                              &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn attributes_marker_indices() {
+    let display = support::build_display();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            void main() {
+                if (gl_VertexID == 0) {
+                    gl_Position = vec4(-1.0, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 1) {
+                    gl_Position = vec4(1.0, 1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 2) {
+                    gl_Position = vec4(-1.0, -1.0, 0.0, 1.0);
+                } else if (gl_VertexID == 3) {
+                    gl_Position = vec4(1.0, -1.0, 0.0, 1.0);
+                }
+            }
+        ",
+        "
+            #version 140
+
+            out vec4 color;
+
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let indices = glium::index::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                                 &[0u16, 1, 2, 3]).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(glium::vertex::EmptyVertexAttributes { len: 4 },
+                              &indices, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instancing() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        glium::vertex::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = match buffer2.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            in vec2 position;
+            in vec3 color;
+
+            out vec3 v_color;
+            flat out int instance;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                v_color = color;
+                instance = gl_InstanceID;
+            }
+        ",
+        "
+            #version 330
+            in vec3 v_color;
+            flat in int instance;
+
+            void main() {
+                if (instance != 3) {
+                    discard;
+                }
+
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, buffer2), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instancing_vertex_buffer_any() {
+    let display = support::build_display();
+
+    let mesh = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [0.0,  1.0] },
+                Vertex { position: [1.0,  1.0] },
+                Vertex { position: [0.0, -1.0] },
+                Vertex { position: [1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    // each instance covers one half of the texture with its own tint
+    let instances: glium::vertex::VertexBufferAny = {
+        #[derive(Copy, Clone)]
+        struct Instance {
+            offset: [f32; 2],
+            tint: [f32; 3],
+        }
+
+        implement_vertex!(Instance, offset, tint);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Instance { offset: [-1.0, 0.0], tint: [1.0, 0.0, 0.0] },
+                Instance { offset: [ 0.0, 0.0], tint: [0.0, 1.0, 0.0] },
+            ]
+        ).unwrap().into()
+    };
+
+    let instances = match instances.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 140
+
+            in vec2 position;
+            in vec2 offset;
+            in vec3 tint;
+
+            out vec3 v_tint;
+
+            void main() {
+                gl_Position = vec4(position + offset, 0.0, 1.0);
+                v_tint = tint;
+            }
+        ",
+        "
+            #version 140
+            in vec3 v_tint;
+            out vec4 f_color;
+
+            void main() {
+                f_color = vec4(v_tint, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&mesh, instances), &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
+    assert_eq!(data[0][data[0].len() - 1], (0, 255, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instancing_binding_index() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    // both attributes of the instances share the same binding and thus the same divisor
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Instance {
+            color: [f32; 3],
+            alpha: f32,
+        }
+
+        implement_vertex!(Instance, color, alpha);
+
+        glium::vertex::VertexBuffer::new(&display,
+            &[
+                Instance { color: [0.0, 0.0, 1.0], alpha: 0.0 },
+                Instance { color: [1.0, 0.0, 0.0], alpha: 1.0 },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = match buffer2.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            in vec2 position;
+            in vec3 color;
+            in float alpha;
+
+            out vec4 v_color;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                v_color = vec4(color, alpha);
+            }
+        ",
+        "
+            #version 330
+            in vec4 v_color;
+
+            void main() {
+                if (v_color.a == 0.0) {
+                    discard;
+                }
+
+                gl_FragColor = v_color;
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let source2: glium::vertex::VerticesSource = buffer2.into();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, source2.with_binding_index(2)), &index_buffer, &program,
+                              &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instance_buffer_update() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    #[derive(Copy, Clone)]
+    struct Instance {
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Instance, color);
+
+    let mut instances = glium::vertex::InstanceBuffer::empty(&display, 2).unwrap();
+    assert_eq!(instances.len(), 0);
+    assert_eq!(instances.capacity(), 2);
+
+    instances.update(&[
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+    ]).unwrap();
+    assert_eq!(instances.len(), 5);
+    assert_eq!(instances.capacity(), 8);
+
+    instances.update(&[
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [1.0, 0.0, 0.0] },
+    ]).unwrap();
+    assert_eq!(instances.len(), 4);
+    assert_eq!(instances.capacity(), 8);
+
+    let per_instance = match instances.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
+
+            in vec2 position;
+            in vec3 color;
+
+            out vec3 v_color;
+            flat out int instance;
+
+            void main() {
+                gl_Position = vec4(position, 0.0, 1.0);
+                v_color = color;
+                instance = gl_InstanceID;
+            }
+        ",
+        "
+            #version 330
+            in vec3 v_color;
+            flat in int instance;
+
+            void main() {
+                if (instance != 3) {
+                    discard;
+                }
+
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, per_instance), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn per_instance_length_mismatch() {
+    let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        glium::vertex::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [1.0, 0.0, 0.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = match buffer2.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let buffer3 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [f32; 3],
+        }
+
+        implement_vertex!(Vertex, color);
+
+        glium::vertex::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+                Vertex { color: [0.0, 0.0, 1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer3 = match buffer3.per_instance() {
+        Ok(b) => b,
+        Err(_) => return
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 110
+
+                void main() {
+                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                void main() {
+                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+            fragment: "
+                #version 100
+
+                void main() {
+                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let mut frame = display.draw();
+    match frame.draw((&buffer1, buffer2, buffer3), &index_buffer, &program, &uniform!{},
+                     &Default::default())
+    {
+        Err(glium::DrawError::InstancesCountMismatch) => (),
+        a => panic!("{:?}", a)
+    }
+
+    frame.finish().unwrap();
+    display.assert_no_error(None);
+}
+
+#[test]
+fn normalized_vertex() {
+        let display = support::build_display();
+
+    let buffer1 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            position: [f32; 2],
+        }
+
+        implement_vertex!(Vertex, position);
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { position: [-1.0,  1.0] },
+                Vertex { position: [ 1.0,  1.0] },
+                Vertex { position: [-1.0, -1.0] },
+                Vertex { position: [ 1.0, -1.0] },
+            ]
+        ).unwrap()
+    };
+
+    let buffer2 = {
+        #[derive(Copy, Clone)]
+        struct Vertex {
+            color: [u8; 3],
+        }
+
+        implement_vertex!(Vertex, color normalize(true));
+
+        glium::VertexBuffer::new(&display,
+            &[
+                Vertex { color: [0, 0, 0] },
+                Vertex { color: [128, 0, 0] },
+                Vertex { color: [0, 128, 0] },
+                Vertex { color: [0, 0, 128] },
+            ]
+        ).unwrap()
+    };
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec3 color;
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec3 color;
+
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            ",
+        }).unwrap();
+
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw((&buffer1, &buffer2), &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    // If the data wasn't normalized, the 128 would be 255 instead
+    assert_eq!(data[0][0], (0, 128, 0, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn normalized_vertex_color() {
+    use glium::vertex::AttributeBinding;
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [u8; 4],
+    }
+
+    implement_vertex!(Vertex, position binding(AttributeBinding::Auto),
+                              color binding(AttributeBinding::FloatNormalized));
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], color: [255, 0, 128, 255] },
+        Vertex { position: [ 1.0,  1.0], color: [255, 0, 128, 255] },
+        Vertex { position: [-1.0, -1.0], color: [255, 0, 128, 255] },
+        Vertex { position: [ 1.0, -1.0], color: [255, 0, 128, 255] },
+    ]).unwrap();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
+
+                attribute lowp vec2 position;
+                attribute lowp vec4 color;
+
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        }).unwrap();
 
-            void main() {
-                if (gl_VertexID == 0) {
-                    gl_Position = vec4(-1.0, 1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 1) {
-                    gl_Position = vec4(1.0, 1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 2) {
-                    gl_Position = vec4(-1.0, -1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 3) {
-                    gl_Position = vec4(1.0, -1.0, 0.0, 1.0);
+    let texture = support::build_renderable_texture(&display);
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&vb, &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 128, 255));
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn vertex_format_change_same_buffer() {
+    use glium::vertex::{AttributeBinding, VerticesSource};
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+        color: [u8; 4],
+    }
+
+    implement_vertex!(Vertex, position, color);
+
+    let vb = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0], color: [128, 0, 0, 255] },
+        Vertex { position: [ 1.0,  1.0], color: [128, 0, 0, 255] },
+        Vertex { position: [-1.0, -1.0], color: [128, 0, 0, 255] },
+        Vertex { position: [ 1.0, -1.0], color: [128, 0, 0, 255] },
+    ]).unwrap();
+
+    // same buffer, same offsets, only the binding of `color` differs
+    let raw_format = vb.get_bindings().clone();
+    let normalized_format: glium::vertex::VertexFormat = raw_format.iter().map(|b| {
+        if b.0 == "color" {
+            (b.0.clone(), b.1, b.2, AttributeBinding::FloatNormalized)
+        } else {
+            b.clone()
+        }
+    }).collect::<Vec<_>>().into();
+
+    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
+                                               &[0u16, 1, 2, 3]).unwrap();
+
+    let program = program!(&display,
+        110 => {
+            vertex: "
+                #version 110
+
+                attribute vec2 position;
+                attribute vec4 color;
+
+                varying vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
                 }
-            }
-        ",
-        "
-            #version 140
+            ",
+            fragment: "
+                #version 110
+                varying vec4 v_color;
 
-            out vec4 color;
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        },
+        100 => {
+            vertex: "
+                #version 100
 
-            void main() {
-                color = vec4(1.0, 0.0, 0.0, 1.0);
-            }
-        ",
-        None) {
-        Ok(p) => p,
-        _ => return
-    };
+                attribute lowp vec2 position;
+                attribute lowp vec4 color;
+
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_Position = vec4(position, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 100
+                varying lowp vec4 v_color;
+
+                void main() {
+                    gl_FragColor = v_color;
+                }
+            ",
+        }).unwrap();
 
     let texture = support::build_renderable_texture(&display);
+
     texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw(glium::vertex::EmptyVertexAttributes { len: 4 },
-                              &glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
-                              &program, &uniform!{},
+    texture.as_surface().draw(VerticesSource::VertexBuffer(vb.as_slice_any(), &raw_format,
+                                                           false),
+                              &index_buffer, &program, &uniform!{},
                               &Default::default()).unwrap();
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    assert_eq!(data[0][0], (255, 0, 0, 255));
 
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(VerticesSource::VertexBuffer(vb.as_slice_any(), &normalized_format,
+                                                           false),
+                              &index_buffer, &program, &uniform!{},
+                              &Default::default()).unwrap();
     let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    for row in data.iter() {
-        for pixel in row.iter() {
-            assert_eq!(pixel, &(255, 0, 0, 255));
-        }
-    }
+    assert_eq!(data[0][0], (128, 0, 0, 255));
 
     display.assert_no_error(None);
 }
 
 #[test]
-fn attributes_marker_indices() {
+fn instancing_divisor_probe() {
     let display = support::build_display();
 
-    let program = match glium::Program::from_source(&display,
-        "
-            #version 140
+    let first = glium::vertex::is_instancing_divisor_working(&display);
+    let second = glium::vertex::is_instancing_divisor_working(&display);
+    assert_eq!(first, second);
 
-            void main() {
-                if (gl_VertexID == 0) {
-                    gl_Position = vec4(-1.0, 1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 1) {
-                    gl_Position = vec4(1.0, 1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 2) {
-                    gl_Position = vec4(-1.0, -1.0, 0.0, 1.0);
-                } else if (gl_VertexID == 3) {
-                    gl_Position = vec4(1.0, -1.0, 0.0, 1.0);
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instanced_vertices_cpu_fallback() {
+    use glium::vertex::{InstancedVertices, InstancingMode};
+
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    #[derive(Copy, Clone)]
+    struct Instance {
+        offset: f32,
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Instance, offset, color);
+
+    // the left half of the screen, moved to the right half by the second instance
+    let triangles = [
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [0.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [0.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [0.0, -1.0] },
+    ];
+    let instances = [
+        Instance { offset: 0.0, color: [1.0, 0.0, 0.0] },
+        Instance { offset: 1.0, color: [1.0, 0.0, 0.0] },
+    ];
+
+    let vertices = InstancedVertices::new(&display, &triangles, &instances,
+                                          InstancingMode::CpuFallback).unwrap();
+
+    let hardware = glium::VertexBuffer::new(&display, &instances).unwrap().per_instance().is_ok();
+    if !hardware {
+        assert!(vertices.is_expanded());
+    }
+
+    let program = program!(&display,
+        140 => {
+            vertex: "
+                #version 140
+
+                in vec2 position;
+                in float offset;
+                in vec3 color;
+                out vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position.x + offset, position.y, 0.0, 1.0);
+                    v_color = color;
                 }
-            }
-        ",
-        "
-            #version 140
+            ",
+            fragment: "
+                #version 140
 
-            out vec4 color;
+                in vec3 v_color;
+                out vec4 f_color;
 
-            void main() {
-                color = vec4(1.0, 0.0, 0.0, 1.0);
-            }
-        ",
-        None) {
-        Ok(p) => p,
-        _ => return
-    };
+                void main() {
+                    f_color = vec4(v_color, 1.0);
+                }
+            "
+        },
+        110 => {
+            vertex: "
+                #version 110
 
-    let indices = glium::index::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
-                                                 &[0u16, 1, 2, 3]).unwrap();
+                attribute vec2 position;
+                attribute float offset;
+                attribute vec3 color;
+                varying vec3 v_color;
+
+                void main() {
+                    gl_Position = vec4(position.x + offset, position.y, 0.0, 1.0);
+                    v_color = color;
+                }
+            ",
+            fragment: "
+                #version 110
+
+                varying vec3 v_color;
+
+                void main() {
+                    gl_FragColor = vec4(v_color, 1.0);
+                }
+            "
+        },
+    ).unwrap();
 
     let texture = support::build_renderable_texture(&display);
     texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw(glium::vertex::EmptyVertexAttributes { len: 4 },
-                              &indices, &program, &uniform!{},
-                              &Default::default()).unwrap();
+    texture.as_surface().draw(&vertices, &glium::index::NoIndices(PrimitiveType::TrianglesList),
+                              &program, &uniform!{}, &Default::default()).unwrap();
+
+    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
+    for row in data.iter() {
+        for pixel in row.iter() {
+            assert_eq!(pixel, &(255, 0, 0, 255));
+        }
+    }
+
+    // forcing the expansion gives the same result
+    let expanded = InstancedVertices::new(&display, &triangles, &instances,
+                                          InstancingMode::Cpu).unwrap();
+    assert!(expanded.is_expanded());
+
+    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
+    texture.as_surface().draw(&expanded, &glium::index::NoIndices(PrimitiveType::TrianglesList),
+                              &program, &uniform!{}, &Default::default()).unwrap();
 
     let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
     for row in data.iter() {
@@ -444,52 +1791,138 @@ fn attributes_marker_indices() {
         }
     }
 
+    // the indices would only address the first instance
+    let indices = glium::IndexBuffer::new(&display, PrimitiveType::TrianglesList,
+                                          &[0u16, 1, 2, 3, 4, 5]).unwrap();
+    match texture.as_surface().draw(&expanded, &indices, &program, &uniform!{},
+                                    &Default::default())
+    {
+        Err(glium::DrawError::ExpandedInstancesWithIndices) => (),
+        res => panic!("{:?}", res),
+    }
+
     display.assert_no_error(None);
 }
 
 #[test]
-fn instancing() {
+fn divisor_of() {
     let display = support::build_display();
 
-    let buffer1 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            position: [f32; 2],
-        }
+    #[derive(Copy, Clone)]
+    struct Instance {
+        color: [f32; 3],
+    }
 
-        implement_vertex!(Vertex, position);
+    implement_vertex!(Instance, color);
 
-        glium::VertexBuffer::new(&display,
-            &[
-                Vertex { position: [-1.0,  1.0] },
-                Vertex { position: [ 1.0,  1.0] },
-                Vertex { position: [-1.0, -1.0] },
-                Vertex { position: [ 1.0, -1.0] },
-            ]
-        ).unwrap()
+    let vertices = glium::VertexBuffer::positions_2d(&display, &[[0.0, 0.0]]).unwrap();
+    let instances = glium::VertexBuffer::new(&display, &[Instance { color: [1.0, 0.0, 0.0] }])
+                                             .unwrap();
+
+    let source: glium::vertex::VerticesSource = (&vertices).into();
+    assert_eq!(source.divisor_of("position"), Some(0));
+    assert_eq!(source.divisor_of("color"), None);
+
+    let source: glium::vertex::VerticesSource = match instances.per_instance() {
+        Ok(source) => source.into(),
+        Err(_) => return
     };
+    assert_eq!(source.divisor_of("color"), Some(1));
+    assert_eq!(source.divisor_of("position"), None);
 
-    let buffer2 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            color: [f32; 3],
-        }
+    let source: glium::vertex::VerticesSource = glium::vertex::EmptyInstanceAttributes { len: 1 }
+                                                                                     .into();
+    assert_eq!(source.divisor_of("color"), None);
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn instance_buffer_shrink_to_fit() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Instance {
+        color: [f32; 3],
+    }
+
+    implement_vertex!(Instance, color);
+
+    let mut instances = glium::vertex::InstanceBuffer::empty(&display, 8).unwrap();
+    instances.update(&[
+        Instance { color: [0.0, 0.0, 1.0] },
+        Instance { color: [0.0, 1.0, 0.0] },
+        Instance { color: [1.0, 0.0, 0.0] },
+    ]).unwrap();
+
+    match instances.shrink_to_fit() {
+        Ok(()) => (),
+        Err(glium::vertex::BufferCreationError::BufferCreationError(
+            glium::buffer::BufferCreationError::NotSupported)) =>
+        {
+            assert_eq!(instances.capacity(), 8);
+            return;
+        },
+        Err(e) => panic!("{:?}", e),
+    };
+
+    assert_eq!(instances.len(), 3);
+    assert_eq!(instances.capacity(), 3);
+
+    if let Ok(data) = instances.read() {
+        assert_eq!(data, vec![
+            Instance { color: [0.0, 0.0, 1.0] },
+            Instance { color: [0.0, 1.0, 0.0] },
+            Instance { color: [1.0, 0.0, 0.0] },
+        ]);
+    }
+
+    display.assert_no_error(None);
+}
+
+#[test]
+fn multiple_divisors() {
+    let display = support::build_display();
+
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
+
+    implement_vertex!(Vertex, position);
+
+    #[derive(Copy, Clone)]
+    struct Instance {
+        offset: f32,
+    }
+
+    implement_vertex!(Instance, offset);
+
+    #[derive(Copy, Clone)]
+    struct Group {
+        color: [f32; 3],
+    }
 
-        implement_vertex!(Vertex, color);
+    implement_vertex!(Group, color);
 
-        glium::vertex::VertexBuffer::new(&display,
-            &[
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [1.0, 0.0, 0.0] },
-            ]
-        ).unwrap()
-    };
+    let vertices = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [ 1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [ 1.0, -1.0] },
+    ]).unwrap();
 
-    let buffer2 = match buffer2.per_instance() {
-        Ok(b) => b,
-        Err(_) => return
+    // the two last instances are drawn at the same place and share the second color
+    let instances = glium::VertexBuffer::new(&display, &[
+        Instance { offset: 10.0 }, Instance { offset: 10.0 },
+        Instance { offset: 0.0 }, Instance { offset: 0.0 },
+    ]).unwrap();
+
+    let groups = glium::VertexBuffer::new(&display, &[
+        Group { color: [0.0, 0.0, 1.0] }, Group { color: [1.0, 0.0, 0.0] },
+    ]).unwrap();
+
+    let (instances, groups) = match (instances.per_instance(), groups.per_instance()) {
+        (Ok(i), Ok(g)) => (i, g),
+        _ => return
     };
 
     let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
@@ -500,27 +1933,21 @@ fn instancing() {
             #version 330
 
             in vec2 position;
+            in float offset;
             in vec3 color;
 
             out vec3 v_color;
-            flat out int instance;
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0);
+                gl_Position = vec4(position.x + offset, position.y, 0.0, 1.0);
                 v_color = color;
-                instance = gl_InstanceID;
             }
         ",
         "
             #version 330
             in vec3 v_color;
-            flat in int instance;
 
             void main() {
-                if (instance != 3) {
-                    discard;
-                }
-
                 gl_FragColor = vec4(v_color, 1.0);
             }
         ",
@@ -529,10 +1956,13 @@ fn instancing() {
         _ => return
     };
 
+    let groups = groups.with_divisor(2);
+    assert_eq!(groups.divisor_of("color"), Some(2));
+
     let texture = support::build_renderable_texture(&display);
     texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw((&buffer1, buffer2), &index_buffer, &program, &uniform!{},
-                              &Default::default()).unwrap();
+    texture.as_surface().draw((&vertices, instances, groups), &index_buffer, &program,
+                              &uniform!{}, &Default::default()).unwrap();
 
     let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
     for row in data.iter() {
@@ -545,220 +1975,80 @@ fn instancing() {
 }
 
 #[test]
-fn per_instance_length_mismatch() {
+fn divisor_too_few_elements() {
     let display = support::build_display();
 
-    let buffer1 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            position: [f32; 2],
-        }
-
-        implement_vertex!(Vertex, position);
-
-        glium::VertexBuffer::new(&display,
-            &[
-                Vertex { position: [-1.0,  1.0] },
-                Vertex { position: [ 1.0,  1.0] },
-                Vertex { position: [-1.0, -1.0] },
-                Vertex { position: [ 1.0, -1.0] },
-            ]
-        ).unwrap()
-    };
+    #[derive(Copy, Clone)]
+    struct Vertex {
+        position: [f32; 2],
+    }
 
-    let buffer2 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            color: [f32; 3],
-        }
+    implement_vertex!(Vertex, position);
 
-        implement_vertex!(Vertex, color);
+    #[derive(Copy, Clone)]
+    struct Instance {
+        offset: f32,
+    }
 
-        glium::vertex::VertexBuffer::new(&display,
-            &[
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [1.0, 0.0, 0.0] },
-            ]
-        ).unwrap()
-    };
+    implement_vertex!(Instance, offset);
 
-    let buffer2 = match buffer2.per_instance() {
-        Ok(b) => b,
-        Err(_) => return
-    };
+    #[derive(Copy, Clone)]
+    struct Group {
+        color: [f32; 3],
+    }
 
-    let buffer3 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            color: [f32; 3],
-        }
+    implement_vertex!(Group, color);
 
-        implement_vertex!(Vertex, color);
+    let vertices = glium::VertexBuffer::new(&display, &[
+        Vertex { position: [-1.0,  1.0] }, Vertex { position: [ 1.0,  1.0] },
+        Vertex { position: [-1.0, -1.0] }, Vertex { position: [ 1.0, -1.0] },
+    ]).unwrap();
 
-        glium::vertex::VertexBuffer::new(&display,
-            &[
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-                Vertex { color: [0.0, 0.0, 1.0] },
-            ]
-        ).unwrap()
-    };
+    let instances = glium::VertexBuffer::new(&display, &[Instance { offset: 0.0 }; 5]).unwrap();
+    let groups = glium::VertexBuffer::new(&display, &[Group { color: [1.0, 0.0, 0.0] }; 2]).unwrap();
 
-    let buffer3 = match buffer3.per_instance() {
-        Ok(b) => b,
-        Err(_) => return
+    let (instances, groups) = match (instances.per_instance(), groups.per_instance()) {
+        (Ok(i), Ok(g)) => (i, g),
+        _ => return
     };
 
-    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
-                                               &[0u16, 1, 2, 3]).unwrap();
-
-    let program = program!(&display,
-        110 => {
-            vertex: "
-                #version 110
+    let program = match glium::Program::from_source(&display,
+        "
+            #version 330
 
-                void main() {
-                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
-                }
-            ",
-            fragment: "
-                #version 110
+            in vec2 position;
+            in float offset;
+            in vec3 color;
 
-                void main() {
-                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
-                }
-            ",
-        },
-        100 => {
-            vertex: "
-                #version 100
+            out vec3 v_color;
 
-                void main() {
-                    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
-                }
-            ",
-            fragment: "
-                #version 100
+            void main() {
+                gl_Position = vec4(position.x + offset, position.y, 0.0, 1.0);
+                v_color = color;
+            }
+        ",
+        "
+            #version 330
+            in vec3 v_color;
 
-                void main() {
-                    gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
-                }
-            ",
-        }).unwrap();
+            void main() {
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        ",
+        None) {
+        Ok(p) => p,
+        _ => return
+    };
 
-    let mut frame = display.draw();
-    match frame.draw((&buffer1, buffer2, buffer3), &index_buffer, &program, &uniform!{},
-                     &Default::default())
+    // two groups of two instances can't cover five instances
+    let texture = support::build_renderable_texture(&display);
+    match texture.as_surface().draw((&vertices, instances, groups.with_divisor(2)),
+                                    &glium::index::NoIndices(PrimitiveType::TriangleStrip),
+                                    &program, &uniform!{}, &Default::default())
     {
         Err(glium::DrawError::InstancesCountMismatch) => (),
         a => panic!("{:?}", a)
-    }
-
-    frame.finish().unwrap();
-    display.assert_no_error(None);
-}
-
-#[test]
-fn normalized_vertex() {
-        let display = support::build_display();
-
-    let buffer1 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            position: [f32; 2],
-        }
-
-        implement_vertex!(Vertex, position);
-
-        glium::VertexBuffer::new(&display,
-            &[
-                Vertex { position: [-1.0,  1.0] },
-                Vertex { position: [ 1.0,  1.0] },
-                Vertex { position: [-1.0, -1.0] },
-                Vertex { position: [ 1.0, -1.0] },
-            ]
-        ).unwrap()
-    };
-
-    let buffer2 = {
-        #[derive(Copy, Clone)]
-        struct Vertex {
-            color: [u8; 3],
-        }
-
-        implement_vertex!(Vertex, color normalize(true));
-
-        glium::VertexBuffer::new(&display,
-            &[
-                Vertex { color: [0, 0, 0] },
-                Vertex { color: [128, 0, 0] },
-                Vertex { color: [0, 128, 0] },
-                Vertex { color: [0, 0, 128] },
-            ]
-        ).unwrap()
     };
 
-    let index_buffer = glium::IndexBuffer::new(&display, PrimitiveType::TriangleStrip,
-                                               &[0u16, 1, 2, 3]).unwrap();
-
-    let program = program!(&display,
-        110 => {
-            vertex: "
-                #version 110
-
-                attribute vec2 position;
-                attribute vec3 color;
-
-                varying vec3 v_color;
-
-                void main() {
-                    gl_Position = vec4(position, 0.0, 1.0);
-                    v_color = color;
-                }
-            ",
-            fragment: "
-                #version 110
-                varying vec3 v_color;
-
-                void main() {
-                    gl_FragColor = vec4(v_color, 1.0);
-                }
-            ",
-        },
-        100 => {
-            vertex: "
-                #version 100
-
-                attribute lowp vec2 position;
-                attribute lowp vec3 color;
-
-                varying lowp vec3 v_color;
-
-                void main() {
-                    gl_Position = vec4(position, 0.0, 1.0);
-                    v_color = color;
-                }
-            ",
-            fragment: "
-                #version 100
-                varying lowp vec3 v_color;
-
-                void main() {
-                    gl_FragColor = vec4(v_color, 1.0);
-                }
-            ",
-        }).unwrap();
-
-    let texture = support::build_renderable_texture(&display);
-    texture.as_surface().clear_color(0.0, 0.0, 0.0, 0.0);
-    texture.as_surface().draw((&buffer1, &buffer2), &index_buffer, &program, &uniform!{},
-                              &Default::default()).unwrap();
-
-    let data: Vec<Vec<(u8, u8, u8, u8)>> = texture.read();
-    // If the data wasn't normalized, the 128 would be 255 instead
-    assert_eq!(data[0][0], (0, 128, 0, 255));
-
     display.assert_no_error(None);
 }
@@ -0,0 +1,74 @@
+/*!
+
+Checks the binding indices of vertex sources against dummy OpenGL 3.3 functions.
+
+*/
+#[macro_use]
+extern crate glium;
+extern crate libc;
+
+use glium::{DrawError, Surface};
+use glium::index::{NoIndices, PrimitiveType};
+use glium::vertex::VerticesSource;
+
+mod dummy;
+
+#[derive(Copy, Clone)]
+struct Vertex {
+    position: [f32; 2],
+}
+
+implement_vertex!(Vertex, position);
+
+/// Draws two vertex buffers, the second one with the binding index `index`.
+fn draw_with_binding_index(context: &std::rc::Rc<glium::backend::Context>, index: u32)
+                           -> Result<(), DrawError>
+{
+    let program = glium::Program::from_source(context, "", "", None).unwrap();
+    let buffer1 = glium::VertexBuffer::<Vertex>::empty(context, 3).unwrap();
+    let buffer2 = glium::VertexBuffer::<Vertex>::empty(context, 3).unwrap();
+    let source1: VerticesSource = (&buffer1).into();
+    let source2: VerticesSource = (&buffer2).into();
+
+    let mut frame = glium::Frame::new(context.clone(), (800, 600));
+    let result = frame.draw((source1, source2.with_binding_index(index)),
+                            NoIndices(PrimitiveType::TrianglesList), &program, &uniform!{},
+                            &Default::default());
+    frame.finish().unwrap();
+    result
+}
+
+#[test]
+fn binding_index_out_of_range() {
+    let context = dummy::build_context(b"3.3.0\0", &[b"GL_ARB_vertex_attrib_binding\0"]);
+
+    match draw_with_binding_index(&context, 16) {
+        Err(DrawError::BindingIndexOutOfRange) => (),
+        r => panic!("{:?}", r)
+    }
+
+    match draw_with_binding_index(&context, u32::max_value()) {
+        Err(DrawError::BindingIndexOutOfRange) => (),
+        r => panic!("{:?}", r)
+    }
+}
+
+#[test]
+fn binding_index_conflict() {
+    let context = dummy::build_context(b"3.3.0\0", &[b"GL_ARB_vertex_attrib_binding\0"]);
+
+    match draw_with_binding_index(&context, 0) {
+        Err(DrawError::BindingIndexConflict) => (),
+        r => panic!("{:?}", r)
+    }
+}
+
+#[test]
+fn binding_index_not_supported() {
+    let context = dummy::build_context(b"3.3.0\0", &[]);
+
+    match draw_with_binding_index(&context, 1) {
+        Err(DrawError::BindingIndexNotSupported) => (),
+        r => panic!("{:?}", r)
+    }
+}